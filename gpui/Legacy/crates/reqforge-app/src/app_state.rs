@@ -7,15 +7,20 @@
 //! - Text is managed internally by gpui-component (Rope-backed)
 //! - String allocation only occurs at ownership boundaries (save/send)
 
-use gpui::{AppContext, Context, Entity, Window};
+use gpui::{App, AppContext, Context, Entity, EventEmitter, Window};
 use gpui_component::input::InputState;
 use reqforge_core::{
-    ReqForgeCore,
+    AuthConfig, ReqForgeCore, SnippetLanguage, WorkspaceEvent, WorkspaceItem, WorkspaceWatcher,
+    import_export::error::ImportError,
     models::request::{HttpMethod, KeyValuePair, RequestDefinition},
     models::response::HttpResponse,
 };
 use uuid::Uuid;
 use std::sync::Arc;
+use std::time::Duration;
+
+/// How often the workspace directory is checked for changes made elsewhere.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Main application state using GPUI's Entity system.
 ///
@@ -30,19 +35,81 @@ pub struct AppState {
     pub active_tab: Option<usize>,
     /// Currently active environment ID
     pub active_env_id: Option<Uuid>,
+    /// Watcher on the workspace directory, for reloading changes made elsewhere
+    watcher: WorkspaceWatcher,
+    /// Changes were seen on disk but not reloaded yet
+    reload_pending: bool,
 }
 
+/// Emitted for each change `poll_workspace` picks up from disk. Subscribe with
+/// `cx.subscribe(&app_state, ...)` to refresh views or prompt on conflicts.
+impl EventEmitter<WorkspaceEvent> for AppState {}
+
 impl AppState {
     /// Create a new AppState with the given core engine.
     pub fn new(core: ReqForgeCore) -> Self {
+        let watcher = core.watcher();
         Self {
             core: Arc::new(core),
             tabs: Vec::new(),
             active_tab: None,
             active_env_id: None,
+            watcher,
+            reload_pending: false,
         }
     }
 
+    /// Poll the workspace for changes every `WATCH_INTERVAL` until the state
+    /// is dropped.
+    pub fn watch_workspace(app_state: &Entity<Self>, cx: &mut App) {
+        let app_state = app_state.downgrade();
+        cx.spawn(async move |cx| {
+            loop {
+                cx.background_executor().timer(WATCH_INTERVAL).await;
+                if app_state.update(cx, |state, cx| state.poll_workspace(cx)).is_err() {
+                    break;
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// Reload collections and environments changed on disk and emit a
+    /// `WorkspaceEvent` for each change. Conflicts with unsaved edits are
+    /// left for the subscriber to resolve via `ReqForgeCore::resolve_conflict`.
+    /// A failed reload is retried on the next poll.
+    pub fn poll_workspace(&mut self, cx: &mut Context<Self>) {
+        if !self.watcher.poll().is_empty() {
+            self.reload_pending = true;
+        }
+        if !self.reload_pending {
+            return;
+        }
+        // While the core is shared with in-flight requests, leave the changes
+        // for a later poll
+        let Some(core) = Arc::get_mut(&mut self.core) else { return };
+        let events = match core.reload() {
+            Ok(events) => events,
+            Err(e) => {
+                log::error!("Failed to reload workspace, retrying: {}", e);
+                return;
+            }
+        };
+        self.reload_pending = false;
+
+        if let Some(env_id) = self.active_env_id
+            && events.contains(&WorkspaceEvent::Removed(WorkspaceItem::Environment(env_id)))
+        {
+            self.active_env_id = None;
+        }
+
+        for event in events {
+            log::info!("Workspace changed on disk: {:?}", event);
+            cx.emit(event);
+        }
+        cx.notify();
+    }
+
     /// Get a reference to the active tab, if any.
     pub fn active_tab(&self) -> Option<&TabState> {
         self.active_tab.and_then(|index| self.tabs.get(index))
@@ -76,6 +143,70 @@ impl AppState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        let tab = Self::build_tab(req, collection_id, window, cx);
+        self.open_tab(tab);
+    }
+
+    /// Replace the active tab's request with one parsed from a curl command
+    /// line, keeping the tab's request ID and name.
+    ///
+    /// The editor has no auth settings, so Basic and Bearer credentials from
+    /// the command become an Authorization header.
+    pub fn apply_curl_to_active_tab(
+        &mut self,
+        command: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Result<(), ImportError> {
+        let mut req = reqforge_core::parse_curl_command(command)?;
+        let Some(index) = self.active_tab else {
+            return Ok(());
+        };
+        let current = &self.tabs[index];
+        req.id = current.request_id;
+        req.name = current.name.clone();
+        let authorization = match &req.auth {
+            AuthConfig::Basic { username, password } => {
+                Some(reqforge_core::http::auth::basic_authorization(username, password))
+            }
+            AuthConfig::Bearer { token } => Some(format!("Bearer {}", token)),
+            _ => None,
+        };
+        if let Some(value) = authorization {
+            req.headers.push(KeyValuePair {
+                key: "Authorization".to_string(),
+                value,
+                enabled: true,
+                description: None,
+            });
+        }
+
+        let mut tab = Self::build_tab(&req, current.collection_id, window, cx);
+        tab.is_dirty = true;
+        self.tabs[index] = tab;
+        cx.notify();
+        Ok(())
+    }
+
+    /// The active tab's request as a code snippet (curl, Python, ...), with
+    /// variables resolved and auth inherited from its collection.
+    pub fn active_tab_snippet(
+        &self,
+        language: SnippetLanguage,
+        redact_secrets: bool,
+        cx: &mut Context<Self>,
+    ) -> Option<String> {
+        let req = self.active_tab()?.to_request_definition(cx);
+        Some(self.core.code_snippet(&req, language, redact_secrets))
+    }
+
+    /// Create the entities for a tab editing `req`.
+    fn build_tab(
+        req: &RequestDefinition,
+        collection_id: Uuid,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> TabState {
         // Create URL input state
         let url_input = cx.new(|cx| {
             InputState::new(window, cx)
@@ -86,8 +217,10 @@ impl AppState {
         // Create body input state based on BodyType
         let body_content = match &req.body {
             reqforge_core::models::request::BodyType::Raw { content, .. } => content.clone(),
-            reqforge_core::models::request::BodyType::FormUrlEncoded(_) => {
-                // Convert form data to URL-encoded string
+            reqforge_core::models::request::BodyType::FormUrlEncoded(_)
+            | reqforge_core::models::request::BodyType::Multipart(_)
+            | reqforge_core::models::request::BodyType::Binary { .. } => {
+                // Summarise form, multipart and file bodies as text
                 crate::bridge::body_type_to_string(&req.body).unwrap_or_default()
             }
             reqforge_core::models::request::BodyType::None => String::new(),
@@ -129,7 +262,7 @@ impl AppState {
             })
             .collect();
 
-        let tab = TabState::new(
+        TabState::new(
             req.id,
             collection_id,
             req.name.clone(),
@@ -138,9 +271,7 @@ impl AppState {
             body_input,
             headers,
             params,
-        );
-
-        self.open_tab(tab);
+        )
     }
 
     /// Close the currently active tab.
@@ -194,6 +325,11 @@ impl AppState {
             headers,
             query_params,
             body,
+            auth: Default::default(),
+            settings: Default::default(),
+            variables: Default::default(),
+            scripts: Default::default(),
+            assertions: Vec::new(),
             created_at: now,
             updated_at: now,
         };
@@ -304,6 +440,11 @@ impl TabState {
             headers,
             query_params,
             body,
+            auth: Default::default(),
+            settings: Default::default(),
+            variables: Default::default(),
+            scripts: Default::default(),
+            assertions: Vec::new(),
             created_at: now,
             updated_at: now,
         }
@@ -16,7 +16,7 @@
 use gpui::{App, AppContext, Context, Window, Entity};
 use gpui_component::input::InputState;
 use reqforge_core::models::request::{
-    RequestDefinition, KeyValuePair, BodyType, RawContentType, HttpMethod,
+    RequestDefinition, KeyValuePair, BodyType, RawContentType, HttpMethod, MultipartValue,
 };
 use crate::app_state::{AppState, TabState, KeyValueRow};
 use uuid::Uuid;
@@ -76,6 +76,11 @@ pub fn build_request_from_components(
         headers,
         query_params: params,
         body,
+        auth: Default::default(),
+        settings: Default::default(),
+        variables: Default::default(),
+        scripts: Default::default(),
+        assertions: Vec::new(),
         created_at: now,
         updated_at: now,
     }
@@ -117,8 +122,8 @@ pub fn populate_tab_from_request(
     // Create body input state based on BodyType
     let body_content = match &req.body {
         BodyType::Raw { content, .. } => content.clone(),
-        BodyType::FormUrlEncoded(_) => {
-            // Convert form data to URL-encoded string
+        BodyType::FormUrlEncoded(_) | BodyType::Multipart(_) | BodyType::Binary { .. } => {
+            // Summarise form, multipart and file bodies as text
             body_type_to_string(&req.body).unwrap_or_default()
         }
         BodyType::None => String::new(),
@@ -296,6 +301,23 @@ pub fn body_type_to_string(body: &BodyType) -> Option<String> {
                 Some(encoded.join("&"))
             }
         }
+        BodyType::Multipart(parts) => {
+            // One `name=value` or `name=@path` line per enabled part, like curl's -F
+            let lines: Vec<String> = parts
+                .iter()
+                .filter(|p| p.enabled)
+                .map(|p| match &p.value {
+                    MultipartValue::Text(value) => format!("{}={}", p.name, value),
+                    MultipartValue::File { path, .. } => format!("{}=@{}", p.name, path),
+                })
+                .collect();
+            if lines.is_empty() {
+                None
+            } else {
+                Some(lines.join("\n"))
+            }
+        }
+        BodyType::Binary { path } => Some(format!("@{}", path)),
     }
 }
 
@@ -372,6 +394,21 @@ mod tests {
         assert!(result_str.contains("age="));
     }
 
+    /// Test body_type_to_string with Multipart and Binary
+    #[test]
+    fn test_body_type_to_string_multipart_and_binary() {
+        use reqforge_core::models::request::MultipartPart;
+
+        let body = BodyType::Multipart(vec![
+            MultipartPart::text("title", "Hello"),
+            MultipartPart::file("avatar", "/tmp/a.png"),
+        ]);
+        assert_eq!(body_type_to_string(&body), Some("title=Hello\navatar=@/tmp/a.png".to_string()));
+
+        let body = BodyType::Binary { path: "/tmp/blob.bin".to_string() };
+        assert_eq!(body_type_to_string(&body), Some("@/tmp/blob.bin".to_string()));
+    }
+
     /// Test build_request_from_components
     #[test]
     fn test_build_request_from_components() {
@@ -51,6 +51,7 @@ fn main() {
 
         // Create application state - core is moved into AppState
         let app_state = cx.new(|_cx| app_state::AppState::new(core));
+        app_state::AppState::watch_workspace(&app_state, cx);
 
         // Create the RootView entity
         let root_view = cx.new(|cx| ui::RootView::new(app_state, cx));
@@ -4,13 +4,16 @@
 //! - Method selector dropdown and URL input with Send button
 //! - Sub-tabs for Params/Headers/Body
 //! - Integration with AppState for request execution
+//! - cURL import (paste a curl command into the URL bar and press Send) and
+//!   "Copy as" code snippets (cURL, Rust, Python, JavaScript, Go, HTTPie, PowerShell)
 
 use crate::app_state::AppState;
 use gpui_component::input::InputState;
-use gpui::{div, px, App, AppContext, Context, Entity, InteractiveElement, IntoElement, MouseButton, ParentElement, Render, Styled, Subscription, Window};
+use gpui::{div, px, App, AppContext, ClipboardItem, Context, Entity, InteractiveElement, IntoElement, MouseButton, ParentElement, Render, Styled, Subscription, Window};
 use gpui_component::{h_flex, v_flex, ActiveTheme, Icon, IconName, button::Button, checkbox::Checkbox, input::Input};
 use reqforge_core::models::request::{HttpMethod, KeyValuePair, BodyType};
 use reqforge_core::models::response::HttpResponse;
+use reqforge_core::SnippetLanguage;
 use uuid::Uuid;
 
 /// Sub-tabs within the request editor.
@@ -64,6 +67,12 @@ pub struct RequestEditor {
     selected_method: HttpMethod,
     /// Whether the method dropdown is open
     method_dropdown_open: bool,
+    /// Whether the "Copy as" menu is open
+    snippet_menu_open: bool,
+    /// Whether copied snippets have secrets replaced with <redacted>
+    redact_snippets: bool,
+    /// Inputs for variables the last send could not resolve
+    variable_prompt: Vec<(String, Entity<InputState>)>,
     /// Subscription to AppState changes
     _subscription: Subscription,
 }
@@ -80,6 +89,9 @@ impl RequestEditor {
             active_sub_tab: RequestSubTab::Params,
             selected_method: HttpMethod::GET,
             method_dropdown_open: false,
+            snippet_menu_open: false,
+            redact_snippets: true,
+            variable_prompt: Vec::new(),
             _subscription: subscription,
         }
     }
@@ -113,6 +125,82 @@ impl RequestEditor {
         cx.notify();
     }
 
+    /// Toggle the "Copy as" menu open/closed.
+    fn toggle_snippet_menu(&mut self, cx: &mut Context<Self>) {
+        self.snippet_menu_open = !self.snippet_menu_open;
+        cx.notify();
+    }
+
+    /// Copy the active tab's request to the clipboard as a code snippet.
+    fn copy_snippet(&mut self, language: SnippetLanguage, cx: &mut Context<Self>) {
+        let redact = self.redact_snippets;
+        let snippet = self.app_state.update(cx, |app, cx| app.active_tab_snippet(language, redact, cx));
+        if let Some(snippet) = snippet {
+            cx.write_to_clipboard(ClipboardItem::new_string(snippet));
+        }
+        self.snippet_menu_open = false;
+        cx.notify();
+    }
+
+    /// Render the "Copy as" menu: one entry per language, plus the redaction toggle.
+    fn render_snippet_menu(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let items: Vec<_> = SnippetLanguage::all()
+            .iter()
+            .map(|&language| {
+                div()
+                    .h(px(28.0))
+                    .px_3()
+                    .rounded_md()
+                    .flex()
+                    .items_center()
+                    .cursor_pointer()
+                    .text_sm()
+                    .child(language.display_name())
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(move |this, _, _window, cx| {
+                            this.copy_snippet(language, cx);
+                        }),
+                    )
+            })
+            .collect();
+
+        let redact_toggle = h_flex()
+            .h(px(28.0))
+            .px_3()
+            .gap_2()
+            .items_center()
+            .border_t_1()
+            .border_color(cx.theme().border)
+            .cursor_pointer()
+            .child(Checkbox::new("redact-snippets").checked(self.redact_snippets))
+            .child(div().text_sm().child("Redact secrets"))
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(|this, _, _window, cx| {
+                    this.redact_snippets = !this.redact_snippets;
+                    cx.notify();
+                }),
+            );
+
+        div()
+            .absolute()
+            .top(px(44.0))
+            .right(px(8.0))
+            .min_w(px(180.0))
+            .bg(cx.theme().background)
+            .border_1()
+            .border_color(cx.theme().border)
+            .rounded_md()
+            .child(
+                v_flex()
+                    .gap_1()
+                    .p_1()
+                    .children(items)
+                    .child(redact_toggle),
+            )
+    }
+
     /// Render the method selector dropdown button.
     fn render_method_selector(&self, cx: &mut Context<Self>) -> impl IntoElement {
         let method_name = self.selected_method.to_string();
@@ -555,10 +643,12 @@ impl RequestEditor {
     /// 1. Gets the active tab and validates URL is present
     /// 2. Sets is_loading = true and triggers re-render
     /// 3. Builds the request from the tab state
-    /// 4. Spawns an async task using cx.spawn() for execution
-    /// 5. On completion: updates last_response, sets is_loading = false
-    /// 6. Handles errors gracefully by displaying them in the response viewer
-    fn on_send(&mut self, _window: &mut Window, cx: &mut Context<Self>) {
+    /// 4. If variables are undefined, shows inputs for them and stops; the
+    ///    prompt's Send button calls this again with the entered values
+    /// 5. Spawns an async task using cx.spawn() for execution
+    /// 6. On completion: updates last_response, sets is_loading = false
+    /// 7. Handles errors gracefully by displaying them in the response viewer
+    fn on_send(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         // Get the active tab's draft request to validate URL is present
         let app_state = self.app_state.clone();
 
@@ -575,6 +665,29 @@ impl RequestEditor {
             }
         };
 
+        // A pasted curl command fills in the tab instead of being sent
+        if url.trim_start().starts_with("curl ") {
+            let result = app_state.update(cx, |app, cx| app.apply_curl_to_active_tab(&url, window, cx));
+            if let Err(error) = result {
+                let error_response = HttpResponse {
+                    status: 0,
+                    status_text: "Invalid cURL Command".to_string(),
+                    headers: std::collections::HashMap::new(),
+                    body: bytes::Bytes::from(format!("Error: {}", error.message())),
+                    size_bytes: 0,
+                    elapsed: std::time::Duration::ZERO,
+                    timing: Default::default(),
+                };
+                app_state.update(cx, |app, cx| {
+                    if let Some(tab) = app.active_tab_mut() {
+                        tab.last_response = Some(error_response);
+                    }
+                    cx.notify();
+                });
+            }
+            return;
+        }
+
         // Validate that URL is not empty
         if url.trim().is_empty() {
             let error_response = HttpResponse {
@@ -584,6 +697,7 @@ impl RequestEditor {
                 body: bytes::Bytes::from("Error: URL cannot be empty. Please enter a valid URL."),
                 size_bytes: 0,
                 elapsed: std::time::Duration::ZERO,
+                timing: Default::default(),
             };
 
             app_state.update(cx, |app, cx| {
@@ -655,16 +769,51 @@ impl RequestEditor {
                 headers,
                 query_params,
                 body,
+                auth: Default::default(),
+                settings: Default::default(),
+                variables: Default::default(),
+                scripts: Default::default(),
+                assertions: Vec::new(),
                 created_at: chrono::Utc::now(),
                 updated_at: chrono::Utc::now(),
             }
         };
 
+        // Ask for variables without a value instead of sending a request
+        // that would fail with HttpError::UnresolvedVariables
+        let answers: std::collections::HashMap<String, String> = self
+            .variable_prompt
+            .iter()
+            .map(|(name, input)| (name.clone(), input.read(cx).text().to_string()))
+            .collect();
+        let missing: Vec<String> = core
+            .missing_variables(&request)
+            .into_iter()
+            .filter(|name| !answers.contains_key(name))
+            .collect();
+        if !missing.is_empty() {
+            for name in missing {
+                let input = cx.new(|cx| InputState::new(window, cx));
+                self.variable_prompt.push((name, input));
+            }
+            app_state.update(cx, |app, cx| {
+                if let Some(tab) = app.active_tab_mut() {
+                    tab.is_loading = false;
+                }
+                cx.notify();
+            });
+            return;
+        }
+        self.variable_prompt.clear();
+
         // Use the executor directly to spawn the async task
         let async_cx = cx.to_async();
         async_cx.spawn(async move |cx| {
-            // Execute the request using the core
-            let result = core.execute_request(&request).await;
+            // Execute the request using the core, with any prompted values
+            let result = core
+                .execute_with_overrides(&request, &answers)
+                .await
+                .map(|(response, _)| response);
 
             // Update the app state with the response or error
             app_state.update(cx, |app, cx| {
@@ -682,6 +831,7 @@ impl RequestEditor {
                                 body: bytes::Bytes::from(error_body),
                                 size_bytes: 0,
                                 elapsed: std::time::Duration::ZERO,
+                                timing: Default::default(),
                             });
                         }
                     }
@@ -815,6 +965,55 @@ impl Render for RequestEditor {
             )
         };
 
+        // Build the prompt for variables that have no value
+        let variable_prompt = (!self.variable_prompt.is_empty()).then(|| {
+            v_flex()
+                .id("variable-prompt")
+                .gap_2()
+                .p_2()
+                .border_b_1()
+                .border_color(cx.theme().border)
+                .child(
+                    div()
+                        .text_sm()
+                        .font_weight(gpui::FontWeight::SEMIBOLD)
+                        .text_color(cx.theme().muted_foreground)
+                        .child("Enter values for undefined variables"),
+                )
+                .children(self.variable_prompt.iter().map(|(name, input)| {
+                    h_flex()
+                        .gap_2()
+                        .items_center()
+                        .child(
+                            div()
+                                .w(px(160.0))
+                                .font_family("Monospace")
+                                .text_sm()
+                                .child(format!("{{{{{}}}}}", name)),
+                        )
+                        .child(div().flex_1().child(Input::new(input)))
+                }))
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(
+                            Button::new("send-with-variables")
+                                .label("Send")
+                                .on_click(cx.listener(|this, _, window, cx| {
+                                    this.on_send(window, cx);
+                                })),
+                        )
+                        .child(
+                            Button::new("cancel-variable-prompt")
+                                .label("Cancel")
+                                .on_click(cx.listener(|this, _, _window, cx| {
+                                    this.variable_prompt.clear();
+                                    cx.notify();
+                                })),
+                        ),
+                )
+        });
+
         // Build sub-tab bar
         let active_tab = self.active_sub_tab;
         let sub_tabs: Vec<_> = RequestSubTab::all().iter().map(|&tab| {
@@ -1126,9 +1325,18 @@ impl Render for RequestEditor {
                             .items_center()
                             .child(method_selector)
                             .child(url_input)
-                            .child(send_button),
+                            .child(send_button)
+                            .child(
+                                Button::new("copy-as")
+                                    .label("Copy as...")
+                                    .on_click(cx.listener(|this, _, _window, cx| {
+                                        this.toggle_snippet_menu(cx);
+                                    })),
+                            ),
                     ),
             )
+            // Prompt for undefined variables
+            .children(variable_prompt)
             // Sub-tab bar
             .child(
                 h_flex()
@@ -1146,6 +1354,9 @@ impl Render for RequestEditor {
         if let Some(dropdown) = method_dropdown {
             result = result.child(dropdown);
         }
+        if self.snippet_menu_open {
+            result = result.child(self.render_snippet_menu(cx));
+        }
 
         result
     }
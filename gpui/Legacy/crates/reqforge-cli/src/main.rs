@@ -1,11 +1,18 @@
 use clap::{Parser, Subcommand};
 use reqforge_core::{
-    ReqForgeCore, models::{HttpMethod, BodyType, KeyValuePair, RawContentType},
+    ReqForgeCore, JsonStore, SqliteStore, Store, Interpolator, SecretExport, CollectionLayout, models::{HttpMethod, BodyType, KeyValuePair, MultipartPart, MultipartValue, RawContentType},
+    CollectionRunner, RequestRunResult, RunReport, load_data_file,
     export_collection, import_collection, export_environment, import_environment,
-    export_all, import_all, import_collection_from_postman, import_collection_from_openapi,
+    export_all, import_all, import_collection_from_postman, import_openapi, import_collection_from_har,
+    parse_curl_command, to_curl_command, SnippetLanguage, HistoryFilter,
+    secrets::{decrypt_secrets, is_encrypted},
+    store::{migrate, sqlite_store::DATABASE_FILE},
 };
 use serde_json::Value;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// CLI tool for ReqForge - HTTP client with import/export capabilities
 #[derive(Parser, Debug)]
@@ -23,6 +30,48 @@ enum Commands {
     Execute {
         /// Path to the JSON file containing the request definition
         request_file: PathBuf,
+        /// Environment to activate (ID or name)
+        #[arg(short, long)]
+        environment: Option<String>,
+        /// Show how each variable resolves instead of sending the request
+        /// (pre-request scripts are not run)
+        #[arg(long)]
+        dry_run: bool,
+        /// Ask for the value of any variable that is not defined
+        #[arg(long)]
+        prompt: bool,
+        /// Workspace directory (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        workspace: PathBuf,
+    },
+    /// Run every request in a collection (or folder) and report the results
+    Run {
+        /// Collection ID or name
+        collection: String,
+        /// Only run requests inside this folder (ID or name)
+        #[arg(long)]
+        folder: Option<String>,
+        /// Environment to activate (ID or name)
+        #[arg(short, long)]
+        environment: Option<String>,
+        /// Number of iterations (default: number of data rows, or 1)
+        #[arg(short = 'n', long)]
+        iterations: Option<usize>,
+        /// CSV or JSON data file with per-iteration variables
+        #[arg(short, long)]
+        data: Option<PathBuf>,
+        /// Delay between requests in milliseconds
+        #[arg(long, default_value_t = 0)]
+        delay_ms: u64,
+        /// Stop at the first failed request
+        #[arg(long)]
+        bail: bool,
+        /// Write a JUnit XML report to this path
+        #[arg(long)]
+        junit: Option<PathBuf>,
+        /// Write a JSON report to this path
+        #[arg(long)]
+        json: Option<PathBuf>,
         /// Workspace directory (default: current directory)
         #[arg(short, long, default_value = ".")]
         workspace: PathBuf,
@@ -35,18 +84,110 @@ enum Commands {
         /// Output file path
         #[arg(short, long)]
         output: PathBuf,
+        /// Secret variable values: strip, encrypt or include (default: strip)
+        #[arg(long, default_value = "strip")]
+        secrets: String,
+        /// Passphrase for --secrets encrypt (default: $REQFORGE_PASSPHRASE)
+        #[arg(long)]
+        passphrase: Option<String>,
         /// Workspace directory (default: current directory)
         #[arg(short, long, default_value = ".")]
         workspace: PathBuf,
     },
-    /// Import a collection from a JSON file
+    /// Import a collection from a JSON, Postman, OpenAPI (JSON or YAML) or HAR file
     ImportCollection {
         /// Input file path
         #[arg(short, long)]
         input: PathBuf,
-        /// Import format (default: json)
+        /// Import format: json, postman, openapi or har (default: json)
         #[arg(short, long, default_value = "json")]
         format: String,
+        /// Passphrase for encrypted secret values (default: $REQFORGE_PASSPHRASE)
+        #[arg(long)]
+        passphrase: Option<String>,
+        /// Workspace directory (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        workspace: PathBuf,
+    },
+    /// Add a request to a collection from a curl command line
+    ImportCurl {
+        /// The curl command (default: read from stdin)
+        command: Option<String>,
+        /// Collection to add the request to (ID or name)
+        #[arg(short, long)]
+        collection: String,
+        /// Folder inside the collection (ID or name)
+        #[arg(long)]
+        folder: Option<String>,
+        /// Request name (default: method and path)
+        #[arg(short, long)]
+        name: Option<String>,
+        /// Workspace directory (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        workspace: PathBuf,
+    },
+    /// Print a saved request as a curl command line
+    ExportCurl {
+        /// Request ID or name
+        request: String,
+        /// Only look in this collection (ID or name)
+        #[arg(short, long)]
+        collection: Option<String>,
+        /// Environment to resolve variables from (ID or name)
+        #[arg(short, long)]
+        environment: Option<String>,
+        /// Workspace directory (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        workspace: PathBuf,
+    },
+    /// Print a saved request as code in another language
+    Snippet {
+        /// Request ID or name
+        request: String,
+        /// curl, rust, python, javascript, go, httpie or powershell
+        #[arg(short, long, default_value = "curl")]
+        language: String,
+        /// Replace secret variables and credentials with <redacted>
+        #[arg(long)]
+        redact: bool,
+        /// Only look in this collection (ID or name)
+        #[arg(short, long)]
+        collection: Option<String>,
+        /// Environment to resolve variables from (ID or name)
+        #[arg(short, long)]
+        environment: Option<String>,
+        /// Workspace directory (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        workspace: PathBuf,
+    },
+    /// Export request history, with stored response bodies, to a HAR file
+    ExportHar {
+        /// Output file path
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Only entries whose URL contains this text
+        #[arg(long)]
+        url: Option<String>,
+        /// Only the most recent N matching entries
+        #[arg(long)]
+        last: Option<usize>,
+        /// Replace credentials and secret-looking headers and query parameters with <redacted>
+        #[arg(long)]
+        redact: bool,
+        /// Workspace directory (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        workspace: PathBuf,
+    },
+    /// Generate an OpenAPI 3.1 document (JSON, or YAML for .yaml/.yml) from a collection and its history
+    ExportOpenapi {
+        /// Collection to describe (ID or name)
+        collection: String,
+        /// Output file path
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Environment to take server URLs from (ID or name)
+        #[arg(short, long)]
+        environment: Option<String>,
         /// Workspace directory (default: current directory)
         #[arg(short, long, default_value = ".")]
         workspace: PathBuf,
@@ -59,6 +200,12 @@ enum Commands {
         /// Output file path
         #[arg(short, long)]
         output: PathBuf,
+        /// Secret values: strip, encrypt or include (default: strip)
+        #[arg(long, default_value = "strip")]
+        secrets: String,
+        /// Passphrase for --secrets encrypt (default: $REQFORGE_PASSPHRASE)
+        #[arg(long)]
+        passphrase: Option<String>,
         /// Workspace directory (default: current directory)
         #[arg(short, long, default_value = ".")]
         workspace: PathBuf,
@@ -68,6 +215,9 @@ enum Commands {
         /// Input file path
         #[arg(short, long)]
         input: PathBuf,
+        /// Passphrase for encrypted secret values (default: $REQFORGE_PASSPHRASE)
+        #[arg(long)]
+        passphrase: Option<String>,
         /// Workspace directory (default: current directory)
         #[arg(short, long, default_value = ".")]
         workspace: PathBuf,
@@ -77,6 +227,12 @@ enum Commands {
         /// Output zip file path
         #[arg(short, long)]
         output: PathBuf,
+        /// Secret values: strip, encrypt or include (default: strip)
+        #[arg(long, default_value = "strip")]
+        secrets: String,
+        /// Passphrase for --secrets encrypt (default: $REQFORGE_PASSPHRASE)
+        #[arg(long)]
+        passphrase: Option<String>,
         /// Workspace directory (default: current directory)
         #[arg(short, long, default_value = ".")]
         workspace: PathBuf,
@@ -86,6 +242,40 @@ enum Commands {
         /// Input zip file path
         #[arg(short, long)]
         input: PathBuf,
+        /// Passphrase for encrypted secret values (default: $REQFORGE_PASSPHRASE)
+        #[arg(long)]
+        passphrase: Option<String>,
+        /// Workspace directory (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        workspace: PathBuf,
+    },
+    /// Convert the workspace's collections to another on-disk layout
+    Layout {
+        /// json (one file per collection) or files (one YAML file per request)
+        to: String,
+        /// Workspace directory (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        workspace: PathBuf,
+    },
+    /// Move the workspace, including request history, to another storage backend
+    Backend {
+        /// json (files in the workspace directory) or sqlite (a single reqforge.db database)
+        to: String,
+        /// Passphrase, if secrets are locked (default: $REQFORGE_PASSPHRASE)
+        #[arg(long)]
+        passphrase: Option<String>,
+        /// Workspace directory (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        workspace: PathBuf,
+    },
+    /// Protect stored secret values with a passphrase, or switch back to a local keyfile
+    Secrets {
+        /// New passphrase (omit to use a keyfile)
+        #[arg(long)]
+        passphrase: Option<String>,
+        /// Current passphrase, if secrets are locked (default: $REQFORGE_PASSPHRASE)
+        #[arg(long)]
+        current: Option<String>,
         /// Workspace directory (default: current directory)
         #[arg(short, long, default_value = ".")]
         workspace: PathBuf,
@@ -97,26 +287,57 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Execute { request_file, workspace } => {
-            execute_request(request_file, workspace).await?;
+        Commands::Execute { request_file, environment, dry_run, prompt, workspace } => {
+            execute_request(request_file, environment, dry_run, prompt, workspace).await?;
+        }
+        Commands::Run { collection, folder, environment, iterations, data, delay_ms, bail, junit, json, workspace } => {
+            let options = RunOptions { folder, environment, iterations, data, delay_ms, bail, junit, json };
+            let passed = run_collection_cmd(collection, options, workspace).await?;
+            if !passed {
+                std::process::exit(1);
+            }
+        }
+        Commands::ExportCollection { id, output, secrets, passphrase, workspace } => {
+            export_collection_cmd(id, output, secret_export(&secrets, passphrase)?, workspace)?;
+        }
+        Commands::ImportCollection { input, format, passphrase, workspace } => {
+            import_collection_cmd(input, format, passphrase_or_env(passphrase), workspace)?;
         }
-        Commands::ExportCollection { id, output, workspace } => {
-            export_collection_cmd(id, output, workspace)?;
+        Commands::ImportCurl { command, collection, folder, name, workspace } => {
+            import_curl_cmd(command, collection, folder, name, workspace)?;
         }
-        Commands::ImportCollection { input, format, workspace } => {
-            import_collection_cmd(input, format, workspace)?;
+        Commands::ExportCurl { request, collection, environment, workspace } => {
+            export_curl_cmd(request, collection, environment, workspace)?;
         }
-        Commands::ExportEnvironment { id, output, workspace } => {
-            export_environment_cmd(id, output, workspace)?;
+        Commands::Snippet { request, language, redact, collection, environment, workspace } => {
+            snippet_cmd(request, language, redact, collection, environment, workspace)?;
         }
-        Commands::ImportEnvironment { input, workspace } => {
-            import_environment_cmd(input, workspace)?;
+        Commands::ExportHar { output, url, last, redact, workspace } => {
+            export_har_cmd(output, url, last, redact, workspace)?;
         }
-        Commands::ExportWorkspace { output, workspace } => {
-            export_workspace_cmd(output, workspace)?;
+        Commands::ExportOpenapi { collection, output, environment, workspace } => {
+            export_openapi_cmd(collection, output, environment, workspace)?;
         }
-        Commands::ImportWorkspace { input, workspace } => {
-            import_workspace_cmd(input, workspace)?;
+        Commands::ExportEnvironment { id, output, secrets, passphrase, workspace } => {
+            export_environment_cmd(id, output, secret_export(&secrets, passphrase)?, workspace)?;
+        }
+        Commands::ImportEnvironment { input, passphrase, workspace } => {
+            import_environment_cmd(input, passphrase_or_env(passphrase), workspace)?;
+        }
+        Commands::ExportWorkspace { output, secrets, passphrase, workspace } => {
+            export_workspace_cmd(output, secret_export(&secrets, passphrase)?, workspace)?;
+        }
+        Commands::ImportWorkspace { input, passphrase, workspace } => {
+            import_workspace_cmd(input, passphrase_or_env(passphrase), workspace)?;
+        }
+        Commands::Layout { to, workspace } => {
+            layout_cmd(to, workspace)?;
+        }
+        Commands::Backend { to, passphrase, workspace } => {
+            backend_cmd(to, passphrase_or_env(passphrase), workspace)?;
+        }
+        Commands::Secrets { passphrase, current, workspace } => {
+            secrets_cmd(passphrase, passphrase_or_env(current), workspace)?;
         }
     }
 
@@ -124,7 +345,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 /// Execute a single HTTP request
-async fn execute_request(request_file: PathBuf, workspace: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+async fn execute_request(
+    request_file: PathBuf,
+    environment: Option<String>,
+    dry_run: bool,
+    prompt: bool,
+    workspace: PathBuf,
+) -> Result<(), Box<dyn std::error::Error>> {
     // Read and parse the JSON file
     let json_content = std::fs::read_to_string(&request_file)
         .map_err(|e| format!("Failed to read file {}: {}", request_file.display(), e))?;
@@ -136,12 +363,28 @@ async fn execute_request(request_file: PathBuf, workspace: PathBuf) -> Result<()
     let request_definition = parse_request_definition(json_value)?;
 
     // Create ReqForgeCore instance
-    let mut core = ReqForgeCore::open(workspace)?;
+    let mut core = open_workspace(&workspace)?;
+    if let Some(environment) = &environment {
+        let env = core.environments.iter()
+            .find(|e| e.id.to_string() == *environment || e.name == *environment)
+            .ok_or_else(|| format!("Environment '{}' not found", environment))?;
+        core.active_environment_id = Some(env.id);
+    }
+
+    if dry_run {
+        print_dry_run(&core, &request_definition);
+        return Ok(());
+    }
 
     // Execute the request
     println!("Executing request: {} {}\n", request_definition.method, request_definition.url);
 
-    let response = core.execute_request(&request_definition).await?;
+    let answers = if prompt {
+        prompt_for_variables(&core.missing_variables(&request_definition))?
+    } else {
+        HashMap::new()
+    };
+    let (response, _) = core.execute_with_overrides(&request_definition, &answers).await?;
 
     // Print the response
     print_response(&response);
@@ -149,9 +392,136 @@ async fn execute_request(request_file: PathBuf, workspace: PathBuf) -> Result<()
     Ok(())
 }
 
+/// Options for the `run` command
+struct RunOptions {
+    folder: Option<String>,
+    environment: Option<String>,
+    iterations: Option<usize>,
+    data: Option<PathBuf>,
+    delay_ms: u64,
+    bail: bool,
+    junit: Option<PathBuf>,
+    json: Option<PathBuf>,
+}
+
+/// Run a collection headlessly. Returns whether every request passed.
+async fn run_collection_cmd(collection: String, options: RunOptions, workspace: PathBuf) -> Result<bool, Box<dyn std::error::Error>> {
+    let mut core = open_workspace(&workspace)?;
+
+    let collection = core.collections.iter()
+        .find(|c| c.id.to_string() == collection || c.name == collection)
+        .ok_or_else(|| format!("Collection '{}' not found", collection))?;
+    let collection_id = collection.id;
+
+    let folder_id = match &options.folder {
+        Some(folder) => {
+            let found = uuid::Uuid::parse_str(folder).ok()
+                .and_then(|id| collection.find_folder(id))
+                .or_else(|| collection.find_folder_by_name(folder))
+                .ok_or_else(|| format!("Folder '{}' not found in collection '{}'", folder, collection.name))?;
+            Some(found.id)
+        }
+        None => None,
+    };
+
+    if let Some(environment) = &options.environment {
+        let env = core.environments.iter()
+            .find(|e| e.id.to_string() == *environment || e.name == *environment)
+            .ok_or_else(|| format!("Environment '{}' not found", environment))?;
+        core.active_environment_id = Some(env.id);
+    }
+
+    let mut runner = CollectionRunner::new(&core, collection_id)
+        .with_delay(Duration::from_millis(options.delay_ms))
+        .with_stop_on_failure(options.bail);
+    if let Some(folder_id) = folder_id {
+        runner = runner.with_folder(folder_id);
+    }
+    if let Some(iterations) = options.iterations {
+        runner = runner.with_iterations(iterations);
+    }
+    if let Some(data) = &options.data {
+        let rows = load_data_file(data)
+            .map_err(|e| format!("Failed to load data file {}: {}", data.display(), e))?;
+        runner = runner.with_data(rows);
+    }
+
+    println!("{:<5} {:<4} {:<7} {:<40} {:<7} {:>8}  TESTS", "ITER", "", "METHOD", "REQUEST", "STATUS", "TIME");
+    let report = runner.run_with_progress(print_run_row).await?;
+    print_run_summary(&report);
+
+    if let Some(path) = &options.junit {
+        std::fs::write(path, report.to_junit_xml())
+            .map_err(|e| format!("Failed to write JUnit report {}: {}", path.display(), e))?;
+        println!("JUnit report written to: {}", path.display());
+    }
+    if let Some(path) = &options.json {
+        let json = serde_json::to_string_pretty(&report)?;
+        std::fs::write(path, json)
+            .map_err(|e| format!("Failed to write JSON report {}: {}", path.display(), e))?;
+        println!("JSON report written to: {}", path.display());
+    }
+
+    Ok(report.all_passed())
+}
+
+/// Print one row of the live run table
+fn print_run_row(result: &RequestRunResult) {
+    let mut name = result.folder_path.clone();
+    name.push(result.request_name.clone());
+    let name = truncate(&name.join(" / "), 40);
+    let status = result.status.map(|s| s.to_string()).unwrap_or_else(|| "ERR".to_string());
+    let tests = if result.test_report.is_empty() {
+        "-".to_string()
+    } else {
+        format!("{}/{}", result.test_report.passed_count(), result.test_report.results.len())
+    };
+    let mark = if result.passed() { "ok" } else { "FAIL" };
+
+    println!(
+        "{:<5} {:<4} {:<7} {:<40} {:<7} {:>6}ms  {}",
+        result.iteration + 1, mark, result.method.to_string(), name, status, result.elapsed_millis, tests,
+    );
+    if let Some(error) = &result.error {
+        println!("      error: {}", error);
+    }
+    for failure in result.test_report.results.iter().filter(|r| !r.passed) {
+        println!("      failed: {} - {}", failure.assertion, failure.message.as_deref().unwrap_or("assertion failed"));
+    }
+}
+
+/// Print totals after a run
+fn print_run_summary(report: &RunReport) {
+    println!();
+    println!("Collection: {}", report.collection_name);
+    println!("Iterations: {}", report.iterations);
+    println!(
+        "Requests:   {} total, {} failed",
+        report.total_requests(), report.failed_requests(),
+    );
+    println!(
+        "Assertions: {} passed, {} failed",
+        report.passed_assertions(), report.failed_assertions(),
+    );
+    println!("Duration:   {} ms", report.duration_millis);
+    if report.stopped_early {
+        println!("Run stopped early after the first failure");
+    }
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        text.to_string()
+    } else {
+        let mut out: String = text.chars().take(max - 1).collect();
+        out.push('…');
+        out
+    }
+}
+
 /// Export a collection to a JSON file
-fn export_collection_cmd(id: String, output: PathBuf, workspace: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-    let core = ReqForgeCore::open(workspace)?;
+fn export_collection_cmd(id: String, output: PathBuf, secrets: SecretExport, workspace: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let core = open_workspace(&workspace)?;
 
     let collection_id = uuid::Uuid::parse_str(&id)
         .map_err(|e| format!("Invalid collection ID: {}", e))?;
@@ -160,7 +530,7 @@ fn export_collection_cmd(id: String, output: PathBuf, workspace: PathBuf) -> Res
         .find(|c| c.id == collection_id)
         .ok_or_else(|| format!("Collection with ID {} not found", id))?;
 
-    export_collection(collection, &output)
+    export_collection(collection, &output, &secrets)
         .map_err(|e| format!("Failed to export collection: {}", e))?;
 
     println!("Collection exported successfully to: {}", output.display());
@@ -168,18 +538,26 @@ fn export_collection_cmd(id: String, output: PathBuf, workspace: PathBuf) -> Res
 }
 
 /// Import a collection from a JSON file
-fn import_collection_cmd(input: PathBuf, format: String, workspace: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-    let mut core = ReqForgeCore::open(&workspace)?;
+fn import_collection_cmd(input: PathBuf, format: String, passphrase: Option<String>, workspace: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let mut core = open_workspace(&workspace)?;
 
-    let collection = match format.to_lowercase().as_str() {
+    let mut environments = Vec::new();
+    let mut collection = match format.to_lowercase().as_str() {
         "json" => import_collection(&input)
             .map_err(|e| format!("Failed to import JSON collection: {}", e))?,
         "postman" => import_collection_from_postman(&input)
             .map_err(|e| format!("Failed to import Postman collection: {}", e))?,
-        "openapi" | "swagger" => import_collection_from_openapi(&input)
-            .map_err(|e| format!("Failed to import OpenAPI spec: {}", e))?,
-        _ => return Err(format!("Unsupported format: {}. Supported: json, postman, openapi", format).into()),
+        "openapi" | "swagger" => {
+            let import = import_openapi(&input)
+                .map_err(|e| format!("Failed to import OpenAPI spec: {}", e))?;
+            environments = import.environments;
+            import.collection
+        }
+        "har" => import_collection_from_har(&input)
+            .map_err(|e| format!("Failed to import HAR file: {}", e))?,
+        _ => return Err(format!("Unsupported format: {}. Supported: json, postman, openapi, har", format).into()),
     };
+    decrypt_imported_collection(&mut collection, passphrase.as_deref())?;
 
     println!("Imported collection: '{}' with {} requests", collection.name, collection.requests.len());
 
@@ -188,12 +566,223 @@ fn import_collection_cmd(input: PathBuf, format: String, workspace: PathBuf) ->
         .map_err(|e| format!("Failed to save collection to workspace: {}", e))?;
 
     println!("Collection saved to workspace");
+
+    // One environment per server in an OpenAPI spec
+    if !environments.is_empty() {
+        for environment in &environments {
+            println!("Imported environment: '{}'", environment.name);
+        }
+        core.environments.extend(environments);
+        core.store.save_environments(&core.environments)
+            .map_err(|e| format!("Failed to save environments to workspace: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Parse a curl command and save the request into a collection
+fn import_curl_cmd(
+    command: Option<String>,
+    collection: String,
+    folder: Option<String>,
+    name: Option<String>,
+    workspace: PathBuf,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let command = match command {
+        Some(command) => command,
+        None => {
+            let mut command = String::new();
+            std::io::stdin().read_to_string(&mut command)?;
+            command
+        }
+    };
+    let mut request = parse_curl_command(&command)
+        .map_err(|e| format!("Failed to import curl command: {}", e))?;
+    if let Some(name) = name {
+        request.name = name;
+    }
+
+    let core = open_workspace(&workspace)?;
+    let mut collection = core.collections.iter()
+        .find(|c| c.id.to_string() == collection || c.name == collection)
+        .ok_or_else(|| format!("Collection '{}' not found", collection))?
+        .clone();
+    let folder_id = match &folder {
+        Some(folder) => {
+            let found = uuid::Uuid::parse_str(folder).ok()
+                .and_then(|id| collection.find_folder(id))
+                .or_else(|| collection.find_folder_by_name(folder))
+                .ok_or_else(|| format!("Folder '{}' not found in collection '{}'", folder, collection.name))?;
+            Some(found.id)
+        }
+        None => None,
+    };
+
+    println!("Imported request: {} {} as '{}'", request.method, request.url, request.name);
+    collection.add_request(request, folder_id);
+    core.store.save_collection(&collection)
+        .map_err(|e| format!("Failed to save collection to workspace: {}", e))?;
+
+    println!("Added to collection '{}'", collection.name);
+    Ok(())
+}
+
+/// Print a saved request, with variables resolved, as a curl command
+fn export_curl_cmd(
+    request: String,
+    collection: Option<String>,
+    environment: Option<String>,
+    workspace: PathBuf,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let core = open_workspace_with_environment(&workspace, environment.as_deref())?;
+    let found = find_request(&core, &request, collection.as_deref())?;
+    println!("{}", to_curl_command(&core.resolve_request(found)));
+    Ok(())
+}
+
+/// Print a saved request, with variables resolved, as a code snippet
+fn snippet_cmd(
+    request: String,
+    language: String,
+    redact: bool,
+    collection: Option<String>,
+    environment: Option<String>,
+    workspace: PathBuf,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let language = SnippetLanguage::from_name(&language).ok_or_else(|| {
+        let names: Vec<&str> = SnippetLanguage::all().iter().map(|l| l.name()).collect();
+        format!("Unsupported language: {}. Supported: {}", language, names.join(", "))
+    })?;
+    let core = open_workspace_with_environment(&workspace, environment.as_deref())?;
+    let found = find_request(&core, &request, collection.as_deref())?;
+    println!("{}", core.code_snippet(found, language, redact));
+    Ok(())
+}
+
+/// Write matching history entries to a HAR file
+fn export_har_cmd(
+    output: PathBuf,
+    url: Option<String>,
+    last: Option<usize>,
+    redact: bool,
+    workspace: PathBuf,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let core = open_workspace(&workspace)?;
+    let filter = HistoryFilter { url, ..Default::default() };
+    let mut entries = core.search_history(&filter);
+    if let Some(last) = last {
+        entries.truncate(last);
+    }
+    if entries.is_empty() {
+        return Err("No matching history entries".into());
+    }
+
+    core.export_history_har(&entries, redact, &output)
+        .map_err(|e| format!("Failed to export HAR: {}", e))?;
+    println!("Exported {} history entries to: {}", entries.len(), output.display());
+    Ok(())
+}
+
+/// Write an OpenAPI document describing a collection
+fn export_openapi_cmd(
+    collection: String,
+    output: PathBuf,
+    environment: Option<String>,
+    workspace: PathBuf,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let core = open_workspace_with_environment(&workspace, environment.as_deref())?;
+    let collection = core.collections.iter()
+        .find(|c| c.id.to_string() == collection || c.name == collection)
+        .ok_or_else(|| format!("Collection '{}' not found", collection))?;
+
+    core.export_openapi(collection, &output)
+        .map_err(|e| format!("Failed to export OpenAPI document: {}", e))?;
+    println!("OpenAPI document for '{}' written to: {}", collection.name, output.display());
+    Ok(())
+}
+
+/// Open a workspace and activate an environment (ID or name)
+fn open_workspace_with_environment(workspace: &Path, environment: Option<&str>) -> Result<ReqForgeCore, Box<dyn std::error::Error>> {
+    let mut core = open_workspace(workspace)?;
+    if let Some(environment) = environment {
+        let env = core.environments.iter()
+            .find(|e| e.id.to_string() == environment || e.name == environment)
+            .ok_or_else(|| format!("Environment '{}' not found", environment))?;
+        core.active_environment_id = Some(env.id);
+    }
+    Ok(core)
+}
+
+/// Find a saved request by ID or name, optionally within one collection (ID or name)
+fn find_request<'a>(
+    core: &'a ReqForgeCore,
+    request: &str,
+    collection: Option<&str>,
+) -> Result<&'a reqforge_core::RequestDefinition, String> {
+    let mut matches = core.collections.iter()
+        .filter(|c| collection.is_none_or(|name| c.id.to_string() == name || c.name == name))
+        .flat_map(|c| c.requests.values())
+        .filter(|r| r.id.to_string() == request || r.name == request);
+    let found = matches.next().ok_or_else(|| format!("Request '{}' not found", request))?;
+    if matches.next().is_some() {
+        return Err(format!("More than one request is named '{}'; use its ID or --collection", request));
+    }
+    Ok(found)
+}
+
+/// Open a workspace, unlocking passphrase-protected secrets from $REQFORGE_PASSPHRASE
+fn open_workspace(workspace: &Path) -> Result<ReqForgeCore, Box<dyn std::error::Error>> {
+    let mut core = ReqForgeCore::open(workspace)?;
+    if core.store.secrets_locked()? {
+        match passphrase_or_env(None) {
+            Some(passphrase) => core.unlock_secrets(&passphrase)
+                .map_err(|e| format!("Failed to unlock secrets: {}", e))?,
+            None => eprintln!("Secrets are locked; set REQFORGE_PASSPHRASE to use them"),
+        }
+    }
+    Ok(core)
+}
+
+fn passphrase_or_env(passphrase: Option<String>) -> Option<String> {
+    passphrase.or_else(|| std::env::var("REQFORGE_PASSPHRASE").ok())
+}
+
+fn secret_export(mode: &str, passphrase: Option<String>) -> Result<SecretExport, String> {
+    match mode.to_lowercase().as_str() {
+        "strip" => Ok(SecretExport::Strip),
+        "include" | "plaintext" => Ok(SecretExport::Plaintext),
+        "encrypt" => passphrase_or_env(passphrase)
+            .map(SecretExport::Encrypt)
+            .ok_or_else(|| "--secrets encrypt needs --passphrase or REQFORGE_PASSPHRASE".to_string()),
+        _ => Err(format!("Unsupported secrets mode: {}. Supported: strip, encrypt, include", mode)),
+    }
+}
+
+/// Decrypt `enc:v1:` secret values in an imported environment or collection
+fn decrypt_imported(name: &str, variables: &mut [reqforge_core::models::Variable], passphrase: Option<&str>) -> Result<(), String> {
+    if !variables.iter().any(|v| is_encrypted(&v.value)) {
+        return Ok(());
+    }
+    let passphrase = passphrase.ok_or_else(|| {
+        format!("'{}' has encrypted secrets; pass --passphrase or set REQFORGE_PASSPHRASE", name)
+    })?;
+    decrypt_secrets(variables, passphrase)
+        .map_err(|e| format!("Failed to decrypt secrets in '{}': {}", name, e))?;
+    Ok(())
+}
+
+/// `decrypt_imported` for a collection's variables and those of its folders and requests
+fn decrypt_imported_collection(collection: &mut reqforge_core::models::Collection, passphrase: Option<&str>) -> Result<(), String> {
+    let name = collection.name.clone();
+    decrypt_imported(&name, &mut collection.variables, passphrase)?;
+    for (_, variables) in collection.item_variables_mut() {
+        decrypt_imported(&name, variables, passphrase)?;
+    }
     Ok(())
 }
 
 /// Export an environment to a JSON file
-fn export_environment_cmd(id: String, output: PathBuf, workspace: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-    let core = ReqForgeCore::open(workspace)?;
+fn export_environment_cmd(id: String, output: PathBuf, secrets: SecretExport, workspace: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let core = open_workspace(&workspace)?;
 
     let environment_id = uuid::Uuid::parse_str(&id)
         .map_err(|e| format!("Invalid environment ID: {}", e))?;
@@ -202,7 +791,7 @@ fn export_environment_cmd(id: String, output: PathBuf, workspace: PathBuf) -> Re
         .find(|e| e.id == environment_id)
         .ok_or_else(|| format!("Environment with ID {} not found", id))?;
 
-    export_environment(environment, &output)
+    export_environment(environment, &output, &secrets)
         .map_err(|e| format!("Failed to export environment: {}", e))?;
 
     println!("Environment exported successfully to: {}", output.display());
@@ -210,13 +799,14 @@ fn export_environment_cmd(id: String, output: PathBuf, workspace: PathBuf) -> Re
 }
 
 /// Import an environment from a JSON file
-fn import_environment_cmd(input: PathBuf, workspace: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-    let environment = import_environment(&input)
+fn import_environment_cmd(input: PathBuf, passphrase: Option<String>, workspace: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let mut environment = import_environment(&input)
         .map_err(|e| format!("Failed to import environment: {}", e))?;
+    decrypt_imported(&environment.name, &mut environment.variables, passphrase.as_deref())?;
 
     println!("Imported environment: '{}' with {} variables", environment.name, environment.variables.len());
 
-    let mut core = ReqForgeCore::open(&workspace)?;
+    let mut core = open_workspace(&workspace)?;
 
     // Check if environment already exists
     if core.environments.iter().any(|e| e.id == environment.id) {
@@ -232,12 +822,12 @@ fn import_environment_cmd(input: PathBuf, workspace: PathBuf) -> Result<(), Box<
 }
 
 /// Export entire workspace to a zip archive
-fn export_workspace_cmd(output: PathBuf, workspace: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-    let core = ReqForgeCore::open(&workspace)?;
+fn export_workspace_cmd(output: PathBuf, secrets: SecretExport, workspace: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let core = open_workspace(&workspace)?;
 
     println!("Exporting {} collections and {} environments...", core.collections.len(), core.environments.len());
 
-    export_all(&core.collections, &core.environments, &output)
+    export_all(&core.collections, &core.environments, &output, &secrets)
         .map_err(|e| format!("Failed to export workspace: {}", e))?;
 
     println!("Workspace exported successfully to: {}", output.display());
@@ -245,13 +835,19 @@ fn export_workspace_cmd(output: PathBuf, workspace: PathBuf) -> Result<(), Box<d
 }
 
 /// Import entire workspace from a zip archive
-fn import_workspace_cmd(input: PathBuf, workspace: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-    let workspace_import = import_all(&input)
+fn import_workspace_cmd(input: PathBuf, passphrase: Option<String>, workspace: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let mut workspace_import = import_all(&input)
         .map_err(|e| format!("Failed to import workspace: {}", e))?;
+    for collection in &mut workspace_import.collections {
+        decrypt_imported_collection(collection, passphrase.as_deref())?;
+    }
+    for environment in &mut workspace_import.environments {
+        decrypt_imported(&environment.name, &mut environment.variables, passphrase.as_deref())?;
+    }
 
     println!("Imported {} collections and {} environments", workspace_import.collections.len(), workspace_import.environments.len());
 
-    let core = ReqForgeCore::open(&workspace)?;
+    let core = open_workspace(&workspace)?;
 
     // Save all imported collections and environments
     for collection in &workspace_import.collections {
@@ -283,6 +879,114 @@ fn import_workspace_cmd(input: PathBuf, workspace: PathBuf) -> Result<(), Box<dy
     Ok(())
 }
 
+/// Convert collections to another on-disk layout
+fn layout_cmd(to: String, workspace: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let layout = match to.to_lowercase().as_str() {
+        "json" => CollectionLayout::Json,
+        "files" | "yaml" => CollectionLayout::Files,
+        _ => return Err(format!("Unsupported layout: {}. Supported: json, files", to).into()),
+    };
+    if workspace.join(DATABASE_FILE).exists() {
+        return Err("Workspace uses the sqlite backend; layouts only apply to the json backend".into());
+    }
+    let mut store = JsonStore::open(&workspace)?;
+    if store.collection_layout() == layout {
+        println!("Workspace already uses the {} layout", to.to_lowercase());
+        return Ok(());
+    }
+    let converted = store.set_collection_layout(layout)
+        .map_err(|e| format!("Failed to convert collections: {}", e))?;
+
+    println!("Converted {} collections to the {} layout", converted, to.to_lowercase());
+    Ok(())
+}
+
+/// Set or remove the passphrase protecting stored secrets
+fn secrets_cmd(passphrase: Option<String>, current: Option<String>, workspace: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let core = ReqForgeCore::open(&workspace)?;
+    if core.store.secrets_locked()? {
+        let current = current.ok_or("Secrets are locked; pass --current or set REQFORGE_PASSPHRASE")?;
+        core.store.unlock_secrets(&current)
+            .map_err(|e| format!("Failed to unlock secrets: {}", e))?;
+    }
+    core.store.set_secrets_passphrase(passphrase.as_deref())
+        .map_err(|e| format!("Failed to re-encrypt secrets: {}", e))?;
+
+    match passphrase {
+        Some(_) => println!("Secrets are now protected by a passphrase"),
+        None => println!("Secrets are now protected by a local keyfile"),
+    }
+    Ok(())
+}
+
+/// Copy the workspace into another storage backend. The JSON files are left
+/// in place when moving to sqlite; the database is removed when moving back.
+fn backend_cmd(to: String, passphrase: Option<String>, workspace: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let database = workspace.join(DATABASE_FILE);
+    let to_sqlite = match to.to_lowercase().as_str() {
+        "sqlite" => true,
+        "json" => false,
+        _ => return Err(format!("Unsupported backend: {}. Supported: json, sqlite", to).into()),
+    };
+    if database.exists() == to_sqlite {
+        println!("Workspace already uses the {} backend", to.to_lowercase());
+        return Ok(());
+    }
+
+    let result = (|| -> Result<_, Box<dyn std::error::Error>> {
+        // Files left from an earlier move to sqlite are out of date, and their
+        // secrets may be locked with an old passphrase
+        if !to_sqlite {
+            for name in ["secrets.json", "secret.key"] {
+                let path = workspace.join(name);
+                if path.exists() {
+                    std::fs::remove_file(path)?;
+                }
+            }
+        }
+        let (from, target): (Box<dyn Store>, Box<dyn Store>) = if to_sqlite {
+            (Box::new(JsonStore::open(&workspace)?), Box::new(SqliteStore::open(&database)?))
+        } else {
+            (Box::new(SqliteStore::open(&database)?), Box::new(JsonStore::open(&workspace)?))
+        };
+        let protected = from.secrets_locked()?;
+        if protected {
+            let passphrase = passphrase.as_deref().ok_or("Secrets are locked; pass --passphrase or set REQFORGE_PASSPHRASE")?;
+            from.unlock_secrets(passphrase)
+                .map_err(|e| format!("Failed to unlock secrets: {}", e))?;
+        }
+        if !to_sqlite {
+            for col in target.list_collections()? {
+                target.delete_collection(&col)?;
+            }
+        }
+        let report = migrate(from.as_ref(), target.as_ref())?;
+        if let (true, Some(passphrase)) = (protected, passphrase.as_deref()) {
+            target.set_secrets_passphrase(Some(passphrase))?;
+        }
+        Ok(report)
+    })();
+
+    // A failed move to sqlite must not leave a half-written database that
+    // would be opened instead of the JSON files; a move back removes it
+    let remove_database = result.is_err() == to_sqlite;
+    if remove_database {
+        for name in [DATABASE_FILE.to_string(), format!("{}.key", DATABASE_FILE)] {
+            let path = workspace.join(name);
+            if path.exists() {
+                std::fs::remove_file(path)?;
+            }
+        }
+    }
+    let report = result?;
+
+    println!(
+        "Moved {} collections, {} environments, {} globals and {} history entries to the {} backend",
+        report.collections, report.environments, report.globals, report.history, to.to_lowercase()
+    );
+    Ok(())
+}
+
 fn parse_request_definition(json: Value) -> Result<reqforge_core::RequestDefinition, String> {
     use chrono::Utc;
     use uuid::Uuid;
@@ -352,6 +1056,20 @@ fn parse_request_definition(json: Value) -> Result<reqforge_core::RequestDefinit
     // Parse body
     let body = parse_body_type(json.get("body"))?;
 
+    // Parse auth (same shape as the stored AuthConfig)
+    let auth = match json.get("auth") {
+        Some(value) => serde_json::from_value(value.clone())
+            .map_err(|e| format!("Invalid 'auth' field: {}", e))?,
+        None => Default::default(),
+    };
+
+    // Parse per-request engine overrides (same shape as RequestSettings)
+    let settings = match json.get("settings") {
+        Some(value) => serde_json::from_value(value.clone())
+            .map_err(|e| format!("Invalid 'settings' field: {}", e))?,
+        None => Default::default(),
+    };
+
     let now = Utc::now();
 
     Ok(reqforge_core::RequestDefinition {
@@ -362,6 +1080,11 @@ fn parse_request_definition(json: Value) -> Result<reqforge_core::RequestDefinit
         headers,
         query_params,
         body,
+        auth,
+        settings,
+        variables: Default::default(),
+        scripts: Default::default(),
+        assertions: Vec::new(),
         created_at: now,
         updated_at: now,
     })
@@ -412,6 +1135,40 @@ fn parse_body_type(body_value: Option<&Value>) -> Result<BodyType, String> {
                             .collect();
                         Ok(BodyType::FormUrlEncoded(fields?))
                     }
+                    "Multipart" => {
+                        // Parts are either {"name", "value"} or {"name", "file", "filename"?}
+                        let parts = map.get("parts")
+                            .and_then(|v| v.as_array())
+                            .unwrap_or(&vec![])
+                            .iter()
+                            .map(|v| {
+                                let name = v.get("name").and_then(|n| n.as_str()).unwrap_or("").to_string();
+                                let value = match v.get("file").and_then(|f| f.as_str()) {
+                                    Some(path) => MultipartValue::File {
+                                        path: path.to_string(),
+                                        filename: v.get("filename").and_then(|f| f.as_str()).map(String::from),
+                                    },
+                                    None => MultipartValue::Text(
+                                        v.get("value").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                                    ),
+                                };
+                                MultipartPart {
+                                    name,
+                                    value,
+                                    content_type: v.get("content_type").and_then(|c| c.as_str()).map(String::from),
+                                    enabled: v.get("enabled").and_then(|e| e.as_bool()).unwrap_or(true),
+                                }
+                            })
+                            .collect();
+                        Ok(BodyType::Multipart(parts))
+                    }
+                    "Binary" => {
+                        let path = map.get("path")
+                            .and_then(|v| v.as_str())
+                            .ok_or("Binary body missing 'path' field")?
+                            .to_string();
+                        Ok(BodyType::Binary { path })
+                    }
                     _ => Err(format!("Unknown body type: {}", content_type)),
                 }
             } else {
@@ -422,11 +1179,56 @@ fn parse_body_type(body_value: Option<&Value>) -> Result<BodyType, String> {
     }
 }
 
+/// Read a value for each missing variable from stdin
+fn prompt_for_variables(names: &[String]) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+    let mut answers = HashMap::new();
+    for name in names {
+        print!("Value for {{{{{}}}}}: ", name);
+        std::io::stdout().flush()?;
+        let mut value = String::new();
+        std::io::stdin().read_line(&mut value)?;
+        answers.insert(name.clone(), value.trim_end_matches(['\r', '\n']).to_string());
+    }
+    Ok(answers)
+}
+
+/// Print the resolved request and where each placeholder's value comes from
+fn print_dry_run(core: &ReqForgeCore, request: &reqforge_core::RequestDefinition) {
+    let vars = core.variable_scope(Some(request)).to_map();
+    match Interpolator::try_resolve(request, &vars) {
+        Ok(resolved) => println!("{} {}\n", resolved.method, resolved.url),
+        Err(e) => println!("{} {}\n\nerror: {}\n", request.method, request.url, e),
+    }
+
+    let placeholders = core.explain_request(request);
+    if placeholders.is_empty() {
+        println!("No variables used");
+        return;
+    }
+    println!("{:<30} {:<40} SOURCE", "PLACEHOLDER", "VALUE");
+    for placeholder in placeholders {
+        let value = match (&placeholder.value, &placeholder.error) {
+            (_, Some(error)) => format!("error: {}", error),
+            (Some(value), None) => value.clone(),
+            (None, None) => "<unresolved>".to_string(),
+        };
+        let source = match (&placeholder.source, &placeholder.variable) {
+            (Some(source), _) => source.to_string(),
+            (None, None) => "dynamic".to_string(),
+            (None, Some(_)) => "-".to_string(),
+        };
+        println!("{:<30} {:<40} {}", truncate(&placeholder.placeholder, 30), truncate(&value, 40), source);
+    }
+}
+
 fn print_response(response: &reqforge_core::HttpResponse) {
     println!("=== Response ===");
     println!("\nStatus: {} {}", response.status, response.status_text);
     println!("Size: {} bytes", response.size_bytes);
     println!("Elapsed: {:?}", response.elapsed);
+    for (phase, duration) in response.timing.phases() {
+        println!("  {:<20} {:?}", phase, duration);
+    }
 
     println!("\n--- Headers ---");
     for (key, value) in &response.headers {
@@ -0,0 +1,89 @@
+//! End-to-end tests for `reqforge-cli run` against a local mock server.
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+use reqforge_core::models::{Assertion, HttpMethod, Variable};
+use reqforge_core::{Collection, Environment, ReqForgeCore, RequestDefinition};
+use tempfile::TempDir;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// A workspace with a "Smoke" collection checking `GET /health` for `expected_status`,
+/// and a "Local" environment pointing at `base_url`.
+fn create_workspace(dir: &Path, base_url: &str, expected_status: u16) {
+    let mut core = ReqForgeCore::open(dir).unwrap();
+    let mut env = Environment::new("Local");
+    env.variables.push(Variable { key: "base_url".into(), value: base_url.into(), secret: false, enabled: true });
+    core.environments.push(env);
+
+    let mut request = RequestDefinition::new("Health", HttpMethod::GET, "{{base_url}}/health");
+    request.assertions.push(Assertion::StatusEquals(expected_status));
+    let mut collection = Collection::new("Smoke");
+    collection.add_request(request, None);
+    core.collections.push(collection);
+    core.save_all().unwrap();
+}
+
+fn run_cli(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_reqforge-cli")).args(args).output().unwrap()
+}
+
+async fn health_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_run_passes_and_writes_junit_report() {
+    let server = health_server().await;
+    let dir = TempDir::new().unwrap();
+    create_workspace(dir.path(), &server.uri(), 200);
+    let junit = dir.path().join("report.xml");
+
+    let workspace = dir.path().to_str().unwrap();
+    let output = run_cli(&["run", "Smoke", "-e", "Local", "--junit", junit.to_str().unwrap(), "-w", workspace]);
+
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let xml = std::fs::read_to_string(&junit).unwrap();
+    assert!(xml.contains("<testsuites name=\"Smoke\" tests=\"1\" failures=\"0\""), "{}", xml);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_run_exits_with_failure_on_failed_assertion() {
+    let server = health_server().await;
+    let dir = TempDir::new().unwrap();
+    create_workspace(dir.path(), &server.uri(), 201);
+    let junit = dir.path().join("report.xml");
+    let json = dir.path().join("report.json");
+
+    let workspace = dir.path().to_str().unwrap();
+    let output = run_cli(&[
+        "run", "Smoke", "-e", "Local", "--junit", junit.to_str().unwrap(), "--json", json.to_str().unwrap(), "-w", workspace,
+    ]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(std::fs::read_to_string(&junit).unwrap().contains("<failure"));
+    let report: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&json).unwrap()).unwrap();
+    assert_eq!(report["results"][0]["status"], 200);
+}
+
+#[test]
+fn test_run_rejects_unknown_collection_and_bad_arguments() {
+    let dir = TempDir::new().unwrap();
+    create_workspace(dir.path(), "http://127.0.0.1:9", 200);
+    let workspace = dir.path().to_str().unwrap();
+
+    let output = run_cli(&["run", "Missing", "-w", workspace]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Collection 'Missing' not found"));
+
+    // Usage errors are reported by clap with its own exit code
+    let output = run_cli(&["run", "Smoke", "--iterations", "many", "-w", workspace]);
+    assert_eq!(output.status.code(), Some(2));
+}
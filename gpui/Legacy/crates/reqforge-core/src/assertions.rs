@@ -0,0 +1,227 @@
+//! Evaluation of declarative response assertions.
+//!
+//! Assertions are attached to `RequestDefinition::assertions` and evaluated
+//! against the `HttpResponse` returned by `HttpEngine::execute`.
+
+use serde_json::Value;
+
+use crate::models::assertion::{Assertion, AssertionResult, TestReport};
+use crate::models::response::HttpResponse;
+
+/// Evaluate every assertion against a response.
+pub fn evaluate_assertions(assertions: &[Assertion], response: &HttpResponse) -> TestReport {
+    // Parse the body once for all JSON path assertions
+    let json = response
+        .body_text()
+        .and_then(|text| serde_json::from_str::<Value>(text).ok());
+
+    TestReport {
+        results: assertions
+            .iter()
+            .map(|assertion| evaluate(assertion, response, json.as_ref()))
+            .collect(),
+    }
+}
+
+fn evaluate(assertion: &Assertion, response: &HttpResponse, json: Option<&Value>) -> AssertionResult {
+    let (passed, actual, message) = match assertion {
+        Assertion::StatusEquals(expected) => {
+            let passed = response.status == *expected;
+            let message = (!passed).then(|| format!("expected status {}, got {}", expected, response.status));
+            (passed, Some(response.status.to_string()), message)
+        }
+        Assertion::HeaderPresent(name) => {
+            let value = response
+                .headers
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.clone());
+            let passed = value.is_some();
+            let message = (!passed).then(|| format!("header '{}' not found", name));
+            (passed, value, message)
+        }
+        Assertion::JsonPathEquals { path, expected } => match lookup_json(json, path) {
+            Ok(value) => {
+                let passed = value == expected;
+                let message = (!passed).then(|| format!("expected {}, got {}", expected, value));
+                (passed, Some(value.to_string()), message)
+            }
+            Err(message) => (false, None, Some(message)),
+        },
+        Assertion::JsonPathMatches { path, pattern } => {
+            match (lookup_json(json, path), regex::Regex::new(pattern)) {
+                (_, Err(e)) => (false, None, Some(format!("invalid pattern: {}", e))),
+                (Err(message), _) => (false, None, Some(message)),
+                (Ok(value), Ok(re)) => {
+                    let text = json_to_text(value);
+                    let passed = re.is_match(&text);
+                    let message = (!passed).then(|| format!("'{}' does not match /{}/", text, pattern));
+                    (passed, Some(text), message)
+                }
+            }
+        }
+        Assertion::ResponseTimeBelow(limit) => {
+            let elapsed = response.elapsed.as_millis() as u64;
+            let passed = elapsed < *limit;
+            let message = (!passed).then(|| format!("took {} ms, limit is {} ms", elapsed, limit));
+            (passed, Some(format!("{} ms", elapsed)), message)
+        }
+        Assertion::BodyContains(text) => {
+            let passed = response.body_text().map(|b| b.contains(text.as_str())).unwrap_or(false);
+            let message = (!passed).then(|| format!("body does not contain '{}'", text));
+            (passed, None, message)
+        }
+    };
+
+    AssertionResult {
+        assertion: assertion.clone(),
+        passed,
+        actual,
+        message,
+    }
+}
+
+fn lookup_json<'a>(json: Option<&'a Value>, path: &str) -> Result<&'a Value, String> {
+    let json = json.ok_or_else(|| "response body is not valid JSON".to_string())?;
+    json_path(json, path).ok_or_else(|| format!("path '{}' not found", path))
+}
+
+/// Render a JSON value as text for pattern matching; strings are unquoted.
+fn json_to_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Segment of a parsed JSON path.
+#[derive(Debug, PartialEq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Resolve a simple JSON path (`$.a.b[0]['c d']`) against a value.
+pub fn json_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    let segments = parse_json_path(path)?;
+    segments.iter().try_fold(value, |current, segment| match segment {
+        PathSegment::Key(key) => current.get(key.as_str()),
+        PathSegment::Index(index) => current.get(*index),
+    })
+}
+
+fn parse_json_path(path: &str) -> Option<Vec<PathSegment>> {
+    let path = path.trim();
+    let path = path.strip_prefix('$').unwrap_or(path);
+    let chars: Vec<char> = path.chars().collect();
+    let mut segments = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '.' => i += 1,
+            '[' => {
+                let close = chars[i..].iter().position(|c| *c == ']')? + i;
+                let inner: String = chars[i + 1..close].iter().collect();
+                let inner = inner.trim();
+                let quoted = inner
+                    .strip_prefix('\'')
+                    .and_then(|s| s.strip_suffix('\''))
+                    .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')));
+                match quoted {
+                    Some(key) => segments.push(PathSegment::Key(key.to_string())),
+                    None => segments.push(PathSegment::Index(inner.parse().ok()?)),
+                }
+                i = close + 1;
+            }
+            _ => {
+                let end = chars[i..]
+                    .iter()
+                    .position(|c| *c == '.' || *c == '[')
+                    .map(|p| p + i)
+                    .unwrap_or(chars.len());
+                segments.push(PathSegment::Key(chars[i..end].iter().collect()));
+                i = end;
+            }
+        }
+    }
+
+    Some(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    fn response(status: u16, body: &str, elapsed_ms: u64) -> HttpResponse {
+        let mut headers = HashMap::new();
+        headers.insert("content-type".to_string(), "application/json".to_string());
+        HttpResponse {
+            status,
+            status_text: String::new(),
+            headers,
+            body: Bytes::from(body.to_string()),
+            size_bytes: body.len(),
+            elapsed: Duration::from_millis(elapsed_ms),
+            timing: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_json_path_parsing() {
+        let value = serde_json::json!({"data": {"items": [{"id": 7}], "odd key": true}});
+        assert_eq!(json_path(&value, "$.data.items[0].id"), Some(&serde_json::json!(7)));
+        assert_eq!(json_path(&value, "data['odd key']"), Some(&serde_json::json!(true)));
+        assert_eq!(json_path(&value, "$"), Some(&value));
+        assert_eq!(json_path(&value, "$.data.items[3]"), None);
+        assert_eq!(json_path(&value, "$.data.items[x]"), None);
+    }
+
+    #[test]
+    fn test_all_assertions_pass() {
+        let resp = response(200, r#"{"user": {"id": 1, "email": "a@example.com"}}"#, 20);
+        let assertions = vec![
+            Assertion::StatusEquals(200),
+            Assertion::HeaderPresent("Content-Type".to_string()),
+            Assertion::JsonPathEquals { path: "$.user.id".to_string(), expected: serde_json::json!(1) },
+            Assertion::JsonPathMatches { path: "$.user.email".to_string(), pattern: r"^\S+@example\.com$".to_string() },
+            Assertion::ResponseTimeBelow(500),
+            Assertion::BodyContains("email".to_string()),
+        ];
+
+        let report = evaluate_assertions(&assertions, &resp);
+        assert!(report.all_passed());
+        assert_eq!(report.passed_count(), 6);
+        assert_eq!(report.results[3].actual.as_deref(), Some("a@example.com"));
+    }
+
+    #[test]
+    fn test_failures_carry_messages() {
+        let resp = response(404, "not json", 900);
+        let assertions = vec![
+            Assertion::StatusEquals(200),
+            Assertion::HeaderPresent("X-Missing".to_string()),
+            Assertion::JsonPathEquals { path: "$.id".to_string(), expected: serde_json::json!(1) },
+            Assertion::ResponseTimeBelow(500),
+            Assertion::BodyContains("found".to_string()),
+        ];
+
+        let report = evaluate_assertions(&assertions, &resp);
+        assert_eq!(report.failed_count(), 5);
+        assert!(report.results.iter().all(|r| r.message.is_some()));
+        assert_eq!(report.results[0].actual.as_deref(), Some("404"));
+    }
+
+    #[test]
+    fn test_invalid_pattern_fails() {
+        let resp = response(200, r#"{"a": "b"}"#, 1);
+        let report = evaluate_assertions(
+            &[Assertion::JsonPathMatches { path: "a".to_string(), pattern: "(".to_string() }],
+            &resp,
+        );
+        assert!(!report.all_passed());
+    }
+}
@@ -0,0 +1,803 @@
+//! Code snippets that send a request from other languages and tools.
+//!
+//! `generate_snippet` renders a request whose placeholders are already
+//! resolved (`ReqForgeCore::code_snippet` does that first) as a program or
+//! command that runs as-is. Connection settings such as timeouts and proxies
+//! are only carried over to cURL.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use url::form_urlencoded;
+
+use crate::import_export::formats::curl::shell_quote;
+use crate::import_export::to_curl_command;
+use crate::models::{
+    ApiKeyLocation, AuthConfig, BodyType, HttpMethod, KeyValuePair, MultipartPart, MultipartValue, RequestDefinition,
+};
+
+/// Stands in for secret values in redacted snippets.
+pub const REDACTED: &str = "<redacted>";
+
+/// Header and query parameter names containing any of these are treated as
+/// secrets when redacting.
+const SECRET_NAME_PARTS: &[&str] = &[
+    "authorization", "token", "secret", "password", "passwd", "api-key", "api_key", "apikey", "cookie",
+    "session", "signature", "credential",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnippetLanguage {
+    Curl,
+    RustReqwest,
+    PythonRequests,
+    JavaScriptFetch,
+    GoNetHttp,
+    Httpie,
+    PowerShell,
+}
+
+impl SnippetLanguage {
+    /// All languages in display order.
+    pub fn all() -> &'static [SnippetLanguage] {
+        &[
+            SnippetLanguage::Curl,
+            SnippetLanguage::RustReqwest,
+            SnippetLanguage::PythonRequests,
+            SnippetLanguage::JavaScriptFetch,
+            SnippetLanguage::GoNetHttp,
+            SnippetLanguage::Httpie,
+            SnippetLanguage::PowerShell,
+        ]
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            SnippetLanguage::Curl => "cURL",
+            SnippetLanguage::RustReqwest => "Rust (reqwest)",
+            SnippetLanguage::PythonRequests => "Python (requests)",
+            SnippetLanguage::JavaScriptFetch => "JavaScript (fetch)",
+            SnippetLanguage::GoNetHttp => "Go (net/http)",
+            SnippetLanguage::Httpie => "HTTPie",
+            SnippetLanguage::PowerShell => "PowerShell",
+        }
+    }
+
+    /// Short name used on the command line, e.g. `python`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SnippetLanguage::Curl => "curl",
+            SnippetLanguage::RustReqwest => "rust",
+            SnippetLanguage::PythonRequests => "python",
+            SnippetLanguage::JavaScriptFetch => "javascript",
+            SnippetLanguage::GoNetHttp => "go",
+            SnippetLanguage::Httpie => "httpie",
+            SnippetLanguage::PowerShell => "powershell",
+        }
+    }
+
+    /// Look a language up by `name()`, ignoring case.
+    pub fn from_name(name: &str) -> Option<SnippetLanguage> {
+        Self::all().iter().copied().find(|language| language.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// Render `req` as a snippet in `language`.
+///
+/// With `redact_secrets`, auth credentials and headers and query parameters
+/// whose names look secret (`Authorization`, `X-Api-Key`, `access_token`, ...)
+/// are replaced with `REDACTED`.
+pub fn generate_snippet(req: &RequestDefinition, language: SnippetLanguage, redact_secrets: bool) -> String {
+    let redacted;
+    let req = if redact_secrets {
+        redacted = redact(req);
+        &redacted
+    } else {
+        req
+    };
+    let prepared = Prepared::new(req);
+    match language {
+        SnippetLanguage::Curl => to_curl_command(req),
+        SnippetLanguage::RustReqwest => rust_reqwest(&prepared),
+        SnippetLanguage::PythonRequests => python_requests(&prepared),
+        SnippetLanguage::JavaScriptFetch => javascript_fetch(&prepared),
+        SnippetLanguage::GoNetHttp => go_net_http(&prepared),
+        SnippetLanguage::Httpie => httpie(&prepared),
+        SnippetLanguage::PowerShell => powershell(&prepared),
+    }
+}
+
+/// A copy of `req` with credentials replaced by `REDACTED`.
+pub fn redact(req: &RequestDefinition) -> RequestDefinition {
+    let mut req = req.clone();
+    let secret = || REDACTED.to_string();
+    match &mut req.auth {
+        AuthConfig::Basic { password, .. } | AuthConfig::Digest { password, .. } => *password = secret(),
+        AuthConfig::Bearer { token } => *token = secret(),
+        AuthConfig::ApiKey { value, .. } => *value = secret(),
+        AuthConfig::AwsSigV4 { secret_access_key, session_token, .. } => {
+            *secret_access_key = secret();
+            if let Some(token) = session_token {
+                *token = secret();
+            }
+        }
+        AuthConfig::OAuth2(config) => config.client_secret = secret(),
+        AuthConfig::Inherit | AuthConfig::None => {}
+    }
+    for pair in req.headers.iter_mut().chain(req.query_params.iter_mut()) {
+        if is_secret_name(&pair.key) {
+            // Keep the scheme of `Authorization: Bearer ...`
+            pair.value = match pair.value.split_once(' ') {
+                Some((scheme, _)) if pair.key.to_ascii_lowercase().ends_with("authorization") => {
+                    format!("{} {}", scheme, REDACTED)
+                }
+                _ => secret(),
+            };
+        }
+    }
+    req
+}
+
+fn is_secret_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SECRET_NAME_PARTS.iter().any(|part| name.contains(part))
+}
+
+/// What every generator needs: the full URL, the headers to send and the
+/// auth that is not sent as a plain header.
+struct Prepared<'a> {
+    req: &'a RequestDefinition,
+    url: String,
+    headers: Vec<(String, String)>,
+}
+
+impl<'a> Prepared<'a> {
+    fn new(req: &'a RequestDefinition) -> Self {
+        let mut query: Vec<(&str, &str)> = enabled(&req.query_params).collect();
+        if let AuthConfig::ApiKey { key, value, location: ApiKeyLocation::Query } = &req.auth {
+            query.push((key, value));
+        }
+        let mut url = req.url.clone();
+        if !query.is_empty() {
+            let encoded = form_urlencoded::Serializer::new(String::new()).extend_pairs(query).finish();
+            url.push(if url.contains('?') { '&' } else { '?' });
+            url.push_str(&encoded);
+        }
+
+        let mut headers: Vec<(String, String)> =
+            enabled(&req.headers).map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        match &req.auth {
+            AuthConfig::Bearer { token } => headers.push(("Authorization".into(), format!("Bearer {}", token))),
+            AuthConfig::ApiKey { key, value, location: ApiKeyLocation::Header } => headers.push((key.clone(), value.clone())),
+            _ => {}
+        }
+        if let BodyType::Raw { content_type, .. } = &req.body
+            && !headers.iter().any(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+        {
+            headers.push(("Content-Type".into(), content_type.mime_type().into()));
+        }
+        Self { req, url, headers }
+    }
+
+    fn basic_auth(&self) -> Option<(&str, &str)> {
+        match &self.req.auth {
+            AuthConfig::Basic { username, password } => Some((username, password)),
+            _ => None,
+        }
+    }
+
+    /// Why the request's auth is missing from the snippet, if it is.
+    fn unsupported_auth(&self, digest_supported: bool) -> Option<&'static str> {
+        match &self.req.auth {
+            AuthConfig::Digest { .. } if !digest_supported => Some("Digest auth is not included"),
+            AuthConfig::AwsSigV4 { .. } => Some("AWS SigV4 signing is not included"),
+            AuthConfig::OAuth2(_) => Some("OAuth 2.0: add an Authorization: Bearer header with a current access token"),
+            _ => None,
+        }
+    }
+}
+
+fn enabled(pairs: &[KeyValuePair]) -> impl Iterator<Item = (&str, &str)> {
+    pairs.iter().filter(|p| p.enabled).map(|p| (p.key.as_str(), p.value.as_str()))
+}
+
+fn enabled_parts(parts: &[MultipartPart]) -> impl Iterator<Item = &MultipartPart> {
+    parts.iter().filter(|p| p.enabled)
+}
+
+/// The name a file part is sent with: its override, or the path's last component.
+fn upload_name<'a>(path: &'a str, filename: &'a Option<String>) -> &'a str {
+    filename
+        .as_deref()
+        .or_else(|| Path::new(path).file_name().and_then(|n| n.to_str()))
+        .unwrap_or(path)
+}
+
+/// A double-quoted string literal; JSON escaping is valid in Python,
+/// JavaScript and Go.
+fn quoted(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_default()
+}
+
+fn rust_string(s: &str) -> String {
+    if s.contains(['"', '\\']) && !s.contains("\"#") && !s.chars().any(|c| c.is_control() && c != '\n') {
+        format!("r#\"{}\"#", s)
+    } else {
+        format!("{:?}", s)
+    }
+}
+
+fn rust_reqwest(p: &Prepared) -> String {
+    let mut lines = vec![
+        "#[tokio::main]".to_string(),
+        "async fn main() -> Result<(), Box<dyn std::error::Error>> {".to_string(),
+    ];
+    if let BodyType::Multipart(parts) = &p.req.body {
+        lines.push("    let form = reqwest::multipart::Form::new()".to_string());
+        for part in enabled_parts(parts) {
+            let mime = part.content_type.as_ref().map(|ct| format!(".mime_str({})?", rust_string(ct))).unwrap_or_default();
+            match &part.value {
+                MultipartValue::Text(value) if mime.is_empty() => {
+                    lines.push(format!("        .text({}, {})", rust_string(&part.name), rust_string(value)));
+                }
+                MultipartValue::Text(value) => lines.push(format!(
+                    "        .part({}, reqwest::multipart::Part::text({}){})",
+                    rust_string(&part.name), rust_string(value), mime
+                )),
+                MultipartValue::File { path, filename } => lines.push(format!(
+                    "        .part({}, reqwest::multipart::Part::bytes(std::fs::read({})?).file_name({}){})",
+                    rust_string(&part.name), rust_string(path), rust_string(upload_name(path, filename)), mime
+                )),
+            }
+        }
+        if let Some(last) = lines.last_mut() {
+            last.push(';');
+        }
+    }
+
+    lines.push("    let response = reqwest::Client::new()".to_string());
+    let url = rust_string(&p.url);
+    lines.push(match p.req.method {
+        HttpMethod::OPTIONS => format!("        .request(reqwest::Method::OPTIONS, {})", url),
+        ref method => format!("        .{}({})", method.to_string().to_lowercase(), url),
+    });
+    for (key, value) in &p.headers {
+        lines.push(format!("        .header({}, {})", rust_string(key), rust_string(value)));
+    }
+    if let Some((username, password)) = p.basic_auth() {
+        lines.push(format!("        .basic_auth({}, Some({}))", rust_string(username), rust_string(password)));
+    }
+    if let Some(note) = p.unsupported_auth(false) {
+        lines.push(format!("        // {}", note));
+    }
+    match &p.req.body {
+        BodyType::None => {}
+        BodyType::Raw { content, .. } => lines.push(format!("        .body({})", rust_string(content))),
+        BodyType::FormUrlEncoded(pairs) => {
+            let pairs: Vec<String> = enabled(pairs)
+                .map(|(k, v)| format!("({}, {})", rust_string(k), rust_string(v)))
+                .collect();
+            lines.push(format!("        .form(&[{}])", pairs.join(", ")));
+        }
+        BodyType::Multipart(_) => lines.push("        .multipart(form)".to_string()),
+        BodyType::Binary { path } => lines.push(format!("        .body(std::fs::read({})?)", rust_string(path))),
+    }
+    lines.extend([
+        "        .send()".to_string(),
+        "        .await?;".to_string(),
+        String::new(),
+        "    println!(\"{}\", response.status());".to_string(),
+        "    println!(\"{}\", response.text().await?);".to_string(),
+        "    Ok(())".to_string(),
+        "}".to_string(),
+    ]);
+    lines.join("\n")
+}
+
+fn python_requests(p: &Prepared) -> String {
+    let mut lines = vec!["import requests".to_string()];
+    if matches!(p.req.auth, AuthConfig::Digest { .. }) {
+        lines.push("from requests.auth import HTTPDigestAuth".to_string());
+    }
+    lines.push(String::new());
+    lines.push(format!("url = {}", quoted(&p.url)));
+    let mut args = vec!["url".to_string()];
+
+    if !p.headers.is_empty() {
+        lines.push("headers = {".to_string());
+        for (key, value) in &p.headers {
+            lines.push(format!("    {}: {},", quoted(key), quoted(value)));
+        }
+        lines.push("}".to_string());
+        args.push("headers=headers".to_string());
+    }
+
+    match &p.req.body {
+        BodyType::None => {}
+        BodyType::Raw { content, .. } => {
+            lines.push(format!("data = {}", quoted(content)));
+            args.push("data=data".to_string());
+        }
+        BodyType::FormUrlEncoded(pairs) => {
+            lines.push("data = [".to_string());
+            for (key, value) in enabled(pairs) {
+                lines.push(format!("    ({}, {}),", quoted(key), quoted(value)));
+            }
+            lines.push("]".to_string());
+            args.push("data=data".to_string());
+        }
+        BodyType::Multipart(parts) => {
+            // `(None, value)` sends a plain field rather than a file
+            lines.push("files = [".to_string());
+            for part in enabled_parts(parts) {
+                let content_type = part.content_type.as_deref().map(|ct| format!(", {}", quoted(ct))).unwrap_or_default();
+                let value = match &part.value {
+                    MultipartValue::Text(value) => format!("(None, {}{})", quoted(value), content_type),
+                    MultipartValue::File { path, filename } => format!(
+                        "({}, open({}, \"rb\"){})",
+                        quoted(upload_name(path, filename)), quoted(path), content_type
+                    ),
+                };
+                lines.push(format!("    ({}, {}),", quoted(&part.name), value));
+            }
+            lines.push("]".to_string());
+            args.push("files=files".to_string());
+        }
+        BodyType::Binary { path } => {
+            lines.push(format!("data = open({}, \"rb\")", quoted(path)));
+            args.push("data=data".to_string());
+        }
+    }
+
+    match &p.req.auth {
+        AuthConfig::Basic { username, password } => args.push(format!("auth=({}, {})", quoted(username), quoted(password))),
+        AuthConfig::Digest { username, password } => {
+            args.push(format!("auth=HTTPDigestAuth({}, {})", quoted(username), quoted(password)));
+        }
+        _ => {}
+    }
+    if let Some(note) = p.unsupported_auth(true) {
+        lines.push(format!("# {}", note));
+    }
+
+    lines.push(String::new());
+    lines.push(format!("response = requests.{}({})", p.req.method.to_string().to_lowercase(), args.join(", ")));
+    lines.push("print(response.status_code)".to_string());
+    lines.push("print(response.text)".to_string());
+    lines.join("\n")
+}
+
+fn javascript_fetch(p: &Prepared) -> String {
+    let mut lines = Vec::new();
+    let reads_files = matches!(&p.req.body, BodyType::Binary { .. })
+        || matches!(&p.req.body, BodyType::Multipart(parts)
+            if enabled_parts(parts).any(|part| matches!(part.value, MultipartValue::File { .. })));
+    if reads_files {
+        lines.push("import { readFile } from \"node:fs/promises\";".to_string());
+        lines.push(String::new());
+    }
+
+    if let BodyType::Multipart(parts) = &p.req.body {
+        lines.push("const form = new FormData();".to_string());
+        for part in enabled_parts(parts) {
+            let options = part.content_type.as_deref().map(|ct| format!(", {{ type: {} }}", quoted(ct))).unwrap_or_default();
+            lines.push(match &part.value {
+                MultipartValue::Text(value) if options.is_empty() => {
+                    format!("form.append({}, {});", quoted(&part.name), quoted(value))
+                }
+                MultipartValue::Text(value) => {
+                    format!("form.append({}, new Blob([{}]{}));", quoted(&part.name), quoted(value), options)
+                }
+                MultipartValue::File { path, filename } => format!(
+                    "form.append({}, new Blob([await readFile({})]{}), {});",
+                    quoted(&part.name), quoted(path), options, quoted(upload_name(path, filename))
+                ),
+            });
+        }
+        lines.push(String::new());
+    }
+    if let Some(note) = p.unsupported_auth(false) {
+        lines.push(format!("// {}", note));
+    }
+
+    lines.push(format!("const response = await fetch({}, {{", quoted(&p.url)));
+    lines.push(format!("  method: {},", quoted(&p.req.method.to_string())));
+    if !p.headers.is_empty() || p.basic_auth().is_some() {
+        lines.push("  headers: {".to_string());
+        for (key, value) in &p.headers {
+            lines.push(format!("    {}: {},", quoted(key), quoted(value)));
+        }
+        if let Some((username, password)) = p.basic_auth() {
+            lines.push(format!("    \"Authorization\": \"Basic \" + btoa({}),", quoted(&format!("{}:{}", username, password))));
+        }
+        lines.push("  },".to_string());
+    }
+    match &p.req.body {
+        BodyType::None => {}
+        BodyType::Raw { content, .. } => lines.push(format!("  body: {},", quoted(content))),
+        BodyType::FormUrlEncoded(pairs) => {
+            lines.push("  body: new URLSearchParams([".to_string());
+            for (key, value) in enabled(pairs) {
+                lines.push(format!("    [{}, {}],", quoted(key), quoted(value)));
+            }
+            lines.push("  ]),".to_string());
+        }
+        BodyType::Multipart(_) => lines.push("  body: form,".to_string()),
+        BodyType::Binary { path } => lines.push(format!("  body: await readFile({}),", quoted(path))),
+    }
+    lines.push("});".to_string());
+    lines.push(String::new());
+    lines.push("console.log(response.status);".to_string());
+    lines.push("console.log(await response.text());".to_string());
+    lines.join("\n")
+}
+
+/// A Go string literal, using a raw string when that reads better.
+fn go_string(s: &str) -> String {
+    if s.contains(['"', '\n']) && !s.contains(['`', '\r']) {
+        format!("`{}`", s)
+    } else {
+        quoted(s)
+    }
+}
+
+fn go_net_http(p: &Prepared) -> String {
+    let mut imports: BTreeSet<&str> = ["fmt", "io", "net/http"].into();
+    let mut body_lines = Vec::new();
+    let mut content_type = None;
+    let body = match &p.req.body {
+        BodyType::None => "nil",
+        BodyType::Raw { content, .. } => {
+            imports.insert("strings");
+            body_lines.push(format!("\tbody := strings.NewReader({})", go_string(content)));
+            "body"
+        }
+        BodyType::FormUrlEncoded(pairs) => {
+            imports.insert("net/url");
+            imports.insert("strings");
+            body_lines.push("\tform := url.Values{}".to_string());
+            for (key, value) in enabled(pairs) {
+                body_lines.push(format!("\tform.Add({}, {})", go_string(key), go_string(value)));
+            }
+            body_lines.push("\tbody := strings.NewReader(form.Encode())".to_string());
+            content_type = Some("\"application/x-www-form-urlencoded\"".to_string());
+            "body"
+        }
+        BodyType::Multipart(parts) => {
+            imports.extend(["bytes", "mime/multipart"]);
+            body_lines.push("\tbody := &bytes.Buffer{}".to_string());
+            body_lines.push("\twriter := multipart.NewWriter(body)".to_string());
+            for part in enabled_parts(parts) {
+                match &part.value {
+                    MultipartValue::Text(value) => {
+                        body_lines.push(format!("\tcheck(writer.WriteField({}, {}))", go_string(&part.name), go_string(value)));
+                    }
+                    MultipartValue::File { path, filename } => {
+                        imports.insert("os");
+                        body_lines.extend([
+                            "\t{".to_string(),
+                            format!("\t\tfile, err := os.Open({})", go_string(path)),
+                            "\t\tcheck(err)".to_string(),
+                            "\t\tdefer file.Close()".to_string(),
+                            format!(
+                                "\t\tpart, err := writer.CreateFormFile({}, {})",
+                                go_string(&part.name), go_string(upload_name(path, filename))
+                            ),
+                            "\t\tcheck(err)".to_string(),
+                            "\t\t_, err = io.Copy(part, file)".to_string(),
+                            "\t\tcheck(err)".to_string(),
+                            "\t}".to_string(),
+                        ]);
+                    }
+                }
+            }
+            body_lines.push("\tcheck(writer.Close())".to_string());
+            content_type = Some("writer.FormDataContentType()".to_string());
+            "body"
+        }
+        BodyType::Binary { path } => {
+            imports.insert("os");
+            body_lines.push(format!("\tbody, err := os.Open({})", go_string(path)));
+            body_lines.push("\tcheck(err)".to_string());
+            body_lines.push("\tdefer body.Close()".to_string());
+            "body"
+        }
+    };
+
+    let mut lines = vec!["package main".to_string(), String::new(), "import (".to_string()];
+    lines.extend(imports.iter().map(|import| format!("\t\"{}\"", import)));
+    lines.extend([")".to_string(), String::new(), "func main() {".to_string()]);
+    if !body_lines.is_empty() {
+        lines.append(&mut body_lines);
+        lines.push(String::new());
+    }
+
+    let method = match p.req.method {
+        HttpMethod::GET => "MethodGet",
+        HttpMethod::POST => "MethodPost",
+        HttpMethod::PUT => "MethodPut",
+        HttpMethod::PATCH => "MethodPatch",
+        HttpMethod::DELETE => "MethodDelete",
+        HttpMethod::HEAD => "MethodHead",
+        HttpMethod::OPTIONS => "MethodOptions",
+    };
+    lines.push(format!("\treq, err := http.NewRequest(http.{}, {}, {})", method, go_string(&p.url), body));
+    lines.push("\tcheck(err)".to_string());
+    for (key, value) in &p.headers {
+        lines.push(format!("\treq.Header.Add({}, {})", go_string(key), go_string(value)));
+    }
+    if let Some(content_type) = content_type {
+        lines.push(format!("\treq.Header.Set(\"Content-Type\", {})", content_type));
+    }
+    if let Some((username, password)) = p.basic_auth() {
+        lines.push(format!("\treq.SetBasicAuth({}, {})", go_string(username), go_string(password)));
+    }
+    if let Some(note) = p.unsupported_auth(false) {
+        lines.push(format!("\t// {}", note));
+    }
+
+    lines.extend([
+        String::new(),
+        "\tresp, err := http.DefaultClient.Do(req)".to_string(),
+        "\tcheck(err)".to_string(),
+        "\tdefer resp.Body.Close()".to_string(),
+        String::new(),
+        "\trespBody, err := io.ReadAll(resp.Body)".to_string(),
+        "\tcheck(err)".to_string(),
+        "\tfmt.Println(resp.Status)".to_string(),
+        "\tfmt.Println(string(respBody))".to_string(),
+        "}".to_string(),
+        String::new(),
+        "func check(err error) {".to_string(),
+        "\tif err != nil {".to_string(),
+        "\t\tpanic(err)".to_string(),
+        "\t}".to_string(),
+        "}".to_string(),
+    ]);
+    lines.join("\n")
+}
+
+fn httpie(p: &Prepared) -> String {
+    let mut first = vec!["http".to_string()];
+    match &p.req.body {
+        BodyType::FormUrlEncoded(_) => first.push("--form".to_string()),
+        BodyType::Multipart(_) => first.push("--multipart".to_string()),
+        _ => {}
+    }
+    match &p.req.auth {
+        AuthConfig::Basic { username, password } => {
+            first.push(format!("-a {}", shell_quote(&format!("{}:{}", username, password))));
+        }
+        AuthConfig::Digest { username, password } => {
+            first.push(format!("-A digest -a {}", shell_quote(&format!("{}:{}", username, password))));
+        }
+        _ => {}
+    }
+    first.push(p.req.method.to_string());
+    first.push(shell_quote(&p.url));
+
+    // Request items: `Name:value` headers, `Name;` for an empty header,
+    // `key=value` fields and `name@path` files
+    let mut items = Vec::new();
+    for (key, value) in &p.headers {
+        let item = if value.is_empty() { format!("{};", key) } else { format!("{}:{}", key, value) };
+        items.push(shell_quote(&item));
+    }
+    match &p.req.body {
+        BodyType::None => {}
+        BodyType::Raw { content, .. } => items.push(format!("--raw {}", shell_quote(content))),
+        BodyType::FormUrlEncoded(pairs) => {
+            items.extend(enabled(pairs).map(|(key, value)| shell_quote(&format!("{}={}", key, value))));
+        }
+        BodyType::Multipart(parts) => {
+            for part in enabled_parts(parts) {
+                let mut item = match &part.value {
+                    MultipartValue::Text(value) => format!("{}={}", part.name, value),
+                    MultipartValue::File { path, .. } => format!("{}@{}", part.name, path),
+                };
+                if let Some(content_type) = &part.content_type {
+                    item.push_str(";type=");
+                    item.push_str(content_type);
+                }
+                items.push(shell_quote(&item));
+            }
+        }
+        BodyType::Binary { path } => items.push(format!("< {}", shell_quote(path))),
+    }
+
+    let mut lines = Vec::new();
+    if let Some(note) = p.unsupported_auth(true) {
+        lines.push(format!("# {}", note));
+    }
+    let mut command = first.join(" ");
+    for item in items {
+        command.push_str(" \\\n  ");
+        command.push_str(&item);
+    }
+    lines.push(command);
+    lines.join("\n")
+}
+
+/// A single-quoted PowerShell string, which expands nothing.
+fn ps_string(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+fn powershell(p: &Prepared) -> String {
+    let mut lines = Vec::new();
+    let mut args = vec![format!("-Uri {}", ps_string(&p.url))];
+    let method = p.req.method.to_string();
+    args.push(format!("-Method {}{}", &method[..1], method[1..].to_lowercase()));
+
+    // The Content-Type header has its own parameter
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for (key, value) in &p.headers {
+        if key.eq_ignore_ascii_case("content-type") {
+            args.push(format!("-ContentType {}", ps_string(value)));
+        } else {
+            headers.push((ps_string(key), ps_string(value)));
+        }
+    }
+    if let Some((username, password)) = p.basic_auth() {
+        headers.push((
+            ps_string("Authorization"),
+            format!(
+                "'Basic ' + [Convert]::ToBase64String([Text.Encoding]::UTF8.GetBytes({}))",
+                ps_string(&format!("{}:{}", username, password))
+            ),
+        ));
+    }
+    if !headers.is_empty() {
+        lines.push("$headers = @{".to_string());
+        for (key, value) in headers {
+            lines.push(format!("    {} = {}", key, value));
+        }
+        lines.push("}".to_string());
+        args.push("-Headers $headers".to_string());
+    }
+
+    match &p.req.body {
+        BodyType::None => {}
+        BodyType::Raw { content, .. } => {
+            lines.push(format!("$body = {}", ps_string(content)));
+            args.push("-Body $body".to_string());
+        }
+        BodyType::FormUrlEncoded(pairs) => {
+            lines.push("$body = @{".to_string());
+            for (key, value) in enabled(pairs) {
+                lines.push(format!("    {} = {}", ps_string(key), ps_string(value)));
+            }
+            lines.push("}".to_string());
+            args.push("-Body $body".to_string());
+            args.push(format!("-ContentType {}", ps_string("application/x-www-form-urlencoded")));
+        }
+        BodyType::Multipart(parts) => {
+            // -Form needs PowerShell 7
+            lines.push("$form = @{".to_string());
+            for part in enabled_parts(parts) {
+                let value = match &part.value {
+                    MultipartValue::Text(value) => ps_string(value),
+                    MultipartValue::File { path, .. } => format!("Get-Item -Path {}", ps_string(path)),
+                };
+                lines.push(format!("    {} = {}", ps_string(&part.name), value));
+            }
+            lines.push("}".to_string());
+            args.push("-Form $form".to_string());
+        }
+        BodyType::Binary { path } => args.push(format!("-InFile {}", ps_string(path))),
+    }
+    if let Some(note) = p.unsupported_auth(false) {
+        lines.push(format!("# {}", note));
+    }
+
+    lines.push(format!("$response = Invoke-WebRequest {}", args.join(" ")));
+    lines.push("$response.StatusCode".to_string());
+    lines.push("$response.Content".to_string());
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::RawContentType;
+
+    fn pair(key: &str, value: &str) -> KeyValuePair {
+        KeyValuePair { key: key.to_string(), value: value.to_string(), enabled: true, description: None }
+    }
+
+    fn json_post() -> RequestDefinition {
+        let mut req = RequestDefinition::new("Create", HttpMethod::POST, "https://api.example.com/users");
+        req.query_params.push(pair("notify", "true"));
+        req.headers.push(pair("Accept", "application/json"));
+        req.auth = AuthConfig::Basic { username: "ada".into(), password: "hunter2".into() };
+        req.body = BodyType::Raw { content: "{\"name\": \"Ada\"}".into(), content_type: RawContentType::Json };
+        req
+    }
+
+    #[test]
+    fn test_snippets_for_json_post() {
+        let req = json_post();
+        let url = "https://api.example.com/users?notify=true";
+
+        let rust = generate_snippet(&req, SnippetLanguage::RustReqwest, false);
+        assert!(rust.contains(&format!(".post(\"{}\")", url)), "{}", rust);
+        assert!(rust.contains(".header(\"Content-Type\", \"application/json\")"), "{}", rust);
+        assert!(rust.contains(".basic_auth(\"ada\", Some(\"hunter2\"))"), "{}", rust);
+        assert!(rust.contains(".body(r#\"{\"name\": \"Ada\"}\"#)"), "{}", rust);
+
+        let python = generate_snippet(&req, SnippetLanguage::PythonRequests, false);
+        assert!(python.contains("data = \"{\\\"name\\\": \\\"Ada\\\"}\""), "{}", python);
+        assert!(python.contains("response = requests.post(url, headers=headers, data=data, auth=(\"ada\", \"hunter2\"))"), "{}", python);
+
+        let js = generate_snippet(&req, SnippetLanguage::JavaScriptFetch, false);
+        assert!(js.contains(&format!("const response = await fetch(\"{}\", {{", url)), "{}", js);
+        assert!(js.contains("\"Authorization\": \"Basic \" + btoa(\"ada:hunter2\"),"), "{}", js);
+
+        let go = generate_snippet(&req, SnippetLanguage::GoNetHttp, false);
+        assert!(go.contains("\tbody := strings.NewReader(`{\"name\": \"Ada\"}`)"), "{}", go);
+        assert!(go.contains(&format!("http.NewRequest(http.MethodPost, \"{}\", body)", url)), "{}", go);
+        assert!(go.contains("\t\"strings\"\n"), "{}", go);
+        assert!(go.contains("req.SetBasicAuth(\"ada\", \"hunter2\")"), "{}", go);
+
+        let httpie = generate_snippet(&req, SnippetLanguage::Httpie, false);
+        assert!(httpie.starts_with(&format!("http -a ada:hunter2 POST '{}'", url)), "{}", httpie);
+        assert!(httpie.contains("--raw '{\"name\": \"Ada\"}'"), "{}", httpie);
+
+        let ps = generate_snippet(&req, SnippetLanguage::PowerShell, false);
+        assert!(ps.contains(&format!("Invoke-WebRequest -Uri '{}' -Method Post -ContentType 'application/json' -Headers $headers -Body $body", url)), "{}", ps);
+
+        assert_eq!(generate_snippet(&req, SnippetLanguage::Curl, false), to_curl_command(&req));
+    }
+
+    #[test]
+    fn test_multipart_snippets() {
+        let mut req = RequestDefinition::new("Upload", HttpMethod::PUT, "https://x.test/files");
+        let mut file = MultipartPart::file("doc", "/tmp/report.csv");
+        file.content_type = Some("text/csv".into());
+        req.body = BodyType::Multipart(vec![MultipartPart::text("title", "Q1"), file]);
+
+        let python = generate_snippet(&req, SnippetLanguage::PythonRequests, false);
+        assert!(python.contains("    (\"title\", (None, \"Q1\")),"), "{}", python);
+        assert!(python.contains("    (\"doc\", (\"report.csv\", open(\"/tmp/report.csv\", \"rb\"), \"text/csv\")),"), "{}", python);
+        assert!(python.contains("requests.put(url, files=files)"), "{}", python);
+
+        let go = generate_snippet(&req, SnippetLanguage::GoNetHttp, false);
+        assert!(go.contains("writer.CreateFormFile(\"doc\", \"report.csv\")"), "{}", go);
+        assert!(go.contains("req.Header.Set(\"Content-Type\", writer.FormDataContentType())"), "{}", go);
+
+        let js = generate_snippet(&req, SnippetLanguage::JavaScriptFetch, false);
+        assert!(js.starts_with("import { readFile } from \"node:fs/promises\";"), "{}", js);
+        assert!(js.contains("form.append(\"doc\", new Blob([await readFile(\"/tmp/report.csv\")], { type: \"text/csv\" }), \"report.csv\");"), "{}", js);
+
+        let httpie = generate_snippet(&req, SnippetLanguage::Httpie, false);
+        assert!(httpie.contains("'doc@/tmp/report.csv;type=text/csv'"), "{}", httpie);
+    }
+
+    #[test]
+    fn test_redaction() {
+        let mut req = json_post();
+        req.headers.push(pair("Authorization", "Bearer abc.def"));
+        req.headers.push(pair("X-Api-Key", "k-123"));
+        req.query_params.push(pair("access_token", "t-456"));
+
+        for &language in SnippetLanguage::all() {
+            let snippet = generate_snippet(&req, language, true);
+            for secret in ["hunter2", "abc.def", "k-123", "t-456"] {
+                assert!(!snippet.contains(secret), "{} leaked {}:\n{}", language.name(), secret, snippet);
+            }
+        }
+        let redacted = redact(&req);
+        assert_eq!(redacted.headers[1].value, "Bearer <redacted>");
+        assert_eq!(redacted.headers[0].value, "application/json");
+        assert!(matches!(&redacted.auth, AuthConfig::Basic { username, .. } if username == "ada"));
+    }
+
+    #[test]
+    fn test_language_names() {
+        for &language in SnippetLanguage::all() {
+            assert_eq!(SnippetLanguage::from_name(language.name()), Some(language));
+        }
+        assert_eq!(SnippetLanguage::from_name("Python"), Some(SnippetLanguage::PythonRequests));
+        assert_eq!(SnippetLanguage::from_name("cobol"), None);
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::cookie::{Cookie, domain_matches};
+
+/// Scope used for cookies shared by the whole workspace.
+pub const WORKSPACE_SCOPE: &str = "workspace";
+
+/// Cookies captured from responses and sent with later requests.
+///
+/// Cookies live in named scopes: `WORKSPACE_SCOPE`, or one scope per
+/// environment id when `per_environment` is enabled, so logging in against
+/// staging does not leak a session into production.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CookieJar {
+    #[serde(default)]
+    pub per_environment: bool,
+    #[serde(default)]
+    scopes: BTreeMap<String, Vec<Cookie>>,
+}
+
+impl CookieJar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Capture cookies from a response's `Set-Cookie` header (values separated
+    /// by newlines, as `HttpEngine` joins them). Returns the number stored.
+    ///
+    /// Expired cookies delete their stored counterpart.
+    pub fn store_set_cookie(&mut self, scope: &str, url: &url::Url, set_cookie: &str) -> usize {
+        let mut stored = 0;
+        for cookie in set_cookie.lines().filter_map(|line| Cookie::parse(line, url)) {
+            if cookie.is_expired() {
+                self.remove(scope, &cookie.domain, &cookie.path, &cookie.name);
+            } else {
+                self.set(scope, cookie);
+                stored += 1;
+            }
+        }
+        stored
+    }
+
+    /// The `Cookie` header value for a request to `url`, if any cookies match.
+    ///
+    /// Cookies with longer paths come first, as RFC 6265 recommends.
+    pub fn cookie_header(&self, scope: &str, url: &url::Url) -> Option<String> {
+        let mut matching: Vec<&Cookie> = self
+            .scopes
+            .get(scope)?
+            .iter()
+            .filter(|c| c.matches(url))
+            .collect();
+        if matching.is_empty() {
+            return None;
+        }
+        matching.sort_by_key(|c| std::cmp::Reverse(c.path.len()));
+        Some(
+            matching
+                .iter()
+                .map(|c| format!("{}={}", c.name, c.value))
+                .collect::<Vec<_>>()
+                .join("; "),
+        )
+    }
+
+    /// Unexpired cookies in a scope, optionally only those for `domain` and
+    /// its subdomains.
+    pub fn list(&self, scope: &str, domain: Option<&str>) -> Vec<Cookie> {
+        self.scopes
+            .get(scope)
+            .map(|cookies| {
+                cookies
+                    .iter()
+                    .filter(|c| !c.is_expired())
+                    .filter(|c| domain.is_none_or(|d| domain_matches(&c.domain, &d.to_ascii_lowercase())))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Add a cookie, replacing one with the same name, domain and path.
+    pub fn set(&mut self, scope: &str, cookie: Cookie) {
+        let cookies = self.scopes.entry(scope.to_string()).or_default();
+        match cookies.iter_mut().find(|c| c.same_identity(&cookie)) {
+            Some(existing) => *existing = cookie,
+            None => cookies.push(cookie),
+        }
+    }
+
+    pub fn remove(&mut self, scope: &str, domain: &str, path: &str, name: &str) -> bool {
+        let Some(cookies) = self.scopes.get_mut(scope) else {
+            return false;
+        };
+        let before = cookies.len();
+        cookies.retain(|c| !(c.domain == domain && c.path == path && c.name == name));
+        cookies.len() != before
+    }
+
+    /// Remove all cookies in a scope, or only those for `domain` and its
+    /// subdomains. Returns the number removed.
+    pub fn clear(&mut self, scope: &str, domain: Option<&str>) -> usize {
+        let Some(cookies) = self.scopes.get_mut(scope) else {
+            return 0;
+        };
+        let before = cookies.len();
+        match domain {
+            Some(domain) => {
+                let domain = domain.to_ascii_lowercase();
+                cookies.retain(|c| !domain_matches(&c.domain, &domain));
+            }
+            None => cookies.clear(),
+        }
+        before - cookies.len()
+    }
+
+    /// Drop expired cookies from every scope.
+    pub fn remove_expired(&mut self) {
+        for cookies in self.scopes.values_mut() {
+            cookies.retain(|c| !c.is_expired());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jar_captures_and_sends_cookies() {
+        let mut jar = CookieJar::new();
+        let login = url::Url::parse("https://api.example.com/auth/login").unwrap();
+        let stored = jar.store_set_cookie(
+            WORKSPACE_SCOPE,
+            &login,
+            "session=abc; Path=/; HttpOnly\ncsrf=xyz; Path=/auth\ntracking=1; Domain=other.com",
+        );
+        assert_eq!(stored, 2);
+
+        let header = jar.cookie_header(WORKSPACE_SCOPE, &login).unwrap();
+        assert_eq!(header, "csrf=xyz; session=abc");
+        let users = url::Url::parse("https://api.example.com/users").unwrap();
+        assert_eq!(jar.cookie_header(WORKSPACE_SCOPE, &users).unwrap(), "session=abc");
+        assert!(jar.cookie_header("other-env", &users).is_none());
+
+        // Re-setting replaces the value; an expired cookie deletes it
+        jar.store_set_cookie(WORKSPACE_SCOPE, &login, "session=def; Path=/");
+        assert_eq!(jar.cookie_header(WORKSPACE_SCOPE, &users).unwrap(), "session=def");
+        jar.store_set_cookie(WORKSPACE_SCOPE, &login, "session=; Path=/; Max-Age=0");
+        assert!(jar.cookie_header(WORKSPACE_SCOPE, &users).is_none());
+    }
+
+    #[test]
+    fn test_list_and_clear_by_domain() {
+        let mut jar = CookieJar::new();
+        jar.set(WORKSPACE_SCOPE, Cookie::new("a", "1", "example.com"));
+        jar.set(WORKSPACE_SCOPE, Cookie::new("b", "2", "api.example.com"));
+        jar.set(WORKSPACE_SCOPE, Cookie::new("c", "3", "other.org"));
+
+        assert_eq!(jar.list(WORKSPACE_SCOPE, None).len(), 3);
+        assert_eq!(jar.list(WORKSPACE_SCOPE, Some("example.com")).len(), 2);
+        assert_eq!(jar.list(WORKSPACE_SCOPE, Some("api.example.com")).len(), 1);
+
+        assert!(jar.remove(WORKSPACE_SCOPE, "other.org", "/", "c"));
+        assert_eq!(jar.clear(WORKSPACE_SCOPE, Some("Example.com")), 2);
+        assert!(jar.list(WORKSPACE_SCOPE, None).is_empty());
+    }
+}
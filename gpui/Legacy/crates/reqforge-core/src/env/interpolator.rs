@@ -1,59 +1,368 @@
+use base64::Engine;
+use rand::Rng;
 use regex::Regex;
 use std::collections::HashMap;
 use std::borrow::Cow;
-use crate::models::request::{RequestDefinition, BodyType, KeyValuePair};
+use std::convert::Infallible;
+use std::sync::LazyLock;
+use crate::env::variables::{VariableScope, VariableSource};
+use crate::models::request::{RequestDefinition, BodyType, KeyValuePair, MultipartValue};
 
+static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{\{\s*([^{}]+?)\s*\}\}").unwrap());
+
+/// How many variables deep a value may reference other variables.
+pub const MAX_RESOLUTION_DEPTH: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum InterpolationError {
+    /// Variables that reference each other, e.g. `["a", "b", "a"]`.
+    #[error("Variable cycle: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+    #[error("Variable '{name}' references variables more than {limit} levels deep")]
+    TooDeep { name: String, limit: usize },
+}
+
+/// What a placeholder resolves to, for tooltips and dry runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaceholderExplanation {
+    /// The placeholder as written, e.g. `{{token | urlencode}}`.
+    pub placeholder: String,
+    /// The variable it reads; `None` for dynamic values such as `$uuid`.
+    pub variable: Option<String>,
+    /// The resolved value, or `None` if it cannot be resolved. Dynamic
+    /// values show a sample that will differ when the request is sent.
+    pub value: Option<String>,
+    /// The scope layer that provides `variable`.
+    pub source: Option<VariableSource>,
+    pub error: Option<InterpolationError>,
+}
+
+/// Substitutes `{{...}}` placeholders in requests.
+///
+/// A placeholder holds a variable name or a dynamic value, optionally followed
+/// by filters:
+///
+/// - `{{name}}` — a variable from the active environment
+/// - `{{$uuid}}` — a random v4 UUID
+/// - `{{$timestamp}}` — Unix time in seconds
+/// - `{{$isoDate}}` — the current UTC time in RFC 3339
+/// - `{{$randomInt}}`, `{{$randomInt(1,100)}}` — a random integer, inclusive (0 to 1000 by default)
+/// - `{{$base64(name)}}` — a variable, base64-encoded
+/// - `{{$env:HOME}}` — a process environment variable
+/// - `{{token | urlencode}}` — filters: `urlencode`, `base64`, `upper`, `lower`, `trim`
+///
+/// Variable values may contain placeholders themselves; they are expanded
+/// up to `MAX_RESOLUTION_DEPTH` levels. Dynamic values are generated again
+/// for every placeholder.
 pub struct Interpolator;
 
 impl Interpolator {
     /// Resolve all `{{var}}` placeholders in a request definition,
     /// returning a new owned copy with concrete values.
+    ///
+    /// Placeholders that cannot be evaluated, including variable cycles,
+    /// are left untouched. Use `try_resolve` to report cycles instead.
     pub fn resolve(
         req: &RequestDefinition,
         vars: &HashMap<String, String>,
     ) -> RequestDefinition {
+        let resolved = Self::map_strings(req, &mut |text| {
+            Ok::<_, Infallible>(Self::replace(text, vars).into_owned())
+        });
+        match resolved {
+            Ok(resolved) => resolved,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Like `resolve`, but fails if a variable references itself through
+    /// other variables or nests deeper than `MAX_RESOLUTION_DEPTH`.
+    pub fn try_resolve(
+        req: &RequestDefinition,
+        vars: &HashMap<String, String>,
+    ) -> Result<RequestDefinition, InterpolationError> {
+        Self::map_strings(req, &mut |text| {
+            Self::expand(text, vars, &mut Vec::new(), true).map(Cow::into_owned)
+        })
+    }
+
+    /// Explain every placeholder in `input`: the value it resolves to and
+    /// which scope layer provides it.
+    pub fn explain(input: &str, scope: &VariableScope) -> Vec<PlaceholderExplanation> {
+        let vars = scope.to_map();
+        PLACEHOLDER
+            .captures_iter(input)
+            .map(|caps| {
+                let expr = &caps[1];
+                let variable = Self::referenced_variable(expr).map(str::to_string);
+                let (value, error) = match Self::evaluate(expr, &vars, &mut Vec::new()) {
+                    Ok(value) => (value, None),
+                    Err(error) => (None, Some(error)),
+                };
+                PlaceholderExplanation {
+                    placeholder: caps[0].to_string(),
+                    source: variable.as_deref().and_then(|name| scope.source(name)).cloned(),
+                    variable,
+                    value,
+                    error,
+                }
+            })
+            .collect()
+    }
+
+    /// `explain` for every placeholder in a request, each listed once in
+    /// the order it first appears (URL, headers, params, auth, body).
+    pub fn explain_request(req: &RequestDefinition, scope: &VariableScope) -> Vec<PlaceholderExplanation> {
+        let mut explanations: Vec<PlaceholderExplanation> = Vec::new();
+        let _ = Self::map_strings(req, &mut |text| {
+            for explanation in Self::explain(text, scope) {
+                if !explanations.iter().any(|e| e.placeholder == explanation.placeholder) {
+                    explanations.push(explanation);
+                }
+            }
+            Ok::<_, Infallible>(String::new())
+        });
+        explanations
+    }
+
+    /// Placeholders still left in a resolved request, by variable name (or
+    /// expression, for dynamic values), each listed once. Disabled headers,
+    /// params and multipart parts are ignored since they are not sent.
+    pub fn unresolved(req: &RequestDefinition) -> Vec<String> {
+        let mut sent = req.clone();
+        sent.headers.retain(|h| h.enabled);
+        sent.query_params.retain(|p| p.enabled);
+        match &mut sent.body {
+            BodyType::FormUrlEncoded(pairs) => pairs.retain(|p| p.enabled),
+            BodyType::Multipart(parts) => parts.retain(|p| p.enabled),
+            _ => {}
+        }
+
+        let mut names: Vec<String> = Vec::new();
+        let _ = Self::map_strings(&sent, &mut |text| {
+            for caps in PLACEHOLDER.captures_iter(text) {
+                let name = Self::referenced_variable(&caps[1]).unwrap_or(&caps[1]).to_string();
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+            Ok::<_, Infallible>(String::new())
+        });
+        names
+    }
+
+    /// Copy a request, passing every string that may hold placeholders
+    /// through `f`.
+    fn map_strings<E>(
+        req: &RequestDefinition,
+        f: &mut dyn FnMut(&str) -> Result<String, E>,
+    ) -> Result<RequestDefinition, E> {
         let mut resolved = req.clone();
-        resolved.url = Self::replace(&resolved.url, vars).into_owned();
-        Self::resolve_pairs(&mut resolved.headers, vars);
-        Self::resolve_pairs(&mut resolved.query_params, vars);
+        resolved.url = f(&resolved.url)?;
+        Self::map_pairs(&mut resolved.headers, f)?;
+        Self::map_pairs(&mut resolved.query_params, f)?;
+        for value in resolved.auth.values_mut() {
+            *value = f(value)?;
+        }
         resolved.body = match &resolved.body {
             BodyType::None => BodyType::None,
             BodyType::Raw { content, content_type } => BodyType::Raw {
-                content: Self::replace(content, vars).into_owned(),
+                content: f(content)?,
                 content_type: content_type.clone(),
             },
             BodyType::FormUrlEncoded(pairs) => {
                 let mut p = pairs.clone();
-                Self::resolve_pairs(&mut p, vars);
+                Self::map_pairs(&mut p, f)?;
                 BodyType::FormUrlEncoded(p)
             }
+            BodyType::Multipart(parts) => {
+                let mut p = parts.clone();
+                for part in p.iter_mut() {
+                    part.name = f(&part.name)?;
+                    part.content_type = part.content_type.as_deref().map(&mut *f).transpose()?;
+                    part.value = match &part.value {
+                        MultipartValue::Text(value) => MultipartValue::Text(f(value)?),
+                        MultipartValue::File { path, filename } => MultipartValue::File {
+                            path: f(path)?,
+                            filename: filename.as_deref().map(&mut *f).transpose()?,
+                        },
+                    };
+                }
+                BodyType::Multipart(p)
+            }
+            BodyType::Binary { path } => BodyType::Binary {
+                path: f(path)?,
+            },
         };
-        resolved
+        Ok(resolved)
     }
 
-    /// Replace `{{var}}` placeholders with values from vars.
+    /// Replace `{{...}}` placeholders with values from vars or dynamic values.
     /// Returns `Cow<'a, str>` — borrows input when no substitutions needed,
     /// owns an allocated String only when interpolation occurs.
     fn replace<'a>(input: &'a str, vars: &HashMap<String, String>) -> Cow<'a, str> {
+        match Self::expand(input, vars, &mut Vec::new(), false) {
+            Ok(replaced) => replaced,
+            Err(_) => Cow::Borrowed(input),
+        }
+    }
+
+    /// Expand placeholders in `input`. `stack` holds the variables being
+    /// expanded, outermost first. Unless `strict`, placeholders that fail
+    /// are left as written.
+    fn expand<'a>(
+        input: &'a str,
+        vars: &HashMap<String, String>,
+        stack: &mut Vec<String>,
+        strict: bool,
+    ) -> Result<Cow<'a, str>, InterpolationError> {
         // Fast-path: no placeholders = zero allocation
         if !input.contains("{{") {
-            return Cow::Borrowed(input);
+            return Ok(Cow::Borrowed(input));
+        }
+
+        let mut output = String::with_capacity(input.len());
+        let mut last = 0;
+        for caps in PLACEHOLDER.captures_iter(input) {
+            let placeholder = caps.get(0).expect("whole match");
+            output.push_str(&input[last..placeholder.start()]);
+            match Self::evaluate(&caps[1], vars, stack) {
+                Ok(Some(value)) => output.push_str(&value),
+                Ok(None) => output.push_str(placeholder.as_str()),
+                Err(error) if strict => return Err(error),
+                Err(_) => output.push_str(placeholder.as_str()),
+            }
+            last = placeholder.end();
         }
+        output.push_str(&input[last..]);
+        Ok(Cow::Owned(output))
+    }
 
-        // Slow-path: regex substitution
-        let re = Regex::new(r"\{\{(\w+)\}\}").unwrap(); // compile once in real code
-        Cow::Owned(re.replace_all(input, |caps: &regex::Captures| {
-            let key = &caps[1];
-            vars.get(key).cloned().unwrap_or_else(|| format!("{{{{{}}}}}", key))
-        }).to_string())
+    /// Evaluate a placeholder expression: a value followed by `| filter`s.
+    fn evaluate(
+        expr: &str,
+        vars: &HashMap<String, String>,
+        stack: &mut Vec<String>,
+    ) -> Result<Option<String>, InterpolationError> {
+        let mut parts = expr.split('|').map(str::trim);
+        let Some(mut value) = Self::value(parts.next().unwrap_or_default(), vars, stack)? else {
+            return Ok(None);
+        };
+        for filter in parts {
+            value = match filter {
+                "urlencode" => url_encode(&value),
+                "base64" => base64::engine::general_purpose::STANDARD.encode(value),
+                "upper" => value.to_uppercase(),
+                "lower" => value.to_lowercase(),
+                "trim" => value.trim().to_string(),
+                _ => return Ok(None),
+            };
+        }
+        Ok(Some(value))
     }
 
-    fn resolve_pairs(pairs: &mut Vec<KeyValuePair>, vars: &HashMap<String, String>) {
+    fn value(
+        term: &str,
+        vars: &HashMap<String, String>,
+        stack: &mut Vec<String>,
+    ) -> Result<Option<String>, InterpolationError> {
+        let Some(dynamic) = term.strip_prefix('$') else {
+            return Self::variable(term, vars, stack);
+        };
+        if let Some(name) = dynamic.strip_prefix("env:") {
+            return Ok(std::env::var(name.trim()).ok());
+        }
+        let (function, args) = match dynamic.split_once('(') {
+            Some((function, rest)) => match rest.strip_suffix(')') {
+                Some(args) => (function.trim(), Some(args)),
+                None => return Ok(None),
+            },
+            None => (dynamic, None),
+        };
+        Ok(match (function, args) {
+            ("uuid", None) => Some(uuid::Uuid::new_v4().to_string()),
+            ("timestamp", None) => Some(chrono::Utc::now().timestamp().to_string()),
+            ("isoDate", None) => {
+                Some(chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true))
+            }
+            ("randomInt", None) => Some(rand::rng().random_range(0..=1000).to_string()),
+            ("randomInt", Some(args)) => random_int(args),
+            ("base64", Some(name)) => Self::variable(name.trim(), vars, stack)?
+                .map(|value| base64::engine::general_purpose::STANDARD.encode(value)),
+            _ => None,
+        })
+    }
+
+    /// A variable's value with any placeholders in it expanded.
+    fn variable(
+        name: &str,
+        vars: &HashMap<String, String>,
+        stack: &mut Vec<String>,
+    ) -> Result<Option<String>, InterpolationError> {
+        let Some(value) = vars.get(name) else {
+            return Ok(None);
+        };
+        if !value.contains("{{") {
+            return Ok(Some(value.clone()));
+        }
+        if let Some(start) = stack.iter().position(|n| n == name) {
+            let mut cycle = stack[start..].to_vec();
+            cycle.push(name.to_string());
+            return Err(InterpolationError::Cycle(cycle));
+        }
+        if stack.len() >= MAX_RESOLUTION_DEPTH {
+            return Err(InterpolationError::TooDeep { name: name.to_string(), limit: MAX_RESOLUTION_DEPTH });
+        }
+
+        stack.push(name.to_string());
+        let expanded = Self::expand(value, vars, stack, true).map(Cow::into_owned);
+        stack.pop();
+        expanded.map(Some)
+    }
+
+    /// The variable an expression reads, if any.
+    fn referenced_variable(expr: &str) -> Option<&str> {
+        let term = expr.split('|').next()?.trim();
+        match term.strip_prefix('$') {
+            Some(dynamic) => dynamic
+                .strip_prefix("base64(")
+                .and_then(|rest| rest.strip_suffix(')'))
+                .map(str::trim),
+            None => Some(term),
+        }
+    }
+
+    fn map_pairs<E>(
+        pairs: &mut [KeyValuePair],
+        f: &mut dyn FnMut(&str) -> Result<String, E>,
+    ) -> Result<(), E> {
         for pair in pairs.iter_mut() {
-            pair.key = Self::replace(&pair.key, vars).into_owned();
-            pair.value = Self::replace(&pair.value, vars).into_owned();
+            pair.key = f(&pair.key)?;
+            pair.value = f(&pair.value)?;
+        }
+        Ok(())
+    }
+}
+
+/// `$randomInt(min, max)`, inclusive.
+fn random_int(args: &str) -> Option<String> {
+    let (min, max) = args.split_once(',')?;
+    let min: i64 = min.trim().parse().ok()?;
+    let max: i64 = max.trim().parse().ok()?;
+    (min <= max).then(|| rand::rng().random_range(min..=max).to_string())
+}
+
+/// Percent-encode everything except RFC 3986 unreserved characters.
+fn url_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
         }
     }
+    encoded
 }
 
 #[cfg(test)]
@@ -198,4 +507,178 @@ mod tests {
         );
         assert_eq!(result, "value1 start value2 middle value1 end value2");
     }
+
+    #[test]
+    fn test_resolve_multipart_and_binary_bodies() {
+        use crate::models::request::{HttpMethod, MultipartPart};
+
+        let mut vars = HashMap::new();
+        vars.insert("dir".into(), "/data".into());
+        vars.insert("title".into(), "Report".into());
+
+        let mut req = RequestDefinition::new("Upload", HttpMethod::POST, "https://example.com");
+        req.body = BodyType::Multipart(vec![
+            MultipartPart::text("title", "{{title}}"),
+            MultipartPart::file("file", "{{dir}}/report.pdf"),
+        ]);
+        let resolved = Interpolator::resolve(&req, &vars);
+        let BodyType::Multipart(parts) = resolved.body else { panic!("expected multipart body") };
+        assert_eq!(parts[0].value, MultipartValue::Text("Report".into()));
+        assert_eq!(parts[1].value, MultipartValue::File { path: "/data/report.pdf".into(), filename: None });
+
+        req.body = BodyType::Binary { path: "{{dir}}/blob.bin".into() };
+        let resolved = Interpolator::resolve(&req, &vars);
+        assert!(matches!(resolved.body, BodyType::Binary { ref path } if path == "/data/blob.bin"));
+    }
+
+    #[test]
+    fn test_dynamic_variables() {
+        let mut vars = HashMap::new();
+        vars.insert("user".into(), "alice:secret".into());
+
+        let id = Interpolator::replace("{{$uuid}}", &vars);
+        assert!(uuid::Uuid::parse_str(&id).is_ok());
+        assert_ne!(Interpolator::replace("{{$uuid}}", &vars), id);
+
+        let ts: i64 = Interpolator::replace("{{$timestamp}}", &vars).parse().unwrap();
+        assert!((chrono::Utc::now().timestamp() - ts).abs() < 5);
+        let iso = Interpolator::replace("{{$isoDate}}", &vars);
+        assert!(chrono::DateTime::parse_from_rfc3339(&iso).is_ok());
+
+        for _ in 0..20 {
+            let n: i64 = Interpolator::replace("{{$randomInt(1, 3)}}", &vars).parse().unwrap();
+            assert!((1..=3).contains(&n));
+        }
+        let n: i64 = Interpolator::replace("{{$randomInt}}", &vars).parse().unwrap();
+        assert!((0..=1000).contains(&n));
+
+        assert_eq!(Interpolator::replace("Basic {{$base64(user)}}", &vars), "Basic YWxpY2U6c2VjcmV0");
+        assert_eq!(
+            Interpolator::replace("{{$env:PATH}}", &vars),
+            std::env::var("PATH").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_filters() {
+        let mut vars = HashMap::new();
+        vars.insert("token".into(), " a b/c&d ".into());
+        vars.insert("name".into(), "Alice".into());
+
+        assert_eq!(Interpolator::replace("{{token | urlencode}}", &vars), "%20a%20b%2Fc%26d%20");
+        assert_eq!(Interpolator::replace("{{ token|trim|urlencode }}", &vars), "a%20b%2Fc%26d");
+        assert_eq!(Interpolator::replace("{{name | upper}}-{{name|lower}}", &vars), "ALICE-alice");
+        assert_eq!(Interpolator::replace("{{name | base64}}", &vars), "QWxpY2U=");
+        assert_eq!(Interpolator::replace("{{$uuid | upper}}", &vars).len(), 36);
+    }
+
+    #[test]
+    fn test_unresolvable_expressions_preserved() {
+        let vars = HashMap::new();
+        for input in [
+            "{{missing | upper}}",
+            "{{$unknown}}",
+            "{{$randomInt(5,1)}}",
+            "{{$randomInt(a,b)}}",
+            "{{$base64(missing)}}",
+            "{{$env:REQFORGE_SURELY_UNSET_VAR}}",
+            "{{ missing }}",
+        ] {
+            assert_eq!(Interpolator::replace(input, &vars), input);
+        }
+        let mut vars = HashMap::new();
+        vars.insert("name".into(), "x".into());
+        assert_eq!(Interpolator::replace("{{name | reverse}}", &vars), "{{name | reverse}}");
+    }
+
+    #[test]
+    fn test_recursive_resolution() {
+        let mut vars = HashMap::new();
+        vars.insert("host".into(), "api.example.com".into());
+        vars.insert("base_url".into(), "https://{{host}}/{{version}}".into());
+        vars.insert("version".into(), "v{{major}}".into());
+        vars.insert("major".into(), "2".into());
+        vars.insert("auth".into(), "{{missing}}".into());
+
+        assert_eq!(Interpolator::replace("{{base_url}}/users", &vars), "https://api.example.com/v2/users");
+        assert_eq!(Interpolator::replace("{{$base64(version)}}", &vars), "djI=");
+        assert_eq!(Interpolator::replace("{{auth}}", &vars), "{{missing}}");
+    }
+
+    #[test]
+    fn test_cycles_and_depth_limit() {
+        let mut vars = HashMap::new();
+        vars.insert("a".into(), "x{{b}}".into());
+        vars.insert("b".into(), "{{c}}".into());
+        vars.insert("c".into(), "{{a}}".into());
+        vars.insert("ok".into(), "fine".into());
+
+        // `replace` leaves the cyclic placeholder and resolves the rest
+        assert_eq!(Interpolator::replace("{{a}} {{ok}}", &vars), "{{a}} fine");
+
+        let req = RequestDefinition::new("Cycle", crate::models::request::HttpMethod::GET, "https://{{ok}}/{{b}}");
+        let err = Interpolator::try_resolve(&req, &vars).unwrap_err();
+        assert_eq!(err, InterpolationError::Cycle(vec!["b".into(), "c".into(), "a".into(), "b".into()]));
+        assert_eq!(err.to_string(), "Variable cycle: b -> c -> a -> b");
+
+        let mut chain = HashMap::new();
+        for i in 0..=MAX_RESOLUTION_DEPTH {
+            chain.insert(format!("v{}", i), format!("{{{{v{}}}}}", i + 1));
+        }
+        chain.insert(format!("v{}", MAX_RESOLUTION_DEPTH + 1), "end".into());
+        let req = RequestDefinition::new("Deep", crate::models::request::HttpMethod::GET, "{{v0}}");
+        assert!(matches!(
+            Interpolator::try_resolve(&req, &chain),
+            Err(InterpolationError::TooDeep { limit: MAX_RESOLUTION_DEPTH, .. })
+        ));
+        let req = RequestDefinition::new("Shallow", crate::models::request::HttpMethod::GET, "{{v2}}");
+        assert_eq!(Interpolator::try_resolve(&req, &chain).unwrap().url, "end");
+    }
+
+    #[test]
+    fn test_explain() {
+        use crate::models::environment::Variable;
+        let var = |key: &str, value: &str| Variable { key: key.into(), value: value.into(), secret: false, enabled: true };
+
+        let mut scope = VariableScope::new();
+        scope.push_variables(VariableSource::Global, &[var("host", "example.com")]);
+        scope.push_variables(VariableSource::Environment, &[var("base_url", "https://{{host}}"), var("loop", "{{loop}}")]);
+
+        let explained = Interpolator::explain("{{base_url}}/{{$uuid}}/{{ missing }}/{{loop}}/{{$base64(host)}}", &scope);
+        assert_eq!(explained.len(), 5);
+        assert_eq!(explained[0].placeholder, "{{base_url}}");
+        assert_eq!(explained[0].value.as_deref(), Some("https://example.com"));
+        assert_eq!(explained[0].source, Some(VariableSource::Environment));
+        assert!(explained[1].variable.is_none() && explained[1].value.is_some());
+        assert_eq!((explained[2].variable.as_deref(), &explained[2].value), (Some("missing"), &None));
+        assert_eq!(explained[3].error, Some(InterpolationError::Cycle(vec!["loop".into(), "loop".into()])));
+        assert_eq!(explained[4].source, Some(VariableSource::Global));
+
+        let mut req = RequestDefinition::new("Explain", crate::models::request::HttpMethod::GET, "{{base_url}}/a");
+        req.headers.push(KeyValuePair { key: "Host".into(), value: "{{host}}".into(), enabled: true, description: None });
+        req.query_params.push(KeyValuePair { key: "h".into(), value: "{{base_url}}".into(), enabled: true, description: None });
+        let placeholders: Vec<_> = Interpolator::explain_request(&req, &scope).into_iter().map(|e| e.placeholder).collect();
+        assert_eq!(placeholders, vec!["{{base_url}}", "{{host}}"]);
+    }
+
+    #[test]
+    fn test_unresolved_placeholders() {
+        use crate::models::request::{HttpMethod, MultipartPart};
+
+        let mut req = RequestDefinition::new("Missing", HttpMethod::POST, "{{base_url}}/users/{{id}}");
+        req.headers.push(KeyValuePair { key: "Authorization".into(), value: "Bearer {{token | trim}}".into(), enabled: true, description: None });
+        req.headers.push(KeyValuePair { key: "X-Off".into(), value: "{{disabled}}".into(), enabled: false, description: None });
+        req.query_params.push(KeyValuePair { key: "home".into(), value: "{{$env:REQFORGE_SURELY_UNSET_VAR}}".into(), enabled: true, description: None });
+        req.body = BodyType::Multipart(vec![MultipartPart::text("id", "{{id}}")]);
+
+        let mut vars = HashMap::new();
+        vars.insert("base_url".into(), "https://example.com".into());
+        let resolved = Interpolator::resolve(&req, &vars);
+        assert_eq!(Interpolator::unresolved(&resolved), vec!["id", "token", "$env:REQFORGE_SURELY_UNSET_VAR"]);
+
+        vars.insert("id".into(), "7".into());
+        vars.insert("token".into(), "t".into());
+        req.query_params.clear();
+        assert!(Interpolator::unresolved(&Interpolator::resolve(&req, &vars)).is_empty());
+    }
 }
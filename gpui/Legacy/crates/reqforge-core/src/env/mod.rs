@@ -1,3 +1,5 @@
 pub mod interpolator;
+pub mod variables;
 
 pub use interpolator::*;
+pub use variables::*;
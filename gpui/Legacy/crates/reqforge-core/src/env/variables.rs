@@ -1 +1,170 @@
-// Variables module - to be implemented
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::environment::Variable;
+
+/// Where a variable's value came from, from lowest to highest precedence.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VariableSource {
+    /// Workspace-wide globals.
+    Global,
+    Collection,
+    /// A folder containing the request, by name. Inner folders win.
+    Folder(String),
+    Environment,
+    /// Variables defined on the request itself.
+    Request,
+    /// Values set by scripts during this session.
+    Runtime,
+    /// A data-file row fed in by the collection runner.
+    Data,
+}
+
+impl std::fmt::Display for VariableSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VariableSource::Global => write!(f, "global"),
+            VariableSource::Collection => write!(f, "collection"),
+            VariableSource::Folder(name) => write!(f, "folder '{}'", name),
+            VariableSource::Environment => write!(f, "environment"),
+            VariableSource::Request => write!(f, "request"),
+            VariableSource::Runtime => write!(f, "runtime"),
+            VariableSource::Data => write!(f, "data"),
+        }
+    }
+}
+
+/// A variable's effective value and the layer that set it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScopedVariable {
+    pub key: String,
+    pub value: String,
+    pub source: VariableSource,
+}
+
+/// Variable layers in resolution order; later layers override earlier ones.
+///
+/// `ReqForgeCore::variable_scope` builds the chain globals → collection →
+/// folders → environment → request → runtime for a request.
+#[derive(Debug, Clone, Default)]
+pub struct VariableScope {
+    layers: Vec<(VariableSource, HashMap<String, String>)>,
+}
+
+impl VariableScope {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a layer that overrides everything added before it.
+    pub fn push_layer(&mut self, source: VariableSource, vars: HashMap<String, String>) {
+        if !vars.is_empty() {
+            self.layers.push((source, vars));
+        }
+    }
+
+    /// Add the enabled variables from a list as a layer.
+    pub fn push_variables(&mut self, source: VariableSource, vars: &[Variable]) {
+        self.push_layer(source, enabled_variables(vars));
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.lookup(key).map(|(value, _)| value)
+    }
+
+    /// The layer that provides `key`'s effective value.
+    pub fn source(&self, key: &str) -> Option<&VariableSource> {
+        self.lookup(key).map(|(_, source)| source)
+    }
+
+    /// Every value `key` has, highest precedence first. The first entry is
+    /// the effective one; the rest are shadowed.
+    pub fn candidates(&self, key: &str) -> Vec<(&VariableSource, &str)> {
+        self.layers
+            .iter()
+            .rev()
+            .filter_map(|(source, vars)| vars.get(key).map(|value| (source, value.as_str())))
+            .collect()
+    }
+
+    /// All effective variables with their sources, sorted by key.
+    pub fn variables(&self) -> Vec<ScopedVariable> {
+        let mut effective: HashMap<&str, (&str, &VariableSource)> = HashMap::new();
+        for (source, vars) in &self.layers {
+            for (key, value) in vars {
+                effective.insert(key, (value, source));
+            }
+        }
+        let mut variables: Vec<ScopedVariable> = effective
+            .into_iter()
+            .map(|(key, (value, source))| ScopedVariable {
+                key: key.to_string(),
+                value: value.to_string(),
+                source: source.clone(),
+            })
+            .collect();
+        variables.sort_by(|a, b| a.key.cmp(&b.key));
+        variables
+    }
+
+    /// The flattened map used for interpolation.
+    pub fn to_map(&self) -> HashMap<String, String> {
+        let mut map = HashMap::new();
+        for (_, vars) in &self.layers {
+            map.extend(vars.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        map
+    }
+
+    fn lookup(&self, key: &str) -> Option<(&str, &VariableSource)> {
+        self.layers
+            .iter()
+            .rev()
+            .find_map(|(source, vars)| vars.get(key).map(|value| (value.as_str(), source)))
+    }
+}
+
+/// The enabled variables in a list as a map.
+pub fn enabled_variables(vars: &[Variable]) -> HashMap<String, String> {
+    vars.iter()
+        .filter(|v| v.enabled)
+        .map(|v| (v.key.clone(), v.value.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn var(key: &str, value: &str) -> Variable {
+        Variable { key: key.into(), value: value.into(), secret: false, enabled: true }
+    }
+
+    #[test]
+    fn test_later_layers_override_earlier_ones() {
+        let mut scope = VariableScope::new();
+        scope.push_variables(VariableSource::Global, &[var("host", "global.test"), var("retries", "3")]);
+        scope.push_variables(VariableSource::Collection, &[var("host", "collection.test"), var("version", "v1")]);
+        scope.push_variables(VariableSource::Folder("Users".into()), &[var("version", "v2")]);
+        let mut disabled = var("host", "ignored.test");
+        disabled.enabled = false;
+        scope.push_variables(VariableSource::Environment, &[disabled]);
+
+        assert_eq!(scope.get("host"), Some("collection.test"));
+        assert_eq!(scope.source("host"), Some(&VariableSource::Collection));
+        assert_eq!(scope.source("version"), Some(&VariableSource::Folder("Users".into())));
+        assert_eq!(scope.source("retries"), Some(&VariableSource::Global));
+        assert_eq!(scope.get("missing"), None);
+        assert_eq!(
+            scope.candidates("host"),
+            vec![(&VariableSource::Collection, "collection.test"), (&VariableSource::Global, "global.test")]
+        );
+
+        let map = scope.to_map();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["version"], "v2");
+        let keys: Vec<_> = scope.variables().into_iter().map(|v| v.key).collect();
+        assert_eq!(keys, vec!["host", "retries", "version"]);
+    }
+}
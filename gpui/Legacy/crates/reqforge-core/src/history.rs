@@ -1,30 +1,176 @@
-use std::collections::VecDeque;
-use std::path::PathBuf;
+use std::collections::{HashSet, VecDeque};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
 
 use crate::http::{HttpEngine, HttpError};
-use crate::models::history::{RequestHistoryEntry, ResponseSnapshot};
-use crate::models::request::RequestDefinition;
-use crate::store::StoreError;
+use crate::models::history::{HistoryFilter, RequestHistoryEntry, StoredBody};
+use crate::store::schema::{self, Document};
+use crate::store::{StoreError, write_atomic};
 
 const DEFAULT_MAX_HISTORY_SIZE: usize = 100;
+/// Bodies larger than this are truncated before being stored.
+const DEFAULT_MAX_BODY_SIZE: usize = 1024 * 1024;
+/// Compressed size of all stored bodies; the oldest are dropped beyond it.
+const DEFAULT_MAX_TOTAL_BODY_SIZE: u64 = 50 * 1024 * 1024;
+/// Unreferenced bodies younger than this are left alone when pruning, as
+/// they may belong to an entry that is about to be added.
+const ORPHAN_GRACE_PERIOD: Duration = Duration::from_secs(60);
+
+/// Where history entries and their gzip-compressed response bodies are
+/// persisted. Each `Store` provides one (see `Store::history`).
+pub trait HistoryBackend: Send + Sync + std::fmt::Debug {
+    /// Entries, most recent first; empty if nothing has been saved yet.
+    fn load_entries(&self) -> Result<Vec<RequestHistoryEntry>, StoreError>;
+    fn save_entries(&self, entries: &[RequestHistoryEntry]) -> Result<(), StoreError>;
+
+    fn write_body(&self, id: uuid::Uuid, compressed: &[u8]) -> Result<(), StoreError>;
+    fn read_body(&self, id: uuid::Uuid) -> Result<Option<Vec<u8>>, StoreError>;
+    fn remove_body(&self, id: uuid::Uuid) -> Result<(), StoreError>;
+    /// Remove bodies of entries not in `kept` that were written before `cutoff`.
+    fn remove_orphaned_bodies(&self, kept: &HashSet<uuid::Uuid>, cutoff: SystemTime) -> Result<(), StoreError>;
+    fn clear_bodies(&self) -> Result<(), StoreError>;
+}
+
+/// History kept in `history.json`, with response bodies in
+/// `history/{entry_id}.gz` next to it.
+#[derive(Debug, Clone)]
+pub struct FileHistory {
+    history_path: PathBuf,
+    body_dir: PathBuf,
+}
+
+impl FileHistory {
+    pub fn new(workspace_dir: &Path) -> Self {
+        Self {
+            history_path: workspace_dir.join("history.json"),
+            body_dir: workspace_dir.join("history"),
+        }
+    }
+
+    fn body_path(&self, id: uuid::Uuid) -> PathBuf {
+        self.body_dir.join(format!("{}.gz", id))
+    }
+}
+
+impl HistoryBackend for FileHistory {
+    fn load_entries(&self) -> Result<Vec<RequestHistoryEntry>, StoreError> {
+        if !self.history_path.exists() {
+            return Ok(Vec::new());
+        }
+        let data = std::fs::read_to_string(&self.history_path)?;
+        Ok(schema::from_str(Document::History, &data)?)
+    }
+
+    fn save_entries(&self, entries: &[RequestHistoryEntry]) -> Result<(), StoreError> {
+        write_atomic(&self.history_path, schema::to_string(Document::History, entries)?)?;
+        Ok(())
+    }
+
+    fn write_body(&self, id: uuid::Uuid, compressed: &[u8]) -> Result<(), StoreError> {
+        std::fs::create_dir_all(&self.body_dir)?;
+        write_atomic(&self.body_path(id), compressed)?;
+        Ok(())
+    }
+
+    fn read_body(&self, id: uuid::Uuid) -> Result<Option<Vec<u8>>, StoreError> {
+        let path = self.body_path(id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(std::fs::read(path)?))
+    }
+
+    fn remove_body(&self, id: uuid::Uuid) -> Result<(), StoreError> {
+        let path = self.body_path(id);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    fn remove_orphaned_bodies(&self, kept: &HashSet<uuid::Uuid>, cutoff: SystemTime) -> Result<(), StoreError> {
+        if !self.body_dir.exists() {
+            return Ok(());
+        }
+        let kept: HashSet<PathBuf> = kept.iter().map(|id| self.body_path(*id)).collect();
+        for entry in std::fs::read_dir(&self.body_dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if !kept.contains(&path) && entry.metadata()?.modified()? < cutoff {
+                std::fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn clear_bodies(&self) -> Result<(), StoreError> {
+        if self.body_dir.exists() {
+            std::fs::remove_dir_all(&self.body_dir)?;
+        }
+        Ok(())
+    }
+}
 
 /// Manager for request history with persistence
+///
+/// Entries and their response bodies are persisted by a `HistoryBackend`:
+/// `new` keeps them in files in the workspace directory (see `FileHistory`).
 #[derive(Debug)]
 pub struct RequestHistory {
     entries: VecDeque<RequestHistoryEntry>,
     max_size: usize,
-    history_path: PathBuf,
+    max_total_body_size: u64,
+    bodies: BodyStore,
     is_dirty: bool,
 }
 
+/// Writes response bodies for history entries. It is cheap to clone, so a
+/// body can be compressed and written without holding a lock on the
+/// history, and the entry added afterwards with
+/// `RequestHistory::add_entry_with_stored_body`.
+#[derive(Debug, Clone)]
+pub struct BodyStore {
+    backend: Arc<dyn HistoryBackend>,
+    max_body_size: usize,
+}
+
+impl BodyStore {
+    /// Compress and write a body for entry `id`, truncated to the per-entry limit.
+    pub fn write(&self, id: uuid::Uuid, body: &[u8]) -> Result<Option<StoredBody>, StoreError> {
+        if self.max_body_size == 0 || body.is_empty() {
+            return Ok(None);
+        }
+        let kept = &body[..body.len().min(self.max_body_size)];
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(kept)?;
+        let compressed = encoder.finish()?;
+        self.backend.write_body(id, &compressed)?;
+        Ok(Some(StoredBody {
+            compressed_bytes: compressed.len() as u64,
+            truncated: kept.len() < body.len(),
+        }))
+    }
+}
+
 impl RequestHistory {
-    /// Create a new RequestHistory manager
+    /// Create a new RequestHistory manager, kept in `workspace_dir`
     pub fn new(workspace_dir: PathBuf) -> Self {
-        let history_path = workspace_dir.join("history.json");
+        Self::with_backend(Arc::new(FileHistory::new(&workspace_dir)))
+    }
+
+    /// Create a new RequestHistory manager persisted by `backend`
+    pub fn with_backend(backend: Arc<dyn HistoryBackend>) -> Self {
         Self {
             entries: VecDeque::with_capacity(DEFAULT_MAX_HISTORY_SIZE),
             max_size: DEFAULT_MAX_HISTORY_SIZE,
-            history_path,
+            max_total_body_size: DEFAULT_MAX_TOTAL_BODY_SIZE,
+            bodies: BodyStore { backend, max_body_size: DEFAULT_MAX_BODY_SIZE },
             is_dirty: false,
         }
     }
@@ -35,35 +181,37 @@ impl RequestHistory {
         self
     }
 
-    /// Load history from disk
-    pub fn load(&mut self) -> Result<(), StoreError> {
-        if !self.history_path.exists() {
-            return Ok(());
-        }
+    /// Set the largest response body stored per entry, in bytes. Larger
+    /// bodies are truncated; 0 stores no bodies.
+    pub fn with_max_body_size(mut self, max_body_size: usize) -> Self {
+        self.bodies.max_body_size = max_body_size;
+        self
+    }
 
-        let data = std::fs::read_to_string(&self.history_path)?;
-        let loaded: Vec<RequestHistoryEntry> = serde_json::from_str(&data)?;
+    /// Set the total compressed size of stored bodies, in bytes. Bodies of
+    /// the oldest entries are dropped to stay under it.
+    pub fn with_max_total_body_size(mut self, max_total_body_size: u64) -> Self {
+        self.max_total_body_size = max_total_body_size;
+        self
+    }
 
-        self.entries.clear();
-        for entry in loaded.into_iter().rev().take(self.max_size) {
-            self.entries.push_back(entry);
-        }
+    /// Load history from the backend
+    pub fn load(&mut self) -> Result<(), StoreError> {
+        let loaded = self.bodies.backend.load_entries()?;
+        self.entries = loaded.into_iter().take(self.max_size).collect();
 
         self.is_dirty = false;
         Ok(())
     }
 
-    /// Save history to disk if dirty
+    /// Save history to the backend if dirty
     pub fn save(&self) -> Result<(), StoreError> {
         if !self.is_dirty {
             return Ok(());
         }
 
         let entries: Vec<_> = self.entries.iter().cloned().collect();
-        let json = serde_json::to_string_pretty(&entries)?;
-        std::fs::write(&self.history_path, json)?;
-
-        Ok(())
+        self.bodies.backend.save_entries(&entries)
     }
 
     /// Add a new history entry
@@ -71,13 +219,62 @@ impl RequestHistory {
         self.entries.push_front(entry);
 
         // Trim to max size
+        let mut pruned = false;
         while self.entries.len() > self.max_size {
-            self.entries.pop_back();
+            if let Some(removed) = self.entries.pop_back() {
+                self.remove_body(&removed);
+                pruned = true;
+            }
+        }
+        if pruned {
+            let _ = self.remove_orphaned_bodies();
         }
 
         self.is_dirty = true;
     }
 
+    /// Add a new history entry, storing `body` as its response body (subject
+    /// to the size limits). The entry is added even if storing the body fails.
+    pub fn add_entry_with_body(&mut self, entry: RequestHistoryEntry, body: &[u8]) -> Result<(), StoreError> {
+        let stored = self.bodies.write(entry.id, body);
+        self.add_entry_with_stored_body(entry, stored.as_ref().ok().cloned().flatten());
+        stored.map(|_| ())
+    }
+
+    /// Add a new history entry whose response body was already written with
+    /// `body_store()`.
+    pub fn add_entry_with_stored_body(&mut self, mut entry: RequestHistoryEntry, body: Option<StoredBody>) {
+        entry.response.body = body;
+        self.add_entry(entry);
+        self.enforce_total_body_size();
+    }
+
+    /// A handle for writing response bodies outside a lock on the history.
+    pub fn body_store(&self) -> BodyStore {
+        self.bodies.clone()
+    }
+
+    /// The stored response body of an entry, if it was kept.
+    pub fn response_body(&self, id: uuid::Uuid) -> Result<Option<Vec<u8>>, StoreError> {
+        let Some(entry) = self.get_entry(id) else { return Ok(None) };
+        if entry.response.body.is_none() {
+            return Ok(None);
+        }
+        let Some(compressed) = self.bodies.backend.read_body(id)? else { return Ok(None) };
+        let mut body = Vec::new();
+        GzDecoder::new(compressed.as_slice()).read_to_end(&mut body)?;
+        Ok(Some(body))
+    }
+
+    /// Entries matching `filter`, most recent first
+    pub fn search(&self, filter: &HistoryFilter) -> Vec<RequestHistoryEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| filter.matches(entry))
+            .cloned()
+            .collect()
+    }
+
     /// Get all history entries, most recent first
     pub fn get_all(&self) -> Vec<RequestHistoryEntry> {
         self.entries.iter().cloned().collect()
@@ -100,6 +297,7 @@ impl RequestHistory {
     /// Clear all history
     pub fn clear(&mut self) {
         self.entries.clear();
+        let _ = self.bodies.backend.clear_bodies();
         self.is_dirty = true;
     }
 
@@ -113,6 +311,35 @@ impl RequestHistory {
         self.entries.is_empty()
     }
 
+    fn remove_body(&self, entry: &RequestHistoryEntry) {
+        if entry.response.body.is_some() {
+            let _ = self.bodies.backend.remove_body(entry.id);
+        }
+    }
+
+    /// Drop the bodies of the oldest entries until the total fits the limit.
+    fn enforce_total_body_size(&mut self) {
+        let mut total: u64 = self.entries.iter().filter_map(|e| e.response.body.as_ref()).map(|b| b.compressed_bytes).sum();
+        for index in (0..self.entries.len()).rev() {
+            if total <= self.max_total_body_size { break; }
+            let entry = &self.entries[index];
+            let Some(body) = &entry.response.body else { continue };
+            total -= body.compressed_bytes;
+            self.remove_body(entry);
+            self.entries[index].response.body = None;
+        }
+    }
+
+    /// Remove bodies left by entries that were never saved or were trimmed,
+    /// except recent ones (see `ORPHAN_GRACE_PERIOD`).
+    fn remove_orphaned_bodies(&self) -> Result<(), StoreError> {
+        let kept: HashSet<uuid::Uuid> = self.entries.iter()
+            .filter(|e| e.response.body.is_some())
+            .map(|e| e.id)
+            .collect();
+        self.bodies.backend.remove_orphaned_bodies(&kept, SystemTime::now() - ORPHAN_GRACE_PERIOD)
+    }
+
     /// Replay a history entry
     pub async fn replay(
         &self,
@@ -142,7 +369,8 @@ pub enum ReplayError {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::request::HttpMethod;
+    use crate::models::history::ResponseSnapshot;
+    use crate::models::request::{HttpMethod, RequestDefinition};
 
     #[test]
     fn test_add_entry() {
@@ -156,6 +384,9 @@ mod tests {
             size_bytes: 100,
             elapsed_millis: 50,
             success: true,
+            timing: None,
+            headers: Default::default(),
+            body: None,
         };
 
         let entry = RequestHistoryEntry::new(request, response, None, None);
@@ -182,6 +413,9 @@ mod tests {
                 size_bytes: 100,
                 elapsed_millis: 50,
                 success: true,
+                timing: None,
+                headers: Default::default(),
+                body: None,
             };
 
             let entry = RequestHistoryEntry::new(request, response, None, None);
@@ -208,6 +442,9 @@ mod tests {
                 size_bytes: 100,
                 elapsed_millis: 50,
                 success: true,
+                timing: None,
+                headers: Default::default(),
+                body: None,
             };
 
             let entry = RequestHistoryEntry::new(request, response, None, None);
@@ -232,6 +469,9 @@ mod tests {
             size_bytes: 100,
             elapsed_millis: 50,
             success: true,
+            timing: None,
+            headers: Default::default(),
+            body: None,
         };
 
         let entry = RequestHistoryEntry::new(request, response, None, None);
@@ -256,6 +496,9 @@ mod tests {
             size_bytes: 100,
             elapsed_millis: 50,
             success: true,
+            timing: None,
+            headers: Default::default(),
+            body: None,
         };
 
         let entry = RequestHistoryEntry::new(request, response, None, None);
@@ -272,4 +515,114 @@ mod tests {
         assert_eq!(loaded[0].request.name, "Test");
         assert_eq!(loaded[0].response.status, 200);
     }
+
+    fn entry(method: HttpMethod, url: &str, status: u16) -> RequestHistoryEntry {
+        let response = ResponseSnapshot {
+            status,
+            status_text: String::new(),
+            size_bytes: 0,
+            elapsed_millis: 1,
+            success: (200..300).contains(&status),
+            timing: None,
+            headers: [("content-type".to_string(), "application/json".to_string())].into(),
+            body: None,
+        };
+        RequestHistoryEntry::new(RequestDefinition::new("Test", method, url), response, None, None)
+    }
+
+    #[test]
+    fn test_response_bodies_are_stored_and_capped() {
+        let temp = tempfile::tempdir().unwrap();
+        let mut history = RequestHistory::new(temp.path().to_path_buf())
+            .with_max_size(3)
+            .with_max_body_size(10);
+
+        let first = entry(HttpMethod::GET, "https://example.com/a", 200);
+        let first_id = first.id;
+        history.add_entry_with_body(first, b"{\"id\": 1}").unwrap();
+        assert_eq!(history.response_body(first_id).unwrap().unwrap(), b"{\"id\": 1}");
+        assert!(!history.get_entry(first_id).unwrap().response.body.as_ref().unwrap().truncated);
+
+        let long = entry(HttpMethod::GET, "https://example.com/b", 200);
+        let long_id = long.id;
+        history.add_entry_with_body(long, b"0123456789abcdef").unwrap();
+        assert_eq!(history.response_body(long_id).unwrap().unwrap(), b"0123456789");
+        assert!(history.get_entry(long_id).unwrap().response.body.as_ref().unwrap().truncated);
+
+        // Bodies survive a reload, and trimmed entries take their bodies with them
+        history.save().unwrap();
+        let mut reloaded = RequestHistory::new(temp.path().to_path_buf()).with_max_size(3);
+        reloaded.load().unwrap();
+        assert_eq!(reloaded.get_all()[0].id, long_id);
+        assert_eq!(reloaded.response_body(first_id).unwrap().unwrap(), b"{\"id\": 1}");
+        for _ in 0..3 {
+            reloaded.add_entry_with_body(entry(HttpMethod::GET, "https://example.com/c", 200), b"c").unwrap();
+        }
+        assert!(!temp.path().join(format!("history/{}.gz", first_id)).exists());
+
+        // Over the total limit, the oldest bodies are dropped but entries are kept
+        let mut capped = RequestHistory::new(temp.path().join("capped")).with_max_total_body_size(1);
+        let old = entry(HttpMethod::GET, "https://example.com/old", 200);
+        let old_id = old.id;
+        capped.add_entry_with_body(old, b"old body").unwrap();
+        capped.add_entry_with_body(entry(HttpMethod::GET, "https://example.com/new", 200), b"new body").unwrap();
+        assert_eq!(capped.len(), 2);
+        assert!(capped.response_body(old_id).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_orphaned_bodies_are_removed_when_pruning() {
+        let temp = tempfile::tempdir().unwrap();
+        let bodies = temp.path().join("history");
+        std::fs::create_dir_all(&bodies).unwrap();
+        let stale = bodies.join(format!("{}.gz", uuid::Uuid::new_v4()));
+        std::fs::File::create(&stale)
+            .unwrap()
+            .set_modified(SystemTime::now() - ORPHAN_GRACE_PERIOD * 2)
+            .unwrap();
+        let fresh = bodies.join(format!("{}.gz", uuid::Uuid::new_v4()));
+        std::fs::write(&fresh, b"").unwrap();
+
+        let mut history = RequestHistory::new(temp.path().to_path_buf()).with_max_size(1);
+        history.load().unwrap();
+        history.add_entry(entry(HttpMethod::GET, "https://example.com/a", 200));
+        assert!(stale.exists(), "loading and adding without pruning leave the files alone");
+
+        // A body written outside the history, for an entry added afterwards
+        let pending = entry(HttpMethod::GET, "https://example.com/b", 200);
+        let body = history.body_store().write(pending.id, b"pending").unwrap();
+        history.add_entry_with_stored_body(pending.clone(), body);
+        assert!(!stale.exists());
+        assert!(fresh.exists(), "recent files may belong to an entry being added");
+        assert_eq!(history.response_body(pending.id).unwrap().unwrap(), b"pending");
+    }
+
+    #[test]
+    fn test_search_filters() {
+        let temp = tempfile::tempdir().unwrap();
+        let mut history = RequestHistory::new(temp.path().to_path_buf());
+        let env_id = uuid::Uuid::new_v4();
+
+        let mut old = entry(HttpMethod::GET, "{{base_url}}/users", 200);
+        old.timestamp -= chrono::Duration::days(2);
+        history.add_entry(old);
+        let mut created = entry(HttpMethod::POST, "{{base_url}}/users", 201);
+        created.environment_id = Some(env_id);
+        history.add_entry(created);
+        history.add_entry(entry(HttpMethod::GET, "{{base_url}}/orders/7", 404));
+        history.add_entry(entry(HttpMethod::GET, "{{base_url}}/Users/7", 500));
+
+        let count = |filter: HistoryFilter| history.search(&filter).len();
+        assert_eq!(count(HistoryFilter::default()), 4);
+        assert_eq!(count(HistoryFilter { url: Some("/users".into()), ..Default::default() }), 3);
+        assert_eq!(count(HistoryFilter { method: Some(HttpMethod::POST), ..Default::default() }), 1);
+        assert_eq!(count(HistoryFilter { status: Some(400..=599), ..Default::default() }), 2);
+        assert_eq!(count(HistoryFilter { environment_id: Some(env_id), ..Default::default() }), 1);
+        let yesterday = chrono::Utc::now() - chrono::Duration::days(1);
+        assert_eq!(count(HistoryFilter { since: Some(yesterday), ..Default::default() }), 3);
+        assert_eq!(count(HistoryFilter { until: Some(yesterday), ..Default::default() }), 1);
+
+        let results = history.search(&HistoryFilter { url: Some("users".into()), method: Some(HttpMethod::GET), ..Default::default() });
+        assert_eq!(results.iter().map(|e| e.response.status).collect::<Vec<_>>(), vec![500, 200]);
+    }
 }
@@ -1,13 +1,27 @@
 use reqwest::Client;
-use crate::models::request::{RequestDefinition, BodyType, RawContentType};
+use crate::models::request::{RequestDefinition, BodyType};
 use crate::models::response::HttpResponse;
+use crate::http::multipart::{encode_multipart, guess_content_type};
+use crate::http::auth::{self, DigestChallenge, SigV4Credentials};
+use crate::http::timing::{self, TimedConnectLayer, TimedResolver};
+use crate::http::tls;
+use crate::models::auth::{ApiKeyLocation, AuthConfig, OAuth2Config, OAuth2Token};
+use crate::models::settings::{EngineSettings, HttpVersionPref, RedirectPolicy};
 use crate::validation::ValidationError;
+use crate::scripting::ScriptError;
+use crate::env::interpolator::InterpolationError;
+use std::collections::HashMap;
+use std::sync::Mutex;
 use std::time::Instant;
 use std::time::Duration;
-use bytes::Bytes;
 
 pub struct HttpEngine {
-    client: Client,
+    settings: EngineSettings,
+    /// Clients built so far, with the settings each was built from. Requests
+    /// with overrides get their own client.
+    clients: Mutex<Vec<(EngineSettings, Client)>>,
+    /// OAuth 2.0 tokens keyed by `auth::token_cache_key`.
+    token_cache: Mutex<HashMap<String, OAuth2Token>>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -18,18 +32,91 @@ pub enum HttpError {
     UrlParse(String),
     #[error("Validation error: {0}")]
     Validation(#[from] ValidationError),
+    #[error("Script error: {0}")]
+    Script(#[from] ScriptError),
+    #[error("Failed to read body file '{path}': {source}")]
+    BodyFile { path: String, source: std::io::Error },
+    #[error("Authentication error: {0}")]
+    Auth(String),
+    #[error("Invalid engine settings: {0}")]
+    Settings(String),
+    #[error("Variable error: {0}")]
+    Interpolation(#[from] InterpolationError),
+    #[error("Unresolved variables: {}", .0.join(", "))]
+    UnresolvedVariables(Vec<String>),
 }
 
 impl HttpEngine {
+    /// An engine with the default settings (10s connect / 30s total timeouts).
     pub fn new() -> Self {
-        let client = Client::builder()
-            .danger_accept_invalid_certs(false)   // make configurable later
-            // Prevent requests from hanging indefinitely (can be made configurable later)
-            .connect_timeout(Duration::from_secs(10))
-            .timeout(Duration::from_secs(30))
-            .build()
-            .expect("failed to build HTTP client");
-        Self { client }
+        Self::with_settings(EngineSettings::default())
+    }
+
+    /// An engine with workspace settings. Clients are built on first use, so
+    /// problems such as a missing CA bundle surface as `HttpError::Settings`
+    /// when a request is sent.
+    pub fn with_settings(settings: EngineSettings) -> Self {
+        Self {
+            settings,
+            clients: Mutex::new(Vec::new()),
+            token_cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn settings(&self) -> &EngineSettings {
+        &self.settings
+    }
+
+    /// Replace the workspace settings, discarding clients built from the old ones.
+    pub fn set_settings(&mut self, settings: EngineSettings) {
+        self.settings = settings;
+        if let Ok(clients) = self.clients.get_mut() {
+            clients.clear();
+        }
+    }
+
+    /// The client for the workspace settings, shared with the OAuth 2.0 helpers.
+    pub(crate) fn client(&self) -> Result<Client, HttpError> {
+        self.client_for(&self.settings)
+    }
+
+    /// Return a cached client built from `settings`, building one if needed.
+    fn client_for(&self, settings: &EngineSettings) -> Result<Client, HttpError> {
+        if let Ok(clients) = self.clients.lock()
+            && let Some((_, client)) = clients.iter().find(|(s, _)| s == settings)
+        {
+            return Ok(client.clone());
+        }
+
+        let client = build_client(settings)?;
+        if let Ok(mut clients) = self.clients.lock() {
+            clients.push((settings.clone(), client.clone()));
+        }
+        Ok(client)
+    }
+
+    /// Forget all cached OAuth 2.0 tokens so the next request fetches new ones.
+    pub fn clear_token_cache(&self) {
+        if let Ok(mut cache) = self.token_cache.lock() {
+            cache.clear();
+        }
+    }
+
+    /// Return a cached, unexpired token for this configuration or fetch a new one.
+    async fn oauth2_access_token(&self, config: &OAuth2Config) -> Result<String, HttpError> {
+        let key = auth::token_cache_key(config);
+        if let Ok(cache) = self.token_cache.lock()
+            && let Some(token) = cache.get(&key).filter(|t| !t.is_expired())
+        {
+            return Ok(token.access_token.clone());
+        }
+
+        let token = auth::fetch_oauth2_token(&self.client()?, config).await?;
+        let access_token = token.access_token.clone();
+        if let Ok(mut cache) = self.token_cache.lock() {
+            cache.insert(key, token);
+        }
+        Ok(access_token)
     }
 
     /// Execute a fully-resolved RequestDefinition.
@@ -54,34 +141,61 @@ impl HttpEngine {
             crate::models::request::HttpMethod::OPTIONS => reqwest::Method::OPTIONS,
         };
 
-        let mut builder = self.client.request(method, &req.url);
+        // Fetch an OAuth 2.0 token before building, so a failure sends nothing
+        let oauth2_token = match &req.auth {
+            AuthConfig::OAuth2(config) => Some(self.oauth2_access_token(config).await?),
+            _ => None,
+        };
+
+        let client = if req.settings.is_empty() {
+            self.client()?
+        } else {
+            self.client_for(&self.settings.with_overrides(&req.settings))?
+        };
+        let mut builder = client.request(method, &req.url);
 
         // Query params
-        let query_params: Vec<(&str, &str)> = req.query_params
+        let mut query_params: Vec<(&str, &str)> = req.query_params
             .iter()
             .filter(|p| p.enabled)
             .map(|p| (p.key.as_str(), p.value.as_str()))
             .collect();
+        if let AuthConfig::ApiKey { key, value, location: ApiKeyLocation::Query } = &req.auth {
+            query_params.push((key.as_str(), value.as_str()));
+        }
         if !query_params.is_empty() {
             builder = builder.query(&query_params);
         }
 
-        // Headers
+        // Headers. Multipart bodies always send their own Content-Type,
+        // since it has to carry the generated boundary.
+        let is_multipart = matches!(req.body, BodyType::Multipart(_));
         for h in req.headers.iter().filter(|h| h.enabled) {
+            if is_multipart && h.key.eq_ignore_ascii_case("content-type") {
+                continue;
+            }
             builder = builder.header(&h.key, &h.value);
         }
 
+        // Auth that only needs a header; Digest and SigV4 are handled below
+        builder = match &req.auth {
+            AuthConfig::Basic { username, password } => {
+                builder.header("Authorization", auth::basic_authorization(username, password))
+            }
+            AuthConfig::Bearer { token } => builder.header("Authorization", format!("Bearer {}", token)),
+            AuthConfig::ApiKey { key, value, location: ApiKeyLocation::Header } => builder.header(key, value),
+            AuthConfig::OAuth2(_) => match &oauth2_token {
+                Some(token) => builder.header("Authorization", format!("Bearer {}", token)),
+                None => builder,
+            },
+            _ => builder,
+        };
+
         // Body
         builder = match &req.body {
             BodyType::None => builder,
             BodyType::Raw { content, content_type } => {
-                let mime = match content_type {
-                    RawContentType::Json => "application/json",
-                    RawContentType::Xml  => "application/xml",
-                    RawContentType::Text => "text/plain",
-                    RawContentType::Html => "text/html",
-                };
-                builder.header("Content-Type", mime).body(content.clone())
+                builder.header("Content-Type", content_type.mime_type()).body(content.clone())
             }
             BodyType::FormUrlEncoded(pairs) => {
                 let form: Vec<(&str, &str)> = pairs.iter()
@@ -90,21 +204,72 @@ impl HttpEngine {
                     .collect();
                 if form.is_empty() { builder } else { builder.form(&form) }
             }
+            BodyType::Multipart(parts) => {
+                let encoded = encode_multipart(parts)
+                    .await
+                    .map_err(|(path, source)| HttpError::BodyFile { path, source })?;
+                builder.header("Content-Type", encoded.content_type).body(encoded.body)
+            }
+            BodyType::Binary { path } => {
+                let contents = tokio::fs::read(path)
+                    .await
+                    .map_err(|source| HttpError::BodyFile { path: path.clone(), source })?;
+                let has_content_type = req.headers.iter()
+                    .any(|h| h.enabled && h.key.eq_ignore_ascii_case("content-type"));
+                if has_content_type {
+                    builder.body(contents)
+                } else {
+                    builder.header("Content-Type", guess_content_type(path)).body(contents)
+                }
+            }
+        };
+
+        let mut request = builder.build()?;
+        if let AuthConfig::AwsSigV4 { access_key_id, secret_access_key, session_token, region, service } = &req.auth {
+            let credentials = SigV4Credentials { access_key_id, secret_access_key, region, service };
+            sign_aws_v4(&mut request, credentials, session_token.as_deref())?;
+        }
+
+        // Digest auth needs the server's challenge, so keep a copy to resend
+        let digest_retry = match &req.auth {
+            AuthConfig::Digest { .. } => request.try_clone(),
+            _ => None,
         };
 
         let start = Instant::now();
-        let response = builder.send().await?;
-        let elapsed = start.elapsed();
+        let (sent, marks) = timing::record_phases(async {
+            let mut request_sent = start;
+            let mut response = client.execute(request).await?;
+            if let AuthConfig::Digest { username, password } = &req.auth
+                && let Some(mut retry) = digest_retry
+                && response.status() == reqwest::StatusCode::UNAUTHORIZED
+                && let Some(challenge) = digest_challenge(&response)
+            {
+                let url = retry.url();
+                let uri = match url.query() {
+                    Some(query) => format!("{}?{}", url.path(), query),
+                    None => url.path().to_string(),
+                };
+                let cnonce = uuid::Uuid::new_v4().simple().to_string();
+                let header = auth::digest_authorization(username, password, retry.method().as_str(), &uri, &challenge, &cnonce)?;
+                retry.headers_mut().insert(reqwest::header::AUTHORIZATION, header_value(&header)?);
+                request_sent = Instant::now();
+                response = client.execute(retry).await?;
+            }
+            Ok::<_, HttpError>((response, request_sent))
+        })
+        .await;
+        let (response, request_sent) = sent?;
+        let headers_at = Instant::now();
 
         let status = response.status().as_u16();
         let status_text = response.status().canonical_reason().unwrap_or("").to_string();
-        let headers = response.headers().iter()
-            .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
-            .collect();
+        let headers = collect_headers(response.headers());
 
         // Use Bytes directly — reqwest returns Bytes, no allocation needed
         let body = response.bytes().await?;
         let size = body.len();
+        let timing = marks.breakdown(start, request_sent, headers_at, Instant::now());
 
         Ok(HttpResponse {
             status,
@@ -112,7 +277,131 @@ impl HttpEngine {
             headers,
             body,                 // Bytes — refcounted, cheap clone
             size_bytes: size,
-            elapsed,
+            elapsed: timing.total,
+            timing,
         })
     }
 }
+
+impl Default for HttpEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Build a client from engine settings, reading any certificate files.
+fn build_client(settings: &EngineSettings) -> Result<Client, HttpError> {
+    let mut builder = Client::builder().connect_timeout(Duration::from_millis(settings.connect_timeout_ms));
+    if let Some(ms) = settings.timeout_ms {
+        builder = builder.timeout(Duration::from_millis(ms));
+    }
+
+    if let Some(proxy) = &settings.proxy {
+        let mut reqwest_proxy = reqwest::Proxy::all(&proxy.url)
+            .map_err(|e| HttpError::Settings(format!("Invalid proxy URL '{}': {}", proxy.url, e)))?;
+        if let Some(username) = proxy.username.as_deref().filter(|u| !u.is_empty()) {
+            reqwest_proxy = reqwest_proxy.basic_auth(username, proxy.password.as_deref().unwrap_or(""));
+        }
+        if !proxy.no_proxy.is_empty() {
+            reqwest_proxy = reqwest_proxy.no_proxy(reqwest::NoProxy::from_string(&proxy.no_proxy.join(",")));
+        }
+        builder = builder.proxy(reqwest_proxy);
+    }
+
+    // CA bundle, client certificate and insecure mode are applied in the
+    // rustls config; the hooks record per-phase timing
+    builder = builder
+        .tls_backend_preconfigured(tls::client_config(settings)?)
+        .dns_resolver(TimedResolver)
+        .connector_layer(TimedConnectLayer);
+
+    builder = builder.redirect(match settings.redirects {
+        RedirectPolicy::None => reqwest::redirect::Policy::none(),
+        RedirectPolicy::Follow { max } => reqwest::redirect::Policy::limited(max),
+    });
+    builder = match settings.http_version {
+        HttpVersionPref::Auto => builder,
+        HttpVersionPref::Http1Only => builder.http1_only(),
+        HttpVersionPref::Http2Only => builder.http2_prior_knowledge(),
+    };
+
+    builder
+        .build()
+        .map_err(|e| HttpError::Settings(format!("Failed to build HTTP client: {}", e)))
+}
+
+/// Flatten response headers into a map. Repeated headers are joined with
+/// `", "`, except `Set-Cookie`, whose values may contain commas and are
+/// joined with newlines instead.
+fn collect_headers(headers: &reqwest::header::HeaderMap) -> HashMap<String, String> {
+    let mut collected: HashMap<String, String> = HashMap::new();
+    for (name, value) in headers {
+        let value = value.to_str().unwrap_or("");
+        match collected.get_mut(name.as_str()) {
+            Some(existing) => {
+                existing.push_str(if name == reqwest::header::SET_COOKIE { "\n" } else { ", " });
+                existing.push_str(value);
+            }
+            None => {
+                collected.insert(name.to_string(), value.to_string());
+            }
+        }
+    }
+    collected
+}
+
+/// First Digest challenge in a response's `WWW-Authenticate` headers.
+fn digest_challenge(response: &reqwest::Response) -> Option<DigestChallenge> {
+    response
+        .headers()
+        .get_all(reqwest::header::WWW_AUTHENTICATE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .find_map(auth::parse_digest_challenge)
+}
+
+/// Add AWS Signature Version 4 headers to a built request.
+fn sign_aws_v4(
+    request: &mut reqwest::Request,
+    credentials: SigV4Credentials<'_>,
+    session_token: Option<&str>,
+) -> Result<(), HttpError> {
+    let url = request.url().clone();
+    let host = match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        (Some(host), None) => host.to_string(),
+        (None, _) => return Err(HttpError::UrlParse("URL has no host to sign".to_string())),
+    };
+    let payload_hash = auth::sigv4_payload_hash(request.body().and_then(|b| b.as_bytes()).unwrap_or_default());
+    let amz_date = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+
+    let mut signed = vec![
+        ("host".to_string(), host),
+        ("x-amz-content-sha256".to_string(), payload_hash.clone()),
+        ("x-amz-date".to_string(), amz_date.clone()),
+    ];
+    if let Some(token) = session_token.filter(|t| !t.is_empty()) {
+        signed.push(("x-amz-security-token".to_string(), token.to_string()));
+    }
+
+    let authorization = auth::sigv4_authorization(
+        request.method().as_str(),
+        &url,
+        &signed,
+        &payload_hash,
+        credentials,
+        &amz_date,
+    );
+
+    let headers = request.headers_mut();
+    for (name, value) in signed.iter().filter(|(name, _)| name != "host") {
+        headers.insert(reqwest::header::HeaderName::from_bytes(name.as_bytes()).expect("static header name"), header_value(value)?);
+    }
+    headers.insert(reqwest::header::AUTHORIZATION, header_value(&authorization)?);
+    Ok(())
+}
+
+fn header_value(value: &str) -> Result<reqwest::header::HeaderValue, HttpError> {
+    reqwest::header::HeaderValue::from_str(value)
+        .map_err(|e| HttpError::Auth(format!("Invalid authorization header value: {}", e)))
+}
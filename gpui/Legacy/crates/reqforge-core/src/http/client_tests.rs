@@ -1,10 +1,12 @@
 #[cfg(test)]
 mod tests {
     use wiremock::{MockServer, Mock, ResponseTemplate, matchers::{method, path, query_param, header}};
-    use wiremock::matchers::body_string;
+    use wiremock::matchers::{body_bytes, body_string, body_string_contains, header_regex};
     use std::time::Duration;
     use crate::http::client::HttpEngine;
-    use crate::models::request::{RequestDefinition, HttpMethod, BodyType, RawContentType, KeyValuePair};
+    use crate::models::request::{RequestDefinition, HttpMethod, BodyType, RawContentType, KeyValuePair, MultipartPart};
+    use crate::http::client::HttpError;
+    use crate::models::auth::{ApiKeyLocation, AuthConfig, OAuth2Config, OAuth2Grant};
 
     /// Helper to create a test request definition
     fn create_test_request(method: HttpMethod, url: &str) -> RequestDefinition {
@@ -572,4 +574,449 @@ mod tests {
         // body_text should be None for non-UTF8 data
         assert!(response.body_text().is_none());
     }
+
+    #[tokio::test]
+    async fn test_post_multipart_body() {
+        // Start mock server
+        let mock_server = MockServer::start().await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("notes.txt");
+        std::fs::write(&file_path, "file contents").unwrap();
+
+        Mock::given(method("POST"))
+            .and(path("/api/upload"))
+            .and(header_regex("content-type", "^multipart/form-data; boundary=.+$"))
+            .and(body_string_contains("name=\"title\"\r\n\r\nHello"))
+            .and(body_string_contains("name=\"file\"; filename=\"notes.txt\"\r\nContent-Type: text/plain"))
+            .and(body_string_contains("file contents"))
+            .respond_with(ResponseTemplate::new(201))
+            .mount(&mock_server)
+            .await;
+
+        // A user-supplied Content-Type must not replace the boundary-carrying one
+        let engine = HttpEngine::new();
+        let url = format!("{}/api/upload", mock_server.uri());
+        let mut request = with_headers(
+            create_test_request(HttpMethod::POST, &url),
+            vec![("Content-Type", "multipart/form-data")],
+        );
+        request.body = BodyType::Multipart(vec![
+            MultipartPart::text("title", "Hello"),
+            MultipartPart::file("file", file_path.to_string_lossy()),
+        ]);
+
+        let response = engine.execute(&request).await.unwrap();
+        assert_eq!(response.status, 201);
+    }
+
+    #[tokio::test]
+    async fn test_put_binary_file_body() {
+        // Start mock server
+        let mock_server = MockServer::start().await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("blob.bin");
+        let data: Vec<u8> = vec![0x00, 0xFF, 0x10, 0x20];
+        std::fs::write(&file_path, &data).unwrap();
+
+        Mock::given(method("PUT"))
+            .and(path("/api/blob"))
+            .and(header("content-type", "application/octet-stream"))
+            .and(body_bytes(data))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&mock_server)
+            .await;
+
+        let engine = HttpEngine::new();
+        let url = format!("{}/api/blob", mock_server.uri());
+        let mut request = create_test_request(HttpMethod::PUT, &url);
+        request.body = BodyType::Binary { path: file_path.to_string_lossy().into_owned() };
+
+        let response = engine.execute(&request).await.unwrap();
+        assert_eq!(response.status, 204);
+
+        // A missing file is reported before anything is sent
+        request.body = BodyType::Binary { path: temp_dir.path().join("missing.bin").to_string_lossy().into_owned() };
+        let result = engine.execute(&request).await;
+        assert!(matches!(result, Err(HttpError::BodyFile { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_basic_and_api_key_auth() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/basic"))
+            .and(header("authorization", "Basic dXNlcjpwYXNz"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/key"))
+            .and(query_param("api_key", "k-123"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+
+        let engine = HttpEngine::new();
+        let mut request = create_test_request(HttpMethod::GET, &format!("{}/basic", mock_server.uri()));
+        request.auth = AuthConfig::Basic { username: "user".into(), password: "pass".into() };
+        assert_eq!(engine.execute(&request).await.unwrap().status, 200);
+
+        let mut request = create_test_request(HttpMethod::GET, &format!("{}/key", mock_server.uri()));
+        request.auth = AuthConfig::ApiKey {
+            key: "api_key".into(),
+            value: "k-123".into(),
+            location: ApiKeyLocation::Query,
+        };
+        assert_eq!(engine.execute(&request).await.unwrap().status, 200);
+    }
+
+    #[tokio::test]
+    async fn test_digest_auth_answers_challenge() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/digest"))
+            .and(header_regex("authorization", r#"^Digest username="alice", realm="api", nonce="n0nce", uri="/digest\?x=1".*qop=auth"#))
+            .respond_with(ResponseTemplate::new(200).set_body_string("welcome"))
+            .with_priority(1)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/digest"))
+            .respond_with(
+                ResponseTemplate::new(401)
+                    .insert_header("www-authenticate", r#"Digest realm="api", qop="auth", nonce="n0nce", opaque="op""#),
+            )
+            .with_priority(2)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let engine = HttpEngine::new();
+        let mut request = create_test_request(HttpMethod::GET, &format!("{}/digest?x=1", mock_server.uri()));
+        request.auth = AuthConfig::Digest { username: "alice".into(), password: "secret".into() };
+
+        let response = engine.execute(&request).await.unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body_text(), Some("welcome"));
+    }
+
+    #[tokio::test]
+    async fn test_oauth2_client_credentials_token_is_cached() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(header("authorization", "Basic YXBwOnNoaA=="))
+            .and(body_string_contains("grant_type=client_credentials"))
+            .and(body_string_contains("scope=read"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "tok-1",
+                "token_type": "Bearer",
+                "expires_in": 3600
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/resource"))
+            .and(header("authorization", "Bearer tok-1"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let engine = HttpEngine::new();
+        let mut request = create_test_request(HttpMethod::GET, &format!("{}/resource", mock_server.uri()));
+        request.auth = AuthConfig::OAuth2(OAuth2Config {
+            grant: OAuth2Grant::ClientCredentials,
+            token_url: format!("{}/token", mock_server.uri()),
+            client_id: "app".into(),
+            client_secret: "shh".into(),
+            scope: Some("read".into()),
+            credentials_in_body: false,
+        });
+
+        assert_eq!(engine.execute(&request).await.unwrap().status, 200);
+        assert_eq!(engine.execute(&request).await.unwrap().status, 200);
+    }
+
+    #[tokio::test]
+    async fn test_oauth2_token_error_is_reported() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(body_string_contains("grant_type=password"))
+            .and(body_string_contains("username=bob"))
+            .respond_with(ResponseTemplate::new(400).set_body_string(r#"{"error":"invalid_grant"}"#))
+            .mount(&mock_server)
+            .await;
+
+        let engine = HttpEngine::new();
+        let mut request = create_test_request(HttpMethod::GET, &format!("{}/resource", mock_server.uri()));
+        request.auth = AuthConfig::OAuth2(OAuth2Config {
+            grant: OAuth2Grant::Password { username: "bob".into(), password: "wrong".into() },
+            token_url: format!("{}/token", mock_server.uri()),
+            client_id: "app".into(),
+            client_secret: String::new(),
+            scope: None,
+            credentials_in_body: true,
+        });
+
+        let error = engine.execute(&request).await.unwrap_err();
+        assert!(matches!(error, HttpError::Auth(ref message) if message.contains("invalid_grant")));
+    }
+
+    #[tokio::test]
+    async fn test_aws_sigv4_headers_are_added() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/"))
+            .and(header_regex(
+                "authorization",
+                r"^AWS4-HMAC-SHA256 Credential=AKID/\d{8}/eu-west-1/execute-api/aws4_request, SignedHeaders=host;x-amz-content-sha256;x-amz-date;x-amz-security-token, Signature=[0-9a-f]{64}$",
+            ))
+            .and(header_regex("x-amz-date", r"^\d{8}T\d{6}Z$"))
+            .and(header("x-amz-security-token", "session"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+
+        let engine = HttpEngine::new();
+        let mut request = create_test_request(HttpMethod::POST, &format!("{}/", mock_server.uri()));
+        request.body = BodyType::Raw { content: "{}".into(), content_type: RawContentType::Json };
+        request.auth = AuthConfig::AwsSigV4 {
+            access_key_id: "AKID".into(),
+            secret_access_key: "secret".into(),
+            session_token: Some("session".into()),
+            region: "eu-west-1".into(),
+            service: "execute-api".into(),
+        };
+
+        assert_eq!(engine.execute(&request).await.unwrap().status, 200);
+    }
+
+    #[tokio::test]
+    async fn test_redirect_policy_and_request_override() {
+        use crate::models::settings::{EngineSettings, RedirectPolicy};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/old"))
+            .respond_with(ResponseTemplate::new(302).insert_header("Location", "/new"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/new"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+
+        let engine = HttpEngine::with_settings(EngineSettings {
+            redirects: RedirectPolicy::None,
+            ..Default::default()
+        });
+        let mut req = create_test_request(HttpMethod::GET, &format!("{}/old", mock_server.uri()));
+        assert_eq!(engine.execute(&req).await.unwrap().status, 302);
+
+        req.settings.redirects = Some(RedirectPolicy::Follow { max: 5 });
+        assert_eq!(engine.execute(&req).await.unwrap().status, 200);
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_override() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/slow"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(500)))
+            .mount(&mock_server)
+            .await;
+
+        let engine = HttpEngine::new();
+        let mut req = create_test_request(HttpMethod::GET, &format!("{}/slow", mock_server.uri()));
+        req.settings.timeout_ms = Some(100);
+
+        let result = engine.execute(&req).await;
+        assert!(matches!(result, Err(HttpError::Reqwest(ref e)) if e.is_timeout()));
+    }
+
+    #[tokio::test]
+    async fn test_requests_go_through_configured_proxy() {
+        use crate::models::settings::{EngineSettings, ProxySettings};
+
+        // The mock server acts as a plain HTTP proxy and answers in place of the origin.
+        let proxy = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/via-proxy"))
+            .and(header("Proxy-Authorization", "Basic dXNlcjpwYXNz"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("proxied"))
+            .mount(&proxy)
+            .await;
+
+        let engine = HttpEngine::with_settings(EngineSettings {
+            proxy: Some(ProxySettings {
+                url: proxy.uri(),
+                username: Some("user".to_string()),
+                password: Some("pass".to_string()),
+                no_proxy: vec!["localhost".to_string()],
+            }),
+            ..Default::default()
+        });
+        let req = create_test_request(HttpMethod::GET, "http://origin.invalid/via-proxy");
+        let response = engine.execute(&req).await.unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body_text(), Some("proxied"));
+
+        // Requests can opt out of the workspace proxy.
+        let mut direct = req.clone();
+        direct.settings.bypass_proxy = true;
+        assert!(matches!(engine.execute(&direct).await, Err(HttpError::Reqwest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_settings_are_reported() {
+        use crate::models::settings::{EngineSettings, ProxySettings, TlsSettings};
+
+        let req = create_test_request(HttpMethod::GET, "http://localhost:1/");
+
+        let engine = HttpEngine::with_settings(EngineSettings {
+            tls: TlsSettings {
+                ca_bundle_path: Some("/nonexistent/ca.pem".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        assert!(matches!(engine.execute(&req).await, Err(HttpError::Settings(ref m)) if m.contains("CA bundle")));
+
+        let engine = HttpEngine::with_settings(EngineSettings {
+            proxy: Some(ProxySettings {
+                url: "http://[::1".to_string(),
+                username: None,
+                password: None,
+                no_proxy: Vec::new(),
+            }),
+            ..Default::default()
+        });
+        assert!(matches!(engine.execute(&req).await, Err(HttpError::Settings(ref m)) if m.contains("Invalid proxy URL")));
+    }
+
+    /// A single-connection SOCKS5 proxy requiring username/password auth.
+    /// Tunnels to `upstream` whatever the client asked for and reports the
+    /// requested destination.
+    async fn socks5_proxy(upstream: std::net::SocketAddr) -> (String, tokio::task::JoinHandle<String>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = tokio::spawn(async move {
+            let (mut client, _) = listener.accept().await.unwrap();
+
+            let mut greeting = [0u8; 2];
+            client.read_exact(&mut greeting).await.unwrap();
+            let mut methods = vec![0u8; greeting[1] as usize];
+            client.read_exact(&mut methods).await.unwrap();
+            assert!(methods.contains(&2), "client must offer username/password auth");
+            client.write_all(&[5, 2]).await.unwrap();
+
+            let mut version = [0u8; 2];
+            client.read_exact(&mut version).await.unwrap();
+            let mut username = vec![0u8; version[1] as usize];
+            client.read_exact(&mut username).await.unwrap();
+            let mut len = [0u8; 1];
+            client.read_exact(&mut len).await.unwrap();
+            let mut password = vec![0u8; len[0] as usize];
+            client.read_exact(&mut password).await.unwrap();
+            let ok = username == b"user" && password == b"pass";
+            client.write_all(&[1, if ok { 0 } else { 1 }]).await.unwrap();
+
+            let mut request = [0u8; 4];
+            client.read_exact(&mut request).await.unwrap();
+            let host = match request[3] {
+                1 => {
+                    let mut ip = [0u8; 4];
+                    client.read_exact(&mut ip).await.unwrap();
+                    std::net::Ipv4Addr::from(ip).to_string()
+                }
+                3 => {
+                    client.read_exact(&mut len).await.unwrap();
+                    let mut name = vec![0u8; len[0] as usize];
+                    client.read_exact(&mut name).await.unwrap();
+                    String::from_utf8(name).unwrap()
+                }
+                other => panic!("unexpected address type {}", other),
+            };
+            let mut port = [0u8; 2];
+            client.read_exact(&mut port).await.unwrap();
+
+            let mut server = tokio::net::TcpStream::connect(upstream).await.unwrap();
+            client.write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0, 0]).await.unwrap();
+            let _ = tokio::io::copy_bidirectional(&mut client, &mut server).await;
+            format!("{}:{}", host, u16::from_be_bytes(port))
+        });
+        (format!("socks5h://{}", addr), handle)
+    }
+
+    #[tokio::test]
+    async fn test_requests_go_through_socks5_proxy() {
+        use crate::models::settings::{EngineSettings, ProxySettings};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/via-socks"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("tunnelled"))
+            .mount(&mock_server)
+            .await;
+
+        let (proxy_url, proxy) = socks5_proxy(*mock_server.address()).await;
+        let engine = HttpEngine::with_settings(EngineSettings {
+            proxy: Some(ProxySettings {
+                url: proxy_url,
+                username: Some("user".to_string()),
+                password: Some("pass".to_string()),
+                no_proxy: Vec::new(),
+            }),
+            ..Default::default()
+        });
+        // With socks5h the proxy resolves the name, so it need not exist locally.
+        let req = create_test_request(HttpMethod::GET, "http://origin.invalid:8080/via-socks");
+        let response = engine.execute(&req).await.unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body_text(), Some("tunnelled"));
+
+        drop(engine);
+        assert_eq!(proxy.await.unwrap(), "origin.invalid:8080");
+    }
+
+    #[tokio::test]
+    async fn test_timing_breakdown_for_new_and_reused_connections() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/timed"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(50)).set_body_string("ok"))
+            .mount(&mock_server)
+            .await;
+
+        // Use a host name so the first request has to resolve it
+        let port = mock_server.address().port();
+        let req = create_test_request(HttpMethod::GET, &format!("http://localhost:{}/timed", port));
+        let engine = HttpEngine::new();
+
+        let first = engine.execute(&req).await.unwrap().timing;
+        assert!(first.dns_lookup.is_some());
+        assert!(first.tcp_connect.is_some());
+        assert_eq!(first.tls_handshake, None);
+        assert!(first.time_to_first_byte >= Duration::from_millis(50));
+        assert!(first.total >= first.time_to_first_byte + first.content_download);
+
+        // The pooled connection is reused, so there are no connection phases
+        let second = engine.execute(&req).await.unwrap();
+        assert_eq!(second.timing.dns_lookup, None);
+        assert_eq!(second.timing.tcp_connect, None);
+        assert_eq!(second.elapsed, second.timing.total);
+        assert_eq!(second.timing.phases().len(), 2);
+    }
 }
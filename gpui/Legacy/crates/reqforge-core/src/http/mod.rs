@@ -1,4 +1,8 @@
 pub mod client;
+pub mod multipart;
+pub mod auth;
+pub mod timing;
+mod tls;
 
 #[cfg(test)]
 mod client_tests;
@@ -0,0 +1,156 @@
+//! `multipart/form-data` encoding for `BodyType::Multipart`.
+//!
+//! Files are read when the request is sent, so a stored request only keeps
+//! their paths.
+
+use std::path::Path;
+
+use crate::models::request::{MultipartPart, MultipartValue};
+
+/// An encoded multipart body together with its `Content-Type` header value.
+#[derive(Debug, Clone)]
+pub struct MultipartBody {
+    pub content_type: String,
+    pub body: Vec<u8>,
+}
+
+/// Encode the enabled parts, reading file parts from disk.
+pub async fn encode_multipart(parts: &[MultipartPart]) -> Result<MultipartBody, (String, std::io::Error)> {
+    let boundary = format!("----ReqForgeBoundary{}", uuid::Uuid::new_v4().simple());
+    let mut body = Vec::new();
+
+    for part in parts.iter().filter(|p| p.enabled) {
+        body.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+        match &part.value {
+            MultipartValue::Text(value) => {
+                body.extend_from_slice(
+                    format!("Content-Disposition: form-data; name=\"{}\"\r\n", escape_quoted(&part.name)).as_bytes(),
+                );
+                if let Some(content_type) = &part.content_type {
+                    body.extend_from_slice(format!("Content-Type: {}\r\n", content_type).as_bytes());
+                }
+                body.extend_from_slice(b"\r\n");
+                body.extend_from_slice(value.as_bytes());
+            }
+            MultipartValue::File { path, filename } => {
+                let contents = tokio::fs::read(path).await.map_err(|e| (path.clone(), e))?;
+                let filename = filename.clone().unwrap_or_else(|| {
+                    Path::new(path)
+                        .file_name()
+                        .map(|n| n.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| path.clone())
+                });
+                let content_type = part
+                    .content_type
+                    .clone()
+                    .unwrap_or_else(|| guess_content_type(path).to_string());
+                body.extend_from_slice(
+                    format!(
+                        "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n",
+                        escape_quoted(&part.name),
+                        escape_quoted(&filename),
+                    )
+                    .as_bytes(),
+                );
+                body.extend_from_slice(format!("Content-Type: {}\r\n\r\n", content_type).as_bytes());
+                body.extend_from_slice(&contents);
+            }
+        }
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+
+    Ok(MultipartBody {
+        content_type: format!("multipart/form-data; boundary={}", boundary),
+        body,
+    })
+}
+
+/// Guess a MIME type from a file extension, falling back to `application/octet-stream`.
+pub fn guess_content_type(path: &str) -> &'static str {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("json") => "application/json",
+        Some("xml") => "application/xml",
+        Some("txt") | Some("log") => "text/plain",
+        Some("csv") => "text/csv",
+        Some("html") | Some("htm") => "text/html",
+        Some("css") => "text/css",
+        Some("js") => "text/javascript",
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("svg") => "image/svg+xml",
+        Some("pdf") => "application/pdf",
+        Some("zip") => "application/zip",
+        Some("gz") => "application/gzip",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Escape quotes and line breaks in a Content-Disposition parameter.
+fn escape_quoted(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encode_text_and_file_parts() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("avatar.png");
+        std::fs::write(&file_path, b"\x89PNG").unwrap();
+
+        let mut disabled = MultipartPart::text("skip", "me");
+        disabled.enabled = false;
+        let mut renamed = MultipartPart::file("upload", file_path.to_string_lossy());
+        renamed.value = MultipartValue::File {
+            path: file_path.to_string_lossy().into_owned(),
+            filename: Some("me.png".to_string()),
+        };
+        renamed.content_type = Some("image/x-custom".to_string());
+
+        let parts = vec![
+            MultipartPart::text("title", "Hello"),
+            MultipartPart::file("avatar", file_path.to_string_lossy()),
+            renamed,
+            disabled,
+        ];
+
+        let encoded = encode_multipart(&parts).await.unwrap();
+        let boundary = encoded.content_type.strip_prefix("multipart/form-data; boundary=").unwrap();
+        let body = String::from_utf8_lossy(&encoded.body);
+
+        assert!(body.contains("Content-Disposition: form-data; name=\"title\"\r\n\r\nHello\r\n"));
+        assert!(body.contains("name=\"avatar\"; filename=\"avatar.png\"\r\nContent-Type: image/png\r\n\r\n"));
+        assert!(body.contains("name=\"upload\"; filename=\"me.png\"\r\nContent-Type: image/x-custom\r\n"));
+        assert!(!body.contains("skip"));
+        assert!(body.ends_with(&format!("--{}--\r\n", boundary)));
+        assert_eq!(body.matches(&format!("--{}\r\n", boundary)).count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_missing_file_reports_path() {
+        let parts = vec![MultipartPart::file("f", "/definitely/not/here.bin")];
+        let (path, _) = encode_multipart(&parts).await.unwrap_err();
+        assert_eq!(path, "/definitely/not/here.bin");
+    }
+
+    #[test]
+    fn test_guess_content_type() {
+        assert_eq!(guess_content_type("a/b/data.JSON"), "application/json");
+        assert_eq!(guess_content_type("photo.jpeg"), "image/jpeg");
+        assert_eq!(guess_content_type("blob"), "application/octet-stream");
+    }
+}
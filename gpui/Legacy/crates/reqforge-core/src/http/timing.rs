@@ -0,0 +1,224 @@
+//! Per-phase request timing.
+//!
+//! reqwest does not report connection phases, so engine clients are built
+//! with three hooks that record into a task-local `PhaseMarks` while a
+//! request is in flight:
+//!
+//! - `TimedResolver` times the DNS lookup,
+//! - `TimedSessionStore` is consulted by rustls as the TLS handshake starts,
+//! - `TimedConnectLayer` completes once TCP (and TLS) are established.
+//!
+//! Connections reused from the pool record none of these phases.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use rustls::client::{ClientSessionMemoryCache, ClientSessionStore, Tls12ClientSessionValue, Tls13ClientSessionValue};
+use rustls::pki_types::ServerName;
+use rustls::NamedGroup;
+use tower::{Layer, Service};
+
+use crate::models::response::TimingBreakdown;
+
+tokio::task_local! {
+    static MARKS: Arc<Mutex<PhaseMarks>>;
+}
+
+/// Instants recorded by the hooks while a request is sent.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct PhaseMarks {
+    dns_start: Option<Instant>,
+    dns_end: Option<Instant>,
+    connect_start: Option<Instant>,
+    tls_start: Option<Instant>,
+    connect_end: Option<Instant>,
+}
+
+impl PhaseMarks {
+    /// Turn the recorded instants into durations. `request_sent` is when the
+    /// final request attempt started; `headers_at` and `done` are when its
+    /// response headers and body arrived.
+    pub(crate) fn breakdown(&self, start: Instant, request_sent: Instant, headers_at: Instant, done: Instant) -> TimingBreakdown {
+        let since = |from: Option<Instant>, to: Option<Instant>| match (from, to) {
+            (Some(from), Some(to)) => Some(to.saturating_duration_since(from)),
+            _ => None,
+        };
+        let tcp_start = self.dns_end.or(self.connect_start);
+        let tcp_end = self.tls_start.or(self.connect_end);
+        let waiting_from = self.connect_end.map_or(request_sent, |c| c.max(request_sent));
+
+        TimingBreakdown {
+            dns_lookup: since(self.dns_start, self.dns_end),
+            tcp_connect: since(tcp_start, tcp_end),
+            tls_handshake: since(self.tls_start, self.connect_end),
+            time_to_first_byte: headers_at.saturating_duration_since(waiting_from),
+            content_download: done.saturating_duration_since(headers_at),
+            total: done.saturating_duration_since(start),
+        }
+    }
+}
+
+/// Run `future` with phase recording enabled and return what was recorded.
+pub(crate) async fn record_phases<F: Future>(future: F) -> (F::Output, PhaseMarks) {
+    let marks = Arc::new(Mutex::new(PhaseMarks::default()));
+    let output = MARKS.scope(marks.clone(), future).await;
+    let recorded = *marks.lock().unwrap_or_else(|e| e.into_inner());
+    (output, recorded)
+}
+
+/// Record into the current request's marks. Does nothing outside
+/// `record_phases`, e.g. for a connection hyper finishes in the background.
+fn mark(update: impl FnOnce(&mut PhaseMarks, Instant)) {
+    let now = Instant::now();
+    let _ = MARKS.try_with(|marks| {
+        if let Ok(mut marks) = marks.lock() {
+            update(&mut marks, now);
+        }
+    });
+}
+
+/// System DNS resolution (like reqwest's default) with timing.
+pub(crate) struct TimedResolver;
+
+impl Resolve for TimedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            mark(|m, now| {
+                m.dns_start.get_or_insert(now);
+            });
+            let addrs: Vec<_> = tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+            mark(|m, now| m.dns_end = Some(now));
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// The default in-memory TLS session cache, noting when a handshake starts.
+#[derive(Debug)]
+pub(crate) struct TimedSessionStore(ClientSessionMemoryCache);
+
+impl TimedSessionStore {
+    pub(crate) fn new() -> Self {
+        Self(ClientSessionMemoryCache::new(256))
+    }
+}
+
+fn mark_tls_start() {
+    mark(|m, now| {
+        m.tls_start.get_or_insert(now);
+    });
+}
+
+impl ClientSessionStore for TimedSessionStore {
+    fn set_kx_hint(&self, server_name: ServerName<'static>, group: NamedGroup) {
+        self.0.set_kx_hint(server_name, group);
+    }
+
+    fn kx_hint(&self, server_name: &ServerName<'_>) -> Option<NamedGroup> {
+        mark_tls_start();
+        self.0.kx_hint(server_name)
+    }
+
+    fn set_tls12_session(&self, server_name: ServerName<'static>, value: Tls12ClientSessionValue) {
+        self.0.set_tls12_session(server_name, value);
+    }
+
+    fn tls12_session(&self, server_name: &ServerName<'_>) -> Option<Tls12ClientSessionValue> {
+        mark_tls_start();
+        self.0.tls12_session(server_name)
+    }
+
+    fn remove_tls12_session(&self, server_name: &ServerName<'static>) {
+        self.0.remove_tls12_session(server_name);
+    }
+
+    fn insert_tls13_ticket(&self, server_name: ServerName<'static>, value: Tls13ClientSessionValue) {
+        self.0.insert_tls13_ticket(server_name, value);
+    }
+
+    fn take_tls13_ticket(&self, server_name: &ServerName<'static>) -> Option<Tls13ClientSessionValue> {
+        mark_tls_start();
+        self.0.take_tls13_ticket(server_name)
+    }
+}
+
+/// Wraps reqwest's connector to note when a new connection is ready.
+#[derive(Clone)]
+pub(crate) struct TimedConnectLayer;
+
+impl<S> Layer<S> for TimedConnectLayer {
+    type Service = TimedConnect<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TimedConnect(inner)
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct TimedConnect<S>(S);
+
+impl<S, R> Service<R> for TimedConnect<S>
+where
+    S: Service<R>,
+    S::Future: Send + 'static,
+    S::Response: Send + 'static,
+    S::Error: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        mark(|m, now| {
+            m.connect_start.get_or_insert(now);
+        });
+        let connecting = self.0.call(request);
+        Box::pin(async move {
+            let result = connecting.await;
+            mark(|m, now| {
+                m.connect_end.get_or_insert(now);
+            });
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_breakdown_from_marks() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let marks = PhaseMarks {
+            dns_start: Some(at(1)),
+            dns_end: Some(at(11)),
+            connect_start: Some(at(1)),
+            tls_start: Some(at(31)),
+            connect_end: Some(at(71)),
+        };
+
+        let timing = marks.breakdown(start, start, at(171), at(181));
+        assert_eq!(timing.dns_lookup, Some(Duration::from_millis(10)));
+        assert_eq!(timing.tcp_connect, Some(Duration::from_millis(20)));
+        assert_eq!(timing.tls_handshake, Some(Duration::from_millis(40)));
+        assert_eq!(timing.time_to_first_byte, Duration::from_millis(100));
+        assert_eq!(timing.content_download, Duration::from_millis(10));
+        assert_eq!(timing.total, Duration::from_millis(181));
+
+        // A reused connection only has the request phases
+        let timing = PhaseMarks::default().breakdown(start, start, at(50), at(60));
+        assert_eq!((timing.dns_lookup, timing.tcp_connect, timing.tls_handshake), (None, None, None));
+        assert_eq!(timing.time_to_first_byte, Duration::from_millis(50));
+    }
+}
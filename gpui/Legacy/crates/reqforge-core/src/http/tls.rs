@@ -0,0 +1,118 @@
+//! rustls configuration for engine clients.
+//!
+//! The engine hands reqwest a ready-made `ClientConfig` so it can install
+//! `TimedSessionStore` for handshake timing. This mirrors what reqwest builds
+//! itself: the platform verifier plus any extra roots, an optional client
+//! certificate and ALPN matching the HTTP version preference.
+
+use std::sync::Arc;
+
+use rustls::client::Resumption;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, SignatureScheme};
+
+use crate::http::client::HttpError;
+use crate::http::timing::TimedSessionStore;
+use crate::models::settings::{EngineSettings, HttpVersionPref};
+
+pub(crate) fn client_config(settings: &EngineSettings) -> Result<ClientConfig, HttpError> {
+    let provider = CryptoProvider::get_default()
+        .cloned()
+        .unwrap_or_else(|| Arc::new(rustls::crypto::aws_lc_rs::default_provider()));
+    let builder = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| HttpError::Settings(format!("Unsupported TLS configuration: {}", e)))?
+        .dangerous();
+
+    let tls = &settings.tls;
+    let builder = if tls.accept_invalid_certs {
+        builder.with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate(provider)))
+    } else {
+        let mut extra_roots = Vec::new();
+        if let Some(path) = &tls.ca_bundle_path {
+            let pem = read_settings_file(path, "CA bundle")?;
+            extra_roots = CertificateDer::pem_slice_iter(&pem)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| HttpError::Settings(format!("Invalid CA bundle '{}': {}", path, e)))?;
+            if extra_roots.is_empty() {
+                return Err(HttpError::Settings(format!("Invalid CA bundle '{}': no certificates found", path)));
+            }
+        }
+        let verifier = rustls_platform_verifier::Verifier::new_with_extra_roots(extra_roots, provider)
+            .map_err(|e| HttpError::Settings(format!("Failed to load certificate verifier: {}", e)))?;
+        builder.with_custom_certificate_verifier(Arc::new(verifier))
+    };
+
+    let mut config = match &tls.client_cert_path {
+        Some(cert_path) => {
+            let cert_pem = read_settings_file(cert_path, "client certificate")?;
+            let key_pem = match &tls.client_key_path {
+                Some(key_path) => read_settings_file(key_path, "client key")?,
+                None => cert_pem.clone(),
+            };
+            let invalid = |e: &dyn std::fmt::Display| {
+                HttpError::Settings(format!("Invalid client certificate '{}': {}", cert_path, e))
+            };
+            let chain = CertificateDer::pem_slice_iter(&cert_pem)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| invalid(&e))?;
+            let key = PrivateKeyDer::from_pem_slice(&key_pem).map_err(|e| invalid(&e))?;
+            builder.with_client_auth_cert(chain, key).map_err(|e| invalid(&e))?
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    config.alpn_protocols = match settings.http_version {
+        HttpVersionPref::Auto => vec![b"h2".to_vec(), b"http/1.1".to_vec()],
+        HttpVersionPref::Http1Only => vec![b"http/1.1".to_vec()],
+        HttpVersionPref::Http2Only => vec![b"h2".to_vec()],
+    };
+    config.resumption = Resumption::store(Arc::new(TimedSessionStore::new()));
+    Ok(config)
+}
+
+pub(crate) fn read_settings_file(path: &str, what: &str) -> Result<Vec<u8>, HttpError> {
+    std::fs::read(path).map_err(|e| HttpError::Settings(format!("Failed to read {} '{}': {}", what, path, e)))
+}
+
+/// Accepts any server certificate, for `TlsSettings::accept_invalid_certs`.
+#[derive(Debug)]
+struct AcceptAnyCertificate(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
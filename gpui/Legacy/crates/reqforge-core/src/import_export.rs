@@ -3,11 +3,18 @@
 //! Supports multiple formats:
 //! - JSON (native format)
 //! - Postman collection export (partial support)
-//! - OpenAPI spec import (basic support)
+//! - OpenAPI spec import (JSON or YAML, 2.x and 3.x) and OpenAPI 3.1 export
+//! - cURL command lines (single requests, both ways)
+//! - HAR 1.2 (import from browser devtools, export of request history)
 
 pub mod error;
 pub mod formats;
 
+pub use formats::curl::{parse_curl_command, to_curl_command};
+pub use formats::har::{parse_har, history_to_har};
+pub use formats::openapi::{OpenApiImport, collection_to_openapi};
+
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
@@ -16,7 +23,10 @@ use zip::write::FileOptions;
 use chrono::Utc;
 use uuid::Uuid;
 
-use crate::models::{Collection, Environment, RequestDefinition, HttpMethod, KeyValuePair, BodyType, RawContentType, CollectionItem, Folder, Variable};
+use crate::models::{Collection, Environment, RequestDefinition, RequestHistoryEntry, HttpMethod, KeyValuePair, BodyType, RawContentType, CollectionItem, Folder, Variable};
+use crate::secrets::{self, PassphraseCipher};
+use crate::store::schema::{self, Document};
+use crate::store::yaml;
 use error::{ImportError, ExportError, ImportErrorKind, ExportErrorKind};
 
 /// Result type for import operations
@@ -26,13 +36,16 @@ pub type ImportResult<T> = Result<T, ImportError>;
 pub type ExportResult<T> = Result<T, ExportError>;
 
 /// Export a collection to a JSON file
-pub fn export_collection(collection: &Collection, path: impl AsRef<Path>) -> ExportResult<()> {
+pub fn export_collection(collection: &Collection, path: impl AsRef<Path>, secrets: &SecretExport) -> ExportResult<()> {
+    let filter = SecretFilter::new(secrets)?;
+    let mut collection = collection.clone();
+    filter.apply_collection(&mut collection)?;
     let path = path.as_ref();
     let file = File::create(path)
         .map_err(|e| ExportError::new(ExportErrorKind::Io, &format!("Failed to create file: {}", e)))?;
 
     let writer = BufWriter::new(file);
-    serde_json::to_writer_pretty(writer, collection)
+    serde_json::to_writer_pretty(writer, &versioned(Document::Collection, &collection)?)
         .map_err(|e| ExportError::new(ExportErrorKind::Serialization, &format!("Failed to serialize collection: {}", e)))?;
 
     Ok(())
@@ -45,8 +58,9 @@ pub fn import_collection(path: impl AsRef<Path>) -> ImportResult<Collection> {
         .map_err(|e| ImportError::new(ImportErrorKind::Io, &format!("Failed to open file: {}", e)))?;
 
     let reader = BufReader::new(file);
-    let collection: Collection = serde_json::from_reader(reader)
+    let value: serde_json::Value = serde_json::from_reader(reader)
         .map_err(|e| ImportError::new(ImportErrorKind::Deserialization, &format!("Failed to deserialize collection: {}", e)))?;
+    let collection: Collection = schema::from_value(Document::Collection, value)?;
 
     validate_collection(&collection)?;
     Ok(collection)
@@ -67,25 +81,136 @@ pub fn import_collection_from_postman(path: impl AsRef<Path>) -> ImportResult<Co
 
 /// Import a collection from OpenAPI specification
 pub fn import_collection_from_openapi(path: impl AsRef<Path>) -> ImportResult<Collection> {
+    import_openapi(path).map(|import| import.collection)
+}
+
+/// Import a collection and one environment per server from an OpenAPI
+/// specification (JSON or YAML)
+pub fn import_openapi(path: impl AsRef<Path>) -> ImportResult<OpenApiImport> {
     let path = path.as_ref();
-    let file = File::open(path)
+    let text = std::fs::read_to_string(path)
         .map_err(|e| ImportError::new(ImportErrorKind::Io, &format!("Failed to open OpenAPI file: {}", e)))?;
 
+    formats::openapi::parse_openapi_document(&text)
+}
+
+/// Import a collection from a HAR file, named after the file
+pub fn import_collection_from_har(path: impl AsRef<Path>) -> ImportResult<Collection> {
+    let path = path.as_ref();
+    let file = File::open(path)
+        .map_err(|e| ImportError::new(ImportErrorKind::Io, &format!("Failed to open HAR file: {}", e)))?;
+
     let reader = BufReader::new(file);
-    let openapi: serde_json::Value = serde_json::from_reader(reader)
-        .map_err(|e| ImportError::new(ImportErrorKind::Deserialization, &format!("Failed to parse OpenAPI JSON: {}", e)))?;
+    let har: serde_json::Value = serde_json::from_reader(reader)
+        .map_err(|e| ImportError::new(ImportErrorKind::Deserialization, &format!("Failed to parse HAR JSON: {}", e)))?;
+
+    let name = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_else(|| "HAR import".to_string());
+    formats::har::parse_har(&har, &name)
+}
+
+/// Export history entries and their stored response bodies to a HAR file.
+/// See `history_to_har`.
+pub fn export_history_to_har(entries: &[(RequestHistoryEntry, Option<Vec<u8>>)], path: impl AsRef<Path>) -> ExportResult<()> {
+    let path = path.as_ref();
+    let file = File::create(path)
+        .map_err(|e| ExportError::new(ExportErrorKind::Io, &format!("Failed to create file: {}", e)))?;
 
-    formats::openapi::parse_openapi_spec(&openapi)
+    let writer = BufWriter::new(file);
+    serde_json::to_writer_pretty(writer, &formats::har::history_to_har(entries))
+        .map_err(|e| ExportError::new(ExportErrorKind::Serialization, &format!("Failed to serialize HAR: {}", e)))?;
+
+    Ok(())
+}
+
+/// Export a collection as an OpenAPI 3.1 document, written as YAML when the
+/// file name ends in `.yaml` or `.yml` and as JSON otherwise. See
+/// `collection_to_openapi`.
+pub fn export_collection_to_openapi(
+    collection: &Collection,
+    history: &[(RequestHistoryEntry, Option<Vec<u8>>)],
+    variables: &HashMap<String, String>,
+    path: impl AsRef<Path>,
+) -> ExportResult<()> {
+    let path = path.as_ref();
+    let document = formats::openapi::collection_to_openapi(collection, history, variables);
+    let yaml = path.extension().is_some_and(|ext| ext == "yaml" || ext == "yml");
+    let text = if yaml {
+        yaml::to_string(&document)
+    } else {
+        serde_json::to_string_pretty(&document)
+            .map_err(|e| ExportError::new(ExportErrorKind::Serialization, &format!("Failed to serialize OpenAPI document: {}", e)))?
+    };
+
+    std::fs::write(path, text)
+        .map_err(|e| ExportError::new(ExportErrorKind::Io, &format!("Failed to write file: {}", e)))
+}
+
+/// How secret variable values (of environments and collections) are written
+/// when exporting
+#[derive(Debug, Clone, Default)]
+pub enum SecretExport {
+    /// Blank secret values so the file is safe to share
+    #[default]
+    Strip,
+    /// Encrypt secret values with a passphrase (see `secrets::decrypt_secrets`)
+    Encrypt(String),
+    /// Write secret values in plaintext
+    Plaintext,
+}
+
+/// A `SecretExport` ready to apply, with the passphrase key derived once per
+/// export
+enum SecretFilter {
+    Strip,
+    Encrypt(PassphraseCipher),
+    Plaintext,
+}
+
+impl SecretFilter {
+    fn new(secrets: &SecretExport) -> ExportResult<Self> {
+        Ok(match secrets {
+            SecretExport::Strip => SecretFilter::Strip,
+            SecretExport::Encrypt(passphrase) => {
+                SecretFilter::Encrypt(PassphraseCipher::new(passphrase).map_err(encryption_error)?)
+            }
+            SecretExport::Plaintext => SecretFilter::Plaintext,
+        })
+    }
+
+    /// Strip or encrypt the secret values among `variables`
+    fn apply(&self, variables: &mut [Variable]) -> ExportResult<()> {
+        match self {
+            SecretFilter::Strip => secrets::strip_secrets(variables),
+            SecretFilter::Encrypt(cipher) => secrets::encrypt_secrets(variables, cipher).map_err(encryption_error)?,
+            SecretFilter::Plaintext => {}
+        }
+        Ok(())
+    }
+
+    /// `apply` to a collection's variables and those of its folders and requests
+    fn apply_collection(&self, collection: &mut Collection) -> ExportResult<()> {
+        self.apply(&mut collection.variables)?;
+        for (_, variables) in collection.item_variables_mut() {
+            self.apply(variables)?;
+        }
+        Ok(())
+    }
+}
+
+fn encryption_error(e: secrets::SecretError) -> ExportError {
+    ExportError::new(ExportErrorKind::Encryption, &e.to_string())
 }
 
 /// Export an environment to a JSON file
-pub fn export_environment(environment: &Environment, path: impl AsRef<Path>) -> ExportResult<()> {
+pub fn export_environment(environment: &Environment, path: impl AsRef<Path>, secrets: &SecretExport) -> ExportResult<()> {
+    let mut environment = environment.clone();
+    SecretFilter::new(secrets)?.apply(&mut environment.variables)?;
     let path = path.as_ref();
     let file = File::create(path)
         .map_err(|e| ExportError::new(ExportErrorKind::Io, &format!("Failed to create file: {}", e)))?;
 
     let writer = BufWriter::new(file);
-    serde_json::to_writer_pretty(writer, environment)
+    serde_json::to_writer_pretty(writer, &versioned(Document::Environment, &environment)?)
         .map_err(|e| ExportError::new(ExportErrorKind::Serialization, &format!("Failed to serialize environment: {}", e)))?;
 
     Ok(())
@@ -98,8 +223,9 @@ pub fn import_environment(path: impl AsRef<Path>) -> ImportResult<Environment> {
         .map_err(|e| ImportError::new(ImportErrorKind::Io, &format!("Failed to open file: {}", e)))?;
 
     let reader = BufReader::new(file);
-    let environment: Environment = serde_json::from_reader(reader)
+    let value: serde_json::Value = serde_json::from_reader(reader)
         .map_err(|e| ImportError::new(ImportErrorKind::Deserialization, &format!("Failed to deserialize environment: {}", e)))?;
+    let environment: Environment = schema::from_value(Document::Environment, value)?;
 
     validate_environment(&environment)?;
     Ok(environment)
@@ -110,7 +236,17 @@ pub fn export_all(
     collections: &[Collection],
     environments: &[Environment],
     path: impl AsRef<Path>,
+    secrets: &SecretExport,
 ) -> ExportResult<()> {
+    let filter = SecretFilter::new(secrets)?;
+    let mut collections = collections.to_vec();
+    for collection in &mut collections {
+        filter.apply_collection(collection)?;
+    }
+    let mut environments = environments.to_vec();
+    for environment in &mut environments {
+        filter.apply(&mut environment.variables)?;
+    }
     let path = path.as_ref();
     let file = File::create(path)
         .map_err(|e| ExportError::new(ExportErrorKind::Io, &format!("Failed to create zip file: {}", e)))?;
@@ -125,12 +261,12 @@ pub fn export_all(
         .map_err(|e| ExportError::new(ExportErrorKind::Io, &format!("Failed to add collections directory: {}", e)))?;
 
     // Export each collection
-    for collection in collections {
+    for collection in &collections {
         let collection_name = format!("collections/{}.json", collection.id);
         zip.start_file(&collection_name, options)
             .map_err(|e| ExportError::new(ExportErrorKind::Io, &format!("Failed to start file: {}", e)))?;
 
-        let json = serde_json::to_string_pretty(collection)
+        let json = serde_json::to_string_pretty(&versioned(Document::Collection, collection)?)
             .map_err(|e| ExportError::new(ExportErrorKind::Serialization, &format!("Failed to serialize collection: {}", e)))?;
 
         zip.write_all(json.as_bytes())
@@ -142,12 +278,12 @@ pub fn export_all(
         .map_err(|e| ExportError::new(ExportErrorKind::Io, &format!("Failed to add environments directory: {}", e)))?;
 
     // Export each environment
-    for environment in environments {
+    for environment in &environments {
         let env_name = format!("environments/{}.json", environment.id);
         zip.start_file(&env_name, options)
             .map_err(|e| ExportError::new(ExportErrorKind::Io, &format!("Failed to start file: {}", e)))?;
 
-        let json = serde_json::to_string_pretty(environment)
+        let json = serde_json::to_string_pretty(&versioned(Document::Environment, environment)?)
             .map_err(|e| ExportError::new(ExportErrorKind::Serialization, &format!("Failed to serialize environment: {}", e)))?;
 
         zip.write_all(json.as_bytes())
@@ -157,6 +293,7 @@ pub fn export_all(
     // Write manifest
     let manifest = formats::manifest::WorkspaceManifest {
         version: "1.0".to_string(),
+        schema_version: schema::SCHEMA_VERSION,
         exported_at: Utc::now(),
         collection_count: collections.len(),
         environment_count: environments.len(),
@@ -197,8 +334,9 @@ pub fn import_all(path: impl AsRef<Path>) -> ImportResult<WorkspaceImport> {
         content
     };
 
-    let _manifest: formats::manifest::WorkspaceManifest = serde_json::from_str(&manifest_json)
+    let manifest: formats::manifest::WorkspaceManifest = serde_json::from_str(&manifest_json)
         .map_err(|e| ImportError::new(ImportErrorKind::InvalidFormat, &format!("Invalid manifest: {}", e)))?;
+    schema::check_version(Document::Export, manifest.schema_version)?;
 
     // Import collections
     let mut collections = Vec::new();
@@ -212,8 +350,9 @@ pub fn import_all(path: impl AsRef<Path>) -> ImportResult<WorkspaceImport> {
             std::io::Read::read_to_string(&mut file, &mut content)
                 .map_err(|e| ImportError::new(ImportErrorKind::Io, &format!("Failed to read collection: {}", e)))?;
 
-            let collection: Collection = serde_json::from_str(&content)
+            let value: serde_json::Value = serde_json::from_str(&content)
                 .map_err(|e| ImportError::new(ImportErrorKind::Deserialization, &format!("Failed to deserialize collection: {}", e)))?;
+            let collection: Collection = schema::from_value(Document::Collection, value)?;
 
             validate_collection(&collection)?;
             collections.push(collection);
@@ -232,8 +371,9 @@ pub fn import_all(path: impl AsRef<Path>) -> ImportResult<WorkspaceImport> {
             std::io::Read::read_to_string(&mut file, &mut content)
                 .map_err(|e| ImportError::new(ImportErrorKind::Io, &format!("Failed to read environment: {}", e)))?;
 
-            let environment: Environment = serde_json::from_str(&content)
+            let value: serde_json::Value = serde_json::from_str(&content)
                 .map_err(|e| ImportError::new(ImportErrorKind::Deserialization, &format!("Failed to deserialize environment: {}", e)))?;
+            let environment: Environment = schema::from_value(Document::Environment, value)?;
 
             validate_environment(&environment)?;
             environments.push(environment);
@@ -250,6 +390,13 @@ pub struct WorkspaceImport {
     pub environments: Vec<Environment>,
 }
 
+/// A model serialized and stamped with the current schema version
+fn versioned<T: serde::Serialize>(document: Document, value: &T) -> ExportResult<serde_json::Value> {
+    let value = serde_json::to_value(value)
+        .map_err(|e| ExportError::new(ExportErrorKind::Serialization, &format!("Failed to serialize {}: {}", document.name(), e)))?;
+    Ok(schema::stamp(document, value))
+}
+
 /// Validate a collection
 fn validate_collection(collection: &Collection) -> ImportResult<()> {
     if collection.name.trim().is_empty() {
@@ -350,7 +497,7 @@ mod tests {
         let temp_dir = tempfile::tempdir().unwrap();
         let export_path = temp_dir.path().join("collection.json");
 
-        assert!(export_collection(&collection, &export_path).is_ok());
+        assert!(export_collection(&collection, &export_path, &SecretExport::Strip).is_ok());
         assert!(export_path.exists());
     }
 
@@ -363,7 +510,7 @@ mod tests {
         let temp_dir = tempfile::tempdir().unwrap();
         let export_path = temp_dir.path().join("collection.json");
 
-        export_collection(&collection, &export_path).unwrap();
+        export_collection(&collection, &export_path, &SecretExport::Strip).unwrap();
         let imported = import_collection(&export_path).unwrap();
 
         assert_eq!(collection.id, imported.id);
@@ -371,6 +518,25 @@ mod tests {
         assert_eq!(collection.requests.len(), imported.requests.len());
     }
 
+    #[test]
+    fn test_import_checks_schema_version() {
+        let mut collection = Collection::new("Test Collection");
+        collection.add_request(RequestDefinition::new("Test Request", HttpMethod::GET, "https://example.com"), None);
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("collection.json");
+
+        // Exports made before versioning still import
+        std::fs::write(&path, serde_json::to_string(&collection).unwrap()).unwrap();
+        assert_eq!(import_collection(&path).unwrap().id, collection.id);
+
+        let mut newer = serde_json::to_value(&collection).unwrap();
+        newer["schema_version"] = (schema::SCHEMA_VERSION + 1).into();
+        std::fs::write(&path, newer.to_string()).unwrap();
+        let err = import_collection(&path).unwrap_err();
+        assert_eq!(err.kind(), &ImportErrorKind::UnsupportedVersion);
+    }
+
     #[test]
     fn test_export_environment() {
         let mut environment = Environment::new("Test Environment");
@@ -384,7 +550,7 @@ mod tests {
         let temp_dir = tempfile::tempdir().unwrap();
         let export_path = temp_dir.path().join("environment.json");
 
-        assert!(export_environment(&environment, &export_path).is_ok());
+        assert!(export_environment(&environment, &export_path, &SecretExport::Plaintext).is_ok());
         assert!(export_path.exists());
     }
 
@@ -401,7 +567,7 @@ mod tests {
         let temp_dir = tempfile::tempdir().unwrap();
         let export_path = temp_dir.path().join("environment.json");
 
-        export_environment(&environment, &export_path).unwrap();
+        export_environment(&environment, &export_path, &SecretExport::Plaintext).unwrap();
         let imported = import_environment(&export_path).unwrap();
 
         assert_eq!(environment.id, imported.id);
@@ -409,6 +575,31 @@ mod tests {
         assert_eq!(environment.variables.len(), imported.variables.len());
     }
 
+    #[test]
+    fn test_export_environment_secret_handling() {
+        let mut environment = Environment::new("Test Environment");
+        environment.variables.push(Variable {
+            key: "API_KEY".to_string(),
+            value: "s3cret-value".to_string(),
+            secret: true,
+            enabled: true,
+        });
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let export_path = temp_dir.path().join("environment.json");
+
+        export_environment(&environment, &export_path, &SecretExport::Strip).unwrap();
+        assert!(!std::fs::read_to_string(&export_path).unwrap().contains("s3cret-value"));
+        assert_eq!(import_environment(&export_path).unwrap().variables[0].value, "");
+
+        export_environment(&environment, &export_path, &SecretExport::Encrypt("pass".into())).unwrap();
+        assert!(!std::fs::read_to_string(&export_path).unwrap().contains("s3cret-value"));
+        let mut imported = import_environment(&export_path).unwrap();
+        assert!(secrets::is_encrypted(&imported.variables[0].value));
+        secrets::decrypt_secrets(&mut imported.variables, "pass").unwrap();
+        assert_eq!(imported.variables[0].value, "s3cret-value");
+    }
+
     #[test]
     fn test_workspace_export_import() {
         let mut collection = Collection::new("Test Collection");
@@ -426,13 +617,88 @@ mod tests {
         let temp_dir = tempfile::tempdir().unwrap();
         let export_path = temp_dir.path().join("workspace.zip");
 
-        export_all(&[collection.clone()], &[environment.clone()], &export_path).unwrap();
+        export_all(&[collection.clone()], &[environment.clone()], &export_path, &SecretExport::Strip).unwrap();
         let imported = import_all(&export_path).unwrap();
 
         assert_eq!(1, imported.collections.len());
         assert_eq!(1, imported.environments.len());
         assert_eq!(collection.id, imported.collections[0].id);
         assert_eq!(environment.id, imported.environments[0].id);
+        assert_eq!(imported.environments[0].variables[0].value, "");
+    }
+
+    #[test]
+    fn test_export_collection_variable_secrets() {
+        let mut collection = Collection::new("Test Collection");
+        collection.variables.push(Variable {
+            key: "client_secret".to_string(),
+            value: "c0llection-secret".to_string(),
+            secret: true,
+            enabled: true,
+        });
+        collection.variables.push(Variable {
+            key: "tenant".to_string(),
+            value: "acme".to_string(),
+            secret: false,
+            enabled: true,
+        });
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let collection_path = temp_dir.path().join("collection.json");
+        export_collection(&collection, &collection_path, &SecretExport::Strip).unwrap();
+        assert!(!std::fs::read_to_string(&collection_path).unwrap().contains("c0llection-secret"));
+        let imported = import_collection(&collection_path).unwrap();
+        assert_eq!(imported.variables[0].value, "");
+        assert_eq!(imported.variables[1].value, "acme");
+
+        let export_path = temp_dir.path().join("workspace.zip");
+        export_all(&[collection], &[], &export_path, &SecretExport::Encrypt("pass".into())).unwrap();
+        let mut imported = import_all(&export_path).unwrap().collections.remove(0);
+        assert!(secrets::is_encrypted(&imported.variables[0].value));
+        assert_eq!(secrets::decrypt_secrets(&mut imported.variables, "pass").unwrap(), 1);
+        assert_eq!(imported.variables[0].value, "c0llection-secret");
+    }
+
+    #[test]
+    fn test_export_folder_and_request_variable_secrets() {
+        let mut collection = Collection::new("Test Collection");
+        let folder_id = Uuid::new_v4();
+        collection.tree.push(CollectionItem::Folder(Box::new(Folder {
+            id: folder_id,
+            name: "Admin".to_string(),
+            children: Vec::new(),
+            auth: Default::default(),
+            variables: vec![Variable {
+                key: "folder_token".to_string(),
+                value: "f0lder-secret".to_string(),
+                secret: true,
+                enabled: true,
+            }],
+        })));
+        let mut request = RequestDefinition::new("Test Request", HttpMethod::GET, "https://example.com");
+        request.variables.push(Variable {
+            key: "request_token".to_string(),
+            value: "r3quest-secret".to_string(),
+            secret: true,
+            enabled: true,
+        });
+        let request_id = request.id;
+        collection.add_request(request, Some(folder_id));
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let collection_path = temp_dir.path().join("collection.json");
+        export_collection(&collection, &collection_path, &SecretExport::Strip).unwrap();
+        let exported = std::fs::read_to_string(&collection_path).unwrap();
+        assert!(!exported.contains("f0lder-secret") && !exported.contains("r3quest-secret"));
+
+        let export_path = temp_dir.path().join("workspace.zip");
+        export_all(&[collection], &[], &export_path, &SecretExport::Encrypt("pass".into())).unwrap();
+        let mut imported = import_all(&export_path).unwrap().collections.remove(0);
+        let request = imported.requests.get_mut(&request_id).unwrap();
+        assert!(secrets::is_encrypted(&request.variables[0].value));
+        assert_eq!(secrets::decrypt_secrets(&mut request.variables, "pass").unwrap(), 1);
+        assert_eq!(request.variables[0].value, "r3quest-secret");
+        assert!(secrets::is_encrypted(&imported.find_folder(folder_id).unwrap().variables[0].value));
     }
 
     #[test]
@@ -441,7 +707,7 @@ mod tests {
         let temp_dir = tempfile::tempdir().unwrap();
         let export_path = temp_dir.path().join("collection.json");
 
-        export_collection(&collection, &export_path).unwrap();
+        export_collection(&collection, &export_path, &SecretExport::Strip).unwrap();
         let result = import_collection(&export_path);
 
         assert!(result.is_err());
@@ -454,7 +720,7 @@ mod tests {
         let temp_dir = tempfile::tempdir().unwrap();
         let export_path = temp_dir.path().join("environment.json");
 
-        export_environment(&environment, &export_path).unwrap();
+        export_environment(&environment, &export_path, &SecretExport::Strip).unwrap();
         let result = import_environment(&export_path);
 
         assert!(result.is_err());
@@ -1,5 +1,7 @@
 //! Error types for import/export operations
 
+use crate::store::schema::SchemaError;
+
 /// Detailed error types for import operations
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ImportErrorKind {
@@ -15,6 +17,12 @@ pub enum ImportErrorKind {
     PostmanFormat,
     /// OpenAPI-specific import error
     OpenApiFormat,
+    /// Invalid or unsupported cURL command line
+    CurlFormat,
+    /// HAR-specific import error
+    HarFormat,
+    /// Written by a newer version of ReqForge
+    UnsupportedVersion,
 }
 
 /// Error that can occur during import operations
@@ -58,6 +66,8 @@ pub enum ExportErrorKind {
     Serialization,
     /// Failed to create zip archive
     ZipError,
+    /// Failed to encrypt secret values
+    Encryption,
 }
 
 /// Error that can occur during export operations
@@ -106,6 +116,18 @@ impl From<std::io::Error> for ExportError {
     }
 }
 
+/// Convert from SchemaError to ImportError
+impl From<SchemaError> for ImportError {
+    fn from(err: SchemaError) -> Self {
+        let kind = match err {
+            SchemaError::Newer { .. } => ImportErrorKind::UnsupportedVersion,
+            SchemaError::Invalid { .. } => ImportErrorKind::InvalidFormat,
+            SchemaError::Serde { .. } => ImportErrorKind::Deserialization,
+        };
+        ImportError::new(kind, &err.to_string())
+    }
+}
+
 /// Convert from serde_json::Error to ImportError
 impl From<serde_json::Error> for ImportError {
     fn from(err: serde_json::Error) -> Self {
@@ -1,5 +1,7 @@
-//! Support for external formats (Postman, OpenAPI, etc.)
+//! Support for external formats (Postman, OpenAPI, cURL, HAR, etc.)
 
+pub mod curl;
+pub mod har;
 pub mod manifest;
 pub mod openapi;
 pub mod postman;
@@ -9,7 +11,7 @@ use serde_json::Value;
 
 use super::error::{ImportError, ImportErrorKind};
 use super::ImportResult;
-use crate::models::{BodyType, HttpMethod, KeyValuePair, RawContentType};
+use crate::models::{BodyType, HttpMethod, KeyValuePair, MultipartPart, MultipartValue, RawContentType};
 
 /// Workspace manifest for full exports
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -115,6 +117,56 @@ fn extract_query_params(query_params_value: &Value) -> Vec<KeyValuePair> {
         .unwrap_or_default()
 }
 
+/// Helper to convert a Postman `formdata` entry into multipart parts.
+///
+/// File entries may list several files in `src`; each becomes its own part.
+fn extract_multipart_parts(value: &Value) -> Vec<MultipartPart> {
+    let Some(obj) = value.as_object() else {
+        return Vec::new();
+    };
+
+    let key = obj.get("key").and_then(|k| k.as_str()).unwrap_or("");
+    if key.is_empty() {
+        return Vec::new();
+    }
+
+    let enabled = obj
+        .get("disabled")
+        .and_then(|d| d.as_bool())
+        .map(|d| !d)
+        .unwrap_or(true);
+    let content_type = obj
+        .get("contentType")
+        .and_then(|c| c.as_str())
+        .filter(|c| !c.is_empty())
+        .map(String::from);
+
+    let values = if obj.get("type").and_then(|t| t.as_str()) == Some("file") {
+        let paths = match obj.get("src") {
+            Some(Value::String(src)) => vec![src.clone()],
+            Some(Value::Array(srcs)) => srcs.iter().filter_map(|s| s.as_str()).map(String::from).collect(),
+            _ => vec![String::new()],
+        };
+        paths
+            .into_iter()
+            .map(|path| MultipartValue::File { path, filename: None })
+            .collect()
+    } else {
+        let text = obj.get("value").and_then(|v| v.as_str()).unwrap_or("");
+        vec![MultipartValue::Text(text.to_string())]
+    };
+
+    values
+        .into_iter()
+        .map(|value| MultipartPart {
+            name: key.to_string(),
+            value,
+            content_type: content_type.clone(),
+            enabled,
+        })
+        .collect()
+}
+
 /// Helper to extract body from various formats
 fn extract_body(body_value: &Value, body_mode: Option<&str>) -> ImportResult<BodyType> {
     match body_mode {
@@ -183,47 +235,23 @@ fn extract_body(body_value: &Value, body_mode: Option<&str>) -> ImportResult<Bod
             Ok(BodyType::FormUrlEncoded(fields))
         }
         Some("formdata") => {
-            // For now, treat formdata as urlencoded (simplified)
-            let fields = body_value
+            let parts = body_value
                 .get("formdata")
                 .and_then(|v| v.as_array())
-                .map(|arr| {
-                    arr.iter()
-                        .filter_map(|v| {
-                            if let Some(obj) = v.as_object() {
-                                let key = obj.get("key").and_then(|k| k.as_str()).unwrap_or("");
-                                let value = obj.get("value").and_then(|v| v.as_str()).unwrap_or("");
-                                let enabled = obj
-                                    .get("disabled")
-                                    .and_then(|d| d.as_bool())
-                                    .map(|d| !d)
-                                    .unwrap_or(true);
-                                let description = obj
-                                    .get("description")
-                                    .and_then(|d| d.as_str())
-                                    .map(String::from);
-
-                                if !key.is_empty()
-                                    && obj.get("type").and_then(|t| t.as_str()) != Some("file")
-                                {
-                                    Some(KeyValuePair {
-                                        key: key.to_string(),
-                                        value: value.to_string(),
-                                        enabled,
-                                        description,
-                                    })
-                                } else {
-                                    None
-                                }
-                            } else {
-                                None
-                            }
-                        })
-                        .collect()
-                })
+                .map(|arr| arr.iter().flat_map(extract_multipart_parts).collect())
                 .unwrap_or_default();
 
-            Ok(BodyType::FormUrlEncoded(fields))
+            Ok(BodyType::Multipart(parts))
+        }
+        Some("file") => {
+            let path = body_value
+                .get("file")
+                .and_then(|f| f.get("src"))
+                .and_then(|s| s.as_str())
+                .unwrap_or("")
+                .to_string();
+
+            Ok(BodyType::Binary { path })
         }
         _ => Ok(BodyType::None),
     }
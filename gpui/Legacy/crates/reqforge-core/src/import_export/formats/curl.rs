@@ -0,0 +1,997 @@
+//! cURL command line support
+//!
+//! `parse_curl_command` reads the curl options that describe a request (the
+//! ones browsers emit for "Copy as cURL" and API docs commonly use) into a
+//! `RequestDefinition`. `to_curl_command` turns a resolved request back into
+//! a command line.
+//!
+//! Options that only affect curl's own output (`-s`, `-v`, `-o`, ...) are
+//! accepted and ignored, as is `--compressed`. Anything else unknown is an
+//! error rather than being silently dropped.
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+use url::form_urlencoded;
+
+use crate::models::{
+    AuthConfig, ApiKeyLocation, BodyType, HttpMethod, HttpVersionPref, KeyValuePair, MultipartPart,
+    MultipartValue, ProxySettings, RawContentType, RedirectPolicy, RequestDefinition, RequestSettings,
+};
+use super::super::error::{ImportError, ImportErrorKind};
+use super::super::ImportResult;
+use super::parse_http_method;
+
+/// curl's own default for `--max-redirs` when following redirects.
+const CURL_MAX_REDIRECTS: usize = 50;
+
+/// Long options that take a value.
+const VALUE_OPTIONS: &[&str] = &[
+    "request", "header", "data", "data-ascii", "data-binary", "data-raw", "data-urlencode", "json",
+    "form", "form-string", "user", "aws-sigv4", "oauth2-bearer", "user-agent", "referer", "cookie",
+    "max-time", "connect-timeout", "max-redirs", "proxy", "proxy-user", "noproxy", "url",
+];
+
+/// Options that only change how curl reports or stores the response.
+const IGNORED_FLAGS: &[&str] = &[
+    "silent", "show-error", "verbose", "include", "fail", "fail-with-body", "no-buffer", "globoff",
+    "remote-name", "compressed", "progress-bar", "no-progress-meter", "no-keepalive", "path-as-is",
+];
+
+/// Like `IGNORED_FLAGS`, but followed by a value to skip.
+const IGNORED_VALUE_OPTIONS: &[&str] = &[
+    "output", "write-out", "cookie-jar", "dump-header", "retry", "retry-delay", "retry-max-time",
+    "stderr", "trace", "trace-ascii", "limit-rate",
+];
+
+fn short_option(flag: char) -> Option<&'static str> {
+    Some(match flag {
+        'X' => "request",
+        'H' => "header",
+        'd' => "data",
+        'F' => "form",
+        'u' => "user",
+        'A' => "user-agent",
+        'e' => "referer",
+        'b' => "cookie",
+        'm' => "max-time",
+        'x' => "proxy",
+        'U' => "proxy-user",
+        'G' => "get",
+        'I' => "head",
+        'k' => "insecure",
+        'L' => "location",
+        's' => "silent",
+        'S' => "show-error",
+        'v' => "verbose",
+        'i' => "include",
+        'f' => "fail",
+        'N' => "no-buffer",
+        'g' => "globoff",
+        'O' => "remote-name",
+        'o' => "output",
+        'w' => "write-out",
+        'c' => "cookie-jar",
+        'D' => "dump-header",
+        _ => return None,
+    })
+}
+
+fn takes_value(option: &str) -> bool {
+    VALUE_OPTIONS.contains(&option) || IGNORED_VALUE_OPTIONS.contains(&option)
+}
+
+fn curl_error(message: &str) -> ImportError {
+    ImportError::new(ImportErrorKind::CurlFormat, message)
+}
+
+/// Parse a `curl ...` command line into a request.
+///
+/// Shell quoting (`'...'`, `"..."`, `$'...'`) and backslash line
+/// continuations are understood; variables and other shell expansions are
+/// not. Without `-X`, the method is POST when the command sends data and GET
+/// otherwise, as curl does.
+pub fn parse_curl_command(command: &str) -> ImportResult<RequestDefinition> {
+    let mut args = split_command_line(command)?.into_iter();
+    match args.next() {
+        Some(program) if program == "curl" || program.ends_with("/curl") || program.eq_ignore_ascii_case("curl.exe") => {}
+        _ => return Err(curl_error("Not a curl command")),
+    }
+
+    let mut curl = CurlArgs::default();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            for url in args.by_ref() {
+                curl.set_url(url)?;
+            }
+        } else if let Some(option) = arg.strip_prefix("--") {
+            let value = if takes_value(option) {
+                Some(args.next().ok_or_else(|| curl_error(&format!("Option --{} needs a value", option)))?)
+            } else {
+                None
+            };
+            curl.apply(option, value)?;
+        } else if let Some(flags) = arg.strip_prefix('-').filter(|flags| !flags.is_empty()) {
+            // Short options can be grouped (-sSL), and the last one may have
+            // its value attached (-XPOST)
+            for (i, flag) in flags.char_indices() {
+                let option = short_option(flag)
+                    .ok_or_else(|| curl_error(&format!("Unsupported curl option -{}", flag)))?;
+                if !takes_value(option) {
+                    curl.apply(option, None)?;
+                    continue;
+                }
+                let attached = &flags[i + flag.len_utf8()..];
+                let value = if attached.is_empty() {
+                    args.next().ok_or_else(|| curl_error(&format!("Option -{} needs a value", flag)))?
+                } else {
+                    attached.to_string()
+                };
+                curl.apply(option, Some(value))?;
+                break;
+            }
+        } else {
+            curl.set_url(arg)?;
+        }
+    }
+
+    curl.into_request()
+}
+
+/// Options collected from the command line, turned into a request once all
+/// of them are known.
+#[derive(Default)]
+struct CurlArgs {
+    url: Option<String>,
+    method: Option<HttpMethod>,
+    headers: Vec<KeyValuePair>,
+    /// Each `-d` style value, already URL-encoded where curl would encode it.
+    data: Vec<String>,
+    data_file: Option<String>,
+    json: bool,
+    form: Vec<MultipartPart>,
+    user: Option<String>,
+    digest: bool,
+    aws_sigv4: Option<String>,
+    bearer: Option<String>,
+    get: bool,
+    head: bool,
+    follow_redirects: bool,
+    max_redirects: Option<usize>,
+    proxy_user: Option<String>,
+    no_proxy: Option<String>,
+    settings: RequestSettings,
+}
+
+impl CurlArgs {
+    fn apply(&mut self, option: &str, value: Option<String>) -> ImportResult<()> {
+        let value = value.unwrap_or_default();
+        match option {
+            "request" => self.method = Some(parse_http_method(&value)?),
+            "header" => self.add_header(&value)?,
+            "data" | "data-ascii" | "data-binary" => self.add_data(value)?,
+            "data-raw" => self.data.push(value),
+            "data-urlencode" => self.data.push(urlencode_data(&value)?),
+            "json" => {
+                self.json = true;
+                self.add_data(value)?;
+            }
+            "form" => self.form.push(parse_form_field(&value, false)?),
+            "form-string" => self.form.push(parse_form_field(&value, true)?),
+            "user" => self.user = Some(value),
+            "digest" => self.digest = true,
+            "basic" => self.digest = false,
+            "aws-sigv4" => self.aws_sigv4 = Some(value),
+            "oauth2-bearer" => self.bearer = Some(value),
+            "user-agent" => self.headers.push(header("User-Agent", value)),
+            "referer" => self.headers.push(header("Referer", value)),
+            // Without `=` the value names a cookie file, which is not read
+            "cookie" => {
+                if value.contains('=') {
+                    self.headers.push(header("Cookie", value));
+                }
+            }
+            "get" => self.get = true,
+            "head" => self.head = true,
+            "insecure" => self.settings.accept_invalid_certs = Some(true),
+            "location" => self.follow_redirects = true,
+            "max-redirs" => {
+                let max = value.parse().map_err(|_| curl_error(&format!("Invalid --max-redirs value: {}", value)))?;
+                self.max_redirects = Some(max);
+            }
+            "max-time" => self.settings.timeout_ms = Some(parse_seconds(option, &value)?),
+            "connect-timeout" => self.settings.connect_timeout_ms = Some(parse_seconds(option, &value)?),
+            "proxy" => {
+                self.settings.proxy = Some(ProxySettings { url: value, username: None, password: None, no_proxy: Vec::new() });
+            }
+            "proxy-user" => self.proxy_user = Some(value),
+            "noproxy" => self.no_proxy = Some(value),
+            "http1.0" | "http1.1" => self.settings.http_version = Some(HttpVersionPref::Http1Only),
+            "http2" => self.settings.http_version = Some(HttpVersionPref::Auto),
+            "http2-prior-knowledge" => self.settings.http_version = Some(HttpVersionPref::Http2Only),
+            "url" => self.set_url(value)?,
+            _ if IGNORED_FLAGS.contains(&option) || IGNORED_VALUE_OPTIONS.contains(&option) => {}
+            _ => return Err(curl_error(&format!("Unsupported curl option --{}", option))),
+        }
+        Ok(())
+    }
+
+    fn set_url(&mut self, url: String) -> ImportResult<()> {
+        if self.url.is_some() {
+            return Err(curl_error("Only one URL per curl command is supported"));
+        }
+        self.url = Some(url);
+        Ok(())
+    }
+
+    /// `Name: value` adds a header; `Name;` adds one with an empty value.
+    /// `Name:` removes a header curl would send itself, so it is skipped.
+    fn add_header(&mut self, value: &str) -> ImportResult<()> {
+        if value.starts_with('@') {
+            return Err(curl_error("Reading headers from a file (-H @file) is not supported"));
+        }
+        if let Some((name, value)) = value.split_once(':') {
+            let value = value.trim();
+            if !value.is_empty() {
+                self.headers.push(header(name.trim(), value));
+            }
+        } else if let Some(name) = value.strip_suffix(';') {
+            self.headers.push(header(name.trim(), ""));
+        } else {
+            return Err(curl_error(&format!("Invalid header: {}", value)));
+        }
+        Ok(())
+    }
+
+    /// `@path` sends a file as the body; anything else is sent as given.
+    fn add_data(&mut self, value: String) -> ImportResult<()> {
+        match value.strip_prefix('@') {
+            Some(path) => {
+                if self.data_file.is_some() {
+                    return Err(curl_error("Only one data file per curl command is supported"));
+                }
+                self.data_file = Some(path.to_string());
+            }
+            None => self.data.push(value),
+        }
+        Ok(())
+    }
+
+    fn into_request(mut self) -> ImportResult<RequestDefinition> {
+        let url = self.url.take().ok_or_else(|| curl_error("No URL in curl command"))?;
+        let url = if url.contains("://") { url } else { format!("http://{}", url) };
+        let (url, mut query_params) = split_query(&url);
+
+        let has_data = !self.data.is_empty() || self.data_file.is_some();
+        if has_data && !self.form.is_empty() {
+            return Err(curl_error("A curl command cannot send both -d and -F data"));
+        }
+        if self.data_file.is_some() && (!self.data.is_empty() || self.get) {
+            return Err(curl_error("A data file (-d @file) cannot be combined with other data or -G"));
+        }
+
+        let method = match self.method.take() {
+            Some(method) => method,
+            None if self.head => HttpMethod::HEAD,
+            None if self.get => HttpMethod::GET,
+            None if has_data || !self.form.is_empty() => HttpMethod::POST,
+            None => HttpMethod::GET,
+        };
+
+        let mut req = RequestDefinition::new(request_name(&method, &url), method, url);
+        req.body = if self.get {
+            query_params.extend(decode_pairs(&self.data.join("&")));
+            BodyType::None
+        } else if !self.form.is_empty() {
+            // The multipart boundary is generated when sending
+            remove_header(&mut self.headers, |value| value.to_ascii_lowercase().starts_with("multipart/form-data"));
+            BodyType::Multipart(std::mem::take(&mut self.form))
+        } else if let Some(path) = self.data_file.take() {
+            BodyType::Binary { path }
+        } else if has_data {
+            self.data_body()
+        } else {
+            BodyType::None
+        };
+        if self.json && !has_header(&self.headers, "accept") {
+            self.headers.push(header("Accept", "application/json"));
+        }
+
+        req.query_params = query_params;
+        req.auth = self.auth()?;
+        req.settings = self.settings()?;
+        req.headers = self.headers;
+        Ok(req)
+    }
+
+    /// The body for `-d` style data. A Content-Type header picks the body
+    /// type; without one, JSON-looking data is JSON and `key=value` data is a
+    /// form (what curl sends by default). The header is dropped when the body
+    /// type already sends exactly that Content-Type.
+    fn data_body(&mut self) -> BodyType {
+        let data = self.data.join(if self.json { "" } else { "&" });
+        let content_type = if self.json {
+            Some("application/json".to_string())
+        } else {
+            header_value(&self.headers, "content-type").map(|value| value.to_ascii_lowercase())
+        };
+
+        let raw = |content_type| BodyType::Raw { content: data.clone(), content_type };
+        let (body, implied) = match content_type.as_deref() {
+            Some(ct) if ct.starts_with("application/x-www-form-urlencoded") => {
+                (BodyType::FormUrlEncoded(decode_pairs(&data)), Some("application/x-www-form-urlencoded"))
+            }
+            Some(ct) if ct.starts_with("application/json") || ct.contains("+json") => (raw(RawContentType::Json), Some("application/json")),
+            Some(ct) if ct.contains("xml") => (raw(RawContentType::Xml), Some("application/xml")),
+            Some(ct) if ct.starts_with("text/html") => (raw(RawContentType::Html), Some("text/html")),
+            Some(_) => (raw(RawContentType::Text), Some("text/plain")),
+            None if looks_like_json(&data) => (raw(RawContentType::Json), None),
+            None if is_form_data(&data) => (BodyType::FormUrlEncoded(decode_pairs(&data)), None),
+            None => (raw(RawContentType::Text), None),
+        };
+        if let Some(implied) = implied {
+            remove_header(&mut self.headers, |value| value.eq_ignore_ascii_case(implied));
+        }
+        body
+    }
+
+    fn auth(&mut self) -> ImportResult<AuthConfig> {
+        let (username, password) = match self.user.take() {
+            Some(user) => match user.split_once(':') {
+                Some((username, password)) => (username.to_string(), password.to_string()),
+                None => (user, String::new()),
+            },
+            None => {
+                return Ok(match self.bearer.take() {
+                    Some(token) => AuthConfig::Bearer { token },
+                    None => AuthConfig::Inherit,
+                });
+            }
+        };
+
+        if let Some(provider) = self.aws_sigv4.take() {
+            let parts: Vec<&str> = provider.split(':').collect();
+            let (region, service) = match parts.as_slice() {
+                [_, _, region, service, ..] => (region.to_string(), service.to_string()),
+                _ => return Err(curl_error(&format!("--aws-sigv4 needs provider:provider:region:service, got {}", provider))),
+            };
+            let session_token = header_value(&self.headers, "x-amz-security-token").map(String::from);
+            self.headers.retain(|h| !h.key.eq_ignore_ascii_case("x-amz-security-token"));
+            return Ok(AuthConfig::AwsSigV4 {
+                access_key_id: username,
+                secret_access_key: password,
+                session_token,
+                region,
+                service,
+            });
+        }
+
+        Ok(if self.digest {
+            AuthConfig::Digest { username, password }
+        } else {
+            AuthConfig::Basic { username, password }
+        })
+    }
+
+    fn settings(&mut self) -> ImportResult<RequestSettings> {
+        let mut settings = std::mem::take(&mut self.settings);
+        if self.follow_redirects {
+            settings.redirects = Some(RedirectPolicy::Follow { max: self.max_redirects.unwrap_or(CURL_MAX_REDIRECTS) });
+        }
+        match self.no_proxy.take() {
+            Some(hosts) if hosts == "*" => settings.bypass_proxy = true,
+            Some(hosts) => {
+                if let Some(proxy) = &mut settings.proxy {
+                    proxy.no_proxy = hosts.split(',').map(|h| h.trim().to_string()).filter(|h| !h.is_empty()).collect();
+                }
+            }
+            None => {}
+        }
+        if let Some(user) = self.proxy_user.take() {
+            let proxy = settings.proxy.as_mut().ok_or_else(|| curl_error("--proxy-user needs --proxy"))?;
+            let (username, password) = user.split_once(':').unwrap_or((&user, ""));
+            proxy.username = Some(username.to_string());
+            proxy.password = Some(password.to_string());
+        }
+        Ok(settings)
+    }
+}
+
+fn header(key: impl Into<String>, value: impl Into<String>) -> KeyValuePair {
+    KeyValuePair { key: key.into(), value: value.into(), enabled: true, description: None }
+}
+
+fn header_value<'a>(headers: &'a [KeyValuePair], name: &str) -> Option<&'a str> {
+    headers.iter().rev().find(|h| h.key.eq_ignore_ascii_case(name)).map(|h| h.value.as_str())
+}
+
+fn has_header(headers: &[KeyValuePair], name: &str) -> bool {
+    headers.iter().any(|h| h.enabled && h.key.eq_ignore_ascii_case(name))
+}
+
+fn remove_header(headers: &mut Vec<KeyValuePair>, matches: impl Fn(&str) -> bool) {
+    headers.retain(|h| !(h.key.eq_ignore_ascii_case("content-type") && matches(&h.value)));
+}
+
+/// Decode `a=1&b=2` into pairs, dropping empty segments.
+fn decode_pairs(data: &str) -> Vec<KeyValuePair> {
+    form_urlencoded::parse(data.as_bytes())
+        .map(|(key, value)| header(key, value))
+        .collect()
+}
+
+/// Split a URL's query string off into decoded parameters. Any fragment is
+/// dropped, since it is never sent.
+fn split_query(url: &str) -> (String, Vec<KeyValuePair>) {
+    let url = url.split_once('#').map_or(url, |(url, _)| url);
+    match url.split_once('?') {
+        Some((base, query)) => (base.to_string(), decode_pairs(query)),
+        None => (url.to_string(), Vec::new()),
+    }
+}
+
+/// A name like `POST /users/42` for an imported request.
+pub(super) fn request_name(method: &HttpMethod, url: &str) -> String {
+    let after_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let path = after_scheme.find('/').map_or("/", |i| &after_scheme[i..]);
+    format!("{} {}", method, path)
+}
+
+fn looks_like_json(data: &str) -> bool {
+    let data = data.trim();
+    (data.starts_with('{') && data.ends_with('}')) || (data.starts_with('[') && data.ends_with(']'))
+}
+
+fn is_form_data(data: &str) -> bool {
+    !data.is_empty()
+        && data.split('&').all(|pair| pair.split_once('=').is_some_and(|(key, _)| !key.is_empty()))
+}
+
+/// Seconds (possibly fractional) to milliseconds.
+fn parse_seconds(option: &str, value: &str) -> ImportResult<u64> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+        .map(|secs| (secs * 1000.0).round() as u64)
+        .ok_or_else(|| curl_error(&format!("Invalid --{} value: {}", option, value)))
+}
+
+/// Encode a `--data-urlencode` value the way curl does: `name=content` and
+/// `=content` encode only the content, anything else is encoded whole.
+fn urlencode_data(value: &str) -> ImportResult<String> {
+    let encode = |s: &str| form_urlencoded::byte_serialize(s.as_bytes()).collect::<String>();
+    if let Some((name, content)) = value.split_once('=') {
+        return Ok(if name.is_empty() { encode(content) } else { format!("{}={}", name, encode(content)) });
+    }
+    if value.contains('@') {
+        return Err(curl_error("Reading --data-urlencode content from a file is not supported"));
+    }
+    Ok(encode(value))
+}
+
+/// Parse a `-F` value: `name=value`, `name=@path` (a file) or `name=<path`
+/// (a file's contents), each optionally followed by `;type=...` and
+/// `;filename=...`. `--form-string` values are always taken literally.
+fn parse_form_field(field: &str, literal: bool) -> ImportResult<MultipartPart> {
+    let (name, content) = field
+        .split_once('=')
+        .ok_or_else(|| curl_error(&format!("Invalid form field (expected name=content): {}", field)))?;
+    if literal {
+        return Ok(MultipartPart::text(name, content));
+    }
+
+    let (value, params) = split_form_params(content);
+    let mut part = match (value.strip_prefix('@'), value.strip_prefix('<')) {
+        (Some(path), _) | (_, Some(path)) => MultipartPart::file(name, path),
+        _ => MultipartPart::text(name, value),
+    };
+    for (key, param) in params {
+        match key.as_str() {
+            "type" => part.content_type = Some(param),
+            "filename" => {
+                if let MultipartValue::File { filename, .. } = &mut part.value {
+                    *filename = Some(param);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(part)
+}
+
+/// Split `value;type=...;filename=...` into the value and its parameters.
+/// The value may be double-quoted to contain `;`; segments that are not
+/// `type` or `filename` parameters stay part of an unquoted value.
+fn split_form_params(content: &str) -> (String, Vec<(String, String)>) {
+    let (mut value, rest) = match content.strip_prefix('"') {
+        Some(quoted) => {
+            let mut value = String::new();
+            let mut chars = quoted.char_indices();
+            let mut end = quoted.len();
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '\\' => value.extend(chars.next().map(|(_, c)| c)),
+                    '"' => {
+                        end = i + 1;
+                        break;
+                    }
+                    c => value.push(c),
+                }
+            }
+            (value, &quoted[end..])
+        }
+        None => match content.split_once(';') {
+            Some((value, rest)) => (value.to_string(), rest),
+            None => (content.to_string(), ""),
+        },
+    };
+
+    let mut params = Vec::new();
+    for segment in rest.split(';').filter(|s| !s.is_empty()) {
+        match segment.split_once('=') {
+            Some((key, param)) if key == "type" || key == "filename" => {
+                params.push((key.to_string(), param.trim_matches('"').to_string()));
+            }
+            _ if params.is_empty() && !content.starts_with('"') => {
+                value.push(';');
+                value.push_str(segment);
+            }
+            _ => {}
+        }
+    }
+    (value, params)
+}
+
+/// Split a command line into arguments following POSIX shell quoting.
+fn split_command_line(command: &str) -> ImportResult<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut chars = command.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err(curl_error("Unterminated ' quote in curl command")),
+                    }
+                }
+            }
+            '"' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => current.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => return Err(curl_error("Unterminated \" quote in curl command")),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err(curl_error("Unterminated \" quote in curl command")),
+                    }
+                }
+            }
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                in_arg = true;
+                read_ansi_c_quoted(&mut chars, &mut current)?;
+            }
+            '\\' => match chars.next() {
+                // Line continuation
+                Some('\n') => {}
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                Some(c) => {
+                    in_arg = true;
+                    current.push(c);
+                }
+                None => {}
+            },
+            c if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            c => {
+                in_arg = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+/// Read the rest of a `$'...'` string, which browsers use for values with
+/// quotes or control characters.
+fn read_ansi_c_quoted(chars: &mut Peekable<Chars<'_>>, out: &mut String) -> ImportResult<()> {
+    let unterminated = || curl_error("Unterminated $' quote in curl command");
+    loop {
+        match chars.next().ok_or_else(unterminated)? {
+            '\'' => return Ok(()),
+            '\\' => match chars.next().ok_or_else(unterminated)? {
+                'n' => out.push('\n'),
+                'r' => out.push('\r'),
+                't' => out.push('\t'),
+                'e' | 'E' => out.push('\u{1b}'),
+                '0' => out.push('\0'),
+                'x' => push_hex_escape(chars, 2, out),
+                'u' => push_hex_escape(chars, 4, out),
+                'U' => push_hex_escape(chars, 8, out),
+                c @ ('\\' | '\'' | '"' | '?') => out.push(c),
+                c => {
+                    out.push('\\');
+                    out.push(c);
+                }
+            },
+            c => out.push(c),
+        }
+    }
+}
+
+fn push_hex_escape(chars: &mut Peekable<Chars<'_>>, max_digits: usize, out: &mut String) {
+    let mut digits = String::new();
+    while digits.len() < max_digits && chars.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+        digits.extend(chars.next());
+    }
+    match u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32) {
+        Some(c) => out.push(c),
+        None => out.push_str(&digits),
+    }
+}
+
+/// Build a curl command line that sends `req`.
+///
+/// `req` should already be resolved (see `ReqForgeCore::resolve_request`);
+/// any placeholders left in it are written out as-is. OAuth 2.0 tokens are
+/// fetched at send time and so are not included. Disabled headers, params
+/// and form fields are left out.
+pub fn to_curl_command(req: &RequestDefinition) -> String {
+    let mut query: Vec<(&str, &str)> = req
+        .query_params
+        .iter()
+        .filter(|p| p.enabled)
+        .map(|p| (p.key.as_str(), p.value.as_str()))
+        .collect();
+    if let AuthConfig::ApiKey { key, value, location: ApiKeyLocation::Query } = &req.auth {
+        query.push((key.as_str(), value.as_str()));
+    }
+    let mut url = req.url.clone();
+    if !query.is_empty() {
+        let encoded = form_urlencoded::Serializer::new(String::new()).extend_pairs(query).finish();
+        url.push(if url.contains('?') { '&' } else { '?' });
+        url.push_str(&encoded);
+    }
+
+    let has_body = !matches!(req.body, BodyType::None);
+    let mut first = vec!["curl".to_string()];
+    match req.method {
+        HttpMethod::GET if !has_body => {}
+        HttpMethod::POST if has_body => {}
+        HttpMethod::HEAD if !has_body => first.push("--head".to_string()),
+        _ => first.push(format!("-X {}", req.method)),
+    }
+    first.push(shell_quote(&url));
+
+    let mut options = Vec::new();
+    for h in req.headers.iter().filter(|h| h.enabled) {
+        options.push(format!("-H {}", shell_quote(&format_header(&h.key, &h.value))));
+    }
+
+    match &req.auth {
+        AuthConfig::Basic { username, password } => {
+            options.push(format!("-u {}", shell_quote(&format!("{}:{}", username, password))));
+        }
+        AuthConfig::Digest { username, password } => {
+            options.push("--digest".to_string());
+            options.push(format!("-u {}", shell_quote(&format!("{}:{}", username, password))));
+        }
+        AuthConfig::Bearer { token } => {
+            options.push(format!("-H {}", shell_quote(&format!("Authorization: Bearer {}", token))));
+        }
+        AuthConfig::ApiKey { key, value, location: ApiKeyLocation::Header } => {
+            options.push(format!("-H {}", shell_quote(&format_header(key, value))));
+        }
+        AuthConfig::AwsSigV4 { access_key_id, secret_access_key, session_token, region, service } => {
+            options.push(format!("--aws-sigv4 {}", shell_quote(&format!("aws:amz:{}:{}", region, service))));
+            options.push(format!("-u {}", shell_quote(&format!("{}:{}", access_key_id, secret_access_key))));
+            if let Some(token) = session_token {
+                options.push(format!("-H {}", shell_quote(&format!("x-amz-security-token: {}", token))));
+            }
+        }
+        _ => {}
+    }
+
+    match &req.body {
+        BodyType::None => {}
+        BodyType::Raw { content, content_type } => {
+            if !has_header(&req.headers, "content-type") {
+                options.push(format!("-H {}", shell_quote(&format!("Content-Type: {}", content_type.mime_type()))));
+            }
+            options.push(format!("--data-raw {}", shell_quote(content)));
+        }
+        BodyType::FormUrlEncoded(pairs) => {
+            for pair in pairs.iter().filter(|p| p.enabled) {
+                let key: String = form_urlencoded::byte_serialize(pair.key.as_bytes()).collect();
+                options.push(format!("--data-urlencode {}", shell_quote(&format!("{}={}", key, pair.value))));
+            }
+        }
+        BodyType::Multipart(parts) => {
+            for part in parts.iter().filter(|p| p.enabled) {
+                options.push(format_form_part(part));
+            }
+        }
+        BodyType::Binary { path } => options.push(format!("--data-binary {}", shell_quote(&format!("@{}", path)))),
+    }
+
+    let settings = &req.settings;
+    if settings.accept_invalid_certs == Some(true) {
+        options.push("-k".to_string());
+    }
+    if let Some(RedirectPolicy::Follow { max }) = settings.redirects {
+        options.push("-L".to_string());
+        if max != CURL_MAX_REDIRECTS {
+            options.push(format!("--max-redirs {}", max));
+        }
+    }
+    if let Some(ms) = settings.connect_timeout_ms {
+        options.push(format!("--connect-timeout {}", format_seconds(ms)));
+    }
+    if let Some(ms) = settings.timeout_ms.filter(|ms| *ms > 0) {
+        options.push(format!("--max-time {}", format_seconds(ms)));
+    }
+    if settings.bypass_proxy {
+        options.push("--noproxy '*'".to_string());
+    } else if let Some(proxy) = &settings.proxy {
+        options.push(format!("-x {}", shell_quote(&proxy.url)));
+        if let Some(username) = &proxy.username {
+            let password = proxy.password.as_deref().unwrap_or("");
+            options.push(format!("-U {}", shell_quote(&format!("{}:{}", username, password))));
+        }
+        if !proxy.no_proxy.is_empty() {
+            options.push(format!("--noproxy {}", shell_quote(&proxy.no_proxy.join(","))));
+        }
+    }
+    match settings.http_version {
+        Some(HttpVersionPref::Http1Only) => options.push("--http1.1".to_string()),
+        Some(HttpVersionPref::Http2Only) => options.push("--http2-prior-knowledge".to_string()),
+        _ => {}
+    }
+
+    let mut command = first.join(" ");
+    for option in options {
+        command.push_str(" \\\n  ");
+        command.push_str(&option);
+    }
+    command
+}
+
+/// `Name: value`, or `Name;` for an empty value (`Name:` would tell curl to
+/// drop the header).
+fn format_header(key: &str, value: &str) -> String {
+    if value.is_empty() {
+        format!("{};", key)
+    } else {
+        format!("{}: {}", key, value)
+    }
+}
+
+fn format_form_part(part: &MultipartPart) -> String {
+    let mut field = match &part.value {
+        MultipartValue::File { path, filename } => {
+            let mut field = format!("{}=@{}", part.name, path);
+            if let Some(filename) = filename {
+                field.push_str(&format!(";filename={}", filename));
+            }
+            field
+        }
+        // -F would read `@`/`<` values as files and split on `;`
+        MultipartValue::Text(value) if part.content_type.is_none() && (value.starts_with(['@', '<', '"']) || value.contains(';')) => {
+            return format!("--form-string {}", shell_quote(&format!("{}={}", part.name, value)));
+        }
+        MultipartValue::Text(value) if value.starts_with(['@', '<']) || value.contains(';') => {
+            format!("{}=\"{}\"", part.name, value.replace('\\', "\\\\").replace('"', "\\\""))
+        }
+        MultipartValue::Text(value) => format!("{}={}", part.name, value),
+    };
+    if let Some(content_type) = &part.content_type {
+        field.push_str(&format!(";type={}", content_type));
+    }
+    format!("-F {}", shell_quote(&field))
+}
+
+fn format_seconds(ms: u64) -> String {
+    if ms.is_multiple_of(1000) {
+        (ms / 1000).to_string()
+    } else {
+        format!("{}", ms as f64 / 1000.0)
+    }
+}
+
+/// Quote an argument for a POSIX shell, leaving simple ones bare.
+pub(crate) fn shell_quote(arg: &str) -> String {
+    let is_plain = !arg.is_empty()
+        && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:@%+=,".contains(c));
+    if is_plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header_pairs(req: &RequestDefinition) -> Vec<(&str, &str)> {
+        req.headers.iter().map(|h| (h.key.as_str(), h.value.as_str())).collect()
+    }
+
+    #[test]
+    fn test_parse_browser_copy_as_curl() {
+        let command = "curl 'https://api.example.com/users?page=2&q=a%20b' \\\n  \
+            -H 'accept: application/json' \\\n  \
+            -H 'content-type: application/json' \\\n  \
+            -H $'x-note: it\\'s' \\\n  \
+            --data-raw '{\"name\":\"Ada\"}' \\\n  \
+            --compressed";
+        let req = parse_curl_command(command).unwrap();
+
+        assert!(matches!(req.method, HttpMethod::POST));
+        assert_eq!(req.url, "https://api.example.com/users");
+        assert_eq!(req.name, "POST /users");
+        let params: Vec<(&str, &str)> = req.query_params.iter().map(|p| (p.key.as_str(), p.value.as_str())).collect();
+        assert_eq!(params, vec![("page", "2"), ("q", "a b")]);
+        // The Content-Type is implied by the JSON body
+        assert_eq!(header_pairs(&req), vec![("accept", "application/json"), ("x-note", "it's")]);
+        match &req.body {
+            BodyType::Raw { content, content_type: RawContentType::Json } => assert_eq!(content, "{\"name\":\"Ada\"}"),
+            other => panic!("unexpected body {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_quoting_and_short_options() {
+        let req = parse_curl_command(r#"curl -sSLk -XPUT "http://localhost:8080/items/1" -H "X-Quote: \"hi\"" -d 'plain text' -u admin:s3cret"#).unwrap();
+        assert!(matches!(req.method, HttpMethod::PUT));
+        assert_eq!(header_pairs(&req), vec![("X-Quote", "\"hi\"")]);
+        assert!(matches!(&req.body, BodyType::Raw { content, content_type: RawContentType::Text } if content == "plain text"));
+        assert!(matches!(&req.auth, AuthConfig::Basic { username, password } if username == "admin" && password == "s3cret"));
+        assert_eq!(req.settings.accept_invalid_certs, Some(true));
+        assert_eq!(req.settings.redirects, Some(RedirectPolicy::Follow { max: CURL_MAX_REDIRECTS }));
+
+        let req = parse_curl_command("curl example.com/health --digest -u user -m 2.5").unwrap();
+        assert!(matches!(req.method, HttpMethod::GET));
+        assert_eq!(req.url, "http://example.com/health");
+        assert!(matches!(&req.auth, AuthConfig::Digest { username, password } if username == "user" && password.is_empty()));
+        assert_eq!(req.settings.timeout_ms, Some(2500));
+    }
+
+    #[test]
+    fn test_parse_form_data_and_get() {
+        let req = parse_curl_command("curl https://x.test/login -d user=ada --data-urlencode 'note=a&b c'").unwrap();
+        assert!(matches!(req.method, HttpMethod::POST));
+        match &req.body {
+            BodyType::FormUrlEncoded(pairs) => {
+                let pairs: Vec<(&str, &str)> = pairs.iter().map(|p| (p.key.as_str(), p.value.as_str())).collect();
+                assert_eq!(pairs, vec![("user", "ada"), ("note", "a&b c")]);
+            }
+            other => panic!("unexpected body {:?}", other),
+        }
+
+        let req = parse_curl_command("curl -G https://x.test/search?lang=en --data-urlencode 'q=rust lang'").unwrap();
+        assert!(matches!(req.method, HttpMethod::GET));
+        assert!(matches!(req.body, BodyType::None));
+        let params: Vec<(&str, &str)> = req.query_params.iter().map(|p| (p.key.as_str(), p.value.as_str())).collect();
+        assert_eq!(params, vec![("lang", "en"), ("q", "rust lang")]);
+
+        let req = parse_curl_command("curl https://x.test/upload --data-binary @./photo.png -H 'Content-Type: image/png'").unwrap();
+        assert!(matches!(&req.body, BodyType::Binary { path } if path == "./photo.png"));
+        assert_eq!(header_pairs(&req), vec![("Content-Type", "image/png")]);
+    }
+
+    #[test]
+    fn test_parse_multipart_form() {
+        let req = parse_curl_command(
+            "curl https://x.test/upload -F 'title=Holiday' -F 'photo=@/tmp/a.png;type=image/png;filename=b.png' -F 'meta=<meta.json' --form-string 'raw=@not-a-file'",
+        )
+        .unwrap();
+        assert!(matches!(req.method, HttpMethod::POST));
+        let BodyType::Multipart(parts) = &req.body else { panic!("expected multipart body") };
+        assert_eq!(parts[0], MultipartPart::text("title", "Holiday"));
+        assert_eq!(parts[1].value, MultipartValue::File { path: "/tmp/a.png".into(), filename: Some("b.png".into()) });
+        assert_eq!(parts[1].content_type.as_deref(), Some("image/png"));
+        assert_eq!(parts[2], MultipartPart::file("meta", "meta.json"));
+        assert_eq!(parts[3], MultipartPart::text("raw", "@not-a-file"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let kind = |command: &str| parse_curl_command(command).unwrap_err().kind().clone();
+        assert_eq!(kind("wget https://x.test"), ImportErrorKind::CurlFormat);
+        assert_eq!(kind("curl -H 'Accept: */*'"), ImportErrorKind::CurlFormat);
+        assert_eq!(kind("curl 'https://x.test"), ImportErrorKind::CurlFormat);
+        assert_eq!(kind("curl --cert client.pem https://x.test"), ImportErrorKind::CurlFormat);
+        assert_eq!(kind("curl https://x.test -d a=1 -F b=2"), ImportErrorKind::CurlFormat);
+        assert_eq!(kind("curl -X FETCH https://x.test"), ImportErrorKind::Validation);
+    }
+
+    #[test]
+    fn test_to_curl_command() {
+        let mut req = RequestDefinition::new("Create", HttpMethod::POST, "https://api.example.com/users");
+        req.query_params.push(header("dry run", "yes"));
+        req.headers.push(header("X-Trace", ""));
+        req.auth = AuthConfig::Bearer { token: "abc".into() };
+        req.body = BodyType::Raw { content: "{\"name\":\"O'Brien\"}".into(), content_type: RawContentType::Json };
+
+        assert_eq!(
+            to_curl_command(&req),
+            "curl 'https://api.example.com/users?dry+run=yes' \\\n  \
+             -H 'X-Trace;' \\\n  \
+             -H 'Authorization: Bearer abc' \\\n  \
+             -H 'Content-Type: application/json' \\\n  \
+             --data-raw '{\"name\":\"O'\\''Brien\"}'"
+        );
+
+        let req = RequestDefinition::new("Ping", HttpMethod::GET, "https://x.test/ping");
+        assert_eq!(to_curl_command(&req), "curl https://x.test/ping");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut req = RequestDefinition::new("Upload", HttpMethod::PATCH, "https://x.test/files/1");
+        req.query_params.push(header("v", "2"));
+        req.headers.push(header("Accept", "text/csv"));
+        req.auth = AuthConfig::Digest { username: "ada".into(), password: "p:w d".into() };
+        let mut file = MultipartPart::file("doc", "/tmp/report 1.csv");
+        file.content_type = Some("text/csv".into());
+        req.body = BodyType::Multipart(vec![MultipartPart::text("note", "a;b \"c\""), file]);
+        req.settings.connect_timeout_ms = Some(1500);
+        req.settings.redirects = Some(RedirectPolicy::Follow { max: 3 });
+        req.settings.http_version = Some(HttpVersionPref::Http1Only);
+        req.settings.proxy = Some(ProxySettings {
+            url: "http://proxy:3128".into(),
+            username: Some("me".into()),
+            password: Some("pw".into()),
+            no_proxy: vec!["localhost".into()],
+        });
+
+        let parsed = parse_curl_command(&to_curl_command(&req)).unwrap();
+        assert!(matches!(parsed.method, HttpMethod::PATCH));
+        assert_eq!(parsed.url, req.url);
+        assert_eq!(parsed.query_params.len(), 1);
+        assert_eq!(header_pairs(&parsed), header_pairs(&req));
+        assert!(matches!(&parsed.auth, AuthConfig::Digest { username, password } if username == "ada" && password == "p:w d"));
+        match (&parsed.body, &req.body) {
+            (BodyType::Multipart(parsed), BodyType::Multipart(original)) => assert_eq!(parsed, original),
+            other => panic!("unexpected bodies {:?}", other),
+        }
+        assert_eq!(parsed.settings, req.settings);
+
+        let mut req = RequestDefinition::new("Form", HttpMethod::PUT, "https://x.test/form");
+        req.body = BodyType::FormUrlEncoded(vec![header("a b", "1&2"), header("c", "")]);
+        let parsed = parse_curl_command(&to_curl_command(&req)).unwrap();
+        assert!(matches!(parsed.method, HttpMethod::PUT));
+        match &parsed.body {
+            BodyType::FormUrlEncoded(pairs) => {
+                let pairs: Vec<(&str, &str)> = pairs.iter().map(|p| (p.key.as_str(), p.value.as_str())).collect();
+                assert_eq!(pairs, vec![("a b", "1&2"), ("c", "")]);
+            }
+            other => panic!("unexpected body {:?}", other),
+        }
+    }
+}
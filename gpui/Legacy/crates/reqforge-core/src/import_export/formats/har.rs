@@ -0,0 +1,552 @@
+//! HTTP Archive (HAR 1.2) support
+//!
+//! `parse_har` turns a HAR file saved from browser devtools into a
+//! collection with one folder per host. Repeated requests (same method, URL
+//! and body) are imported once, and static assets (scripts, stylesheets,
+//! images, fonts, media) are skipped when the HAR records a resource type.
+//!
+//! `history_to_har` writes request history entries, with whatever response
+//! bodies were kept, as a HAR log that browsers and other tools can open.
+
+use std::collections::HashSet;
+
+use base64::Engine as _;
+use serde_json::{Map, Value, json};
+use url::{Url, form_urlencoded};
+use uuid::Uuid;
+
+use crate::models::{
+    ApiKeyLocation, AuthConfig, BodyType, Collection, CollectionItem, Folder, KeyValuePair, MultipartPart,
+    MultipartValue, RawContentType, RequestDefinition, RequestHistoryEntry,
+};
+use super::super::error::{ImportError, ImportErrorKind};
+use super::super::ImportResult;
+use super::curl::request_name;
+use super::parse_http_method;
+
+/// Chrome's `_resourceType` values for requests that are not API calls.
+const STATIC_RESOURCE_TYPES: &[&str] = &["stylesheet", "script", "image", "font", "media", "manifest", "texttrack"];
+
+/// Request headers the HTTP engine sets itself. HTTP/2 pseudo-headers
+/// (`:authority`, ...) are dropped as well.
+const ENGINE_HEADERS: &[&str] = &["host", "content-length", "connection", "accept-encoding"];
+
+fn har_error(message: &str) -> ImportError {
+    ImportError::new(ImportErrorKind::HarFormat, message)
+}
+
+/// Parse a HAR log into a collection named `name`.
+///
+/// Entries with a method ReqForge does not support, or a URL that is not
+/// `http(s)`, are skipped.
+pub fn parse_har(har: &Value, name: &str) -> ImportResult<Collection> {
+    let entries = har
+        .get("log")
+        .and_then(|log| log.get("entries"))
+        .and_then(|entries| entries.as_array())
+        .ok_or_else(|| har_error("Missing 'log.entries' in HAR file"))?;
+
+    let mut collection = Collection::new(name);
+    let mut seen = HashSet::new();
+    let mut hosts: Vec<(String, Uuid)> = Vec::new();
+    for entry in entries {
+        let resource_type = entry.get("_resourceType").and_then(|t| t.as_str()).unwrap_or("");
+        if STATIC_RESOURCE_TYPES.contains(&resource_type) {
+            continue;
+        }
+        let request = entry.get("request").ok_or_else(|| har_error("HAR entry has no 'request'"))?;
+        let Some((req, host, body_text)) = parse_har_request(request)? else {
+            continue;
+        };
+        if !seen.insert((req.method.to_string(), request_url(request).to_string(), body_text)) {
+            continue;
+        }
+
+        let folder_id = match hosts.iter().find(|(name, _)| *name == host) {
+            Some((_, id)) => *id,
+            None => {
+                let id = Uuid::new_v4();
+                collection.tree.push(CollectionItem::Folder(Box::new(Folder {
+                    id,
+                    name: host.clone(),
+                    children: Vec::new(),
+                    auth: AuthConfig::Inherit,
+                    variables: Vec::new(),
+                })));
+                hosts.push((host, id));
+                id
+            }
+        };
+        collection.add_request(req, Some(folder_id));
+    }
+    Ok(collection)
+}
+
+fn request_url(request: &Value) -> &str {
+    request.get("url").and_then(|u| u.as_str()).unwrap_or("")
+}
+
+/// A HAR request, its host (with any non-default port) and its body text,
+/// or `None` if it cannot be imported.
+fn parse_har_request(request: &Value) -> ImportResult<Option<(RequestDefinition, String, String)>> {
+    let url = Url::parse(request_url(request)).map_err(|e| har_error(&format!("Invalid request URL: {}", e)))?;
+    let Some(host) = url.host_str().filter(|_| matches!(url.scheme(), "http" | "https")) else {
+        return Ok(None);
+    };
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    };
+    let Ok(method) = parse_http_method(request.get("method").and_then(|m| m.as_str()).unwrap_or("GET")) else {
+        return Ok(None);
+    };
+
+    let query_params = match request.get("queryString").and_then(|q| q.as_array()) {
+        Some(params) => name_value_pairs(params),
+        None => url.query_pairs().map(|(key, value)| pair(key, value)).collect(),
+    };
+    let mut base = url.clone();
+    base.set_query(None);
+    base.set_fragment(None);
+
+    let mut headers: Vec<KeyValuePair> = request
+        .get("headers")
+        .and_then(|h| h.as_array())
+        .map(|headers| name_value_pairs(headers))
+        .unwrap_or_default();
+    headers.retain(|h| !h.key.starts_with(':') && !ENGINE_HEADERS.contains(&h.key.to_ascii_lowercase().as_str()));
+
+    let post_data = request.get("postData");
+    let body_text = post_data.and_then(|p| p.get("text")).and_then(|t| t.as_str()).unwrap_or("").to_string();
+    let body = match post_data {
+        Some(post_data) => post_data_body(post_data, &body_text, &mut headers),
+        None => BodyType::None,
+    };
+
+    let base = base.to_string();
+    let mut req = RequestDefinition::new(request_name(&method, &base), method, base);
+    req.headers = headers;
+    req.query_params = query_params;
+    req.body = body;
+    Ok(Some((req, host, body_text)))
+}
+
+/// The body for a HAR `postData`, picked by its MIME type. A Content-Type
+/// header that the body type sends anyway is dropped, and so is a multipart
+/// one, whose boundary is generated when sending.
+fn post_data_body(post_data: &Value, text: &str, headers: &mut Vec<KeyValuePair>) -> BodyType {
+    let mime_type = post_data.get("mimeType").and_then(|m| m.as_str()).unwrap_or("").to_ascii_lowercase();
+    let params = post_data.get("params").and_then(|p| p.as_array());
+
+    let raw = |content_type| BodyType::Raw { content: text.to_string(), content_type };
+    let body = if mime_type.starts_with("application/x-www-form-urlencoded") {
+        match params {
+            Some(params) => BodyType::FormUrlEncoded(name_value_pairs(params)),
+            None => BodyType::FormUrlEncoded(form_urlencoded::parse(text.as_bytes()).map(|(k, v)| pair(k, v)).collect()),
+        }
+    } else if mime_type.starts_with("multipart/form-data") {
+        let parts = params.map(|params| params.iter().filter_map(multipart_part).collect()).unwrap_or_default();
+        BodyType::Multipart(parts)
+    } else if mime_type.starts_with("application/json") || mime_type.contains("+json") {
+        raw(RawContentType::Json)
+    } else if mime_type.contains("xml") {
+        raw(RawContentType::Xml)
+    } else if mime_type.starts_with("text/html") {
+        raw(RawContentType::Html)
+    } else if text.is_empty() {
+        BodyType::None
+    } else {
+        raw(RawContentType::Text)
+    };
+
+    let implied = match &body {
+        BodyType::FormUrlEncoded(_) => Some("application/x-www-form-urlencoded"),
+        BodyType::Raw { content_type, .. } => Some(content_type.mime_type()),
+        _ => None,
+    };
+    let multipart = matches!(body, BodyType::Multipart(_));
+    headers.retain(|h| {
+        !(h.key.eq_ignore_ascii_case("content-type")
+            && (multipart || implied.is_some_and(|implied| h.value.eq_ignore_ascii_case(implied))))
+    });
+    body
+}
+
+/// A multipart param; file params keep only the file name, since the
+/// contents are not in the HAR.
+fn multipart_part(param: &Value) -> Option<MultipartPart> {
+    let name = param.get("name").and_then(|n| n.as_str()).filter(|n| !n.is_empty())?;
+    let mut part = match param.get("fileName").and_then(|f| f.as_str()) {
+        Some(file_name) => MultipartPart::file(name, file_name),
+        None => MultipartPart::text(name, param.get("value").and_then(|v| v.as_str()).unwrap_or("")),
+    };
+    part.content_type = param.get("contentType").and_then(|c| c.as_str()).filter(|c| !c.is_empty()).map(String::from);
+    Some(part)
+}
+
+fn name_value_pairs(values: &[Value]) -> Vec<KeyValuePair> {
+    values
+        .iter()
+        .filter_map(|v| {
+            let name = v.get("name").and_then(|n| n.as_str()).filter(|n| !n.is_empty())?;
+            Some(pair(name, v.get("value").and_then(|v| v.as_str()).unwrap_or("")))
+        })
+        .collect()
+}
+
+fn pair(key: impl Into<String>, value: impl Into<String>) -> KeyValuePair {
+    KeyValuePair { key: key.into(), value: value.into(), enabled: true, description: None }
+}
+
+/// Write history entries as a HAR 1.2 log, oldest first.
+///
+/// Each entry's request should already be resolved (variables replaced and
+/// auth no longer `Inherit`); it is written as it would be sent. The bytes
+/// next to each entry are its stored response body, if any.
+pub fn history_to_har(entries: &[(RequestHistoryEntry, Option<Vec<u8>>)]) -> Value {
+    let mut entries: Vec<&(RequestHistoryEntry, Option<Vec<u8>>)> = entries.iter().collect();
+    entries.sort_by_key(|(entry, _)| entry.timestamp);
+    json!({
+        "log": {
+            "version": "1.2",
+            "creator": { "name": "ReqForge", "version": env!("CARGO_PKG_VERSION") },
+            "entries": entries.into_iter().map(|(entry, body)| har_entry(entry, body.as_deref())).collect::<Vec<_>>(),
+        }
+    })
+}
+
+fn har_entry(entry: &RequestHistoryEntry, body: Option<&[u8]>) -> Value {
+    let response = &entry.response;
+    let mut comments = Vec::new();
+    let request = har_request(&entry.request, &mut comments);
+
+    let mut headers: Vec<(&String, &String)> = response.headers.iter().collect();
+    headers.sort();
+    let mime_type = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        .map_or("", |(_, value)| value.as_str());
+    let location = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("location"))
+        .map_or("", |(_, value)| value.as_str());
+
+    let mut content = Map::new();
+    content.insert("size".into(), response.size_bytes.into());
+    content.insert("mimeType".into(), mime_type.into());
+    if let Some(body) = body {
+        match std::str::from_utf8(body) {
+            Ok(text) => {
+                content.insert("text".into(), text.into());
+            }
+            Err(_) => {
+                content.insert("text".into(), base64::engine::general_purpose::STANDARD.encode(body).into());
+                content.insert("encoding".into(), "base64".into());
+            }
+        }
+        if response.body.as_ref().is_some_and(|stored| stored.truncated) {
+            content.insert("comment".into(), format!("Truncated to the first {} bytes", body.len()).into());
+        }
+    }
+    if !response.success && response.status == 0 {
+        comments.push("The request failed before a response was received".to_string());
+    }
+
+    let millis = |d: std::time::Duration| d.as_secs_f64() * 1000.0;
+    let optional = |d: Option<std::time::Duration>| d.map_or(-1.0, millis);
+    let (time, timings) = match &response.timing {
+        Some(timing) => (
+            millis(timing.total),
+            json!({
+                "blocked": -1,
+                "dns": optional(timing.dns_lookup),
+                // HAR's connect time includes the TLS handshake
+                "connect": timing.tcp_connect.map_or(-1.0, |tcp| millis(tcp) + optional(timing.tls_handshake).max(0.0)),
+                "ssl": optional(timing.tls_handshake),
+                "send": 0,
+                "wait": millis(timing.time_to_first_byte),
+                "receive": millis(timing.content_download),
+            }),
+        ),
+        None => (
+            response.elapsed_millis as f64,
+            json!({ "send": 0, "wait": response.elapsed_millis, "receive": 0 }),
+        ),
+    };
+
+    let mut har = json!({
+        "startedDateTime": entry.timestamp.to_rfc3339(),
+        "time": time,
+        "request": request,
+        "response": {
+            "status": response.status,
+            "statusText": response.status_text,
+            "httpVersion": "HTTP/1.1",
+            "cookies": [],
+            "headers": headers.iter().map(|(name, value)| json!({ "name": name, "value": value })).collect::<Vec<_>>(),
+            "content": content,
+            "redirectURL": location,
+            "headersSize": -1,
+            "bodySize": if body.is_some() { response.size_bytes as i64 } else { -1 },
+        },
+        "cache": {},
+        "timings": timings,
+    });
+    if !comments.is_empty() {
+        har["comment"] = comments.join("; ").into();
+    }
+    har
+}
+
+fn har_request(req: &RequestDefinition, comments: &mut Vec<String>) -> Value {
+    let mut query: Vec<(&str, &str)> = enabled(&req.query_params).collect();
+    let mut headers: Vec<(String, String)> = enabled(&req.headers).map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    match &req.auth {
+        AuthConfig::Basic { username, password } => {
+            let credentials = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password));
+            headers.push(("Authorization".into(), format!("Basic {}", credentials)));
+        }
+        AuthConfig::Bearer { token } => headers.push(("Authorization".into(), format!("Bearer {}", token))),
+        AuthConfig::ApiKey { key, value, location: ApiKeyLocation::Header } => headers.push((key.clone(), value.clone())),
+        AuthConfig::ApiKey { key, value, location: ApiKeyLocation::Query } => query.push((key, value)),
+        AuthConfig::Digest { .. } => comments.push("Digest auth is not included".into()),
+        AuthConfig::AwsSigV4 { .. } => comments.push("AWS SigV4 signing is not included".into()),
+        AuthConfig::OAuth2(_) => comments.push("The OAuth 2.0 access token is not included".into()),
+        AuthConfig::Inherit | AuthConfig::None => {}
+    }
+
+    let mut url = req.url.clone();
+    if !query.is_empty() {
+        url.push(if url.contains('?') { '&' } else { '?' });
+        url.push_str(&form_urlencoded::Serializer::new(String::new()).extend_pairs(&query).finish());
+    }
+
+    let post_data = post_data(&req.body, &headers);
+    if let Some((mime_type, _)) = &post_data
+        && !headers.iter().any(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+    {
+        headers.push(("Content-Type".into(), mime_type.clone()));
+    }
+    if let BodyType::Binary { path } = &req.body {
+        comments.push(format!("The request body was read from {}", path));
+    }
+
+    let mut har = json!({
+        "method": req.method.to_string(),
+        "url": url,
+        "httpVersion": "HTTP/1.1",
+        "cookies": [],
+        "headers": headers.iter().map(|(name, value)| json!({ "name": name, "value": value })).collect::<Vec<_>>(),
+        "queryString": query.iter().map(|(name, value)| json!({ "name": name, "value": value })).collect::<Vec<_>>(),
+        "headersSize": -1,
+        "bodySize": post_data.as_ref().map_or(0, |(_, data)| data["text"].as_str().map_or(-1, |text| text.len() as i64)),
+    });
+    if let Some((_, data)) = post_data {
+        har["postData"] = data;
+    }
+    har
+}
+
+/// The MIME type and HAR `postData` for a request body.
+fn post_data(body: &BodyType, headers: &[(String, String)]) -> Option<(String, Value)> {
+    let header_type = headers.iter().rev().find(|(k, _)| k.eq_ignore_ascii_case("content-type")).map(|(_, v)| v.clone());
+    let (mime_type, data) = match body {
+        BodyType::None => return None,
+        BodyType::Raw { content, content_type } => (content_type.mime_type().to_string(), json!({ "text": content })),
+        BodyType::FormUrlEncoded(pairs) => {
+            let pairs: Vec<(&str, &str)> = enabled(pairs).collect();
+            let text = form_urlencoded::Serializer::new(String::new()).extend_pairs(&pairs).finish();
+            let params: Vec<Value> = pairs.iter().map(|(name, value)| json!({ "name": name, "value": value })).collect();
+            ("application/x-www-form-urlencoded".to_string(), json!({ "text": text, "params": params }))
+        }
+        BodyType::Multipart(parts) => {
+            let params: Vec<Value> = parts
+                .iter()
+                .filter(|part| part.enabled)
+                .map(|part| {
+                    let mut param = match &part.value {
+                        MultipartValue::Text(value) => json!({ "name": part.name, "value": value }),
+                        MultipartValue::File { path, filename } => {
+                            let file_name = filename.as_deref().unwrap_or_else(|| path.rsplit(['/', '\\']).next().unwrap_or(path));
+                            json!({ "name": part.name, "fileName": file_name })
+                        }
+                    };
+                    if let Some(content_type) = &part.content_type {
+                        param["contentType"] = content_type.as_str().into();
+                    }
+                    param
+                })
+                .collect();
+            ("multipart/form-data".to_string(), json!({ "params": params }))
+        }
+        BodyType::Binary { .. } => ("application/octet-stream".to_string(), json!({ "text": "" })),
+    };
+    let mime_type = header_type.unwrap_or(mime_type);
+    let mut data = data;
+    data["mimeType"] = mime_type.as_str().into();
+    Some((mime_type, data))
+}
+
+fn enabled(pairs: &[KeyValuePair]) -> impl Iterator<Item = (&str, &str)> {
+    pairs.iter().filter(|p| p.enabled).map(|p| (p.key.as_str(), p.value.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{HttpMethod, ResponseSnapshot};
+
+    fn browser_har() -> Value {
+        json!({
+            "log": {
+                "version": "1.2",
+                "creator": { "name": "WebInspector", "version": "537.36" },
+                "entries": [
+                    {
+                        "_resourceType": "fetch",
+                        "request": {
+                            "method": "POST",
+                            "url": "https://api.example.com/v1/users?page=2",
+                            "httpVersion": "http/2.0",
+                            "headers": [
+                                { "name": ":authority", "value": "api.example.com" },
+                                { "name": "content-type", "value": "application/json" },
+                                { "name": "authorization", "value": "Bearer abc" },
+                                { "name": "content-length", "value": "15" }
+                            ],
+                            "queryString": [{ "name": "page", "value": "2" }],
+                            "postData": { "mimeType": "application/json", "text": "{\"name\":\"Ann\"}" }
+                        }
+                    },
+                    {
+                        "_resourceType": "fetch",
+                        "request": {
+                            "method": "POST",
+                            "url": "https://api.example.com/v1/users?page=2",
+                            "headers": [],
+                            "postData": { "mimeType": "application/json", "text": "{\"name\":\"Ann\"}" }
+                        }
+                    },
+                    {
+                        "_resourceType": "script",
+                        "request": { "method": "GET", "url": "https://cdn.example.com/app.js", "headers": [] }
+                    },
+                    {
+                        "request": {
+                            "method": "POST",
+                            "url": "http://localhost:8080/login",
+                            "headers": [{ "name": "Content-Type", "value": "application/x-www-form-urlencoded" }],
+                            "postData": {
+                                "mimeType": "application/x-www-form-urlencoded",
+                                "params": [{ "name": "user", "value": "ann" }, { "name": "pass", "value": "a b" }]
+                            }
+                        }
+                    },
+                    {
+                        "request": { "method": "GET", "url": "data:image/png;base64,AAAA", "headers": [] }
+                    }
+                ]
+            }
+        })
+    }
+
+    #[test]
+    fn test_parse_har_groups_by_host_and_deduplicates() {
+        let collection = parse_har(&browser_har(), "Session").unwrap();
+        assert_eq!(collection.name, "Session");
+        assert_eq!(collection.requests.len(), 2);
+
+        let folders: Vec<&Folder> = collection
+            .tree
+            .iter()
+            .filter_map(|item| match item {
+                CollectionItem::Folder(folder) => Some(folder.as_ref()),
+                CollectionItem::Request(_) => None,
+            })
+            .collect();
+        assert_eq!(folders.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(), vec!["api.example.com", "localhost:8080"]);
+        assert_eq!(folders[0].children.len(), 1);
+
+        let CollectionItem::Request(id) = &folders[0].children[0] else { panic!("expected a request") };
+        let req = &collection.requests[id];
+        assert_eq!(req.method, HttpMethod::POST);
+        assert_eq!(req.name, "POST /v1/users");
+        assert_eq!(req.url, "https://api.example.com/v1/users");
+        assert_eq!(req.query_params[0].key, "page");
+        let headers: Vec<&str> = req.headers.iter().map(|h| h.key.as_str()).collect();
+        assert_eq!(headers, vec!["authorization"]);
+        assert!(matches!(&req.body, BodyType::Raw { content, content_type: RawContentType::Json } if content == "{\"name\":\"Ann\"}"));
+
+        let CollectionItem::Request(id) = &folders[1].children[0] else { panic!("expected a request") };
+        let req = &collection.requests[id];
+        assert!(req.headers.is_empty());
+        let BodyType::FormUrlEncoded(fields) = &req.body else { panic!("expected a form body") };
+        assert_eq!(fields[1].value, "a b");
+    }
+
+    #[test]
+    fn test_parse_har_errors() {
+        let err = parse_har(&json!({ "log": {} }), "Session").unwrap_err();
+        assert_eq!(err.kind(), &ImportErrorKind::HarFormat);
+        let err = parse_har(&json!({ "log": { "entries": [{ "request": { "url": "not a url" } }] } }), "Session").unwrap_err();
+        assert_eq!(err.kind(), &ImportErrorKind::HarFormat);
+    }
+
+    #[test]
+    fn test_history_to_har() {
+        let mut req = RequestDefinition::new("Create", HttpMethod::POST, "https://api.example.com/users");
+        req.auth = AuthConfig::Basic { username: "ann".into(), password: "pw".into() };
+        req.query_params.push(pair("dry", "1"));
+        req.body = BodyType::Raw { content: "{\"a\":1}".into(), content_type: RawContentType::Json };
+        let response = ResponseSnapshot {
+            status: 201,
+            status_text: "Created".into(),
+            size_bytes: 9,
+            elapsed_millis: 12,
+            success: true,
+            timing: None,
+            headers: [("content-type".to_string(), "application/json".to_string())].into(),
+            body: None,
+        };
+        let entry = RequestHistoryEntry::new(req, response, None, None);
+        let failed = RequestHistoryEntry::new(
+            RequestDefinition::new("Down", HttpMethod::GET, "https://down.example.com"),
+            ResponseSnapshot {
+                status: 0,
+                status_text: "Error".into(),
+                size_bytes: 0,
+                elapsed_millis: 0,
+                success: false,
+                timing: None,
+                headers: Default::default(),
+                body: None,
+            },
+            None,
+            None,
+        );
+
+        let har = history_to_har(&[(entry, Some(b"{\"id\":42}".to_vec())), (failed, None)]);
+        let entries = har["log"]["entries"].as_array().unwrap();
+        assert_eq!(har["log"]["version"], "1.2");
+        assert_eq!(entries.len(), 2);
+
+        let request = &entries[0]["request"];
+        assert_eq!(request["url"], "https://api.example.com/users?dry=1");
+        assert_eq!(request["postData"]["mimeType"], "application/json");
+        assert_eq!(request["postData"]["text"], "{\"a\":1}");
+        let headers = request["headers"].as_array().unwrap();
+        assert!(headers.contains(&json!({ "name": "Authorization", "value": "Basic YW5uOnB3" })));
+        assert!(headers.contains(&json!({ "name": "Content-Type", "value": "application/json" })));
+
+        let response = &entries[0]["response"];
+        assert_eq!(response["status"], 201);
+        assert_eq!(response["content"]["mimeType"], "application/json");
+        assert_eq!(response["content"]["text"], "{\"id\":42}");
+        assert!(entries[1]["response"]["content"].get("text").is_none());
+        assert!(entries[1]["comment"].as_str().unwrap().contains("failed"));
+
+        // What we export imports back
+        let collection = parse_har(&har, "Round trip").unwrap();
+        assert_eq!(collection.requests.len(), 2);
+    }
+}
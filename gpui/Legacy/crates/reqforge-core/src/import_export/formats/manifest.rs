@@ -6,6 +6,10 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkspaceManifest {
     pub version: String,
+    /// Schema version of the documents in the export; 0 for exports made
+    /// before documents were versioned.
+    #[serde(default)]
+    pub schema_version: u32,
     pub exported_at: chrono::DateTime<chrono::Utc>,
     pub collection_count: usize,
     pub environment_count: usize,
@@ -20,6 +24,7 @@ mod tests {
     fn test_manifest_serialization() {
         let manifest = WorkspaceManifest {
             version: "1.0".to_string(),
+            schema_version: crate::store::schema::SCHEMA_VERSION,
             exported_at: Utc::now(),
             collection_count: 2,
             environment_count: 1,
@@ -1,16 +1,80 @@
 //! OpenAPI specification format support
+//!
+//! Reads OpenAPI 3.x and Swagger 2.0 specs, as JSON or YAML. Local `$ref`s
+//! (`#/components/...`, `#/definitions/...`) are followed; references to
+//! other files are not. Each operation becomes a request:
+//! - its URL is `{{base_url}}` plus the path, with path parameters as
+//!   `{{name}}` request variables
+//! - its body is the spec's example, or one generated from the schema
+//! - its auth comes from the spec's security schemes, set on the collection
+//!   for the spec-wide requirement and on requests that differ from it
+//! - it goes in a folder named after its first tag
+//!
+//! Each server becomes an environment holding `base_url`, the server's
+//! variables and empty credential variables for the security schemes. The
+//! first server is also the collection's default `base_url`.
+//!
+//! `collection_to_openapi` goes the other way, describing a collection as an
+//! OpenAPI 3.1 document with schemas inferred from request bodies and from
+//! the responses kept in history.
 
-use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+use serde_json::{Map, Value, json};
+use url::form_urlencoded;
 use uuid::Uuid;
-use chrono::Utc;
 
-use crate::models::{Collection, RequestDefinition, KeyValuePair, BodyType, RawContentType};
+use crate::models::{
+    ApiKeyLocation, AuthConfig, BodyType, Collection, CollectionItem, Environment, Folder, KeyValuePair,
+    MultipartPart, MultipartValue, OAuth2Config, OAuth2Grant, RawContentType, RequestDefinition,
+    RequestHistoryEntry, Variable,
+};
+use crate::store::yaml;
 use super::super::error::{ImportError, ImportErrorKind};
 use super::super::ImportResult;
-use super::{parse_http_method, extract_headers};
+use super::parse_http_method;
+
+/// How deep example generation follows nested and recursive schemas.
+const MAX_SCHEMA_DEPTH: usize = 10;
+
+/// How many `$ref`s in a row are followed before giving up on a cycle.
+const MAX_REF_CHAIN: usize = 32;
+
+/// HTTP methods in OpenAPI
+const METHODS: &[&str] = &["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+
+/// A collection imported from an OpenAPI spec, with one environment per server.
+#[derive(Debug, Clone)]
+pub struct OpenApiImport {
+    pub collection: Collection,
+    pub environments: Vec<Environment>,
+}
+
+fn openapi_error(message: &str) -> ImportError {
+    ImportError::new(ImportErrorKind::OpenApiFormat, message)
+}
+
+/// Parse an OpenAPI document given as JSON or YAML text. YAML is read with
+/// `store::yaml`, so anchors, aliases and merge keys are resolved.
+pub fn parse_openapi_document(text: &str) -> ImportResult<OpenApiImport> {
+    let spec: Value = if text.trim_start().starts_with('{') {
+        serde_json::from_str(text)
+            .map_err(|e| ImportError::new(ImportErrorKind::Deserialization, &format!("Failed to parse OpenAPI JSON: {}", e)))?
+    } else {
+        yaml::from_str(text)
+            .map_err(|e| ImportError::new(ImportErrorKind::Deserialization, &format!("Failed to parse OpenAPI YAML: {}", e)))?
+    };
+    parse_openapi(&spec)
+}
 
 /// Parse an OpenAPI 3.x specification into a ReqForge collection
 pub fn parse_openapi_spec(openapi: &Value) -> ImportResult<Collection> {
+    parse_openapi(openapi).map(|import| import.collection)
+}
+
+/// Parse an OpenAPI 3.x or Swagger 2.0 specification into a collection and
+/// environments.
+pub fn parse_openapi(openapi: &Value) -> ImportResult<OpenApiImport> {
     // Check OpenAPI version
     let version = openapi
         .get("openapi")
@@ -18,129 +82,185 @@ pub fn parse_openapi_spec(openapi: &Value) -> ImportResult<Collection> {
         .and_then(|v| v.as_str());
 
     if !matches!(version, Some(v) if v.starts_with("3.") || v.starts_with("2.")) {
-        return Err(ImportError::new(
-            ImportErrorKind::OpenApiFormat,
-            "Unsupported OpenAPI version. Only 2.x and 3.x are supported."
-        ));
+        return Err(openapi_error("Unsupported OpenAPI version. Only 2.x and 3.x are supported."));
     }
 
-    // Get info
     let info = openapi
         .get("info")
-        .ok_or_else(|| ImportError::new(
-            ImportErrorKind::OpenApiFormat,
-            "Missing 'info' field in OpenAPI spec"
-        ))?;
+        .ok_or_else(|| openapi_error("Missing 'info' field in OpenAPI spec"))?;
 
     let title = info
         .get("title")
         .and_then(|v| v.as_str())
         .unwrap_or("OpenAPI Collection");
 
-    let mut collection = Collection::new(title);
-
-    // Get base URL
-    let base_url = extract_base_url(openapi);
-
-    // Get servers (OpenAPI 3.x)
-    let servers = openapi.get("servers").and_then(|v| v.as_array());
-
-    // Get paths
     let paths = openapi
         .get("paths")
         .and_then(|v| v.as_object())
-        .ok_or_else(|| ImportError::new(
-            ImportErrorKind::OpenApiFormat,
-            "Missing 'paths' field in OpenAPI spec"
-        ))?;
+        .ok_or_else(|| openapi_error("Missing 'paths' field in OpenAPI spec"))?;
 
-    // Process each path
-    for (path, path_item) in paths {
-        if let Some(path_obj) = path_item.as_object() {
-            process_openapi_path(
-                &mut collection,
-                path,
-                path_obj,
-                &base_url,
-                servers,
-                openapi,
-            )?;
-        }
+    let mut collection = Collection::new(title);
+    let mut credentials = Vec::new();
+    if let Some(requirements) = openapi.get("security") {
+        collection.auth = security_auth(requirements, openapi, &mut credentials).unwrap_or(AuthConfig::Inherit);
     }
 
-    Ok(collection)
-}
+    // Requests grouped by their first tag, in the order the spec lists tags
+    let mut tags: Vec<(String, Vec<RequestDefinition>)> = openapi
+        .get("tags")
+        .and_then(|t| t.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|tag| tag.get("name").and_then(|n| n.as_str()))
+        .map(|name| (name.to_string(), Vec::new()))
+        .collect();
+    let mut untagged = Vec::new();
 
-/// Extract the base URL from an OpenAPI spec
-fn extract_base_url(openapi: &Value) -> String {
-    // Try OpenAPI 3.x servers first
-    if let Some(servers) = openapi.get("servers").and_then(|v| v.as_array()) {
-        if let Some(first_server) = servers.first() {
-            if let Some(url) = first_server.get("url").and_then(|v| v.as_str()) {
-                return url.to_string();
+    for (path, path_item) in paths {
+        let path_item = deref(path_item, openapi);
+        let Some(path_obj) = path_item.as_object() else { continue };
+        for method_str in METHODS {
+            let Some(operation) = path_obj.get(*method_str).and_then(|op| op.as_object()) else { continue };
+            let mut request = parse_openapi_operation(method_str, path, path_obj, operation, openapi)?;
+            if let Some(requirements) = operation.get("security")
+                && let Some(auth) = security_auth(requirements, openapi, &mut credentials)
+                && auth != collection.auth
+            {
+                request.auth = auth;
+            }
+
+            match operation.get("tags").and_then(|t| t.get(0)).and_then(|t| t.as_str()) {
+                Some(tag) => match tags.iter_mut().find(|(name, _)| name == tag) {
+                    Some((_, requests)) => requests.push(request),
+                    None => tags.push((tag.to_string(), vec![request])),
+                },
+                None => untagged.push(request),
             }
         }
     }
 
-    // Try Swagger 2.x host and schemes
-    if let Some(host) = openapi.get("host").and_then(|v| v.as_str()) {
-        let schemes = openapi
-            .get("schemes")
-            .and_then(|v| v.as_array())
-            .and_then(|arr| arr.first())
-            .and_then(|v| v.as_str())
-            .unwrap_or("https");
+    for (name, requests) in tags.into_iter().filter(|(_, requests)| !requests.is_empty()) {
+        let folder_id = Uuid::new_v4();
+        collection.tree.push(CollectionItem::Folder(Box::new(Folder {
+            id: folder_id,
+            name,
+            children: Vec::new(),
+            auth: AuthConfig::Inherit,
+            variables: Vec::new(),
+        })));
+        for request in requests {
+            collection.add_request(request, Some(folder_id));
+        }
+    }
+    for request in untagged {
+        collection.add_request(request, None);
+    }
+
+    let servers = extract_servers(openapi);
+    if let Some(server) = servers.first() {
+        collection.variables = server.variables();
+    }
+    let mut environments: Vec<Environment> = servers
+        .iter()
+        .map(|server| {
+            let mut environment = Environment::new(&server.name);
+            environment.variables = server.variables();
+            environment.variables.extend(credentials.iter().cloned());
+            environment
+        })
+        .collect();
+    if environments.is_empty() && !credentials.is_empty() {
+        let mut environment = Environment::new(title);
+        environment.variables = credentials;
+        environments.push(environment);
+    }
+
+    Ok(OpenApiImport { collection, environments })
+}
 
-        let base_path = openapi
-            .get("basePath")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
+/// A server from the spec, with `{name}` placeholders turned into variables.
+struct Server {
+    name: String,
+    url: String,
+    variables: Vec<(String, String)>,
+}
 
-        return format!("{}://{}{}", schemes, host, base_path);
+impl Server {
+    fn variables(&self) -> Vec<Variable> {
+        std::iter::once(variable("base_url", &self.url, false))
+            .chain(self.variables.iter().map(|(key, value)| variable(key, value, false)))
+            .collect()
     }
+}
 
-    String::new()
+fn variable(key: &str, value: &str, secret: bool) -> Variable {
+    Variable { key: key.to_string(), value: value.to_string(), secret, enabled: true }
 }
 
-/// Process an OpenAPI path item
-fn process_openapi_path(
-    collection: &mut Collection,
-    path: &str,
-    path_obj: &serde_json::Map<String, Value>,
-    base_url: &str,
-    servers: Option<&Vec<Value>>,
-    openapi: &Value,
-) -> ImportResult<()> {
-    // HTTP methods in OpenAPI
-    let methods = ["get", "put", "post", "delete", "options", "head", "patch", "trace"];
-
-    for method_str in methods {
-        if let Some(operation) = path_obj.get(method_str) {
-            if let Some(op_obj) = operation.as_object() {
-                let request = parse_openapi_operation(
-                    method_str,
-                    path,
-                    op_obj,
-                    base_url,
-                    servers,
-                    openapi,
-                )?;
-
-                collection.add_request(request, None);
-            }
-        }
+/// The servers of an OpenAPI 3.x spec, or the single host of a Swagger 2.x one.
+fn extract_servers(openapi: &Value) -> Vec<Server> {
+    if let Some(servers) = openapi.get("servers").and_then(|v| v.as_array()) {
+        return servers
+            .iter()
+            .filter_map(|server| {
+                let url = server.get("url").and_then(|v| v.as_str())?;
+                let name = server
+                    .get("description")
+                    .and_then(|d| d.as_str())
+                    .filter(|d| !d.trim().is_empty())
+                    .unwrap_or(url);
+                let variables = server
+                    .get("variables")
+                    .and_then(|v| v.as_object())
+                    .into_iter()
+                    .flatten()
+                    .map(|(key, var)| (key.clone(), var.get("default").map(scalar_text).unwrap_or_default()))
+                    .collect();
+                Some(Server { name: name.to_string(), url: template_to_placeholders(url), variables })
+            })
+            .collect();
     }
 
-    Ok(())
+    // Swagger 2.x host and schemes
+    let base_path = openapi.get("basePath").and_then(|v| v.as_str()).unwrap_or("");
+    let url = match openapi.get("host").and_then(|v| v.as_str()) {
+        Some(host) => {
+            let scheme = openapi
+                .get("schemes")
+                .and_then(|v| v.as_array())
+                .and_then(|arr| arr.first())
+                .and_then(|v| v.as_str())
+                .unwrap_or("https");
+            format!("{}://{}{}", scheme, host, base_path)
+        }
+        None if !base_path.is_empty() => base_path.to_string(),
+        None => return Vec::new(),
+    };
+    vec![Server { name: url.clone(), url, variables: Vec::new() }]
+}
+
+/// Turn OpenAPI `{name}` templates into `{{name}}` placeholders.
+fn template_to_placeholders(template: &str) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else { break };
+        out.push_str(&rest[..start]);
+        out.push_str("{{");
+        out.push_str(&rest[start + 1..start + len]);
+        out.push_str("}}");
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    out
 }
 
 /// Parse an OpenAPI operation into a RequestDefinition
 fn parse_openapi_operation(
     method_str: &str,
     path: &str,
-    operation: &serde_json::Map<String, Value>,
-    base_url: &str,
-    servers: Option<&Vec<Value>>,
+    path_obj: &Map<String, Value>,
+    operation: &Map<String, Value>,
     openapi: &Value,
 ) -> ImportResult<RequestDefinition> {
     let method = parse_http_method(method_str)?;
@@ -154,240 +274,1152 @@ fn parse_openapi_operation(
             // Generate name from method and path
             let path_part = path
                 .split('/')
-                .filter(|s| !s.is_empty() && !s.starts_with('{'))
-                .last()
+                .rfind(|s| !s.is_empty() && !s.starts_with('{'))
                 .unwrap_or("endpoint");
             format!("{} {}", method_str.to_uppercase(), path_part)
         });
 
-    // Construct URL
-    let url = if !base_url.is_empty() {
-        format!("{}{}", base_url, path)
-    } else if let Some(server) = servers.and_then(|s| s.first()) {
-        let server_url = server.get("url").and_then(|v| v.as_str()).unwrap_or("");
-        format!("{}{}", server_url, path)
+    let url = format!("{{{{base_url}}}}{}", template_to_placeholders(path));
+    let mut request = RequestDefinition::new(name, method, url);
+
+    // Path-level parameters apply unless the operation redefines them
+    let operation_params: Vec<&Value> = parameters(operation.get("parameters"), openapi);
+    let mut params: Vec<&Value> = parameters(path_obj.get("parameters"), openapi)
+        .into_iter()
+        .filter(|param| !operation_params.iter().any(|op| same_parameter(op, param)))
+        .collect();
+    params.extend(operation_params);
+
+    let mut form_fields = Vec::new();
+    for param in params {
+        let param_name = param.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        let required = param.get("required").and_then(|v| v.as_bool()).unwrap_or(false);
+        let description = param.get("description").and_then(|v| v.as_str()).map(String::from);
+        let value = parameter_example(param, openapi).map(|v| scalar_text(&v)).unwrap_or_default();
+
+        match param.get("in").and_then(|v| v.as_str()) {
+            Some("path") => request.variables.push(variable(param_name, &value, false)),
+            Some("header") => request.headers.push(KeyValuePair {
+                key: param_name.to_string(),
+                value,
+                enabled: true,
+                description,
+            }),
+            Some("query") => request.query_params.push(KeyValuePair {
+                key: param_name.to_string(),
+                value,
+                enabled: required,
+                description,
+            }),
+            // Swagger 2.x request bodies
+            Some("body") => {
+                let schema = param.get("schema").unwrap_or(&Value::Null);
+                request.body = json_body(example_value(schema, openapi, 0));
+            }
+            Some("formData") => form_fields.push(param),
+            _ => {}
+        }
+    }
+    if !form_fields.is_empty() {
+        request.body = swagger_form_body(&form_fields, operation, openapi);
+    }
+
+    // Request body (OpenAPI 3.x)
+    if let Some(body_obj) = operation.get("requestBody") {
+        request.body = parse_openapi_body(body_obj, openapi)?;
+    }
+
+    Ok(request)
+}
+
+/// A `parameters` list with each `$ref` resolved.
+fn parameters<'a>(list: Option<&'a Value>, openapi: &'a Value) -> Vec<&'a Value> {
+    list.and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .map(|param| deref(param, openapi))
+        .filter(|param| param.is_object())
+        .collect()
+}
+
+fn same_parameter(a: &Value, b: &Value) -> bool {
+    a.get("name") == b.get("name") && a.get("in") == b.get("in")
+}
+
+/// A parameter's example, default or first allowed value, if the spec gives one.
+fn parameter_example(param: &Value, openapi: &Value) -> Option<Value> {
+    if let Some(example) = param.get("example") {
+        return Some(example.clone());
+    }
+    if let Some(example) = first_example(param.get("examples"), openapi) {
+        return Some(example);
+    }
+    // Swagger 2.x keeps the schema keywords on the parameter itself
+    let schema = deref(param.get("schema").unwrap_or(param), openapi);
+    explicit_example(schema)
+}
+
+/// The first value of an `examples` map (3.x) or list (3.1 schemas).
+fn first_example(examples: Option<&Value>, openapi: &Value) -> Option<Value> {
+    match examples? {
+        Value::Object(map) => map.values().next().map(|e| deref(e, openapi)).and_then(|e| e.get("value")).cloned(),
+        Value::Array(list) => list.first().cloned(),
+        _ => None,
+    }
+}
+
+/// A schema's own example, default, const or first enum value.
+fn explicit_example(schema: &Value) -> Option<Value> {
+    schema
+        .get("example")
+        .or_else(|| schema.get("examples").and_then(|e| e.get(0)))
+        .or_else(|| schema.get("default"))
+        .or_else(|| schema.get("const"))
+        .or_else(|| schema.get("enum").and_then(|e| e.get(0)))
+        .cloned()
+}
+
+/// Swagger 2.x `formData` parameters as a form, or multipart if any is a file.
+fn swagger_form_body(fields: &[&Value], operation: &Map<String, Value>, openapi: &Value) -> BodyType {
+    let consumes_multipart = operation
+        .get("consumes")
+        .or_else(|| openapi.get("consumes"))
+        .and_then(|c| c.as_array())
+        .is_some_and(|types| types.iter().any(|t| t.as_str() == Some("multipart/form-data")));
+    let is_file = |field: &&Value| field.get("type").and_then(|t| t.as_str()) == Some("file");
+
+    if consumes_multipart || fields.iter().any(is_file) {
+        BodyType::Multipart(
+            fields
+                .iter()
+                .map(|field| {
+                    let name = field.get("name").and_then(|n| n.as_str()).unwrap_or("");
+                    if is_file(field) {
+                        MultipartPart::file(name, "")
+                    } else {
+                        MultipartPart::text(name, parameter_example(field, openapi).map(|v| scalar_text(&v)).unwrap_or_default())
+                    }
+                })
+                .collect(),
+        )
     } else {
-        path.to_string()
+        BodyType::FormUrlEncoded(
+            fields
+                .iter()
+                .map(|field| KeyValuePair {
+                    key: field.get("name").and_then(|n| n.as_str()).unwrap_or("").to_string(),
+                    value: parameter_example(field, openapi).map(|v| scalar_text(&v)).unwrap_or_default(),
+                    enabled: true,
+                    description: field.get("description").and_then(|d| d.as_str()).map(String::from),
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Parse an OpenAPI request body
+fn parse_openapi_body(body_obj: &Value, openapi: &Value) -> ImportResult<BodyType> {
+    let body_obj = deref(body_obj, openapi);
+    let Some(content) = body_obj.get("content").and_then(|c| c.as_object()) else {
+        return Ok(json_body(json!({})));
+    };
+    let media = |matches: fn(&str) -> bool| {
+        content.iter().find(|(media_type, _)| matches(&media_type.to_ascii_lowercase())).map(|(_, media)| media)
     };
 
-    // Get parameters (headers and query params)
-    let mut headers = Vec::new();
-    let mut query_params = Vec::new();
-
-    // Get operation parameters
-    if let Some(params) = operation.get("parameters").and_then(|v| v.as_array()) {
-        for param in params {
-            if let Some(param_obj) = param.as_object() {
-                let param_in = param_obj.get("in").and_then(|v| v.as_str());
-                let param_name = param_obj.get("name").and_then(|v| v.as_str()).unwrap_or("");
-                let required = param_obj.get("required").and_then(|v| v.as_bool()).unwrap_or(false);
-
-                match param_in {
-                    Some("header") => {
-                        headers.push(KeyValuePair {
-                            key: param_name.to_string(),
-                            value: String::new(),
-                            enabled: true,
-                            description: param_obj.get("description").and_then(|v| v.as_str()).map(String::from),
-                        });
-                    }
-                    Some("query") => {
-                        query_params.push(KeyValuePair {
-                            key: param_name.to_string(),
-                            value: String::new(),
-                            enabled: required,
-                            description: param_obj.get("description").and_then(|v| v.as_str()).map(String::from),
-                        });
-                    }
-                    _ => {}
-                }
+    // JSON first: the media type's example, or one generated from its schema
+    if let Some(media) = media(|t| t.starts_with("application/json") || t.contains("+json")) {
+        let example = media_example(media, openapi)
+            .unwrap_or_else(|| example_value(media.get("schema").unwrap_or(&Value::Null), openapi, 0));
+        return Ok(json_body(example));
+    }
+
+    // Form bodies: one field or part per property of the example, with
+    // binary properties as file parts
+    let fields = |media: &Value| -> Vec<(String, Value, bool)> {
+        let schema = deref(media.get("schema").unwrap_or(&Value::Null), openapi);
+        let example = media_example(media, openapi).unwrap_or_else(|| example_value(schema, openapi, 0));
+        let is_file = |name: &str| {
+            schema.get("properties").and_then(|p| p.get(name)).is_some_and(|prop| is_binary(deref(prop, openapi)))
+        };
+        example
+            .as_object()
+            .into_iter()
+            .flatten()
+            .map(|(name, value)| (name.clone(), value.clone(), is_file(name)))
+            .collect()
+    };
+    if let Some(media) = media(|t| t.starts_with("application/x-www-form-urlencoded")) {
+        let fields = fields(media)
+            .into_iter()
+            .map(|(key, value, _)| KeyValuePair { key, value: scalar_text(&value), enabled: true, description: None })
+            .collect();
+        return Ok(BodyType::FormUrlEncoded(fields));
+    }
+    if let Some(media) = media(|t| t.starts_with("multipart/form-data")) {
+        let parts = fields(media)
+            .into_iter()
+            .map(|(name, value, is_file)| {
+                if is_file { MultipartPart::file(name, "") } else { MultipartPart::text(name, scalar_text(&value)) }
+            })
+            .collect();
+        return Ok(BodyType::Multipart(parts));
+    }
+
+    // Raw binary upload; the file is chosen by the user
+    if media(|t| t.starts_with("application/octet-stream")).is_some() {
+        return Ok(BodyType::Binary { path: String::new() });
+    }
+
+    let text_body = |media: &Value, content_type| BodyType::Raw {
+        content: media_example(media, openapi).map(|e| scalar_text(&e)).unwrap_or_default(),
+        content_type,
+    };
+    if let Some(media) = media(|t| t.contains("xml")) {
+        return Ok(text_body(media, RawContentType::Xml));
+    }
+    if let Some(media) = media(|t| t.starts_with("text/html")) {
+        return Ok(text_body(media, RawContentType::Html));
+    }
+    if let Some(media) = media(|t| t.starts_with("text/")) {
+        return Ok(text_body(media, RawContentType::Text));
+    }
+
+    // Default empty JSON body
+    Ok(json_body(json!({})))
+}
+
+fn json_body(example: Value) -> BodyType {
+    BodyType::Raw {
+        content: serde_json::to_string_pretty(&example).unwrap_or_else(|_| "{}".to_string()),
+        content_type: RawContentType::Json,
+    }
+}
+
+/// A media type's `example`, or the first of its `examples`.
+fn media_example(media: &Value, openapi: &Value) -> Option<Value> {
+    media.get("example").cloned().or_else(|| first_example(media.get("examples"), openapi))
+}
+
+fn is_binary(schema: &Value) -> bool {
+    matches!(schema.get("format").and_then(|f| f.as_str()), Some("binary" | "byte"))
+        || schema.get("type").and_then(|t| t.as_str()) == Some("file")
+}
+
+/// A value as form or parameter text: strings as they are, anything else as JSON.
+fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Generate an example value for a schema, preferring the spec's own
+/// examples, defaults and enum values.
+fn example_value(schema: &Value, openapi: &Value, depth: usize) -> Value {
+    let schema = deref(schema, openapi);
+    if depth > MAX_SCHEMA_DEPTH {
+        return Value::Null;
+    }
+    if let Some(example) = explicit_example(schema) {
+        return example;
+    }
+
+    if let Some(all_of) = schema.get("allOf").and_then(|a| a.as_array()) {
+        let mut merged = Map::new();
+        for part in all_of {
+            match example_value(part, openapi, depth + 1) {
+                Value::Object(map) => merged.extend(map),
+                other if all_of.len() == 1 => return other,
+                _ => {}
             }
         }
+        return Value::Object(merged);
+    }
+    if let Some(first) = ["oneOf", "anyOf"].iter().find_map(|key| schema.get(*key).and_then(|s| s.get(0))) {
+        return example_value(first, openapi, depth + 1);
     }
 
-    // Get request body (OpenAPI 3.x)
-    let body = if let Some(body_obj) = operation.get("body") {
-        parse_openapi_body(body_obj, openapi)?
-    } else {
-        BodyType::None
+    // OpenAPI 3.1 allows a list of types, such as ["string", "null"]
+    let schema_type = match schema.get("type") {
+        Some(Value::Array(types)) => types.iter().filter_map(|t| t.as_str()).find(|t| *t != "null"),
+        Some(t) => t.as_str(),
+        None if schema.get("properties").is_some() => Some("object"),
+        None if schema.get("items").is_some() => Some("array"),
+        None => None,
     };
 
-    // Add example headers if we have a body
-    if !matches!(body, BodyType::None) && !headers.iter().any(|h| h.key.eq_ignore_ascii_case("content-type")) {
-        headers.push(KeyValuePair {
-            key: "Content-Type".to_string(),
-            value: "application/json".to_string(),
-            enabled: true,
-            description: Some("Content-Type header".to_string()),
-        });
+    match schema_type {
+        Some("object") => {
+            let properties = schema.get("properties").and_then(|p| p.as_object());
+            Value::Object(
+                properties
+                    .into_iter()
+                    .flatten()
+                    .map(|(key, prop)| (key.clone(), example_value(prop, openapi, depth + 1)))
+                    .collect(),
+            )
+        }
+        Some("array") => match schema.get("items") {
+            Some(items) => Value::Array(vec![example_value(items, openapi, depth + 1)]),
+            None => Value::Array(Vec::new()),
+        },
+        Some("string") => {
+            let example = match schema.get("format").and_then(|v| v.as_str()) {
+                Some("email") => "user@example.com".to_string(),
+                Some("date-time") => "2024-01-01T00:00:00Z".to_string(),
+                Some("date") => "2024-01-01".to_string(),
+                Some("uuid") => Uuid::new_v4().to_string(),
+                Some("uri" | "url") => "https://example.com".to_string(),
+                Some("binary" | "byte") => String::new(),
+                _ => "string".to_string(),
+            };
+            Value::String(example)
+        }
+        Some("integer") | Some("number") => Value::Number(0.into()),
+        Some("boolean") => Value::Bool(true),
+        _ => Value::Null,
+    }
+}
+
+/// Follow `$ref`s until reaching a value that is not one. Unresolvable
+/// references resolve to `null`.
+fn deref<'a>(value: &'a Value, openapi: &'a Value) -> &'a Value {
+    let mut current = value;
+    for _ in 0..MAX_REF_CHAIN {
+        match current.get("$ref").and_then(|r| r.as_str()) {
+            Some(reference) => current = resolve_ref(reference, openapi).unwrap_or(&Value::Null),
+            None => return current,
+        }
     }
+    &Value::Null
+}
 
-    Ok(RequestDefinition {
-        id: Uuid::new_v4(),
-        name,
-        method,
-        url,
-        headers,
-        query_params,
-        body,
-        created_at: Utc::now(),
-        updated_at: Utc::now(),
+/// Resolve a local $ref (a JSON pointer into the spec)
+fn resolve_ref<'a>(ref_path: &str, openapi: &'a Value) -> Option<&'a Value> {
+    let pointer = ref_path.strip_prefix('#')?;
+    openapi.pointer(pointer)
+}
+
+/// The auth for a `security` requirement list: the first alternative that
+/// uses a single scheme ReqForge supports, or `None` auth for an empty list.
+/// Variables for the scheme's credentials are added to `credentials`.
+fn security_auth(requirements: &Value, openapi: &Value, credentials: &mut Vec<Variable>) -> Option<AuthConfig> {
+    let requirements = requirements.as_array()?;
+    if requirements.is_empty() {
+        return Some(AuthConfig::None);
+    }
+    let schemes = openapi
+        .get("components")
+        .and_then(|c| c.get("securitySchemes"))
+        .or_else(|| openapi.get("securityDefinitions"))?;
+
+    requirements.iter().filter_map(|r| r.as_object()).find_map(|requirement| {
+        if requirement.is_empty() {
+            // `{}` makes auth optional
+            return Some(AuthConfig::None);
+        }
+        if requirement.len() != 1 {
+            return None;
+        }
+        let (name, scopes) = requirement.iter().next()?;
+        let scopes: Vec<&str> = scopes.as_array().into_iter().flatten().filter_map(|s| s.as_str()).collect();
+        let (auth, variables) = scheme_auth(deref(schemes.get(name)?, openapi), &scopes)?;
+        for variable in variables {
+            if !credentials.iter().any(|v| v.key == variable.key) {
+                credentials.push(variable);
+            }
+        }
+        Some(auth)
     })
 }
 
-/// Parse an OpenAPI request body
-fn parse_openapi_body(body_obj: &Value, openapi: &Value) -> ImportResult<BodyType> {
-    // Get content
-    if let Some(content) = body_obj.get("content") {
-        // Try to get JSON content first
-        if let Some(json_content) = content.get("application/json") {
-            // Try to get example
-            if let Some(example) = json_content.get("example") {
-                return Ok(BodyType::Raw {
-                    content: serde_json::to_string_pretty(example).unwrap_or_else(|_| "{}".to_string()),
-                    content_type: RawContentType::Json,
-                });
+/// Auth for a security scheme, with credentials as `{{variables}}`.
+fn scheme_auth(scheme: &Value, scopes: &[&str]) -> Option<(AuthConfig, Vec<Variable>)> {
+    let text = |key: &str| scheme.get(key).and_then(|v| v.as_str()).unwrap_or("");
+    let user_and_password = || vec![variable("username", "", false), variable("password", "", true)];
+
+    match (text("type"), text("scheme").to_ascii_lowercase().as_str()) {
+        ("http", "basic") | ("basic", _) => Some((
+            AuthConfig::Basic { username: "{{username}}".into(), password: "{{password}}".into() },
+            user_and_password(),
+        )),
+        ("http", "digest") => Some((
+            AuthConfig::Digest { username: "{{username}}".into(), password: "{{password}}".into() },
+            user_and_password(),
+        )),
+        ("http", "bearer") => Some((AuthConfig::Bearer { token: "{{token}}".into() }, vec![variable("token", "", true)])),
+        ("apiKey", _) => {
+            let location = match text("in") {
+                "header" => ApiKeyLocation::Header,
+                "query" => ApiKeyLocation::Query,
+                _ => return None,
+            };
+            Some((
+                AuthConfig::ApiKey { key: text("name").to_string(), value: "{{api_key}}".into(), location },
+                vec![variable("api_key", "", true)],
+            ))
+        }
+        ("oauth2", _) => {
+            let (grant, token_url) = oauth2_grant(scheme)?;
+            let mut variables = vec![variable("client_id", "", false), variable("client_secret", "", true)];
+            if matches!(grant, OAuth2Grant::Password { .. }) {
+                variables.extend(user_and_password());
             }
+            let config = OAuth2Config {
+                grant,
+                token_url,
+                client_id: "{{client_id}}".into(),
+                client_secret: "{{client_secret}}".into(),
+                scope: (!scopes.is_empty()).then(|| scopes.join(" ")),
+                credentials_in_body: false,
+            };
+            Some((AuthConfig::OAuth2(config), variables))
+        }
+        _ => None,
+    }
+}
+
+/// The grant and token URL of an OAuth 2.0 scheme. Interactive login is
+/// preferred, then client credentials, then password; implicit flows are
+/// not supported.
+fn oauth2_grant(scheme: &Value) -> Option<(OAuth2Grant, String)> {
+    let text = |value: &Value, key: &str| value.get(key).and_then(|v| v.as_str()).map(String::from);
+    let authorization_code = |flow: &Value| {
+        Some((
+            OAuth2Grant::AuthorizationCode { authorization_url: text(flow, "authorizationUrl")?, redirect_port: None },
+            text(flow, "tokenUrl")?,
+        ))
+    };
+    let password = || OAuth2Grant::Password { username: "{{username}}".into(), password: "{{password}}".into() };
+
+    // OpenAPI 3.x
+    if let Some(flows) = scheme.get("flows") {
+        if let Some(flow) = flows.get("authorizationCode") {
+            return authorization_code(flow);
+        }
+        if let Some(flow) = flows.get("clientCredentials") {
+            return Some((OAuth2Grant::ClientCredentials, text(flow, "tokenUrl")?));
+        }
+        if let Some(flow) = flows.get("password") {
+            return Some((password(), text(flow, "tokenUrl")?));
+        }
+        return None;
+    }
+
+    // Swagger 2.x
+    match scheme.get("flow").and_then(|f| f.as_str())? {
+        "accessCode" => authorization_code(scheme),
+        "application" => Some((OAuth2Grant::ClientCredentials, text(scheme, "tokenUrl")?)),
+        "password" => Some((password(), text(scheme, "tokenUrl")?)),
+        _ => None,
+    }
+}
 
-            // Try to get schema and generate a basic example
-            if let Some(schema) = json_content.get("schema") {
-                let example = generate_example_from_schema(schema, openapi)?;
-                return Ok(BodyType::Raw {
-                    content: example,
-                    content_type: RawContentType::Json,
-                });
+/// Header parameters that OpenAPI describes elsewhere (as media types and
+/// security schemes) and ignores when listed.
+const RESERVED_HEADERS: &[&str] = &["accept", "content-type", "authorization"];
+
+/// Stands in for an unquoted `{{placeholder}}` while a JSON body template is
+/// parsed. It infers to a schema that accepts anything.
+const PLACEHOLDER: &str = "\u{1}placeholder";
+
+/// Describe a collection as an OpenAPI 3.1 document.
+///
+/// Each distinct origin of the request URLs (`{{base_url}}`,
+/// `https://api.example.com`) becomes a server, with placeholders filled
+/// from `variables`; origins that stay unresolved are listed as `/`. The
+/// rest of the URL is the path, where `{{name}}` placeholders become path
+/// parameters. Requests with the same path and method are merged into one
+/// operation, tagged with the innermost folder of the first of them.
+///
+/// `history` holds past responses to the collection's requests with their
+/// stored bodies, as for `history_to_har`; response schemas are inferred
+/// from the JSON ones, and the most recent response is the example. AWS
+/// Signature V4 has no OpenAPI security scheme and is left out.
+pub fn collection_to_openapi(
+    collection: &Collection,
+    history: &[(RequestHistoryEntry, Option<Vec<u8>>)],
+    variables: &HashMap<String, String>,
+) -> Value {
+    let mut schemes = Map::new();
+    let collection_auth = match &collection.auth {
+        AuthConfig::Inherit => AuthConfig::None,
+        auth => auth.clone(),
+    };
+    let collection_security = security_requirement(&collection_auth, &mut schemes, variables);
+
+    let mut servers: Vec<String> = Vec::new();
+    let mut tags: Vec<String> = Vec::new();
+    let mut operations: Vec<ExportedOperation> = Vec::new();
+    for (req, folders) in collection.ordered_requests() {
+        let (origin, path, _) = split_request_url(&req.url);
+        if !origin.is_empty() && !servers.contains(&origin) {
+            servers.push(origin);
+        }
+        let (path, path_params) = path_template(&path);
+        let method = req.method.to_string().to_ascii_lowercase();
+        match operations.iter_mut().find(|op| op.path == path && op.method == method) {
+            Some(operation) => operation.requests.push(req),
+            None => {
+                let tag = folders.last().cloned();
+                if let Some(tag) = &tag
+                    && !tags.contains(tag)
+                {
+                    tags.push(tag.clone());
+                }
+                operations.push(ExportedOperation { path, method, path_params, tag, requests: vec![req] });
+            }
+        }
+    }
+
+    // Newest first, so each example is the latest response
+    let mut history: Vec<&(RequestHistoryEntry, Option<Vec<u8>>)> = history.iter().collect();
+    history.sort_by_key(|pair| std::cmp::Reverse(pair.0.timestamp));
+
+    let mut operation_ids = HashSet::new();
+    let mut paths = Map::new();
+    for exported in operations {
+        let first = exported.requests[0];
+        let auth = collection.effective_auth(first.id);
+        let mut operation = json!({
+            "summary": first.name,
+            "operationId": operation_id(&first.name, &mut operation_ids),
+        });
+        if let Some(tag) = &exported.tag {
+            operation["tags"] = json!([tag]);
+        }
+
+        let parameters = export_parameters(&exported, &auth);
+        if !parameters.is_empty() {
+            operation["parameters"] = Value::Array(parameters);
+        }
+
+        let mut content = Map::new();
+        for req in &exported.requests {
+            if let Some((media_type, schema, example)) = request_body_content(req) {
+                add_content(&mut content, &media_type, schema, example);
             }
         }
+        if !content.is_empty() {
+            operation["requestBody"] = json!({ "content": content });
+        }
 
-        // Try text content
-        if let Some(text_content) = content.get("text/plain") {
-            if let Some(example) = text_content.get("example") {
-                if let Some(text) = example.as_str() {
-                    return Ok(BodyType::Raw {
-                        content: text.to_string(),
-                        content_type: RawContentType::Text,
-                    });
+        let responses = export_responses(&exported.requests, &history);
+        if !responses.is_empty() {
+            operation["responses"] = Value::Object(responses);
+        }
+
+        if auth != collection_auth {
+            match security_requirement(&auth, &mut schemes, variables) {
+                Some(requirement) if Some(&requirement) != collection_security.as_ref() => {
+                    operation["security"] = json!([requirement]);
                 }
+                None if auth == AuthConfig::None => operation["security"] = json!([]),
+                _ => {}
             }
         }
+
+        let path_item = paths.entry(exported.path).or_insert_with(|| json!({}));
+        path_item[exported.method] = operation;
     }
 
-    // Default empty JSON body
-    Ok(BodyType::Raw {
-        content: "{}".to_string(),
-        content_type: RawContentType::Json,
-    })
+    let mut document = json!({
+        "openapi": "3.1.0",
+        "info": { "title": collection.name, "version": "1.0.0" },
+        "paths": paths,
+    });
+    if !servers.is_empty() {
+        document["servers"] = servers.iter().map(|origin| server(origin, variables)).collect();
+    }
+    if !tags.is_empty() {
+        document["tags"] = tags.iter().map(|name| json!({ "name": name })).collect();
+    }
+    if !schemes.is_empty() {
+        document["components"] = json!({ "securitySchemes": schemes });
+    }
+    if let Some(requirement) = collection_security {
+        document["security"] = json!([requirement]);
+    }
+    document
 }
 
-/// Generate a basic example from a JSON schema
-fn generate_example_from_schema(schema: &Value, openapi: &Value) -> ImportResult<String> {
-    let schema_type = schema.get("type").and_then(|v| v.as_str());
+/// The requests that share a path and method.
+struct ExportedOperation<'a> {
+    path: String,
+    method: String,
+    path_params: Vec<String>,
+    tag: Option<String>,
+    requests: Vec<&'a RequestDefinition>,
+}
 
-    let example = match schema_type {
-        Some("object") => {
-            let mut obj = serde_json::Map::new();
-
-            if let Some(props) = schema.get("properties").and_then(|v| v.as_object()) {
-                for (key, prop_schema) in props {
-                    let value = if let Some(ref_schema) = prop_schema.get("$ref") {
-                        // Handle $ref
-                        let ref_path = ref_schema.as_str().unwrap_or("");
-                        resolve_ref(ref_path, openapi)
-                            .and_then(|ref_val| generate_example_value(&ref_val, openapi))
-                            .unwrap_or(serde_json::Value::Null)
-                    } else {
-                        generate_example_value(prop_schema, openapi)
-                            .unwrap_or(serde_json::Value::Null)
-                    };
-                    obj.insert(key.clone(), value);
-                }
+/// Split a request URL into its origin (`{{base_url}}`, `https://host:8080`,
+/// or empty for a bare path), its path and the query parameters written
+/// into it. Any fragment is dropped.
+fn split_request_url(url: &str) -> (String, String, Vec<(String, String)>) {
+    let url = url.split('#').next().unwrap_or_default();
+    let (url, query) = url.split_once('?').unwrap_or((url, ""));
+    let path_start = match url.find("://") {
+        Some(scheme_end) => url[scheme_end + 3..].find('/').map(|i| scheme_end + 3 + i),
+        None if url.starts_with('/') => Some(0),
+        None => url.find('/'),
+    };
+    let (origin, path) = match path_start {
+        Some(start) => (&url[..start], &url[start..]),
+        None => (url, "/"),
+    };
+    let query = form_urlencoded::parse(query.as_bytes()).map(|(k, v)| (k.into_owned(), v.into_owned())).collect();
+    (origin.to_string(), path.to_string(), query)
+}
+
+/// An OpenAPI path template for a request path, `{{id}}` becoming `{id}`,
+/// and the names of its parameters.
+fn path_template(path: &str) -> (String, Vec<String>) {
+    let mut out = String::with_capacity(path.len());
+    let mut names = Vec::new();
+    let mut rest = path;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start..].find("}}") else { break };
+        // `{{id | urlencode}}` and `{{$uuid}}` name the parameter `id` and `uuid`
+        let inner = &rest[start + 2..start + len];
+        let name = inner.split('|').next().unwrap_or_default().trim().trim_start_matches('$');
+        out.push_str(&rest[..start]);
+        out.push('{');
+        out.push_str(name);
+        out.push('}');
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+        rest = &rest[start + len + 2..];
+    }
+    out.push_str(rest);
+    (out, names)
+}
+
+/// Replace the `{{placeholders}}` in `text` that `variables` defines.
+fn fill_variables(text: &str, variables: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start..].find("}}") else { break };
+        out.push_str(&rest[..start]);
+        match variables.get(rest[start + 2..start + len].trim()) {
+            Some(value) => out.push_str(value),
+            None => out.push_str(&rest[start..start + len + 2]),
+        }
+        rest = &rest[start + len + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn server(origin: &str, variables: &HashMap<String, String>) -> Value {
+    let url = fill_variables(origin, variables);
+    if url.contains("{{") || url.is_empty() {
+        json!({ "url": "/", "description": format!("{} was not set when exporting", origin) })
+    } else {
+        json!({ "url": url.trim_end_matches('/') })
+    }
+}
+
+/// A camelCase `operationId` from a request name, unique among `used`.
+fn operation_id(name: &str, used: &mut HashSet<String>) -> String {
+    let mut base = String::new();
+    for word in name.split(|c: char| !c.is_ascii_alphanumeric()).filter(|w| !w.is_empty()) {
+        // All-caps words (`GET`, `API`) are lowercased before capitalizing
+        let word = if word.chars().any(|c| c.is_ascii_lowercase()) { word.to_string() } else { word.to_ascii_lowercase() };
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            if base.is_empty() {
+                base.push(first.to_ascii_lowercase());
+            } else {
+                base.push(first.to_ascii_uppercase());
             }
+            base.push_str(chars.as_str());
+        }
+    }
+    if base.is_empty() {
+        base.push_str("operation");
+    }
 
-            serde_json::to_string_pretty(&serde_json::Value::Object(obj))
-                .unwrap_or_else(|_| "{}".to_string())
-        }
-        Some("array") => {
-            let items = schema.get("items");
-            let item_example = items
-                .and_then(|i| {
-                    if let Some(ref_schema) = i.get("$ref") {
-                        let ref_path = ref_schema.as_str().unwrap_or("");
-                        resolve_ref(ref_path, openapi)
-                            .and_then(|ref_val| generate_example_value(&ref_val, openapi))
-                    } else {
-                        generate_example_value(i, openapi)
-                    }
-                })
-                .unwrap_or(serde_json::Value::Null);
+    let mut id = base.clone();
+    let mut n = 2;
+    while used.contains(&id) {
+        id = format!("{}{}", base, n);
+        n += 1;
+    }
+    used.insert(id.clone());
+    id
+}
 
-            format!("[{}]", serde_json::to_string(&item_example).unwrap_or_default())
+/// Path, query and header parameters of all the requests of an operation,
+/// leaving out the API key that `auth` sends.
+fn export_parameters(exported: &ExportedOperation, auth: &AuthConfig) -> Vec<Value> {
+    let api_key = |location: ApiKeyLocation, name: &str| {
+        matches!(auth, AuthConfig::ApiKey { key, location: l, .. } if *l == location && key.eq_ignore_ascii_case(name))
+    };
+    let first = exported.requests[0];
+    let mut parameters: Vec<Value> = exported
+        .path_params
+        .iter()
+        .map(|name| {
+            let value = first.variables.iter().find(|v| v.enabled && v.key == *name).map(|v| v.value.as_str());
+            parameter(name, "path", value.unwrap_or_default(), true)
+        })
+        .collect();
+    let mut add = |name: &str, location: &str, value: &str| {
+        if !parameters.iter().any(|p| p["name"] == name && p["in"] == location) {
+            parameters.push(parameter(name, location, value, false));
         }
-        _ => "{}".to_string(),
     };
 
-    Ok(example)
+    for req in &exported.requests {
+        let (_, _, inline_query) = split_request_url(&req.url);
+        let query = inline_query.iter().map(|(k, v)| (k.as_str(), v.as_str()));
+        for (key, value) in query.chain(req.query_params.iter().map(|p| (p.key.as_str(), p.value.as_str()))) {
+            if !key.is_empty() && !api_key(ApiKeyLocation::Query, key) {
+                add(key, "query", value);
+            }
+        }
+        for header in &req.headers {
+            let reserved = RESERVED_HEADERS.iter().any(|h| h.eq_ignore_ascii_case(&header.key));
+            if !header.key.is_empty() && !reserved && !api_key(ApiKeyLocation::Header, &header.key) {
+                add(&header.key, "header", &header.value);
+            }
+        }
+    }
+    parameters
 }
 
-/// Generate an example value from a schema
-fn generate_example_value(schema: &Value, openapi: &Value) -> Option<serde_json::Value> {
-    let schema_type = schema.get("type").and_then(|v| v.as_str());
+fn parameter(name: &str, location: &str, value: &str, required: bool) -> Value {
+    let mut param = json!({ "name": name, "in": location, "schema": { "type": "string" } });
+    if required {
+        param["required"] = json!(true);
+    }
+    if let Some(value) = concrete(value) {
+        let example = scalar(value);
+        param["schema"] = infer_schema(&example);
+        param["example"] = example;
+    }
+    param
+}
 
-    // Check for example first
-    if let Some(example) = schema.get("example") {
-        return Some(example.clone());
+/// `value`, unless it is empty or still holds placeholders.
+fn concrete(value: &str) -> Option<&str> {
+    (!value.is_empty() && !value.contains("{{")).then_some(value)
+}
+
+/// A parameter or form value as JSON, typed when it reads as an integer or
+/// a boolean.
+fn scalar(value: &str) -> Value {
+    if let Ok(n) = value.parse::<i64>()
+        && n.to_string() == value
+    {
+        return json!(n);
+    }
+    match value {
+        "true" => json!(true),
+        "false" => json!(false),
+        _ => json!(value),
+    }
+}
+
+/// The media type, schema and example of a request's body.
+fn request_body_content(req: &RequestDefinition) -> Option<(String, Value, Option<Value>)> {
+    let content_type = req
+        .headers
+        .iter()
+        .find(|h| h.enabled && h.key.eq_ignore_ascii_case("content-type"))
+        .map(|h| media_type(&h.value));
+    let binary = || json!({ "type": "string", "format": "binary" });
+
+    match &req.body {
+        BodyType::None => None,
+        BodyType::Raw { content, content_type: RawContentType::Json } => {
+            let media = content_type.unwrap_or_else(|| RawContentType::Json.mime_type().to_string());
+            let (schema, example) = match serde_json::from_str::<Value>(content) {
+                Ok(value) => (infer_schema(&value), (!content.contains("{{")).then_some(value)),
+                Err(_) => match serde_json::from_str::<Value>(&fill_json_placeholders(content)) {
+                    Ok(value) => (infer_schema(&value), None),
+                    Err(_) => (json!({}), None),
+                },
+            };
+            Some((media, schema, example))
+        }
+        BodyType::Raw { content, content_type: raw } => Some((
+            content_type.unwrap_or_else(|| raw.mime_type().to_string()),
+            json!({ "type": "string" }),
+            concrete(content).map(|c| json!(c)),
+        )),
+        BodyType::FormUrlEncoded(fields) => {
+            let fields = fields.iter().filter(|f| f.enabled).map(|f| (f.key.as_str(), Some(f.value.as_str())));
+            let (schema, example) = form_schema(fields);
+            Some(("application/x-www-form-urlencoded".to_string(), schema, example))
+        }
+        BodyType::Multipart(parts) => {
+            let fields = parts.iter().filter(|p| p.enabled).map(|part| match &part.value {
+                MultipartValue::Text(text) => (part.name.as_str(), Some(text.as_str())),
+                MultipartValue::File { .. } => (part.name.as_str(), None),
+            });
+            let (schema, example) = form_schema(fields);
+            Some(("multipart/form-data".to_string(), schema, example))
+        }
+        BodyType::Binary { .. } => {
+            Some((content_type.unwrap_or_else(|| "application/octet-stream".to_string()), binary(), None))
+        }
     }
+}
+
+/// `application/json` from `application/json; charset=utf-8`.
+fn media_type(content_type: &str) -> String {
+    content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase()
+}
 
-    // Check for default
-    if let Some(default) = schema.get("default") {
-        return Some(default.clone());
+/// An object schema for form fields; `None` values are files.
+fn form_schema<'a>(fields: impl Iterator<Item = (&'a str, Option<&'a str>)>) -> (Value, Option<Value>) {
+    let mut properties = Map::new();
+    let mut example = Map::new();
+    for (name, value) in fields {
+        let schema = match value.map(concrete) {
+            None => json!({ "type": "string", "format": "binary" }),
+            Some(Some(value)) => {
+                let value = scalar(value);
+                let schema = infer_schema(&value);
+                example.insert(name.to_string(), value);
+                schema
+            }
+            Some(None) => json!({ "type": "string" }),
+        };
+        properties.insert(name.to_string(), schema);
     }
+    let example = (!example.is_empty()).then_some(Value::Object(example));
+    (json!({ "type": "object", "properties": properties }), example)
+}
 
-    // Generate based on type
-    match schema_type {
-        Some("string") => {
-            let format = schema.get("format").and_then(|v| v.as_str());
-            match format {
-                Some("email") => Some(serde_json::Value::String("user@example.com".to_string())),
-                Some("date-time") => Some(serde_json::Value::String("2024-01-01T00:00:00Z".to_string())),
-                Some("date") => Some(serde_json::Value::String("2024-01-01".to_string())),
-                Some("uuid") => Some(serde_json::Value::String(Uuid::new_v4().to_string())),
-                _ => Some(serde_json::Value::String("string".to_string())),
+/// Replace `{{placeholders}}` outside of JSON strings with `PLACEHOLDER`, so
+/// a body template like `{"id": {{id}}}` parses.
+fn fill_json_placeholders(template: &str) -> String {
+    let mut out = String::with_capacity(template.len());
+    let (mut in_string, mut escaped) = (false, false);
+    let mut rest = template;
+    while let Some(c) = rest.chars().next() {
+        if !in_string
+            && rest.starts_with("{{")
+            && let Some(end) = rest.find("}}")
+        {
+            out.push_str(&json!(PLACEHOLDER).to_string());
+            rest = &rest[end + 2..];
+            continue;
+        }
+        if escaped {
+            escaped = false;
+        } else if in_string && c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            in_string = !in_string;
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+/// Add a media type to a `content` map, merging its schema with any
+/// already there. The first example is kept.
+fn add_content(content: &mut Map<String, Value>, media_type: &str, schema: Value, example: Option<Value>) {
+    match content.get_mut(media_type) {
+        Some(existing) => {
+            existing["schema"] = merge_schemas(existing["schema"].take(), schema);
+            if existing.get("example").is_none()
+                && let Some(example) = example
+            {
+                existing["example"] = example;
             }
         }
-        Some("number") | Some("integer") => {
-            Some(serde_json::Value::Number(serde_json::Number::from(0)))
+        None => {
+            let mut media = json!({ "schema": schema });
+            if let Some(example) = example {
+                media["example"] = example;
+            }
+            content.insert(media_type.to_string(), media);
         }
-        Some("boolean") => {
-            Some(serde_json::Value::Bool(true))
+    }
+}
+
+/// Responses by status code from the history of an operation's requests,
+/// which is newest first.
+fn export_responses(
+    requests: &[&RequestDefinition],
+    history: &[&(RequestHistoryEntry, Option<Vec<u8>>)],
+) -> Map<String, Value> {
+    let mut responses = Map::new();
+    for (entry, body) in history.iter().map(|pair| (&pair.0, pair.1.as_deref())) {
+        let response = &entry.response;
+        if response.status == 0 || !requests.iter().any(|r| r.id == entry.request.id) {
+            continue;
         }
-        Some("array") => {
-            Some(serde_json::Value::Array(vec![]))
+        let description = if response.status_text.is_empty() { "Response" } else { response.status_text.as_str() };
+        let slot = responses.entry(response.status.to_string()).or_insert_with(|| json!({ "description": description }));
+        let Some(body) = body.filter(|b| !b.is_empty()) else { continue };
+
+        let media = response
+            .headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+            .map(|(_, v)| media_type(v))
+            .unwrap_or_else(|| "application/octet-stream".to_string());
+        let truncated = response.body.as_ref().is_some_and(|b| b.truncated);
+        let (schema, example) = if media == "application/json" || media.ends_with("+json") {
+            match serde_json::from_slice::<Value>(body) {
+                Ok(value) => (infer_schema(&value), Some(value)),
+                Err(_) => (json!({}), None),
+            }
+        } else if let Ok(text) = std::str::from_utf8(body) {
+            (json!({ "type": "string" }), (!truncated).then(|| json!(text)))
+        } else {
+            (json!({ "type": "string", "format": "binary" }), None)
+        };
+
+        if slot.get("content").is_none() {
+            slot["content"] = json!({});
         }
-        Some("object") => {
-            Some(serde_json::Value::Object(serde_json::Map::new()))
+        if let Some(content) = slot["content"].as_object_mut() {
+            add_content(content, &media, schema, example);
         }
-        Some("null") => {
-            Some(serde_json::Value::Null)
+    }
+    responses
+}
+
+/// A JSON Schema describing `value`.
+fn infer_schema(value: &Value) -> Value {
+    match value {
+        Value::Null => json!({ "type": "null" }),
+        Value::Bool(_) => json!({ "type": "boolean" }),
+        Value::Number(n) if n.is_f64() => json!({ "type": "number" }),
+        Value::Number(_) => json!({ "type": "integer" }),
+        Value::String(s) if s == PLACEHOLDER => json!({}),
+        Value::String(s) => match string_format(s) {
+            Some(format) => json!({ "type": "string", "format": format }),
+            None => json!({ "type": "string" }),
+        },
+        Value::Array(items) => {
+            let items = items.iter().map(infer_schema).reduce(merge_schemas).unwrap_or_else(|| json!({}));
+            json!({ "type": "array", "items": items })
+        }
+        Value::Object(fields) => {
+            let properties: Map<String, Value> = fields.iter().map(|(k, v)| (k.clone(), infer_schema(v))).collect();
+            let mut schema = json!({ "type": "object", "properties": properties });
+            if !fields.is_empty() {
+                schema["required"] = fields.keys().cloned().collect();
+            }
+            schema
         }
-        _ => None,
     }
 }
 
-/// Resolve a $ref in OpenAPI spec
-fn resolve_ref<'a>(ref_path: &str, openapi: &'a Value) -> Option<&'a Value> {
-    // Handle #/components/schemas/... or #/definitions/...
-    if !ref_path.starts_with("#/") {
-        return None;
+/// A JSON Schema `format` for strings that are clearly dates, UUIDs, URIs
+/// or email addresses.
+fn string_format(s: &str) -> Option<&'static str> {
+    if chrono::DateTime::parse_from_rfc3339(s).is_ok() {
+        Some("date-time")
+    } else if s.len() == 10 && chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok() {
+        Some("date")
+    } else if s.len() == 36 && Uuid::parse_str(s).is_ok() {
+        Some("uuid")
+    } else if (s.starts_with("http://") || s.starts_with("https://")) && url::Url::parse(s).is_ok() {
+        Some("uri")
+    } else if let Some((user, domain)) = s.split_once('@')
+        && !user.is_empty()
+        && domain.contains('.')
+        && !domain.contains('@')
+        && !s.contains(char::is_whitespace)
+    {
+        Some("email")
+    } else {
+        None
+    }
+}
+
+/// Combine the schemas inferred from two samples of the same value. Object
+/// properties are required only if both samples have them; differing types
+/// become `anyOf`, except integers and numbers, which widen to numbers.
+fn merge_schemas(a: Value, b: Value) -> Value {
+    let is_any = |schema: &Value| schema.as_object().is_some_and(|o| o.is_empty());
+    if a == b || is_any(&b) {
+        return a;
+    }
+    if is_any(&a) {
+        return b;
+    }
+
+    if a.get("anyOf").is_some() || b.get("anyOf").is_some() {
+        let mut variants = any_of_variants(a);
+        for variant in any_of_variants(b) {
+            match variants.iter().position(|v| schema_types(v) == schema_types(&variant)) {
+                Some(i) => {
+                    let existing = variants[i].take();
+                    variants[i] = merge_schemas(existing, variant);
+                }
+                None => variants.push(variant),
+            }
+        }
+        return if variants.len() == 1 { variants.remove(0) } else { json!({ "anyOf": variants }) };
+    }
+
+    let (a_types, b_types) = (schema_types(&a), schema_types(&b));
+    let nullable = a_types.iter().chain(&b_types).any(|t| t == "null");
+    let kind = |types: &[String]| types.iter().find(|t| *t != "null").cloned();
+    let merged = match (kind(&a_types), kind(&b_types)) {
+        (None, None) => return json!({ "type": "null" }),
+        (None, Some(_)) => b,
+        (Some(_), None) => a,
+        (Some(x), Some(y)) if x == y => merge_same_kind(&x, a, b),
+        (Some(x), Some(y)) if [x.as_str(), y.as_str()].iter().all(|t| *t == "integer" || *t == "number") => {
+            json!({ "type": "number" })
+        }
+        (Some(x), Some(y)) => {
+            let mut variants = vec![with_type(a, json!(x)), with_type(b, json!(y))];
+            if nullable {
+                variants.push(json!({ "type": "null" }));
+            }
+            return json!({ "anyOf": variants });
+        }
+    };
+    match kind(&schema_types(&merged)) {
+        Some(kind) if nullable => with_type(merged, json!([kind, "null"])),
+        _ => merged,
+    }
+}
+
+/// Merge two schemas whose (non-null) type is `kind`.
+fn merge_same_kind(kind: &str, a: Value, b: Value) -> Value {
+    match kind {
+        "object" => {
+            let mut properties = a.get("properties").and_then(|p| p.as_object()).cloned().unwrap_or_default();
+            for (name, schema) in b.get("properties").and_then(|p| p.as_object()).into_iter().flatten() {
+                let merged = match properties.remove(name) {
+                    Some(existing) => merge_schemas(existing, schema.clone()),
+                    None => schema.clone(),
+                };
+                properties.insert(name.clone(), merged);
+            }
+            let required_in = |schema: &Value| -> Vec<Value> {
+                schema.get("required").and_then(|r| r.as_array()).cloned().unwrap_or_default()
+            };
+            let b_required = required_in(&b);
+            let required: Vec<Value> = required_in(&a).into_iter().filter(|name| b_required.contains(name)).collect();
+            let mut schema = json!({ "type": "object", "properties": properties });
+            if !required.is_empty() {
+                schema["required"] = Value::Array(required);
+            }
+            schema
+        }
+        "array" => {
+            let items = |schema: &Value| schema.get("items").cloned().unwrap_or_else(|| json!({}));
+            json!({ "type": "array", "items": merge_schemas(items(&a), items(&b)) })
+        }
+        _ if a.get("format") == b.get("format") => with_type(a, json!(kind)),
+        _ => json!({ "type": kind }),
+    }
+}
+
+/// The `type` of a schema as a list; empty if it has none.
+fn schema_types(schema: &Value) -> Vec<String> {
+    match schema.get("type") {
+        Some(Value::String(t)) => vec![t.clone()],
+        Some(Value::Array(types)) => types.iter().filter_map(|t| t.as_str().map(String::from)).collect(),
+        _ => Vec::new(),
     }
+}
 
-    let parts: Vec<&str> = ref_path[2..].split('/').collect();
+fn with_type(mut schema: Value, schema_type: Value) -> Value {
+    schema["type"] = schema_type;
+    schema
+}
 
-    let mut current = openapi;
-    for part in parts {
-        current = current.get(part)?;
+fn any_of_variants(schema: Value) -> Vec<Value> {
+    match schema.get("anyOf").and_then(|v| v.as_array()) {
+        Some(variants) => variants.clone(),
+        None => vec![schema],
     }
+}
+
+/// The security requirement for `auth`, adding its scheme to `schemes`.
+/// `None` for auth with no OpenAPI equivalent, or none at all.
+fn security_requirement(auth: &AuthConfig, schemes: &mut Map<String, Value>, variables: &HashMap<String, String>) -> Option<Value> {
+    let (base_name, scheme, scopes) = match auth {
+        AuthConfig::Inherit | AuthConfig::None | AuthConfig::AwsSigV4 { .. } => return None,
+        AuthConfig::Basic { .. } => ("basicAuth", json!({ "type": "http", "scheme": "basic" }), Vec::new()),
+        AuthConfig::Digest { .. } => ("digestAuth", json!({ "type": "http", "scheme": "digest" }), Vec::new()),
+        AuthConfig::Bearer { .. } => ("bearerAuth", json!({ "type": "http", "scheme": "bearer" }), Vec::new()),
+        AuthConfig::ApiKey { key, location, .. } => {
+            let location = match location {
+                ApiKeyLocation::Header => "header",
+                ApiKeyLocation::Query => "query",
+            };
+            ("apiKeyAuth", json!({ "type": "apiKey", "name": key, "in": location }), Vec::new())
+        }
+        AuthConfig::OAuth2(config) => {
+            let scopes: Vec<String> = config.scope.iter().flat_map(|s| s.split_whitespace()).map(String::from).collect();
+            let scope_map: Map<String, Value> = scopes.iter().map(|s| (s.clone(), json!(""))).collect();
+            let token_url = fill_variables(&config.token_url, variables);
+            let flows = match &config.grant {
+                OAuth2Grant::ClientCredentials => {
+                    json!({ "clientCredentials": { "tokenUrl": token_url, "scopes": scope_map } })
+                }
+                OAuth2Grant::Password { .. } => json!({ "password": { "tokenUrl": token_url, "scopes": scope_map } }),
+                OAuth2Grant::AuthorizationCode { authorization_url, .. } => json!({
+                    "authorizationCode": {
+                        "authorizationUrl": fill_variables(authorization_url, variables),
+                        "tokenUrl": token_url,
+                        "scopes": scope_map,
+                    }
+                }),
+            };
+            ("oauth2", json!({ "type": "oauth2", "flows": flows }), scopes)
+        }
+    };
 
-    Some(current)
+    let mut requirement = Map::new();
+    requirement.insert(register_scheme(schemes, base_name, scheme), json!(scopes));
+    Some(Value::Object(requirement))
+}
+
+/// Add a security scheme under a name based on `base_name`, reusing an
+/// equal one. OAuth 2.0 schemes that differ only in scopes are combined.
+fn register_scheme(schemes: &mut Map<String, Value>, base_name: &str, scheme: Value) -> String {
+    let flows = |scheme: &Value| scheme.get("flows").and_then(|f| f.as_object()).cloned();
+    let unscoped = |scheme: &Value| {
+        let mut scheme = scheme.clone();
+        if let Some(flows) = scheme.get_mut("flows").and_then(|f| f.as_object_mut()) {
+            for flow in flows.values_mut() {
+                flow["scopes"] = json!({});
+            }
+        }
+        scheme
+    };
+
+    let wanted = unscoped(&scheme);
+    if let Some((name, existing)) = schemes.iter_mut().find(|(_, s)| unscoped(s) == wanted) {
+        for (flow_name, flow) in flows(&scheme).into_iter().flatten() {
+            if let Some(scopes) = existing["flows"][&flow_name]["scopes"].as_object_mut()
+                && let Some(added) = flow["scopes"].as_object()
+            {
+                scopes.extend(added.clone());
+            }
+        }
+        return name.clone();
+    }
+
+    let mut name = base_name.to_string();
+    let mut n = 2;
+    while schemes.contains_key(&name) {
+        name = format!("{}{}", base_name, n);
+        n += 1;
+    }
+    schemes.insert(name.clone(), scheme);
+    name
 }
 
 #[cfg(test)]
@@ -466,9 +1498,8 @@ mod tests {
         assert!(post_request.is_some());
 
         if let Some(post) = post_request {
-            // The body should be Raw with JSON content, but might be None due to parsing limitations
-            // Let's just verify the request exists and has the right method
             assert_eq!(post.method, HttpMethod::POST);
+            assert!(matches!(&post.body, BodyType::Raw { content, .. } if content.contains("\"name\": \"string\"")));
         }
     }
 
@@ -520,5 +1551,390 @@ mod tests {
 
         assert!(!request.headers.is_empty());
         assert!(request.headers.iter().any(|h| h.key == "Authorization"));
+        assert_eq!(request.url, "{{base_url}}/users/{{id}}");
+        assert_eq!(request.variables[0].key, "id");
+    }
+
+    #[test]
+    fn test_parse_openapi_multipart_and_binary_bodies() {
+        let openapi = serde_json::json!({
+            "components": {
+                "schemas": {
+                    "Upload": {
+                        "type": "object",
+                        "properties": {
+                            "file": { "type": "string", "format": "binary" },
+                            "title": { "type": "string", "example": "Quarterly" }
+                        }
+                    }
+                }
+            }
+        });
+
+        let multipart = serde_json::json!({
+            "content": {
+                "multipart/form-data": { "schema": { "$ref": "#/components/schemas/Upload" } }
+            }
+        });
+        let BodyType::Multipart(parts) = parse_openapi_body(&multipart, &openapi).unwrap() else {
+            panic!("expected multipart body");
+        };
+        assert_eq!(parts.len(), 2);
+        assert!(parts.contains(&MultipartPart::file("file", "")));
+        assert!(parts.contains(&MultipartPart::text("title", "Quarterly")));
+
+        let binary = serde_json::json!({ "content": { "application/octet-stream": {} } });
+        assert!(matches!(parse_openapi_body(&binary, &openapi).unwrap(), BodyType::Binary { .. }));
+    }
+
+    const PETSTORE_YAML: &str = r#"
+openapi: 3.0.3
+info:
+  title: Petstore
+  version: 1.0.0
+servers:
+  - url: https://{region}.petstore.example.com/v1
+    description: Production
+    variables:
+      region:
+        default: eu
+  - url: http://localhost:8080/v1
+    description: Local
+tags:
+  - name: pets
+  - name: store
+security:
+  - bearerAuth: []
+paths:
+  /pets:
+    post:
+      tags: [pets]
+      operationId: createPet
+      requestBody:
+        $ref: '#/components/requestBodies/NewPet'
+  /pets/{petId}:
+    parameters:
+      - $ref: '#/components/parameters/PetId'
+    get:
+      tags: [pets]
+      summary: Get a pet
+      parameters:
+        - name: fields
+          in: query
+          schema: { type: string, enum: [name, tag] }
+  /health:
+    get:
+      summary: Health check
+      security: []
+  /orders:
+    get:
+      tags: [store]
+      summary: List orders
+      security:
+        - apiKey: []
+components:
+  parameters:
+    PetId:
+      name: petId
+      in: path
+      required: true
+      schema: { type: integer, example: 42 }
+  requestBodies:
+    NewPet:
+      content:
+        application/json:
+          schema:
+            $ref: '#/components/schemas/Pet'
+  schemas:
+    Pet:
+      allOf:
+        - $ref: '#/components/schemas/Named'
+        - type: object
+          required: [tag]
+          properties:
+            tag: { type: string, default: dog }
+            owner:
+              $ref: '#/components/schemas/Pet'
+    Named:
+      type: object
+      properties:
+        name: { type: string, example: Rex }
+  securitySchemes:
+    bearerAuth:
+      type: http
+      scheme: bearer
+    apiKey:
+      type: apiKey
+      in: header
+      name: X-API-Key
+"#;
+
+    #[test]
+    fn test_parse_openapi_yaml_with_refs_servers_auth_and_tags() {
+        let import = parse_openapi_document(PETSTORE_YAML).unwrap();
+        let collection = &import.collection;
+        assert_eq!(collection.name, "Petstore");
+        assert_eq!(collection.auth, AuthConfig::Bearer { token: "{{token}}".into() });
+        assert_eq!(collection.variables[0].value, "https://{{region}}.petstore.example.com/v1");
+
+        // Tagged operations in folders, in the spec's tag order
+        let folders: Vec<&Folder> = collection
+            .tree
+            .iter()
+            .filter_map(|item| match item {
+                CollectionItem::Folder(folder) => Some(folder.as_ref()),
+                CollectionItem::Request(_) => None,
+            })
+            .collect();
+        assert_eq!(folders.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(), vec!["pets", "store"]);
+        assert_eq!(folders[0].children.len(), 2);
+        assert!(matches!(collection.tree.last(), Some(CollectionItem::Request(_))));
+
+        let find = |name: &str| collection.requests.values().find(|r| r.name == name).unwrap();
+        let create = find("createPet");
+        let BodyType::Raw { content, content_type: RawContentType::Json } = &create.body else {
+            panic!("expected a JSON body");
+        };
+        let body: Value = serde_json::from_str(content).unwrap();
+        assert_eq!(body["name"], "Rex");
+        assert_eq!(body["tag"], "dog");
+        assert!(body["owner"].is_object(), "recursive schemas stop instead of looping");
+        assert_eq!(create.auth, AuthConfig::Inherit);
+
+        let get = find("Get a pet");
+        assert_eq!(get.url, "{{base_url}}/pets/{{petId}}");
+        assert_eq!((get.variables[0].key.as_str(), get.variables[0].value.as_str()), ("petId", "42"));
+        assert_eq!((get.query_params[0].key.as_str(), get.query_params[0].value.as_str()), ("fields", "name"));
+
+        assert_eq!(find("Health check").auth, AuthConfig::None);
+        assert!(matches!(
+            &find("List orders").auth,
+            AuthConfig::ApiKey { key, location: ApiKeyLocation::Header, .. } if key == "X-API-Key"
+        ));
+
+        // One environment per server, with the credentials to fill in
+        let names: Vec<&str> = import.environments.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["Production", "Local"]);
+        let production = import.environments[0].to_map();
+        assert_eq!(production["region"], "eu");
+        assert_eq!(import.environments[1].to_map()["base_url"], "http://localhost:8080/v1");
+        for key in ["token", "api_key"] {
+            assert!(import.environments[0].variables.iter().any(|v| v.key == key && v.secret));
+        }
+    }
+
+    #[test]
+    fn test_parse_openapi_yaml_with_anchors() {
+        let spec = r#"
+openapi: 3.1.0
+info: { title: Shared, version: "1" }
+x-paging: &paging
+  - name: page
+    in: query
+    schema: { type: integer, example: 2 }
+paths:
+  /users:
+    get:
+      summary: List users
+      parameters: *paging
+      responses:
+        200:
+          description: OK
+  /teams:
+    get:
+      summary: List teams
+      parameters: *paging
+"#;
+        let import = parse_openapi_document(spec).unwrap();
+        for name in ["List users", "List teams"] {
+            let request = import.collection.requests.values().find(|r| r.name == name).unwrap();
+            assert_eq!((request.query_params[0].key.as_str(), request.query_params[0].value.as_str()), ("page", "2"));
+        }
+
+        let err = parse_openapi_document("openapi: 3.1.0\npaths:\n\t/users: {}\n").unwrap_err();
+        assert_eq!(err.kind(), &ImportErrorKind::Deserialization);
+    }
+
+    #[test]
+    fn test_parse_swagger_2_spec() {
+        let spec = serde_json::json!({
+            "swagger": "2.0",
+            "info": { "title": "Legacy", "version": "1" },
+            "host": "legacy.example.com",
+            "basePath": "/api",
+            "schemes": ["http"],
+            "securityDefinitions": {
+                "login": { "type": "oauth2", "flow": "application", "tokenUrl": "https://legacy.example.com/token" }
+            },
+            "paths": {
+                "/items": {
+                    "post": {
+                        "security": [{ "login": ["write"] }],
+                        "parameters": [{ "name": "item", "in": "body", "schema": { "$ref": "#/definitions/Item" } }]
+                    }
+                },
+                "/login": {
+                    "post": {
+                        "parameters": [
+                            { "name": "user", "in": "formData", "type": "string", "default": "ann" },
+                            { "name": "remember", "in": "formData", "type": "boolean" }
+                        ]
+                    }
+                }
+            },
+            "definitions": { "Item": { "properties": { "count": { "type": "integer" } } } }
+        });
+
+        let import = parse_openapi(&spec).unwrap();
+        assert_eq!(import.environments[0].to_map()["base_url"], "http://legacy.example.com/api");
+        let items = import.collection.requests.values().find(|r| r.url.ends_with("/items")).unwrap();
+        assert!(matches!(&items.body, BodyType::Raw { content, .. } if content.contains("\"count\": 0")));
+        let AuthConfig::OAuth2(config) = &items.auth else { panic!("expected OAuth 2.0") };
+        assert_eq!(config.grant, OAuth2Grant::ClientCredentials);
+        assert_eq!(config.scope.as_deref(), Some("write"));
+
+        let login = import.collection.requests.values().find(|r| r.url.ends_with("/login")).unwrap();
+        let BodyType::FormUrlEncoded(fields) = &login.body else { panic!("expected a form body") };
+        assert_eq!((fields[0].key.as_str(), fields[0].value.as_str()), ("user", "ann"));
+    }
+
+    fn pair(key: &str, value: &str) -> KeyValuePair {
+        KeyValuePair { key: key.into(), value: value.into(), enabled: true, description: None }
+    }
+
+    fn json_response(req: &RequestDefinition, status: u16, body: Value, age_secs: i64) -> (RequestHistoryEntry, Option<Vec<u8>>) {
+        let response = crate::models::ResponseSnapshot {
+            status,
+            status_text: if status == 200 { "OK".into() } else { "Not Found".into() },
+            size_bytes: 0,
+            elapsed_millis: 5,
+            success: status < 400,
+            timing: None,
+            headers: [("Content-Type".to_string(), "application/json; charset=utf-8".to_string())].into(),
+            body: None,
+        };
+        let mut entry = RequestHistoryEntry::new(req.clone(), response, None, None);
+        entry.timestamp -= chrono::Duration::seconds(age_secs);
+        (entry, Some(body.to_string().into_bytes()))
+    }
+
+    #[test]
+    fn test_collection_to_openapi() {
+        let mut collection = Collection::new("Users API");
+        collection.auth = AuthConfig::Bearer { token: "{{token}}".into() };
+        let folder_id = Uuid::new_v4();
+        collection.tree.push(CollectionItem::Folder(Box::new(Folder {
+            id: folder_id,
+            name: "Users".into(),
+            children: Vec::new(),
+            auth: AuthConfig::Inherit,
+            variables: Vec::new(),
+        })));
+
+        let mut get = RequestDefinition::new("Get user", HttpMethod::GET, "{{base_url}}/users/{{id}}?verbose=true");
+        get.variables.push(variable("id", "42", false));
+        get.headers.push(pair("X-Trace", "abc"));
+        get.headers.push(pair("Accept", "application/json"));
+        let mut create = RequestDefinition::new("Create user", HttpMethod::POST, "{{base_url}}/users");
+        create.body = BodyType::Raw { content: r#"{"name": "{{name}}", "age": {{age}}}"#.into(), content_type: RawContentType::Json };
+        let mut health = RequestDefinition::new("Health", HttpMethod::GET, "{{base_url}}/health");
+        health.auth = AuthConfig::None;
+        let history = vec![
+            json_response(&get, 200, json!({ "id": 42, "email": "ann@example.com", "tags": [] }), 10),
+            json_response(&get, 200, json!({ "id": 7, "email": null, "score": 1.5 }), 0),
+            json_response(&get, 404, json!({ "error": "not found" }), 5),
+        ];
+        collection.add_request(get, Some(folder_id));
+        collection.add_request(create, Some(folder_id));
+        collection.add_request(health, None);
+
+        let variables = HashMap::from([("base_url".to_string(), "https://api.example.com/".to_string())]);
+        let spec = collection_to_openapi(&collection, &history, &variables);
+        assert_eq!(spec["openapi"], "3.1.0");
+        assert_eq!(spec["info"]["title"], "Users API");
+        assert_eq!(spec["servers"], json!([{ "url": "https://api.example.com" }]));
+        assert_eq!(spec["tags"], json!([{ "name": "Users" }]));
+        assert_eq!(spec["components"]["securitySchemes"]["bearerAuth"], json!({ "type": "http", "scheme": "bearer" }));
+        assert_eq!(spec["security"], json!([{ "bearerAuth": [] }]));
+
+        let get = &spec["paths"]["/users/{id}"]["get"];
+        assert_eq!(get["operationId"], "getUser");
+        assert_eq!(get["tags"], json!(["Users"]));
+        assert!(get.get("security").is_none());
+        assert_eq!(get["parameters"], json!([
+            { "name": "id", "in": "path", "required": true, "schema": { "type": "integer" }, "example": 42 },
+            { "name": "verbose", "in": "query", "schema": { "type": "boolean" }, "example": true },
+            { "name": "X-Trace", "in": "header", "schema": { "type": "string" }, "example": "abc" },
+        ]));
+        let ok = &get["responses"]["200"];
+        assert_eq!(ok["description"], "OK");
+        let media = &ok["content"]["application/json"];
+        assert_eq!(media["example"]["id"], 7);
+        assert_eq!(media["schema"], json!({
+            "type": "object",
+            "properties": {
+                "email": { "type": ["string", "null"], "format": "email" },
+                "id": { "type": "integer" },
+                "score": { "type": "number" },
+                "tags": { "type": "array", "items": {} },
+            },
+            "required": ["email", "id"],
+        }));
+        assert_eq!(get["responses"]["404"]["content"]["application/json"]["schema"]["required"], json!(["error"]));
+
+        let create = &spec["paths"]["/users"]["post"];
+        let body = &create["requestBody"]["content"]["application/json"];
+        assert!(body.get("example").is_none());
+        assert_eq!(body["schema"]["properties"], json!({ "age": {}, "name": { "type": "string" } }));
+        assert!(create.get("responses").is_none());
+
+        assert_eq!(spec["paths"]["/health"]["get"]["security"], json!([]));
+
+        // The importer reads the document back into equivalent requests
+        let import = parse_openapi(&spec).unwrap();
+        let urls: HashSet<&str> = import.collection.requests.values().map(|r| r.url.as_str()).collect();
+        assert_eq!(urls, HashSet::from(["{{base_url}}/users/{{id}}", "{{base_url}}/users", "{{base_url}}/health"]));
+        assert_eq!(import.environments[0].variables[0].value, "https://api.example.com");
+    }
+
+    #[test]
+    fn test_merge_inferred_schemas() {
+        let merged = [json!(1), json!(2.5)].iter().map(infer_schema).reduce(merge_schemas).unwrap();
+        assert_eq!(merged, json!({ "type": "number" }));
+
+        let merged = [json!("a"), json!({ "b": true }), json!(null), json!("c")].iter().map(infer_schema).reduce(merge_schemas).unwrap();
+        assert_eq!(merged, json!({ "anyOf": [
+            { "type": "string" },
+            { "type": "object", "properties": { "b": { "type": "boolean" } }, "required": ["b"] },
+            { "type": "null" },
+        ] }));
+
+        let merged = merge_schemas(infer_schema(&json!("2024-05-01T10:00:00Z")), infer_schema(&json!("soon")));
+        assert_eq!(merged, json!({ "type": "string" }));
+        assert_eq!(infer_schema(&json!("3f2b8c1e-9d4a-4e6b-8f0a-1c2d3e4f5a6b"))["format"], "uuid");
+    }
+
+    #[test]
+    fn test_openapi_export_servers_and_operation_ids() {
+        let mut collection = Collection::new("Mixed");
+        collection.add_request(RequestDefinition::new("List", HttpMethod::GET, "https://a.example.com:8443/items"), None);
+        collection.add_request(RequestDefinition::new("List", HttpMethod::GET, "{{host}}/v2/items/{{$uuid}}"), None);
+        let mut keyed = RequestDefinition::new("POST /things", HttpMethod::POST, "/things");
+        keyed.auth = AuthConfig::ApiKey { key: "X-Key".into(), value: "secret".into(), location: ApiKeyLocation::Header };
+        keyed.headers.push(pair("X-Key", "secret"));
+        collection.add_request(keyed, None);
+
+        let spec = collection_to_openapi(&collection, &[], &HashMap::new());
+        assert_eq!(spec["servers"], json!([
+            { "url": "https://a.example.com:8443" },
+            { "url": "/", "description": "{{host}} was not set when exporting" },
+        ]));
+        assert_eq!(spec["paths"]["/items"]["get"]["operationId"], "list");
+        assert_eq!(spec["paths"]["/v2/items/{uuid}"]["get"]["operationId"], "list2");
+        let things = &spec["paths"]["/things"]["post"];
+        assert_eq!(things["operationId"], "postThings");
+        assert!(things.get("parameters").is_none());
+        assert_eq!(things["security"], json!([{ "apiKeyAuth": [] }]));
+        assert_eq!(spec["components"]["securitySchemes"]["apiKeyAuth"], json!({ "type": "apiKey", "name": "X-Key", "in": "header" }));
     }
 }
@@ -159,6 +159,11 @@ fn parse_postman_request(item: &Value) -> ImportResult<RequestDefinition> {
         headers,
         query_params,
         body,
+        auth: Default::default(),
+        settings: Default::default(),
+        variables: Default::default(),
+        scripts: Default::default(),
+        assertions: Vec::new(),
         created_at: Utc::now(),
         updated_at: Utc::now(),
     })
@@ -200,7 +205,7 @@ fn parse_postman_url(url_value: Option<&Value>) -> ImportResult<String> {
                     if parts.is_empty() {
                         String::new()
                     } else {
-                        "/".to_string() + &parts.join("/")
+                        format!("/{}", parts.join("/"))
                     }
                 }
                 None => String::new(),
@@ -288,4 +293,52 @@ mod tests {
         assert_eq!(request.headers.len(), 1);
         assert!(matches!(request.body, BodyType::Raw { .. }));
     }
+
+    #[test]
+    fn test_parse_postman_formdata_and_file_bodies() {
+        use crate::models::{MultipartPart, MultipartValue};
+
+        let postman_json = serde_json::json!({
+            "info": { "name": "Uploads" },
+            "item": [
+                {
+                    "name": "Upload Avatar",
+                    "request": {
+                        "method": "POST",
+                        "url": "https://api.example.com/avatar",
+                        "body": {
+                            "mode": "formdata",
+                            "formdata": [
+                                { "key": "title", "value": "Me", "type": "text" },
+                                { "key": "avatar", "type": "file", "src": ["/tmp/a.png", "/tmp/b.png"], "contentType": "image/png" },
+                                { "key": "note", "value": "off", "type": "text", "disabled": true }
+                            ]
+                        }
+                    }
+                },
+                {
+                    "name": "Upload Blob",
+                    "request": {
+                        "method": "PUT",
+                        "url": "https://api.example.com/blob",
+                        "body": { "mode": "file", "file": { "src": "/tmp/blob.bin" } }
+                    }
+                }
+            ]
+        });
+
+        let collection = parse_postman_collection(&postman_json).unwrap();
+        let find = |name: &str| collection.requests.values().find(|r| r.name == name).unwrap();
+
+        let BodyType::Multipart(parts) = &find("Upload Avatar").body else {
+            panic!("expected multipart body");
+        };
+        assert_eq!(parts.len(), 4);
+        assert_eq!(parts[0], MultipartPart::text("title", "Me"));
+        assert_eq!(parts[2].value, MultipartValue::File { path: "/tmp/b.png".into(), filename: None });
+        assert_eq!(parts[1].content_type.as_deref(), Some("image/png"));
+        assert!(!parts[3].enabled);
+
+        assert!(matches!(&find("Upload Blob").body, BodyType::Binary { path } if path == "/tmp/blob.bin"));
+    }
 }
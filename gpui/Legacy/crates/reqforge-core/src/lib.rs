@@ -6,62 +6,305 @@ pub mod history;
 pub mod validation;
 pub mod templates;
 pub mod import_export;
+pub mod scripting;
+pub mod assertions;
+pub mod runner;
+pub mod oauth2;
+pub mod cookies;
+pub mod secrets;
+pub mod watcher;
+pub mod codegen;
 
 #[cfg(test)]
 mod integration_tests;
 
 // Re-export commonly used types for external consumers
 pub use models::{collection::Collection, environment::Environment, request::RequestDefinition, response::HttpResponse};
-pub use models::history::{RequestHistoryEntry, ResponseSnapshot};
+pub use models::history::{HistoryFilter, RequestHistoryEntry, ResponseSnapshot, StoredBody};
+pub use models::assertion::{Assertion, AssertionResult, TestReport};
+pub use models::auth::{AuthConfig, ApiKeyLocation, OAuth2Config, OAuth2Grant, OAuth2Token};
+pub use models::cookie::Cookie;
+pub use models::workspace::{CollectionLayout, WorkspaceConfig};
+pub use cookies::CookieJar;
+pub use models::settings::{EngineSettings, HttpVersionPref, ProxySettings, RedirectPolicy, RequestSettings, TlsSettings};
 pub use models::template::{RequestTemplate, TemplateCategory, TemplateVariable, BodyTemplateType};
 pub use http::client::{HttpEngine, HttpError};
-pub use env::interpolator::Interpolator;
+pub use env::interpolator::{Interpolator, InterpolationError, PlaceholderExplanation};
+pub use env::variables::{ScopedVariable, VariableScope, VariableSource};
+pub use store::Store;
 pub use store::json_store::{JsonStore, StoreError};
-pub use history::{RequestHistory, ReplayError};
+pub use store::file_store::FileStore;
+pub use store::sqlite_store::SqliteStore;
+pub use secrets::{SecretError, SecretKey};
+pub use history::{BodyStore, FileHistory, HistoryBackend, RequestHistory, ReplayError};
+pub use watcher::{ConflictResolution, WorkspaceEvent, WorkspaceItem, WorkspaceWatcher};
 pub use validation::{ValidationError, ValidationResult, validate_url, validate_headers, validate_body, validate_request};
 pub use templates::{TemplateManager, TemplateError};
-pub use import_export::{export_collection, import_collection, export_environment, import_environment, export_all, import_all, import_collection_from_postman, import_collection_from_openapi};
-pub use import_export::WorkspaceImport;
-use std::collections::HashMap;
+pub use import_export::{export_collection, import_collection, export_environment, import_environment, export_all, import_all, import_collection_from_postman, import_collection_from_openapi, import_openapi, import_collection_from_har, export_history_to_har, export_collection_to_openapi};
+pub use import_export::{parse_curl_command, to_curl_command, parse_har, history_to_har, collection_to_openapi};
+pub use codegen::{SnippetLanguage, generate_snippet};
+pub use import_export::{OpenApiImport, SecretExport, WorkspaceImport};
+pub use scripting::{ScriptEngine, ScriptError, ScriptOutcome, ScriptPhase};
+pub use assertions::evaluate_assertions;
+pub use runner::{CollectionRunner, RunReport, RequestRunResult, RunnerError, load_data_file};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::RwLock;
 
 /// The top-level headless API surface.
 /// The UI crate only talks to this.
 pub struct ReqForgeCore {
     pub engine: HttpEngine,
-    pub store: JsonStore,
+    pub store: Box<dyn Store>,
+    pub script_engine: ScriptEngine,
     history: RwLock<RequestHistory>,
+    /// Variables set by scripts during this session, layered over the active environment.
+    runtime_vars: RwLock<HashMap<String, String>>,
+    /// Authorization-code tokens, keyed by environment and OAuth 2.0 config.
+    oauth_tokens: RwLock<HashMap<String, OAuth2Token>>,
+    cookie_jar: RwLock<CookieJar>,
+    workspace_dir: PathBuf,
+    /// Fingerprints of collections, environments and globals as last loaded
+    /// or saved, to tell unsaved edits apart when reloading.
+    baselines: RwLock<HashMap<WorkspaceItem, u64>>,
+    /// Workspace-wide variables, overridden by every other scope.
+    pub globals: Vec<models::environment::Variable>,
     pub environments: Vec<Environment>,
     pub collections: Vec<Collection>,
     pub active_environment_id: Option<uuid::Uuid>,
 }
 
 impl ReqForgeCore {
+    /// Open a workspace directory, with a `SqliteStore` if it contains a
+    /// `reqforge.db` database and the default JSON store otherwise.
     pub fn open(workspace_dir: impl Into<std::path::PathBuf>) -> Result<Self, StoreError> {
         let workspace_dir = workspace_dir.into();
-        let store = JsonStore::open(&workspace_dir)?;
+        let database = workspace_dir.join(store::sqlite_store::DATABASE_FILE);
+        let store: Box<dyn Store> = if database.exists() {
+            Box::new(SqliteStore::open(database)?)
+        } else {
+            Box::new(JsonStore::open(&workspace_dir)?)
+        };
+        Self::open_with_store(workspace_dir, store)
+    }
+
+    /// Open a workspace backed by `store`, which also keeps the request
+    /// history.
+    pub fn open_with_store(workspace_dir: impl Into<std::path::PathBuf>, store: Box<dyn Store>) -> Result<Self, StoreError> {
+        let workspace_dir = workspace_dir.into();
         let environments = store.load_environments()?;
+        let globals = store.load_globals()?;
         let collections = store.list_collections()?;
+        let oauth_tokens = store.load_oauth_tokens()?;
+        let settings = store.load_settings()?;
+        let mut cookie_jar = store.load_cookies()?;
+        cookie_jar.remove_expired();
 
-        let mut history = RequestHistory::new(workspace_dir.clone());
+        let mut history = RequestHistory::with_backend(store.history());
         history.load()?;
 
-        Ok(Self {
-            engine: HttpEngine::new(),
+        let core = Self {
+            engine: HttpEngine::with_settings(settings),
             store,
+            script_engine: ScriptEngine::new(),
             history: RwLock::new(history),
+            runtime_vars: RwLock::new(HashMap::new()),
+            oauth_tokens: RwLock::new(oauth_tokens),
+            cookie_jar: RwLock::new(cookie_jar),
+            workspace_dir,
+            baselines: RwLock::new(HashMap::new()),
+            globals,
             environments,
             collections,
             active_environment_id: None,
-        })
+        };
+        core.mark_saved();
+        Ok(core)
+    }
+
+    pub fn workspace_dir(&self) -> &Path {
+        &self.workspace_dir
+    }
+
+    /// A watcher on the workspace directory. Call `reload` when its `poll`
+    /// reports changed files, and again later if that reload fails: `poll`
+    /// reports each change only once.
+    pub fn watcher(&self) -> WorkspaceWatcher {
+        WorkspaceWatcher::new(&self.workspace_dir)
+    }
+
+    /// Reload collections, environments and globals changed on disk.
+    ///
+    /// Items without unsaved edits are replaced with the version on disk.
+    /// Items with unsaved edits that also changed on disk are kept as they
+    /// are and reported as `WorkspaceEvent::Conflict`.
+    pub fn reload(&mut self) -> Result<Vec<WorkspaceEvent>, StoreError> {
+        let collections = self.store.list_collections()?;
+        let environments = self.store.load_environments()?;
+        let globals = self.store.load_globals()?;
+
+        let mut events = Vec::new();
+        // Baselines are only fingerprints, so a poisoned lock is still usable
+        let mut baselines = self.baselines.write().unwrap_or_else(|e| e.into_inner());
+        watcher::reconcile(&mut self.collections, collections, |c| WorkspaceItem::Collection(c.id), &mut baselines, &mut events);
+        watcher::reconcile(&mut self.environments, environments, |e| WorkspaceItem::Environment(e.id), &mut baselines, &mut events);
+        let mut current = vec![std::mem::take(&mut self.globals)];
+        watcher::reconcile(&mut current, vec![globals], |_| WorkspaceItem::Globals, &mut baselines, &mut events);
+        self.globals = current.pop().unwrap_or_default();
+        Ok(events)
+    }
+
+    /// Settle a conflict reported by `reload`, by saving the in-memory
+    /// version or by loading the one on disk.
+    pub fn resolve_conflict(&mut self, item: WorkspaceItem, resolution: ConflictResolution) -> Result<(), StoreError> {
+        match (item, resolution) {
+            (WorkspaceItem::Collection(id), ConflictResolution::KeepMine) => {
+                match self.collections.iter().find(|c| c.id == id) {
+                    Some(col) => self.store.save_collection(col)?,
+                    None => {
+                        if let Some(theirs) = self.store.list_collections()?.into_iter().find(|c| c.id == id) {
+                            self.store.delete_collection(&theirs)?;
+                        }
+                    }
+                }
+            }
+            (WorkspaceItem::Collection(id), ConflictResolution::TakeTheirs) => {
+                let theirs = self.store.list_collections()?.into_iter().find(|c| c.id == id);
+                replace_item(&mut self.collections, theirs, |c| c.id == id);
+            }
+            (WorkspaceItem::Environment(id), ConflictResolution::KeepMine) => {
+                let mut envs = self.store.load_environments()?;
+                let mine = self.environments.iter().find(|e| e.id == id).cloned();
+                replace_item(&mut envs, mine, |e| e.id == id);
+                self.store.save_environments(&envs)?;
+            }
+            (WorkspaceItem::Environment(id), ConflictResolution::TakeTheirs) => {
+                let theirs = self.store.load_environments()?.into_iter().find(|e| e.id == id);
+                replace_item(&mut self.environments, theirs, |e| e.id == id);
+            }
+            (WorkspaceItem::Globals, ConflictResolution::KeepMine) => self.store.save_globals(&self.globals)?,
+            (WorkspaceItem::Globals, ConflictResolution::TakeTheirs) => self.globals = self.store.load_globals()?,
+        }
+
+        let current = match item {
+            WorkspaceItem::Collection(id) => self.collections.iter().find(|c| c.id == id).map(watcher::fingerprint),
+            WorkspaceItem::Environment(id) => self.environments.iter().find(|e| e.id == id).map(watcher::fingerprint),
+            WorkspaceItem::Globals => Some(watcher::fingerprint(&self.globals)),
+        };
+        if let Ok(mut baselines) = self.baselines.write() {
+            match current {
+                Some(print) => baselines.insert(item, print),
+                None => baselines.remove(&item),
+            };
+        }
+        Ok(())
+    }
+
+    /// Record the in-memory collections, environments and globals as saved.
+    fn mark_saved(&self) {
+        let Ok(mut baselines) = self.baselines.write() else { return };
+        baselines.clear();
+        for col in &self.collections {
+            baselines.insert(WorkspaceItem::Collection(col.id), watcher::fingerprint(col));
+        }
+        for env in &self.environments {
+            baselines.insert(WorkspaceItem::Environment(env.id), watcher::fingerprint(env));
+        }
+        baselines.insert(WorkspaceItem::Globals, watcher::fingerprint(&self.globals));
     }
 
     /// Get the merged variable map for the active environment.
+    ///
+    /// Globals are overridden by the environment's values, and values set by
+    /// scripts during this session take precedence over both. Use
+    /// `variable_scope` to include a request's collection and folders.
     pub fn active_vars(&self) -> HashMap<String, String> {
-        self.active_environment_id
-            .and_then(|id| self.environments.iter().find(|e| e.id == id))
-            .map(|e| e.to_map())
-            .unwrap_or_default()
+        self.variable_scope(None).to_map()
+    }
+
+    /// The variable layers that apply to a request: globals, its collection,
+    /// its folders (outermost first), the active environment, the request's
+    /// own variables and runtime values, each overriding the previous ones.
+    ///
+    /// With no request, only globals, the environment and runtime values apply.
+    pub fn variable_scope(&self, req: Option<&RequestDefinition>) -> VariableScope {
+        let mut scope = VariableScope::new();
+        scope.push_variables(VariableSource::Global, &self.globals);
+        if let Some(req) = req
+            && let Some(collection) = self.collection_for_request(req.id)
+        {
+            scope.push_variables(VariableSource::Collection, &collection.variables);
+            for folder in collection.folders_containing(req.id) {
+                scope.push_variables(VariableSource::Folder(folder.name.clone()), &folder.variables);
+            }
+        }
+        if let Some(env) = self.active_environment_id.and_then(|id| self.environments.iter().find(|e| e.id == id)) {
+            scope.push_variables(VariableSource::Environment, &env.variables);
+        }
+        if let Some(req) = req {
+            scope.push_variables(VariableSource::Request, &req.variables);
+        }
+        if let Ok(runtime) = self.runtime_vars.read() {
+            scope.push_layer(VariableSource::Runtime, runtime.clone());
+        }
+        scope
+    }
+
+    /// Get the variables set by scripts during this session.
+    pub fn runtime_vars(&self) -> HashMap<String, String> {
+        self.runtime_vars.read().map(|v| v.clone()).unwrap_or_default()
+    }
+
+    /// Set a session variable that overrides the active environment.
+    pub fn set_runtime_var(&self, key: impl Into<String>, value: impl Into<String>) {
+        if let Ok(mut runtime) = self.runtime_vars.write() {
+            runtime.insert(key.into(), value.into());
+        }
+    }
+
+    /// Discard all variables set by scripts during this session.
+    pub fn clear_runtime_vars(&self) {
+        if let Ok(mut runtime) = self.runtime_vars.write() {
+            runtime.clear();
+        }
+    }
+
+    /// Write session variables into the active environment and clear them.
+    ///
+    /// Existing variables are updated in place; new ones are appended.
+    /// Returns the number of variables written, or 0 if no environment is active.
+    pub fn persist_runtime_vars(&mut self) -> usize {
+        let Some(env) = self.active_environment_id
+            .and_then(|id| self.environments.iter_mut().find(|e| e.id == id))
+        else {
+            return 0;
+        };
+        let runtime = match self.runtime_vars.get_mut() {
+            Ok(runtime) => std::mem::take(runtime),
+            Err(_) => return 0,
+        };
+
+        let count = runtime.len();
+        for (key, value) in runtime {
+            match env.variables.iter_mut().find(|v| v.key == key) {
+                Some(var) => {
+                    var.value = value;
+                    var.enabled = true;
+                }
+                None => env.variables.push(models::environment::Variable {
+                    key,
+                    value,
+                    secret: false,
+                    enabled: true,
+                }),
+            }
+        }
+        count
+    }
+
+    /// Find the collection that owns a request.
+    pub fn collection_for_request(&self, request_id: uuid::Uuid) -> Option<&Collection> {
+        self.collections.iter().find(|c| c.requests.contains_key(&request_id))
     }
 
     /// Get the active environment name if set
@@ -71,11 +314,171 @@ impl ReqForgeCore {
             .map(|e| e.name.clone())
     }
 
+    /// Explain every placeholder in a request using its variable scope.
+    ///
+    /// Variables that pre-request scripts would set are not included.
+    pub fn explain_request(&self, req: &RequestDefinition) -> Vec<PlaceholderExplanation> {
+        Interpolator::explain_request(req, &self.variable_scope(Some(req)))
+    }
+
+    /// Variables a request uses that have no value in its scope, so a UI can
+    /// prompt for them and pass the answers to `execute_with_overrides`.
+    ///
+    /// Variables that pre-request scripts would set are not known here;
+    /// sending still fails with `HttpError::UnresolvedVariables` if any
+    /// remain missing after the scripts run.
+    pub fn missing_variables(&self, req: &RequestDefinition) -> Vec<String> {
+        Interpolator::try_resolve(req, &self.variable_scope(Some(req)).to_map())
+            .map(|resolved| Interpolator::unresolved(&resolved))
+            .unwrap_or_default()
+    }
+
+    /// The request as it would be sent, for copying elsewhere (e.g. as a
+    /// cURL command): auth inherited from its folders and collection, and
+    /// placeholders resolved from its variable scope. Scripts are not run,
+    /// and placeholders without a value are left as-is.
+    pub fn resolve_request(&self, req: &RequestDefinition) -> RequestDefinition {
+        let mut prepared = req.clone();
+        prepared.auth = self.effective_auth(req);
+        Interpolator::resolve(&prepared, &self.variable_scope(Some(req)).to_map())
+    }
+
+    /// The request as a code snippet in `language`, resolved as by
+    /// `resolve_request`. With `redact_secrets`, secret variables and
+    /// credentials appear as `codegen::REDACTED` instead of their values.
+    pub fn code_snippet(&self, req: &RequestDefinition, language: SnippetLanguage, redact_secrets: bool) -> String {
+        generate_snippet(&self.resolve_for_sharing(req, redact_secrets), language, redact_secrets)
+    }
+
+    /// Write history entries, with their stored response bodies, to a HAR
+    /// file. Requests are written as they were sent; with `redact_secrets`,
+    /// credentials and secret-looking headers and query parameters are
+    /// replaced as by `codegen::redact`. Response bodies are written as they
+    /// were received.
+    pub fn export_history_har(
+        &self,
+        entries: &[RequestHistoryEntry],
+        redact_secrets: bool,
+        path: impl AsRef<Path>,
+    ) -> import_export::ExportResult<()> {
+        let entries: Vec<(RequestHistoryEntry, Option<Vec<u8>>)> = entries
+            .iter()
+            .map(|entry| {
+                let mut entry = entry.clone();
+                if redact_secrets {
+                    entry.request = codegen::redact(&entry.request);
+                }
+                // An entry whose body can't be read is exported without it
+                let body = self.history_response_body(entry.id).ok().flatten();
+                (entry, body)
+            })
+            .collect();
+        export_history_to_har(&entries, path)
+    }
+
+    /// Write an OpenAPI 3.1 document describing a collection, with response
+    /// schemas inferred from its requests' history (see
+    /// `collection_to_openapi`). Server URLs are filled in from the global,
+    /// collection and active environment variables; secret ones are not used.
+    pub fn export_openapi(&self, collection: &Collection, path: impl AsRef<Path>) -> import_export::ExportResult<()> {
+        let environment = self.active_environment_id.and_then(|id| self.environments.iter().find(|e| e.id == id));
+        let mut scope = VariableScope::new();
+        scope.push_variables(VariableSource::Global, &self.globals);
+        scope.push_variables(VariableSource::Collection, &collection.variables);
+        let mut lists = vec![self.globals.as_slice(), collection.variables.as_slice()];
+        if let Some(environment) = environment {
+            scope.push_variables(VariableSource::Environment, &environment.variables);
+            lists.push(&environment.variables);
+        }
+        let mut variables = scope.to_map();
+        for secret in lists.into_iter().flatten().filter(|v| v.secret) {
+            variables.remove(&secret.key);
+        }
+
+        let history: Vec<(RequestHistoryEntry, Option<Vec<u8>>)> = self
+            .get_all_history()
+            .into_iter()
+            .filter(|entry| collection.requests.contains_key(&entry.request.id))
+            .map(|entry| {
+                // An entry whose body can't be read still contributes its status
+                let body = self.history_response_body(entry.id).ok().flatten();
+                (entry, body)
+            })
+            .collect();
+        export_collection_to_openapi(collection, &history, &variables, path)
+    }
+
+    /// `resolve_request`, optionally with secret variables resolving to
+    /// `codegen::REDACTED`.
+    fn resolve_for_sharing(&self, req: &RequestDefinition, redact_secrets: bool) -> RequestDefinition {
+        let mut prepared = req.clone();
+        prepared.auth = self.effective_auth(req);
+        let mut vars = self.variable_scope(Some(req)).to_map();
+        if redact_secrets {
+            for key in self.secret_variable_names(req) {
+                vars.insert(key, codegen::REDACTED.to_string());
+            }
+        }
+        Interpolator::resolve(&prepared, &vars)
+    }
+
+    /// Names of the secret variables in a request's scope.
+    fn secret_variable_names(&self, req: &RequestDefinition) -> HashSet<String> {
+        let collection = self.collection_for_request(req.id);
+        let environment = self.active_environment_id.and_then(|id| self.environments.iter().find(|e| e.id == id));
+        let mut lists: Vec<&[models::environment::Variable]> = vec![&self.globals, &req.variables];
+        if let Some(collection) = collection {
+            lists.push(&collection.variables);
+            lists.extend(collection.folders_containing(req.id).into_iter().map(|f| f.variables.as_slice()));
+        }
+        if let Some(environment) = environment {
+            lists.push(&environment.variables);
+        }
+        lists
+            .into_iter()
+            .flatten()
+            .filter(|v| v.secret && v.enabled)
+            .map(|v| v.key.clone())
+            .collect()
+    }
+
     /// Execute a request with environment interpolation.
+    ///
+    /// Fails with `HttpError::UnresolvedVariables` before sending if any
+    /// placeholder has no value.
+    ///
+    /// Collection and request pre-request scripts run (in that order) before
+    /// interpolation; post-response scripts run in the same order once the
+    /// response arrives. Variables set by scripts are kept as runtime vars.
     pub async fn execute_request(&self, req: &RequestDefinition) -> Result<HttpResponse, HttpError> {
-        let vars = self.active_vars();
-        let resolved = Interpolator::resolve(req, &vars);
-        let response = self.engine.execute(&resolved).await;
+        self.execute_request_with_report(req).await.map(|(response, _)| response)
+    }
+
+    /// Execute a request and evaluate its assertions.
+    ///
+    /// The returned `TestReport` is empty when the request defines no
+    /// assertions. The report is also stored with the history entry.
+    pub async fn execute_request_with_report(
+        &self,
+        req: &RequestDefinition,
+    ) -> Result<(HttpResponse, TestReport), HttpError> {
+        self.execute_with_overrides(req, &HashMap::new()).await
+    }
+
+    /// Execute a request with extra variables layered over its variable scope.
+    ///
+    /// Used by the collection runner to feed data-file rows into a request.
+    pub async fn execute_with_overrides(
+        &self,
+        req: &RequestDefinition,
+        overrides: &HashMap<String, String>,
+    ) -> Result<(HttpResponse, TestReport), HttpError> {
+        let mut sent = None;
+        let response = self.execute_with_scripts(req, overrides, &mut sent).await;
+        let report = match &response {
+            Ok(resp) => evaluate_assertions(&req.assertions, resp),
+            Err(_) => TestReport::default(),
+        };
 
         // Add to history regardless of success/failure
         let snapshot = match &response {
@@ -86,30 +489,305 @@ impl ReqForgeCore {
                 size_bytes: 0,
                 elapsed_millis: 0,
                 success: false,
+                timing: None,
+                headers: HashMap::new(),
+                body: None,
             },
         };
 
-        let entry = RequestHistoryEntry::new(
-            req.clone(),
+        // Requests that failed before being resolved are kept as written
+        let mut entry = RequestHistoryEntry::new(
+            sent.unwrap_or_else(|| req.clone()),
             snapshot,
             self.active_environment_id,
             self.active_environment_name(),
         );
+        if !req.assertions.is_empty() {
+            entry = entry.with_test_report(report.clone());
+        }
 
-        // Use write lock to add to history
+        // Compress and write the body before taking the write lock, so the
+        // history is not locked during disk I/O. The entry is kept even if
+        // its body can't be written.
+        let body = match &response {
+            Ok(resp) => self
+                .history
+                .read()
+                .ok()
+                .map(|history| history.body_store())
+                .and_then(|bodies| bodies.write(entry.id, &resp.body).ok().flatten()),
+            Err(_) => None,
+        };
         if let Ok(mut history) = self.history.write() {
-            history.add_entry(entry);
+            history.add_entry_with_stored_body(entry, body);
         }
 
-        response
+        response.map(|resp| (resp, report))
+    }
+
+    /// Run the scripts around sending `req`. `sent` is set to the request as
+    /// it goes out, once scripts and interpolation have resolved it.
+    async fn execute_with_scripts(
+        &self,
+        req: &RequestDefinition,
+        overrides: &HashMap<String, String>,
+        sent: &mut Option<RequestDefinition>,
+    ) -> Result<HttpResponse, HttpError> {
+        let collection = self.collection_for_request(req.id);
+        let mut scripts = Vec::new();
+        if let Some(collection) = collection {
+            scripts.push(collection.scripts.clone());
+        }
+        scripts.push(req.scripts.clone());
+
+        let mut scope = self.variable_scope(Some(req));
+        scope.push_layer(VariableSource::Data, overrides.clone());
+        let mut vars = scope.to_map();
+        let mut prepared = req.clone();
+        prepared.auth = self.effective_auth(req);
+        for script in scripts.iter().filter_map(|s| s.pre_request()) {
+            let outcome = self.script_engine.run_pre_request(script, &mut prepared, &mut vars)?;
+            self.store_runtime_vars(outcome.updated_variables, &outcome.removed_variables);
+        }
+
+        let mut resolved = Interpolator::try_resolve(&prepared, &vars)?;
+        let unresolved = Interpolator::unresolved(&resolved);
+        if !unresolved.is_empty() {
+            return Err(HttpError::UnresolvedVariables(unresolved));
+        }
+        if let AuthConfig::OAuth2(config) = &resolved.auth
+            && matches!(config.grant, OAuth2Grant::AuthorizationCode { .. })
+        {
+            let token = self.stored_oauth2_token(config).await?;
+            resolved.auth = AuthConfig::Bearer { token: token.access_token };
+        }
+
+        // Cookies from the jar, unless the request sets its own Cookie header
+        let cookie_url = url::Url::parse(&resolved.url).ok();
+        let scope = self.cookie_scope();
+        if let Some(url) = &cookie_url
+            && !resolved.headers.iter().any(|h| h.enabled && h.key.eq_ignore_ascii_case("cookie"))
+            && let Some(cookie) = self.cookie_jar.read().ok().and_then(|jar| jar.cookie_header(&scope, url))
+        {
+            resolved.headers.push(models::request::KeyValuePair {
+                key: "Cookie".to_string(),
+                value: cookie,
+                enabled: true,
+                description: None,
+            });
+        }
+
+        let resolved = sent.insert(resolved);
+        let response = self.engine.execute(resolved).await?;
+        if let Some(url) = &cookie_url
+            && let Some(set_cookie) = response.headers.get("set-cookie")
+        {
+            let _ = self.update_cookies(|jar, scope| {
+                jar.store_set_cookie(scope, url, set_cookie);
+            });
+        }
+
+        for script in scripts.iter().filter_map(|s| s.post_response()) {
+            let outcome = self.script_engine.run_post_response(script, resolved, &response, &mut vars)?;
+            self.store_runtime_vars(outcome.updated_variables, &outcome.removed_variables);
+        }
+
+        Ok(response)
+    }
+
+    /// The request's auth with `Inherit` resolved through its folders and collection.
+    fn effective_auth(&self, req: &RequestDefinition) -> AuthConfig {
+        if !req.auth.is_inherit() {
+            return req.auth.clone();
+        }
+        self.collection_for_request(req.id)
+            .map(|c| c.effective_auth(req.id))
+            .unwrap_or(AuthConfig::None)
+    }
+
+    fn oauth_token_key(&self, config: &OAuth2Config) -> String {
+        let env = self.active_environment_id.map(|id| id.to_string()).unwrap_or_else(|| "none".to_string());
+        format!("{}|{}", env, http::auth::token_cache_key(config))
+    }
+
+    /// Run the interactive OAuth 2.0 authorization-code login for a request.
+    ///
+    /// `open_url` receives the login URL and should open it in a browser (or
+    /// show it to the user). The resulting token is stored for the active
+    /// environment and used by later requests until it expires, after which
+    /// it is refreshed automatically.
+    pub async fn authorize_oauth2<F>(&self, req: &RequestDefinition, open_url: F) -> Result<OAuth2Token, HttpError>
+    where
+        F: FnOnce(&str),
+    {
+        let mut prepared = req.clone();
+        prepared.auth = self.effective_auth(req);
+        let AuthConfig::OAuth2(config) = Interpolator::try_resolve(&prepared, &self.variable_scope(Some(req)).to_map())?.auth else {
+            return Err(HttpError::Auth(format!("Request '{}' does not use OAuth 2.0", req.name)));
+        };
+
+        let token = oauth2::authorize(&self.engine.client()?, &config, open_url, oauth2::DEFAULT_AUTHORIZE_TIMEOUT).await?;
+        self.store_oauth_token(&config, token.clone())?;
+        Ok(token)
+    }
+
+    /// The stored authorization-code token for `config`, refreshed if it has expired.
+    async fn stored_oauth2_token(&self, config: &OAuth2Config) -> Result<OAuth2Token, HttpError> {
+        let key = self.oauth_token_key(config);
+        let token = self.oauth_tokens.read().ok().and_then(|tokens| tokens.get(&key).cloned());
+        let Some(token) = token else {
+            return Err(HttpError::Auth(
+                "No OAuth 2.0 token for this environment; authorize the request first".to_string(),
+            ));
+        };
+        if !token.is_expired() {
+            return Ok(token);
+        }
+
+        let Some(refresh_token) = token.refresh_token else {
+            return Err(HttpError::Auth(
+                "The OAuth 2.0 token has expired and cannot be refreshed; authorize the request again".to_string(),
+            ));
+        };
+        let token = http::auth::refresh_oauth2_token(&self.engine.client()?, config, &refresh_token).await?;
+        self.store_oauth_token(config, token.clone())?;
+        Ok(token)
+    }
+
+    fn store_oauth_token(&self, config: &OAuth2Config, token: OAuth2Token) -> Result<(), HttpError> {
+        let key = self.oauth_token_key(config);
+        let Ok(mut tokens) = self.oauth_tokens.write() else {
+            return Ok(());
+        };
+        tokens.insert(key, token);
+        self.store
+            .save_oauth_tokens(&tokens)
+            .map_err(|e| HttpError::Auth(format!("Failed to save OAuth 2.0 token: {}", e)))
+    }
+
+    /// The cookie scope for the active environment.
+    fn cookie_scope(&self) -> String {
+        let per_environment = self.cookie_jar.read().map(|jar| jar.per_environment).unwrap_or(false);
+        match self.active_environment_id {
+            Some(id) if per_environment => id.to_string(),
+            _ => cookies::WORKSPACE_SCOPE.to_string(),
+        }
+    }
+
+    /// Apply a change to the cookie jar in the current scope and save it.
+    fn update_cookies<T>(&self, change: impl FnOnce(&mut CookieJar, &str) -> T) -> Result<T, StoreError> {
+        let scope = self.cookie_scope();
+        let Ok(mut jar) = self.cookie_jar.write() else {
+            return Err(StoreError::Io(std::io::Error::other("cookie jar lock poisoned")));
+        };
+        let result = change(&mut jar, &scope);
+        self.store.save_cookies(&jar)?;
+        Ok(result)
+    }
+
+    /// Cookies for the active environment (or the workspace), optionally only
+    /// those for `domain` and its subdomains.
+    pub fn cookies(&self, domain: Option<&str>) -> Vec<Cookie> {
+        self.cookie_jar
+            .read()
+            .map(|jar| jar.list(&self.cookie_scope(), domain))
+            .unwrap_or_default()
+    }
+
+    /// Add or edit a cookie. A cookie with the same name, domain and path is replaced.
+    pub fn set_cookie(&self, cookie: Cookie) -> Result<(), StoreError> {
+        self.update_cookies(|jar, scope| jar.set(scope, cookie))
+    }
+
+    pub fn remove_cookie(&self, domain: &str, path: &str, name: &str) -> Result<bool, StoreError> {
+        self.update_cookies(|jar, scope| jar.remove(scope, domain, path, name))
+    }
+
+    /// Remove all cookies, or only those for `domain` and its subdomains.
+    /// Returns the number removed.
+    pub fn clear_cookies(&self, domain: Option<&str>) -> Result<usize, StoreError> {
+        self.update_cookies(|jar, scope| jar.clear(scope, domain))
+    }
+
+    /// Whether each environment keeps its own cookies.
+    pub fn cookies_per_environment(&self) -> bool {
+        self.cookie_jar.read().map(|jar| jar.per_environment).unwrap_or(false)
+    }
+
+    /// Switch between one cookie jar for the workspace and one per environment.
+    pub fn set_cookies_per_environment(&self, enabled: bool) -> Result<(), StoreError> {
+        self.update_cookies(|jar, _| jar.per_environment = enabled)
+    }
+
+    /// Forget every stored authorization-code token.
+    pub fn clear_oauth2_tokens(&self) -> Result<(), StoreError> {
+        if let Ok(mut tokens) = self.oauth_tokens.write() {
+            tokens.clear();
+            self.store.save_oauth_tokens(&tokens)?;
+        }
+        Ok(())
+    }
+
+    fn store_runtime_vars(&self, updated: HashMap<String, String>, removed: &[String]) {
+        if updated.is_empty() && removed.is_empty() {
+            return;
+        }
+        if let Ok(mut runtime) = self.runtime_vars.write() {
+            for key in removed {
+                runtime.remove(key);
+            }
+            runtime.extend(updated);
+        }
+    }
+
+    /// Replace the workspace engine settings and save them.
+    pub fn set_engine_settings(&mut self, settings: EngineSettings) -> Result<(), StoreError> {
+        self.store.save_settings(&settings)?;
+        self.engine.set_settings(settings);
+        Ok(())
+    }
+
+    /// Unlock passphrase-protected secrets and fill in the secret values of
+    /// the loaded environments, globals and collections.
+    pub fn unlock_secrets(&mut self, passphrase: &str) -> Result<(), StoreError> {
+        self.store.unlock_secrets(passphrase)?;
+        let unlocked = self.store.load_environments()?;
+        for env in &mut self.environments {
+            let Some(stored) = unlocked.iter().find(|e| e.id == env.id) else { continue };
+            fill_secrets(&mut env.variables, &stored.variables);
+        }
+        fill_secrets(&mut self.globals, &self.store.load_globals()?);
+        let mut unlocked = self.store.list_collections()?;
+        for col in &mut self.collections {
+            let Some(stored) = unlocked.iter_mut().find(|c| c.id == col.id) else { continue };
+            fill_secrets(&mut col.variables, &stored.variables);
+            let stored: HashMap<uuid::Uuid, &mut Vec<models::environment::Variable>> = stored.item_variables_mut().into_iter().collect();
+            for (id, variables) in col.item_variables_mut() {
+                if let Some(stored) = stored.get(&id) {
+                    fill_secrets(variables, stored);
+                }
+            }
+        }
+        let mut settings = self.engine.settings().clone();
+        if let Some(proxy) = settings.proxy.as_mut()
+            && proxy.password.is_none()
+            && let Some(password) = self.store.load_settings()?.proxy.and_then(|proxy| proxy.password)
+        {
+            proxy.password = Some(password);
+            self.engine.set_settings(settings);
+        }
+        Ok(())
     }
 
     /// Persist all state to disk.
     pub fn save_all(&self) -> Result<(), StoreError> {
+        self.store.save_settings(self.engine.settings())?;
         self.store.save_environments(&self.environments)?;
+        self.store.save_globals(&self.globals)?;
         for col in &self.collections {
             self.store.save_collection(col)?;
         }
+        self.mark_saved();
         // Use read lock to save history (save method takes &self)
         if let Ok(history) = self.history.read() {
             let _ = history.save();
@@ -135,6 +813,24 @@ impl ReqForgeCore {
         }
     }
 
+    /// History entries matching `filter`, most recent first
+    pub fn search_history(&self, filter: &HistoryFilter) -> Vec<RequestHistoryEntry> {
+        if let Ok(history) = self.history.read() {
+            history.search(filter)
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// The response body stored with a history entry, if it was kept
+    pub fn history_response_body(&self, entry_id: uuid::Uuid) -> Result<Option<Vec<u8>>, StoreError> {
+        if let Ok(history) = self.history.read() {
+            history.response_body(entry_id)
+        } else {
+            Ok(None)
+        }
+    }
+
     /// Clear all history
     pub fn clear_history(&self) {
         if let Ok(mut history) = self.history.write() {
@@ -145,7 +841,11 @@ impl ReqForgeCore {
     /// Replay a history entry
     pub async fn replay_history(&self, entry_id: uuid::Uuid) -> Result<HttpResponse, ReplayError> {
         if let Ok(history) = self.history.read() {
-            history.replay(entry_id, &self.engine, &self.active_vars()).await
+            let vars = match history.get_entry(entry_id) {
+                Some(entry) => self.variable_scope(Some(&entry.request)).to_map(),
+                None => self.active_vars(),
+            };
+            history.replay(entry_id, &self.engine, &vars).await
         } else {
             Err(ReplayError::EntryNotFound(entry_id))
         }
@@ -169,3 +869,23 @@ impl ReqForgeCore {
         }
     }
 }
+
+/// Fill empty secret values in `variables` from the unlocked `stored` ones.
+fn fill_secrets(variables: &mut [models::environment::Variable], stored: &[models::environment::Variable]) {
+    for var in variables.iter_mut().filter(|v| v.secret && v.value.is_empty()) {
+        if let Some(value) = stored.iter().find(|v| v.key == var.key) {
+            var.value = value.value.clone();
+        }
+    }
+}
+
+/// Replace the item matching `is_item` with `new`, appending it if there is
+/// none, or remove it when `new` is `None`.
+fn replace_item<T>(items: &mut Vec<T>, new: Option<T>, is_item: impl Fn(&T) -> bool) {
+    match (items.iter().position(is_item), new) {
+        (Some(index), Some(new)) => items[index] = new,
+        (None, Some(new)) => items.push(new),
+        (Some(index), None) => { items.remove(index); }
+        (None, None) => {}
+    }
+}
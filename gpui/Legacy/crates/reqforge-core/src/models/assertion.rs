@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+/// A declarative check evaluated against a response after it is received.
+///
+/// JSON paths use a small dot/bracket syntax: `$.data.items[0].id`,
+/// `$['weird key']`. The leading `$` is optional.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum Assertion {
+    /// Response status code equals the given value.
+    StatusEquals(u16),
+    /// A response header with the given name (case-insensitive) is present.
+    HeaderPresent(String),
+    /// The value at a JSON path equals the expected JSON value.
+    JsonPathEquals { path: String, expected: serde_json::Value },
+    /// The value at a JSON path matches a regular expression.
+    JsonPathMatches { path: String, pattern: String },
+    /// The response arrived in under the given number of milliseconds.
+    ResponseTimeBelow(u64),
+    /// The response body contains the given text.
+    BodyContains(String),
+}
+
+impl std::fmt::Display for Assertion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Assertion::StatusEquals(status) => write!(f, "status equals {}", status),
+            Assertion::HeaderPresent(name) => write!(f, "header '{}' is present", name),
+            Assertion::JsonPathEquals { path, expected } => write!(f, "{} equals {}", path, expected),
+            Assertion::JsonPathMatches { path, pattern } => write!(f, "{} matches /{}/", path, pattern),
+            Assertion::ResponseTimeBelow(millis) => write!(f, "response time below {} ms", millis),
+            Assertion::BodyContains(text) => write!(f, "body contains '{}'", text),
+        }
+    }
+}
+
+/// Outcome of evaluating a single assertion.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AssertionResult {
+    pub assertion: Assertion,
+    pub passed: bool,
+    /// The observed value, when one could be extracted.
+    pub actual: Option<String>,
+    /// Explanation of the failure, if any.
+    pub message: Option<String>,
+}
+
+/// Structured pass/fail report for a request's assertions.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct TestReport {
+    pub results: Vec<AssertionResult>,
+}
+
+impl TestReport {
+    pub fn passed_count(&self) -> usize {
+        self.results.iter().filter(|r| r.passed).count()
+    }
+
+    pub fn failed_count(&self) -> usize {
+        self.results.iter().filter(|r| !r.passed).count()
+    }
+
+    /// True when every assertion passed (vacuously true with no assertions).
+    pub fn all_passed(&self) -> bool {
+        self.results.iter().all(|r| r.passed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+}
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+
+/// Authentication applied by `HttpEngine` when a request is sent.
+///
+/// Requests and folders default to `Inherit`, which takes the auth of the
+/// nearest parent folder, then the collection. All string fields may contain
+/// `{{variable}}` placeholders.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub enum AuthConfig {
+    /// Use the auth of the enclosing folder or collection.
+    #[default]
+    Inherit,
+    /// Send no authentication, even if a parent defines some.
+    None,
+    Basic { username: String, password: String },
+    Bearer { token: String },
+    /// A static key sent as a header or query parameter.
+    ApiKey { key: String, value: String, location: ApiKeyLocation },
+    /// HTTP Digest (RFC 7616), answered after the server's 401 challenge.
+    Digest { username: String, password: String },
+    /// AWS Signature Version 4.
+    AwsSigV4 {
+        access_key_id: String,
+        secret_access_key: String,
+        #[serde(default)]
+        session_token: Option<String>,
+        region: String,
+        service: String,
+    },
+    OAuth2(OAuth2Config),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub enum ApiKeyLocation {
+    #[default]
+    Header,
+    Query,
+}
+
+/// OAuth 2.0 settings. Tokens are fetched from `token_url` on first use and
+/// cached by the engine until they expire.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OAuth2Config {
+    pub grant: OAuth2Grant,
+    pub token_url: String,
+    pub client_id: String,
+    #[serde(default)]
+    pub client_secret: String,
+    /// Space-separated scopes.
+    #[serde(default)]
+    pub scope: Option<String>,
+    /// Send client credentials as form fields instead of an HTTP Basic header.
+    #[serde(default)]
+    pub credentials_in_body: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum OAuth2Grant {
+    ClientCredentials,
+    Password { username: String, password: String },
+    /// Interactive user login with PKCE. The redirect is caught by a
+    /// temporary listener on `http://127.0.0.1:{redirect_port}/callback`;
+    /// without a port an ephemeral one is used.
+    AuthorizationCode {
+        authorization_url: String,
+        #[serde(default)]
+        redirect_port: Option<u16>,
+    },
+}
+
+/// An OAuth 2.0 access token, as cached by the engine or stored per environment.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OAuth2Token {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    /// `None` when the server did not say when the token expires.
+    #[serde(default)]
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl OAuth2Token {
+    /// Tokens are treated as expired slightly early to absorb clock skew
+    /// and request latency.
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .map(|at| chrono::Utc::now() + chrono::Duration::seconds(30) >= at)
+            .unwrap_or(false)
+    }
+}
+
+impl AuthConfig {
+    pub fn is_inherit(&self) -> bool {
+        matches!(self, AuthConfig::Inherit)
+    }
+
+    /// Mutable references to every string field, for interpolation.
+    pub fn values_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            AuthConfig::Inherit | AuthConfig::None => Vec::new(),
+            AuthConfig::Basic { username, password } | AuthConfig::Digest { username, password } => {
+                vec![username, password]
+            }
+            AuthConfig::Bearer { token } => vec![token],
+            AuthConfig::ApiKey { key, value, .. } => vec![key, value],
+            AuthConfig::AwsSigV4 { access_key_id, secret_access_key, session_token, region, service } => {
+                let mut values = vec![access_key_id, secret_access_key, region, service];
+                values.extend(session_token.as_mut());
+                values
+            }
+            AuthConfig::OAuth2(config) => {
+                let mut values = vec![&mut config.token_url, &mut config.client_id, &mut config.client_secret];
+                values.extend(config.scope.as_mut());
+                match &mut config.grant {
+                    OAuth2Grant::ClientCredentials => {}
+                    OAuth2Grant::Password { username, password } => {
+                        values.push(username);
+                        values.push(password);
+                    }
+                    OAuth2Grant::AuthorizationCode { authorization_url, .. } => values.push(authorization_url),
+                }
+                values
+            }
+        }
+    }
+}
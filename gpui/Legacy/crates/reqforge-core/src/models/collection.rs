@@ -2,6 +2,9 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use super::folder;
 use super::request::RequestDefinition;
+use super::script::RequestScripts;
+use super::auth::AuthConfig;
+use super::environment::Variable;
 use std::collections::HashMap;
 
 /// A Collection owns an ordered tree of folders/requests
@@ -12,6 +15,15 @@ pub struct Collection {
     pub name: String,
     pub tree: Vec<folder::CollectionItem>,
     pub requests: HashMap<Uuid, RequestDefinition>,
+    /// Scripts that run around every request in this collection.
+    #[serde(default)]
+    pub scripts: RequestScripts,
+    /// Default auth for requests and folders that use `AuthConfig::Inherit`.
+    #[serde(default)]
+    pub auth: AuthConfig,
+    /// Shared defaults for every request, overridden by folders and environments.
+    #[serde(default)]
+    pub variables: Vec<Variable>,
 }
 
 impl Collection {
@@ -21,6 +33,9 @@ impl Collection {
             name: name.into(),
             tree: Vec::new(),
             requests: HashMap::new(),
+            scripts: RequestScripts::default(),
+            auth: AuthConfig::Inherit,
+            variables: Vec::new(),
         }
     }
 
@@ -36,6 +51,145 @@ impl Collection {
         }
     }
 
+    /// Find a folder anywhere in the tree by ID.
+    pub fn find_folder(&self, folder_id: Uuid) -> Option<&folder::Folder> {
+        Self::find_folder_in(&self.tree, &|folder| folder.id == folder_id)
+    }
+
+    /// Find the first folder (depth-first) with the given name.
+    pub fn find_folder_by_name(&self, name: &str) -> Option<&folder::Folder> {
+        Self::find_folder_in(&self.tree, &|folder| folder.name == name)
+    }
+
+    /// The auth that applies to a request after resolving `Inherit` through
+    /// its enclosing folders (innermost first) and then the collection.
+    /// Returns `AuthConfig::None` when nothing in the chain sets auth.
+    pub fn effective_auth(&self, request_id: Uuid) -> AuthConfig {
+        if let Some(req) = self.requests.get(&request_id)
+            && !req.auth.is_inherit()
+        {
+            return req.auth.clone();
+        }
+
+        let mut path = Vec::new();
+        Self::folder_path_to(&self.tree, request_id, &mut path);
+        path.iter()
+            .rev()
+            .map(|folder| &folder.auth)
+            .chain(std::iter::once(&self.auth))
+            .find(|auth| !auth.is_inherit())
+            .cloned()
+            .unwrap_or(AuthConfig::None)
+    }
+
+    /// The folders enclosing a request, outermost first. Empty for requests
+    /// at the top level or not in this collection.
+    pub fn folders_containing(&self, request_id: Uuid) -> Vec<&folder::Folder> {
+        let mut path = Vec::new();
+        Self::folder_path_to(&self.tree, request_id, &mut path);
+        path
+    }
+
+    /// Requests in tree order (depth-first, folders in order), each paired
+    /// with the names of the folders that contain it.
+    pub fn ordered_requests(&self) -> Vec<(&RequestDefinition, Vec<String>)> {
+        let mut out = Vec::new();
+        self.collect_ordered(&self.tree, &mut Vec::new(), &mut out);
+        out
+    }
+
+    /// Like `ordered_requests`, but limited to a single folder's subtree.
+    pub fn ordered_requests_in_folder(&self, folder_id: Uuid) -> Option<Vec<(&RequestDefinition, Vec<String>)>> {
+        let folder = self.find_folder(folder_id)?;
+        let mut out = Vec::new();
+        self.collect_ordered(&folder.children, &mut vec![folder.name.clone()], &mut out);
+        Some(out)
+    }
+
+    /// Whether the collection, any folder or any request has a secret variable.
+    pub fn has_secret_variables(&self) -> bool {
+        fn in_folders(items: &[folder::CollectionItem]) -> bool {
+            items.iter().any(|item| match item {
+                folder::CollectionItem::Folder(folder) => {
+                    folder.variables.iter().any(|v| v.secret) || in_folders(&folder.children)
+                }
+                folder::CollectionItem::Request(_) => false,
+            })
+        }
+        self.variables.iter().any(|v| v.secret)
+            || in_folders(&self.tree)
+            || self.requests.values().any(|req| req.variables.iter().any(|v| v.secret))
+    }
+
+    /// The variables of every folder and request, by folder or request id.
+    pub fn item_variables_mut(&mut self) -> Vec<(Uuid, &mut Vec<Variable>)> {
+        let mut out = Vec::new();
+        Self::collect_folder_variables(&mut self.tree, &mut out);
+        out.extend(self.requests.iter_mut().map(|(id, req)| (*id, &mut req.variables)));
+        out
+    }
+
+    fn collect_folder_variables<'a>(items: &'a mut [folder::CollectionItem], out: &mut Vec<(Uuid, &'a mut Vec<Variable>)>) {
+        for item in items {
+            if let folder::CollectionItem::Folder(folder) = item {
+                let folder = &mut **folder;
+                out.push((folder.id, &mut folder.variables));
+                Self::collect_folder_variables(&mut folder.children, out);
+            }
+        }
+    }
+
+    fn collect_ordered<'a>(
+        &'a self,
+        items: &'a [folder::CollectionItem],
+        path: &mut Vec<String>,
+        out: &mut Vec<(&'a RequestDefinition, Vec<String>)>,
+    ) {
+        for item in items {
+            match item {
+                folder::CollectionItem::Request(id) => {
+                    if let Some(req) = self.requests.get(id) {
+                        out.push((req, path.clone()));
+                    }
+                }
+                folder::CollectionItem::Folder(folder) => {
+                    path.push(folder.name.clone());
+                    self.collect_ordered(&folder.children, path, out);
+                    path.pop();
+                }
+            }
+        }
+    }
+
+    /// Fill `path` with the folders leading to a request; returns false if not found.
+    fn folder_path_to<'a>(items: &'a [folder::CollectionItem], request_id: Uuid, path: &mut Vec<&'a folder::Folder>) -> bool {
+        for item in items {
+            match item {
+                folder::CollectionItem::Request(id) if *id == request_id => return true,
+                folder::CollectionItem::Request(_) => {}
+                folder::CollectionItem::Folder(folder) => {
+                    path.push(folder);
+                    if Self::folder_path_to(&folder.children, request_id, path) {
+                        return true;
+                    }
+                    path.pop();
+                }
+            }
+        }
+        false
+    }
+
+    fn find_folder_in<'a>(
+        items: &'a [folder::CollectionItem],
+        matches: &dyn Fn(&folder::Folder) -> bool,
+    ) -> Option<&'a folder::Folder> {
+        items.iter().find_map(|item| match item {
+            folder::CollectionItem::Folder(folder) if matches(folder) => Some(folder.as_ref()),
+            folder::CollectionItem::Folder(folder) => Self::find_folder_in(&folder.children, matches),
+            folder::CollectionItem::Request(_) => None,
+        })
+    }
+
     fn insert_into_folder(items: &mut Vec<folder::CollectionItem>, folder_id: Uuid, new_item: folder::CollectionItem) -> bool {
         for item in items.iter_mut() {
             if let folder::CollectionItem::Folder(folder) = item {
@@ -0,0 +1,186 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A cookie captured from a `Set-Cookie` header (RFC 6265) or entered by hand.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    /// Lower-case domain without a leading dot.
+    pub domain: String,
+    pub path: String,
+    /// `None` for session cookies, which are still persisted.
+    #[serde(default)]
+    pub expires: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub secure: bool,
+    #[serde(default)]
+    pub http_only: bool,
+    /// Only sent to `domain` itself, not its subdomains (no `Domain` attribute was given).
+    #[serde(default)]
+    pub host_only: bool,
+    #[serde(default)]
+    pub same_site: Option<String>,
+}
+
+impl Cookie {
+    pub fn new(name: impl Into<String>, value: impl Into<String>, domain: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+            domain: domain.into().trim_start_matches('.').to_ascii_lowercase(),
+            path: "/".to_string(),
+            expires: None,
+            secure: false,
+            http_only: false,
+            host_only: false,
+            same_site: None,
+        }
+    }
+
+    /// Parse a `Set-Cookie` header received from `url`.
+    ///
+    /// Returns `None` for malformed headers and for cookies whose `Domain`
+    /// does not cover the URL's host.
+    pub fn parse(header: &str, url: &url::Url) -> Option<Self> {
+        let host = url.host_str()?.to_ascii_lowercase();
+        let mut parts = header.split(';');
+        let (name, value) = parts.next()?.split_once('=')?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+
+        let mut cookie = Cookie::new(name, value.trim().trim_matches('"'), host.clone());
+        cookie.host_only = true;
+        cookie.path = default_path(url.path());
+
+        let mut max_age = None;
+        for attribute in parts {
+            let (key, value) = match attribute.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => (attribute.trim(), ""),
+            };
+            match key.to_ascii_lowercase().as_str() {
+                "domain" if !value.is_empty() => {
+                    let domain = value.trim_start_matches('.').to_ascii_lowercase();
+                    if !domain_matches(&host, &domain) {
+                        return None;
+                    }
+                    cookie.domain = domain;
+                    cookie.host_only = false;
+                }
+                "path" if value.starts_with('/') => cookie.path = value.to_string(),
+                "expires" => cookie.expires = cookie.expires.or(parse_cookie_date(value)),
+                "max-age" => max_age = value.parse::<i64>().ok(),
+                "secure" => cookie.secure = true,
+                "httponly" => cookie.http_only = true,
+                "samesite" if !value.is_empty() => cookie.same_site = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        // Max-Age wins over Expires; zero or negative means "delete now"
+        if let Some(seconds) = max_age {
+            cookie.expires = Some(Utc::now() + chrono::Duration::seconds(seconds.max(0)));
+        }
+        Some(cookie)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires.is_some_and(|at| at <= Utc::now())
+    }
+
+    /// Whether this cookie should be sent with a request to `url`.
+    pub fn matches(&self, url: &url::Url) -> bool {
+        let Some(host) = url.host_str().map(|h| h.to_ascii_lowercase()) else {
+            return false;
+        };
+        let domain_ok = if self.host_only { host == self.domain } else { domain_matches(&host, &self.domain) };
+        domain_ok
+            && path_matches(url.path(), &self.path)
+            && (!self.secure || url.scheme() == "https")
+            && !self.is_expired()
+    }
+
+    /// Cookies with the same name, domain and path replace each other.
+    pub fn same_identity(&self, other: &Cookie) -> bool {
+        self.name == other.name && self.domain == other.domain && self.path == other.path
+    }
+}
+
+/// `host` is `domain` or one of its subdomains.
+pub fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain || host.strip_suffix(domain).is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+fn path_matches(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || request_path[cookie_path.len()..].starts_with('/')))
+}
+
+/// The directory of the request path, used when no `Path` attribute is given.
+fn default_path(request_path: &str) -> String {
+    match request_path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(index) => request_path[..index].to_string(),
+    }
+}
+
+fn parse_cookie_date(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(date) = DateTime::parse_from_rfc2822(value) {
+        return Some(date.with_timezone(&Utc));
+    }
+    // Older servers use dashes: "Wed, 21-Oct-2015 07:28:00 GMT"
+    NaiveDateTime::parse_from_str(value, "%a, %d-%b-%Y %H:%M:%S GMT")
+        .ok()
+        .map(|date| date.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(s: &str) -> url::Url {
+        url::Url::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse_set_cookie() {
+        let cookie = Cookie::parse(
+            "session=abc123; Domain=.Example.com; Path=/api; Expires=Wed, 21 Oct 2065 07:28:00 GMT; Secure; HttpOnly; SameSite=Lax",
+            &url("https://www.example.com/login"),
+        )
+        .unwrap();
+        assert_eq!(cookie.name, "session");
+        assert_eq!(cookie.value, "abc123");
+        assert_eq!(cookie.domain, "example.com");
+        assert_eq!(cookie.path, "/api");
+        assert!(cookie.secure && cookie.http_only && !cookie.host_only);
+        assert_eq!(cookie.same_site.as_deref(), Some("Lax"));
+        assert_eq!(cookie.expires.unwrap().to_rfc3339(), "2065-10-21T07:28:00+00:00");
+
+        // Defaults come from the request URL
+        let cookie = Cookie::parse("theme=dark", &url("http://example.com/app/settings")).unwrap();
+        assert_eq!(cookie.path, "/app");
+        assert!(cookie.host_only);
+
+        // A server cannot set cookies for another domain
+        assert!(Cookie::parse("a=b; Domain=other.com", &url("http://example.com/")).is_none());
+        assert!(Cookie::parse("novalue", &url("http://example.com/")).is_none());
+        assert!(Cookie::parse("gone=1; Max-Age=0", &url("http://example.com/")).unwrap().is_expired());
+    }
+
+    #[test]
+    fn test_cookie_matching() {
+        let cookie = Cookie::parse("id=1; Domain=example.com; Path=/api; Secure", &url("https://example.com/")).unwrap();
+        assert!(cookie.matches(&url("https://example.com/api")));
+        assert!(cookie.matches(&url("https://sub.example.com/api/users")));
+        assert!(!cookie.matches(&url("https://example.com/apix")));
+        assert!(!cookie.matches(&url("http://example.com/api")));
+        assert!(!cookie.matches(&url("https://notexample.com/api")));
+
+        let host_only = Cookie::parse("id=1", &url("http://example.com/")).unwrap();
+        assert!(!host_only.matches(&url("http://sub.example.com/")));
+    }
+}
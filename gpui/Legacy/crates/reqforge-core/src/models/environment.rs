@@ -24,10 +24,6 @@ impl Environment {
     }
 
     pub fn to_map(&self) -> HashMap<String, String> {
-        self.variables
-            .iter()
-            .filter(|v| v.enabled)
-            .map(|v| (v.key.clone(), v.value.clone()))
-            .collect()
+        crate::env::variables::enabled_variables(&self.variables)
     }
 }
@@ -1,15 +1,23 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use super::auth::AuthConfig;
+use super::environment::Variable;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Folder {
     pub id: Uuid,
     pub name: String,
     pub children: Vec<CollectionItem>,
+    /// Auth inherited by requests in this folder that use `AuthConfig::Inherit`.
+    #[serde(default)]
+    pub auth: AuthConfig,
+    /// Variables for requests in this folder, overriding the collection's.
+    #[serde(default)]
+    pub variables: Vec<Variable>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CollectionItem {
     Request(Uuid),              // references RequestDefinition.id
-    Folder(Folder),
+    Folder(Box<Folder>),
 }
@@ -1,7 +1,13 @@
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use super::request::RequestDefinition;
+use super::request::{HttpMethod, RequestDefinition};
+use super::assertion::TestReport;
+use super::response::TimingBreakdown;
 
 /// Snapshot of response information for history
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,17 +17,42 @@ pub struct ResponseSnapshot {
     pub size_bytes: usize,
     pub elapsed_millis: u64,
     pub success: bool,
+    /// Per-phase timing; absent for failed requests and older entries.
+    #[serde(default)]
+    pub timing: Option<TimingBreakdown>,
+    /// Response headers; empty for failed requests and older entries.
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// The response body, if it was kept. Read it with
+    /// `RequestHistory::response_body`.
+    #[serde(default)]
+    pub body: Option<StoredBody>,
+}
+
+/// A response body kept in the history's side directory, gzip-compressed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StoredBody {
+    /// Size of the file on disk.
+    pub compressed_bytes: u64,
+    /// Whether only the first part of the body was kept, because it was
+    /// larger than the per-entry limit.
+    pub truncated: bool,
 }
 
 /// A single entry in the request history
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RequestHistoryEntry {
     pub id: Uuid,
+    /// The request as it was sent: after scripts ran and variables were
+    /// resolved.
     pub request: RequestDefinition,
     pub response: ResponseSnapshot,
     pub timestamp: chrono::DateTime<chrono::Utc>,
     pub environment_id: Option<Uuid>,
     pub environment_name: Option<String>,
+    /// Assertion results, if the request defined any.
+    #[serde(default)]
+    pub test_report: Option<TestReport>,
 }
 
 impl RequestHistoryEntry {
@@ -38,8 +69,15 @@ impl RequestHistoryEntry {
             timestamp: chrono::Utc::now(),
             environment_id,
             environment_name,
+            test_report: None,
         }
     }
+
+    /// Attach the assertion results produced for this execution.
+    pub fn with_test_report(mut self, report: TestReport) -> Self {
+        self.test_report = Some(report);
+        self
+    }
 }
 
 impl From<&crate::HttpResponse> for ResponseSnapshot {
@@ -50,6 +88,37 @@ impl From<&crate::HttpResponse> for ResponseSnapshot {
             size_bytes: resp.size_bytes,
             elapsed_millis: resp.elapsed.as_millis() as u64,
             success: resp.is_success(),
+            timing: Some(resp.timing.clone()),
+            headers: resp.headers.clone(),
+            body: None,
         }
     }
 }
+
+/// Criteria for `RequestHistory::search`. Unset fields match every entry.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    /// Case-insensitive substring of the request URL, as it was sent (with
+    /// variables resolved).
+    pub url: Option<String>,
+    pub method: Option<HttpMethod>,
+    /// Status codes to match, e.g. `404..=404` or `500..=599`. Failed
+    /// requests have status 0.
+    pub status: Option<RangeInclusive<u16>>,
+    pub environment_id: Option<Uuid>,
+    /// Only entries at or after this time.
+    pub since: Option<DateTime<Utc>>,
+    /// Only entries before this time.
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl HistoryFilter {
+    pub fn matches(&self, entry: &RequestHistoryEntry) -> bool {
+        self.url.as_ref().is_none_or(|url| entry.request.url.to_lowercase().contains(&url.to_lowercase()))
+            && self.method.as_ref().is_none_or(|method| entry.request.method == *method)
+            && self.status.as_ref().is_none_or(|status| status.contains(&entry.response.status))
+            && self.environment_id.is_none_or(|id| entry.environment_id == Some(id))
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+            && self.until.is_none_or(|until| entry.timestamp < until)
+    }
+}
@@ -5,6 +5,12 @@ pub mod collection;
 pub mod folder;
 pub mod history;
 pub mod template;
+pub mod script;
+pub mod assertion;
+pub mod auth;
+pub mod settings;
+pub mod cookie;
+pub mod workspace;
 
 pub use request::*;
 pub use response::*;
@@ -13,3 +19,9 @@ pub use collection::*;
 pub use folder::*;
 pub use history::*;
 pub use template::*;
+pub use script::*;
+pub use assertion::*;
+pub use auth::*;
+pub use settings::*;
+pub use cookie::*;
+pub use workspace::*;
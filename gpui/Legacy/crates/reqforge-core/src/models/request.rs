@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use crate::validation::{validate_request, ValidationResult};
+use super::script::RequestScripts;
+use super::assertion::Assertion;
+use super::auth::AuthConfig;
+use super::settings::RequestSettings;
+use super::environment::Variable;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum HttpMethod {
@@ -33,6 +38,49 @@ pub enum BodyType {
     None,
     Raw { content: String, content_type: RawContentType },
     FormUrlEncoded(Vec<KeyValuePair>),
+    Multipart(Vec<MultipartPart>),
+    /// Raw bytes read from a file at send time.
+    Binary { path: String },
+}
+
+/// A single part of a `multipart/form-data` body.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MultipartPart {
+    pub name: String,
+    pub value: MultipartValue,
+    /// Overrides the part's Content-Type. File parts otherwise guess it from
+    /// the file extension; text parts send none.
+    #[serde(default)]
+    pub content_type: Option<String>,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum MultipartValue {
+    Text(String),
+    /// A file read at send time. `filename` overrides the name sent to the
+    /// server, which defaults to the last path component.
+    File { path: String, filename: Option<String> },
+}
+
+impl MultipartPart {
+    pub fn text(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value: MultipartValue::Text(value.into()),
+            content_type: None,
+            enabled: true,
+        }
+    }
+
+    pub fn file(name: impl Into<String>, path: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value: MultipartValue::File { path: path.into(), filename: None },
+            content_type: None,
+            enabled: true,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -44,6 +92,18 @@ pub enum RawContentType {
     Html,
 }
 
+impl RawContentType {
+    /// The Content-Type header sent with a raw body of this type.
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            RawContentType::Json => "application/json",
+            RawContentType::Xml => "application/xml",
+            RawContentType::Text => "text/plain",
+            RawContentType::Html => "text/html",
+        }
+    }
+}
+
 /// The core, persistable request definition.
 /// All string fields may contain `{{variable}}` placeholders.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,6 +115,20 @@ pub struct RequestDefinition {
     pub headers: Vec<KeyValuePair>,
     pub query_params: Vec<KeyValuePair>,
     pub body: BodyType,
+    /// Authentication; `Inherit` defers to the enclosing folder or collection.
+    #[serde(default)]
+    pub auth: AuthConfig,
+    /// Overrides of the workspace engine settings (timeouts, proxy, TLS, redirects).
+    #[serde(default)]
+    pub settings: RequestSettings,
+    /// Request-local variables, overriding the environment.
+    #[serde(default)]
+    pub variables: Vec<Variable>,
+    #[serde(default)]
+    pub scripts: RequestScripts,
+    /// Checks evaluated against the response after execution.
+    #[serde(default)]
+    pub assertions: Vec<Assertion>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
@@ -70,6 +144,11 @@ impl RequestDefinition {
             headers: Vec::new(),
             query_params: Vec::new(),
             body: BodyType::None,
+            auth: AuthConfig::Inherit,
+            settings: RequestSettings::default(),
+            variables: Vec::new(),
+            scripts: RequestScripts::default(),
+            assertions: Vec::new(),
             created_at: now,
             updated_at: now,
         }
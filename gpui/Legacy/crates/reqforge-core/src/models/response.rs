@@ -1,15 +1,48 @@
 use std::collections::HashMap;
 use std::time::Duration;
 use bytes::Bytes;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone)]
 pub struct HttpResponse {
     pub status: u16,
     pub status_text: String,
+    /// Lower-case names. Repeated headers are joined with `", "`, except
+    /// `set-cookie`, which is joined with newlines.
     pub headers: HashMap<String, String>,
     pub body: Bytes,                         // refcounted, cheap clone
     pub size_bytes: usize,
+    /// Total time from sending the request to receiving the whole body.
     pub elapsed: Duration,
+    pub timing: TimingBreakdown,
+}
+
+/// Where the time of a request went.
+///
+/// Connection phases are `None` when a pooled connection was reused (or,
+/// for DNS, when the host is an IP address); TLS is `None` for plain HTTP.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TimingBreakdown {
+    pub dns_lookup: Option<Duration>,
+    pub tcp_connect: Option<Duration>,
+    pub tls_handshake: Option<Duration>,
+    /// From the request being written to the first byte of the response.
+    pub time_to_first_byte: Duration,
+    pub content_download: Duration,
+    pub total: Duration,
+}
+
+impl TimingBreakdown {
+    /// The phases that took place, in order, for display.
+    pub fn phases(&self) -> Vec<(&'static str, Duration)> {
+        let mut phases = Vec::new();
+        phases.extend(self.dns_lookup.map(|d| ("DNS lookup", d)));
+        phases.extend(self.tcp_connect.map(|d| ("TCP connect", d)));
+        phases.extend(self.tls_handshake.map(|d| ("TLS handshake", d)));
+        phases.push(("Time to first byte", self.time_to_first_byte));
+        phases.push(("Content download", self.content_download));
+        phases
+    }
 }
 
 impl HttpResponse {
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// Scripts attached to a request or collection.
+///
+/// Both hooks are Rhai source code evaluated by the sandboxed
+/// `scripting::ScriptEngine`. Empty or missing scripts are skipped.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct RequestScripts {
+    /// Runs before interpolation; may mutate the request and set variables.
+    pub pre_request: Option<String>,
+    /// Runs after the response arrives; may extract values into variables.
+    pub post_response: Option<String>,
+}
+
+impl RequestScripts {
+    pub fn is_empty(&self) -> bool {
+        Self::is_blank(&self.pre_request) && Self::is_blank(&self.post_response)
+    }
+
+    /// Returns the pre-request script if it contains any code.
+    pub fn pre_request(&self) -> Option<&str> {
+        self.pre_request.as_deref().filter(|s| !s.trim().is_empty())
+    }
+
+    /// Returns the post-response script if it contains any code.
+    pub fn post_response(&self) -> Option<&str> {
+        self.post_response.as_deref().filter(|s| !s.trim().is_empty())
+    }
+
+    fn is_blank(script: &Option<String>) -> bool {
+        script.as_deref().map(|s| s.trim().is_empty()).unwrap_or(true)
+    }
+}
@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+
+/// Connection settings used by `HttpEngine` to build its HTTP client.
+///
+/// Stored once per workspace; individual requests can override parts of it
+/// with `RequestSettings`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct EngineSettings {
+    /// Time allowed to establish a connection, in milliseconds.
+    pub connect_timeout_ms: u64,
+    /// Time allowed for the whole request, in milliseconds. `None` waits forever.
+    pub timeout_ms: Option<u64>,
+    pub proxy: Option<ProxySettings>,
+    pub tls: TlsSettings,
+    pub redirects: RedirectPolicy,
+    pub http_version: HttpVersionPref,
+}
+
+impl Default for EngineSettings {
+    fn default() -> Self {
+        Self {
+            connect_timeout_ms: 10_000,
+            timeout_ms: Some(30_000),
+            proxy: None,
+            tls: TlsSettings::default(),
+            redirects: RedirectPolicy::default(),
+            http_version: HttpVersionPref::default(),
+        }
+    }
+}
+
+impl EngineSettings {
+    /// These settings with a request's overrides applied.
+    pub fn with_overrides(&self, overrides: &RequestSettings) -> EngineSettings {
+        let mut settings = self.clone();
+        if let Some(ms) = overrides.connect_timeout_ms {
+            settings.connect_timeout_ms = ms;
+        }
+        if let Some(ms) = overrides.timeout_ms {
+            settings.timeout_ms = (ms > 0).then_some(ms);
+        }
+        if overrides.bypass_proxy {
+            settings.proxy = None;
+        } else if let Some(proxy) = &overrides.proxy {
+            settings.proxy = Some(proxy.clone());
+        }
+        if let Some(insecure) = overrides.accept_invalid_certs {
+            settings.tls.accept_invalid_certs = insecure;
+        }
+        if let Some(redirects) = &overrides.redirects {
+            settings.redirects = redirects.clone();
+        }
+        if let Some(version) = &overrides.http_version {
+            settings.http_version = version.clone();
+        }
+        settings
+    }
+}
+
+/// An HTTP(S) or SOCKS proxy. `socks5h://` resolves host names on the proxy,
+/// `socks5://` resolves them locally.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProxySettings {
+    /// e.g. `http://proxy.internal:3128` or `socks5h://127.0.0.1:1080`
+    pub url: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Hosts that bypass the proxy: exact names, `.suffix` domains, IPs or CIDR ranges.
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(default)]
+pub struct TlsSettings {
+    /// PEM file with extra root certificates to trust.
+    pub ca_bundle_path: Option<String>,
+    /// PEM file with the client certificate chain for mTLS.
+    pub client_cert_path: Option<String>,
+    /// PEM file with the client private key. May be omitted if the key is
+    /// in `client_cert_path`.
+    pub client_key_path: Option<String>,
+    /// Skip certificate and hostname verification. Only for local testing.
+    pub accept_invalid_certs: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum RedirectPolicy {
+    /// Return 3xx responses as-is.
+    None,
+    /// Follow up to `max` redirects.
+    Follow { max: usize },
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        RedirectPolicy::Follow { max: 10 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub enum HttpVersionPref {
+    /// Negotiate HTTP/2 over TLS (ALPN) and fall back to HTTP/1.1.
+    #[default]
+    Auto,
+    Http1Only,
+    /// Speak HTTP/2 without negotiation, including over plain HTTP.
+    Http2Only,
+}
+
+/// Per-request overrides of the workspace `EngineSettings`. `None` keeps the
+/// workspace value.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(default)]
+pub struct RequestSettings {
+    pub connect_timeout_ms: Option<u64>,
+    /// `Some(0)` disables the workspace timeout for this request.
+    pub timeout_ms: Option<u64>,
+    pub proxy: Option<ProxySettings>,
+    /// Connect directly even if the workspace configures a proxy.
+    pub bypass_proxy: bool,
+    pub accept_invalid_certs: Option<bool>,
+    pub redirects: Option<RedirectPolicy>,
+    pub http_version: Option<HttpVersionPref>,
+}
+
+impl RequestSettings {
+    pub fn is_empty(&self) -> bool {
+        *self == RequestSettings::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_overrides() {
+        let workspace = EngineSettings {
+            proxy: Some(ProxySettings {
+                url: "http://proxy:3128".to_string(),
+                username: None,
+                password: None,
+                no_proxy: vec!["localhost".to_string()],
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(workspace.with_overrides(&RequestSettings::default()), workspace);
+
+        let overrides = RequestSettings {
+            timeout_ms: Some(0),
+            bypass_proxy: true,
+            redirects: Some(RedirectPolicy::None),
+            ..Default::default()
+        };
+        let effective = workspace.with_overrides(&overrides);
+        assert_eq!(effective.timeout_ms, None);
+        assert_eq!(effective.proxy, None);
+        assert_eq!(effective.redirects, RedirectPolicy::None);
+        assert_eq!(effective.connect_timeout_ms, 10_000);
+    }
+
+    #[test]
+    fn test_settings_deserialize_with_defaults() {
+        let settings: EngineSettings = serde_json::from_str(r#"{"timeout_ms": 5000}"#).unwrap();
+        assert_eq!(settings.timeout_ms, Some(5000));
+        assert_eq!(settings.connect_timeout_ms, 10_000);
+        assert_eq!(settings.redirects, RedirectPolicy::Follow { max: 10 });
+    }
+}
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// How collections are laid out under `collections/`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollectionLayout {
+    /// One `{id}.json` file per collection.
+    #[default]
+    Json,
+    /// A directory per collection and folder, with one YAML file per request.
+    Files,
+}
+
+/// Workspace-level options stored in `workspace.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkspaceConfig {
+    #[serde(default)]
+    pub collection_layout: CollectionLayout,
+}
@@ -0,0 +1,283 @@
+//! Interactive OAuth 2.0 authorization-code flow with PKCE (RFC 7636).
+//!
+//! A temporary listener on `127.0.0.1` catches the authorization server's
+//! redirect, so the flow works for desktop and CLI use without a hosted
+//! callback page. Token storage and refresh live in `ReqForgeCore`.
+
+use std::time::Duration;
+
+use base64::Engine as _;
+use rand::RngCore;
+use reqwest::Client;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::http::auth::exchange_authorization_code;
+use crate::http::client::HttpError;
+use crate::models::auth::{OAuth2Config, OAuth2Grant, OAuth2Token};
+
+/// How long to wait for the user to finish logging in.
+pub const DEFAULT_AUTHORIZE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// A PKCE code verifier and its S256 challenge.
+#[derive(Debug, Clone)]
+pub struct Pkce {
+    pub verifier: String,
+    pub challenge: String,
+}
+
+impl Pkce {
+    /// Generate a random verifier: 32 random bytes, base64url-encoded (43
+    /// characters), as RFC 7636 recommends.
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; 32];
+        rand::rng().fill_bytes(&mut bytes);
+        Self::from_verifier(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes))
+    }
+
+    pub fn from_verifier(verifier: impl Into<String>) -> Self {
+        let verifier = verifier.into();
+        let challenge = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+        Self { verifier, challenge }
+    }
+}
+
+/// Build the URL the user opens to log in.
+pub fn authorization_url(
+    config: &OAuth2Config,
+    authorization_endpoint: &str,
+    redirect_uri: &str,
+    state: &str,
+    pkce: &Pkce,
+) -> Result<String, HttpError> {
+    let mut url = url::Url::parse(authorization_endpoint)
+        .map_err(|e| HttpError::UrlParse(format!("Invalid authorization URL: {}", e)))?;
+    {
+        let mut query = url.query_pairs_mut();
+        query
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &config.client_id)
+            .append_pair("redirect_uri", redirect_uri)
+            .append_pair("state", state)
+            .append_pair("code_challenge", &pkce.challenge)
+            .append_pair("code_challenge_method", "S256");
+        if let Some(scope) = config.scope.as_deref().filter(|s| !s.is_empty()) {
+            query.append_pair("scope", scope);
+        }
+    }
+    Ok(url.into())
+}
+
+/// A one-shot HTTP listener on the loopback interface that waits for the
+/// authorization server to redirect back with a code.
+pub struct LoopbackListener {
+    listener: TcpListener,
+    redirect_uri: String,
+}
+
+impl LoopbackListener {
+    /// Bind to `127.0.0.1`, on an ephemeral port when `port` is `None`.
+    pub async fn bind(port: Option<u16>) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port.unwrap_or(0))).await?;
+        let port = listener.local_addr()?.port();
+        Ok(Self {
+            listener,
+            redirect_uri: format!("http://127.0.0.1:{}/callback", port),
+        })
+    }
+
+    pub fn redirect_uri(&self) -> &str {
+        &self.redirect_uri
+    }
+
+    /// Wait for `/callback` and return its `code`, checking `state`.
+    ///
+    /// Other paths (such as a browser's favicon request) get a 404 and the
+    /// listener keeps waiting.
+    pub async fn wait_for_code(self, expected_state: &str, timeout: Duration) -> Result<String, HttpError> {
+        let wait = async {
+            loop {
+                let (mut stream, _) = self
+                    .listener
+                    .accept()
+                    .await
+                    .map_err(|e| HttpError::Auth(format!("Loopback listener failed: {}", e)))?;
+                let Some(target) = read_request_target(&mut stream).await else {
+                    continue;
+                };
+                let (path, query) = target.split_once('?').unwrap_or((target.as_str(), ""));
+                if path != "/callback" {
+                    respond(&mut stream, "404 Not Found", "Not found").await;
+                    continue;
+                }
+
+                let params: Vec<(String, String)> = url::form_urlencoded::parse(query.as_bytes())
+                    .into_owned()
+                    .collect();
+                let get = |name: &str| params.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone());
+
+                if let Some(error) = get("error") {
+                    respond(&mut stream, "400 Bad Request", "Authorization failed. You can close this window.").await;
+                    let description = get("error_description").map(|d| format!(": {}", d)).unwrap_or_default();
+                    return Err(HttpError::Auth(format!("Authorization denied ({}){}", error, description)));
+                }
+                if get("state").as_deref() != Some(expected_state) {
+                    respond(&mut stream, "400 Bad Request", "Invalid state. You can close this window.").await;
+                    return Err(HttpError::Auth("Authorization response has an unexpected state".to_string()));
+                }
+                let Some(code) = get("code") else {
+                    respond(&mut stream, "400 Bad Request", "Missing code. You can close this window.").await;
+                    return Err(HttpError::Auth("Authorization response has no code".to_string()));
+                };
+
+                respond(&mut stream, "200 OK", "Authorization complete. You can close this window.").await;
+                return Ok(code);
+            }
+        };
+
+        tokio::time::timeout(timeout, wait)
+            .await
+            .map_err(|_| HttpError::Auth("Timed out waiting for the authorization redirect".to_string()))?
+    }
+}
+
+/// Read the request head and return the request target of a GET request.
+async fn read_request_target(stream: &mut TcpStream) -> Option<String> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !buffer.windows(4).any(|w| w == b"\r\n\r\n") && buffer.len() < 16 * 1024 {
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+
+    let head = String::from_utf8_lossy(&buffer);
+    let mut parts = head.lines().next()?.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some("GET"), Some(target)) => Some(target.to_string()),
+        _ => None,
+    }
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, message: &str) {
+    let body = format!(
+        "<!DOCTYPE html><html><head><title>ReqForge</title></head><body><p>{}</p></body></html>",
+        message
+    );
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// Run the full flow: listen, hand the login URL to `open_url` (which should
+/// open a browser or print it), wait for the redirect and exchange the code.
+pub async fn authorize<F>(
+    client: &Client,
+    config: &OAuth2Config,
+    open_url: F,
+    timeout: Duration,
+) -> Result<OAuth2Token, HttpError>
+where
+    F: FnOnce(&str),
+{
+    let OAuth2Grant::AuthorizationCode { authorization_url: endpoint, redirect_port } = &config.grant else {
+        return Err(HttpError::Auth("OAuth 2.0 config does not use the authorization code grant".to_string()));
+    };
+
+    let listener = LoopbackListener::bind(*redirect_port)
+        .await
+        .map_err(|e| HttpError::Auth(format!("Failed to start loopback listener: {}", e)))?;
+    let redirect_uri = listener.redirect_uri().to_string();
+    let state = uuid::Uuid::new_v4().simple().to_string();
+    let pkce = Pkce::generate();
+
+    open_url(&authorization_url(config, endpoint, &redirect_uri, &state, &pkce)?);
+    let code = listener.wait_for_code(&state, timeout).await?;
+
+    exchange_authorization_code(client, config, &code, &pkce.verifier, &redirect_uri).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pkce_rfc7636_example() {
+        let pkce = Pkce::from_verifier("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk");
+        assert_eq!(pkce.challenge, "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM");
+    }
+
+    #[test]
+    fn test_authorization_url() {
+        let config = OAuth2Config {
+            grant: OAuth2Grant::AuthorizationCode {
+                authorization_url: "https://auth.example.com/authorize?audience=api".to_string(),
+                redirect_port: None,
+            },
+            token_url: "https://auth.example.com/token".to_string(),
+            client_id: "desktop".to_string(),
+            client_secret: String::new(),
+            scope: Some("openid profile".to_string()),
+            credentials_in_body: true,
+        };
+        let pkce = Pkce::from_verifier("verifier");
+        let url = authorization_url(
+            &config,
+            "https://auth.example.com/authorize?audience=api",
+            "http://127.0.0.1:5000/callback",
+            "xyz",
+            &pkce,
+        )
+        .unwrap();
+
+        assert!(url.starts_with("https://auth.example.com/authorize?audience=api&response_type=code&client_id=desktop"));
+        assert!(url.contains("redirect_uri=http%3A%2F%2F127.0.0.1%3A5000%2Fcallback"));
+        assert!(url.contains("state=xyz"));
+        assert!(url.contains("code_challenge_method=S256"));
+        assert!(url.contains("scope=openid+profile"));
+    }
+
+    #[test]
+    fn test_generated_verifier() {
+        let pkce = Pkce::generate();
+        assert_eq!(pkce.verifier.len(), 43);
+        assert!(pkce.verifier.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_ne!(pkce.verifier, Pkce::generate().verifier);
+    }
+
+    #[tokio::test]
+    async fn test_loopback_listener_only_accepts_callback_path() {
+        let listener = LoopbackListener::bind(None).await.unwrap();
+        let base = listener.redirect_uri().to_string();
+
+        let client = tokio::spawn(async move {
+            let other = reqwest::get(format!("{}X?code=wrong&state=s", base)).await.map(|r| r.status().as_u16());
+            let callback = reqwest::get(format!("{}?code=right&state=s", base)).await.map(|r| r.status().as_u16());
+            (other.unwrap(), callback.unwrap())
+        });
+        let code = listener.wait_for_code("s", Duration::from_secs(5)).await.unwrap();
+
+        assert_eq!(code, "right");
+        assert_eq!(client.await.unwrap(), (404, 200));
+    }
+
+    #[tokio::test]
+    async fn test_loopback_listener_rejects_wrong_state() {
+        let listener = LoopbackListener::bind(None).await.unwrap();
+        let callback = format!("{}?code=abc&state=evil", listener.redirect_uri());
+
+        let client = tokio::spawn(async move { reqwest::get(callback).await.map(|r| r.status().as_u16()) });
+        let result = listener.wait_for_code("expected", Duration::from_secs(5)).await;
+
+        assert!(matches!(result, Err(HttpError::Auth(ref m)) if m.contains("state")));
+        assert_eq!(client.await.unwrap().unwrap(), 400);
+    }
+}
@@ -0,0 +1,454 @@
+//! Collection runner for executing a whole collection (or folder) in order.
+//!
+//! The runner walks `Collection::tree` depth-first, executes every request
+//! through `ReqForgeCore` (so scripts, assertions and history all apply) and
+//! collects the results into a `RunReport`.
+
+pub mod junit;
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::assertion::TestReport;
+use crate::models::request::HttpMethod;
+use crate::ReqForgeCore;
+
+/// Errors that prevent a run from starting.
+#[derive(Debug, thiserror::Error)]
+pub enum RunnerError {
+    #[error("Collection not found: {0}")]
+    CollectionNotFound(Uuid),
+    #[error("Folder not found: {0}")]
+    FolderNotFound(Uuid),
+    #[error("Data file error: {0}")]
+    DataFile(String),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Result of executing a single request during a run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestRunResult {
+    /// Zero-based iteration index.
+    pub iteration: usize,
+    pub request_id: Uuid,
+    pub request_name: String,
+    /// Names of the folders containing the request, outermost first.
+    pub folder_path: Vec<String>,
+    pub method: HttpMethod,
+    pub url: String,
+    /// Response status, or `None` if the request failed to send.
+    pub status: Option<u16>,
+    pub elapsed_millis: u64,
+    pub test_report: TestReport,
+    /// Transport, validation or script error, if any.
+    pub error: Option<String>,
+}
+
+impl RequestRunResult {
+    /// A request passes when it was sent successfully and every assertion passed.
+    pub fn passed(&self) -> bool {
+        self.error.is_none() && self.test_report.all_passed()
+    }
+}
+
+/// Aggregate report for a collection run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunReport {
+    pub collection_id: Uuid,
+    pub collection_name: String,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub duration_millis: u64,
+    pub iterations: usize,
+    pub results: Vec<RequestRunResult>,
+    /// True if the run ended early because `stop_on_failure` was set.
+    pub stopped_early: bool,
+}
+
+impl RunReport {
+    pub fn total_requests(&self) -> usize {
+        self.results.len()
+    }
+
+    pub fn failed_requests(&self) -> usize {
+        self.results.iter().filter(|r| !r.passed()).count()
+    }
+
+    pub fn passed_assertions(&self) -> usize {
+        self.results.iter().map(|r| r.test_report.passed_count()).sum()
+    }
+
+    pub fn failed_assertions(&self) -> usize {
+        self.results.iter().map(|r| r.test_report.failed_count()).sum()
+    }
+
+    pub fn all_passed(&self) -> bool {
+        self.results.iter().all(|r| r.passed())
+    }
+
+    /// Render this report as JUnit XML for CI systems.
+    pub fn to_junit_xml(&self) -> String {
+        junit::to_junit_xml(self)
+    }
+}
+
+/// Runs every request of a collection (or one of its folders) in tree order.
+///
+/// # Examples
+///
+/// ```ignore
+/// let report = CollectionRunner::new(&core, collection_id)
+///     .with_iterations(3)
+///     .with_delay(Duration::from_millis(100))
+///     .with_stop_on_failure(true)
+///     .run()
+///     .await?;
+/// ```
+pub struct CollectionRunner<'a> {
+    core: &'a ReqForgeCore,
+    collection_id: Uuid,
+    folder_id: Option<Uuid>,
+    iterations: Option<usize>,
+    delay: Duration,
+    stop_on_failure: bool,
+    data: Vec<HashMap<String, String>>,
+}
+
+impl<'a> CollectionRunner<'a> {
+    pub fn new(core: &'a ReqForgeCore, collection_id: Uuid) -> Self {
+        Self {
+            core,
+            collection_id,
+            folder_id: None,
+            iterations: None,
+            delay: Duration::ZERO,
+            stop_on_failure: false,
+            data: Vec::new(),
+        }
+    }
+
+    /// Only run the requests inside this folder (and its subfolders).
+    pub fn with_folder(mut self, folder_id: Uuid) -> Self {
+        self.folder_id = Some(folder_id);
+        self
+    }
+
+    /// Number of times to run the collection.
+    ///
+    /// Defaults to the number of data rows, or 1 without a data file.
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = Some(iterations.max(1));
+        self
+    }
+
+    /// Pause between consecutive requests.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Stop the run at the first failed request.
+    pub fn with_stop_on_failure(mut self, stop_on_failure: bool) -> Self {
+        self.stop_on_failure = stop_on_failure;
+        self
+    }
+
+    /// Per-iteration variable rows. Iteration `i` uses row `i % rows.len()`.
+    pub fn with_data(mut self, rows: Vec<HashMap<String, String>>) -> Self {
+        self.data = rows;
+        self
+    }
+
+    /// Execute the run and return the aggregate report.
+    pub async fn run(&self) -> Result<RunReport, RunnerError> {
+        self.run_with_progress(|_| {}).await
+    }
+
+    /// Execute the run, invoking `on_result` after every request.
+    pub async fn run_with_progress<F>(&self, mut on_result: F) -> Result<RunReport, RunnerError>
+    where
+        F: FnMut(&RequestRunResult),
+    {
+        let collection = self
+            .core
+            .collections
+            .iter()
+            .find(|c| c.id == self.collection_id)
+            .ok_or(RunnerError::CollectionNotFound(self.collection_id))?;
+
+        let requests = match self.folder_id {
+            Some(folder_id) => collection
+                .ordered_requests_in_folder(folder_id)
+                .ok_or(RunnerError::FolderNotFound(folder_id))?,
+            None => collection.ordered_requests(),
+        };
+
+        let iterations = self.iterations.unwrap_or(self.data.len().max(1));
+        let started_at = chrono::Utc::now();
+        let start = Instant::now();
+        let mut results = Vec::new();
+        let mut stopped_early = false;
+        let empty_row = HashMap::new();
+
+        'iterations: for iteration in 0..iterations {
+            let row = if self.data.is_empty() {
+                &empty_row
+            } else {
+                &self.data[iteration % self.data.len()]
+            };
+
+            for (req, folder_path) in &requests {
+                if !results.is_empty() && !self.delay.is_zero() {
+                    tokio::time::sleep(self.delay).await;
+                }
+
+                let outcome = self.core.execute_with_overrides(req, row).await;
+                let result = match outcome {
+                    Ok((response, test_report)) => RequestRunResult {
+                        iteration,
+                        request_id: req.id,
+                        request_name: req.name.clone(),
+                        folder_path: folder_path.clone(),
+                        method: req.method.clone(),
+                        url: req.url.clone(),
+                        status: Some(response.status),
+                        elapsed_millis: response.elapsed.as_millis() as u64,
+                        test_report,
+                        error: None,
+                    },
+                    Err(e) => RequestRunResult {
+                        iteration,
+                        request_id: req.id,
+                        request_name: req.name.clone(),
+                        folder_path: folder_path.clone(),
+                        method: req.method.clone(),
+                        url: req.url.clone(),
+                        status: None,
+                        elapsed_millis: 0,
+                        test_report: TestReport::default(),
+                        error: Some(e.to_string()),
+                    },
+                };
+
+                on_result(&result);
+                let failed = !result.passed();
+                results.push(result);
+
+                if failed && self.stop_on_failure {
+                    stopped_early = true;
+                    break 'iterations;
+                }
+            }
+        }
+
+        Ok(RunReport {
+            collection_id: collection.id,
+            collection_name: collection.name.clone(),
+            started_at,
+            duration_millis: start.elapsed().as_millis() as u64,
+            iterations,
+            results,
+            stopped_early,
+        })
+    }
+}
+
+/// Load per-iteration data rows from a CSV (header row required) or JSON
+/// (array of objects) file, chosen by extension.
+pub fn load_data_file(path: impl AsRef<Path>) -> Result<Vec<HashMap<String, String>>, RunnerError> {
+    let path = path.as_ref();
+    let is_json = path
+        .extension()
+        .map(|e| e.eq_ignore_ascii_case("json"))
+        .unwrap_or(false);
+
+    let content = std::fs::read_to_string(path)?;
+    if is_json {
+        parse_json_rows(&content)
+    } else {
+        parse_csv_rows(&content)
+    }
+}
+
+fn parse_csv_rows(content: &str) -> Result<Vec<HashMap<String, String>>, RunnerError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::Headers)
+        .from_reader(content.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| RunnerError::DataFile(e.to_string()))?
+        .clone();
+
+    reader
+        .records()
+        .map(|record| {
+            let record = record.map_err(|e| RunnerError::DataFile(e.to_string()))?;
+            Ok(headers
+                .iter()
+                .zip(record.iter())
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect())
+        })
+        .collect()
+}
+
+fn parse_json_rows(content: &str) -> Result<Vec<HashMap<String, String>>, RunnerError> {
+    let value: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| RunnerError::DataFile(e.to_string()))?;
+    let rows = value
+        .as_array()
+        .ok_or_else(|| RunnerError::DataFile("JSON data file must be an array of objects".to_string()))?;
+
+    rows.iter()
+        .map(|row| {
+            let obj = row
+                .as_object()
+                .ok_or_else(|| RunnerError::DataFile("JSON data rows must be objects".to_string()))?;
+            Ok(obj
+                .iter()
+                .map(|(k, v)| {
+                    let value = match v {
+                        serde_json::Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    };
+                    (k.clone(), value)
+                })
+                .collect())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::assertion::Assertion;
+    use crate::models::collection::Collection;
+    use crate::models::folder::{CollectionItem, Folder};
+    use crate::models::request::RequestDefinition;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_parse_csv_rows() {
+        let rows = parse_csv_rows("user, id\nalice,1\nbob,2\n").unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].get("user").map(String::as_str), Some("alice"));
+        assert_eq!(rows[1].get("id").map(String::as_str), Some("2"));
+    }
+
+    #[test]
+    fn test_parse_json_rows() {
+        let rows = parse_json_rows(r#"[{"user": "alice", "id": 1}, {"user": "bob", "id": 2}]"#).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].get("id").map(String::as_str), Some("1"));
+        assert!(parse_json_rows(r#"{"user": "alice"}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_walks_folders_in_order_with_data_rows() {
+        let temp = tempfile::tempdir().unwrap();
+        let mut core = ReqForgeCore::open(temp.path()).unwrap();
+        let server = MockServer::start().await;
+
+        for user in ["alice", "bob"] {
+            Mock::given(method("GET"))
+                .and(path("/users"))
+                .and(query_param("name", user))
+                .respond_with(ResponseTemplate::new(200))
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path("/health"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let mut collection = Collection::new("Smoke");
+        let folder_id = Uuid::new_v4();
+        collection.tree.push(CollectionItem::Folder(Box::new(Folder {
+            id: folder_id,
+            name: "Users".to_string(),
+            children: Vec::new(),
+            auth: Default::default(),
+            variables: Vec::new(),
+        })));
+        let health = RequestDefinition::new("Health", HttpMethod::GET, format!("{}/health", server.uri()));
+        let mut lookup = RequestDefinition::new(
+            "Lookup",
+            HttpMethod::GET,
+            format!("{}/users?name={{{{user}}}}", server.uri()),
+        );
+        lookup.assertions.push(Assertion::StatusEquals(200));
+        collection.add_request(lookup, Some(folder_id));
+        collection.add_request(health, None);
+        let collection_id = collection.id;
+        core.collections.push(collection);
+
+        let mut seen = Vec::new();
+        let report = CollectionRunner::new(&core, collection_id)
+            .with_data(vec![
+                HashMap::from([("user".to_string(), "alice".to_string())]),
+                HashMap::from([("user".to_string(), "bob".to_string())]),
+            ])
+            .run_with_progress(|r| seen.push(r.request_name.clone()))
+            .await
+            .unwrap();
+
+        assert_eq!(report.iterations, 2);
+        assert_eq!(seen, vec!["Lookup", "Health", "Lookup", "Health"]);
+        assert_eq!(report.results[0].folder_path, vec!["Users".to_string()]);
+        assert!(report.all_passed(), "{:?}", report.results);
+        assert_eq!(report.passed_assertions(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stop_on_failure() {
+        let temp = tempfile::tempdir().unwrap();
+        let mut core = ReqForgeCore::open(temp.path()).unwrap();
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+
+        let mut collection = Collection::new("Failing");
+        for name in ["First", "Second"] {
+            let mut req = RequestDefinition::new(name, HttpMethod::GET, server.uri());
+            req.assertions.push(Assertion::StatusEquals(200));
+            collection.add_request(req, None);
+        }
+        let collection_id = collection.id;
+        core.collections.push(collection);
+
+        let report = CollectionRunner::new(&core, collection_id)
+            .with_iterations(3)
+            .with_stop_on_failure(true)
+            .run()
+            .await
+            .unwrap();
+
+        assert!(report.stopped_early);
+        assert_eq!(report.total_requests(), 1);
+        assert_eq!(report.failed_requests(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_folder() {
+        let temp = tempfile::tempdir().unwrap();
+        let mut core = ReqForgeCore::open(temp.path()).unwrap();
+        let collection = Collection::new("Empty");
+        let collection_id = collection.id;
+        core.collections.push(collection);
+
+        let result = CollectionRunner::new(&core, collection_id)
+            .with_folder(Uuid::new_v4())
+            .run()
+            .await;
+        assert!(matches!(result, Err(RunnerError::FolderNotFound(_))));
+    }
+}
@@ -0,0 +1,186 @@
+//! JUnit XML reporter for collection runs.
+//!
+//! Each executed request becomes a `<testsuite>` and each of its assertions a
+//! `<testcase>`, which is the layout most CI systems expect.
+
+use std::fmt::Write;
+
+use super::{RequestRunResult, RunReport};
+
+/// Render a run report as a JUnit XML document.
+pub fn to_junit_xml(report: &RunReport) -> String {
+    let mut xml = String::new();
+    let total_tests: usize = report.results.iter().map(test_count).sum();
+    let total_failures: usize = report.results.iter().map(|r| r.test_report.failed_count()).sum();
+    let total_errors = report.results.iter().filter(|r| r.error.is_some()).count();
+
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{}\">",
+        escape(&report.collection_name),
+        total_tests,
+        total_failures,
+        total_errors,
+        seconds(report.duration_millis),
+    );
+
+    for result in &report.results {
+        write_suite(&mut xml, report, result);
+    }
+
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn write_suite(xml: &mut String, report: &RunReport, result: &RequestRunResult) {
+    let suite_name = suite_name(report, result);
+    let time = seconds(result.elapsed_millis);
+    let _ = writeln!(
+        xml,
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{}\" timestamp=\"{}\">",
+        escape(&suite_name),
+        test_count(result),
+        result.test_report.failed_count(),
+        usize::from(result.error.is_some()),
+        time,
+        report.started_at.to_rfc3339(),
+    );
+
+    if let Some(error) = &result.error {
+        let _ = writeln!(
+            xml,
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"{}\">",
+            escape(&format!("{} {}", result.method, result.url)),
+            escape(&suite_name),
+            time,
+        );
+        let _ = writeln!(xml, "      <error message=\"{}\"/>", escape(error));
+        xml.push_str("    </testcase>\n");
+    } else if result.test_report.is_empty() {
+        let _ = writeln!(
+            xml,
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"{}\"/>",
+            escape(&format!("{} {}", result.method, result.url)),
+            escape(&suite_name),
+            time,
+        );
+    }
+
+    for assertion in &result.test_report.results {
+        let name = escape(&assertion.assertion.to_string());
+        if assertion.passed {
+            let _ = writeln!(xml, "    <testcase name=\"{}\" classname=\"{}\"/>", name, escape(&suite_name));
+        } else {
+            let message = assertion.message.as_deref().unwrap_or("assertion failed");
+            let _ = writeln!(xml, "    <testcase name=\"{}\" classname=\"{}\">", name, escape(&suite_name));
+            let _ = writeln!(xml, "      <failure message=\"{}\"/>", escape(message));
+            xml.push_str("    </testcase>\n");
+        }
+    }
+
+    xml.push_str("  </testsuite>\n");
+}
+
+fn suite_name(report: &RunReport, result: &RequestRunResult) -> String {
+    let mut parts = result.folder_path.clone();
+    parts.push(result.request_name.clone());
+    let name = parts.join(" / ");
+    if report.iterations > 1 {
+        format!("{} [iteration {}]", name, result.iteration + 1)
+    } else {
+        name
+    }
+}
+
+fn test_count(result: &RequestRunResult) -> usize {
+    let assertions = result.test_report.results.len();
+    if result.error.is_some() {
+        assertions + 1
+    } else {
+        assertions.max(1)
+    }
+}
+
+fn seconds(millis: u64) -> String {
+    format!("{:.3}", millis as f64 / 1000.0)
+}
+
+fn escape(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c if (c as u32) < 0x20 && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::assertion::{Assertion, AssertionResult, TestReport};
+    use crate::models::request::HttpMethod;
+    use uuid::Uuid;
+
+    fn result(name: &str, report: TestReport, error: Option<&str>) -> RequestRunResult {
+        RequestRunResult {
+            iteration: 0,
+            request_id: Uuid::new_v4(),
+            request_name: name.to_string(),
+            folder_path: vec!["Users".to_string()],
+            method: HttpMethod::GET,
+            url: "https://example.com/?a=1&b=2".to_string(),
+            status: error.is_none().then_some(200),
+            elapsed_millis: 1500,
+            test_report: report,
+            error: error.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_junit_xml_counts_and_escaping() {
+        let failing = TestReport {
+            results: vec![
+                AssertionResult {
+                    assertion: Assertion::StatusEquals(200),
+                    passed: true,
+                    actual: Some("200".to_string()),
+                    message: None,
+                },
+                AssertionResult {
+                    assertion: Assertion::BodyContains("<ok>".to_string()),
+                    passed: false,
+                    actual: None,
+                    message: Some("body does not contain '<ok>'".to_string()),
+                },
+            ],
+        };
+        let report = RunReport {
+            collection_id: Uuid::new_v4(),
+            collection_name: "Smoke & Sanity".to_string(),
+            started_at: chrono::Utc::now(),
+            duration_millis: 2000,
+            iterations: 1,
+            results: vec![
+                result("List", failing, None),
+                result("Down", TestReport::default(), Some("connection refused")),
+            ],
+            stopped_early: false,
+        };
+
+        let xml = to_junit_xml(&report);
+        assert!(xml.contains("<testsuites name=\"Smoke &amp; Sanity\" tests=\"3\" failures=\"1\" errors=\"1\" time=\"2.000\">"));
+        assert!(xml.contains("<testsuite name=\"Users / List\""));
+        assert!(xml.contains("<failure message=\"body does not contain &apos;&lt;ok&gt;&apos;\"/>"));
+        assert!(xml.contains("<error message=\"connection refused\"/>"));
+        assert!(xml.contains("?a=1&amp;b=2"));
+        assert_eq!(xml.matches("<testsuite ").count(), 2);
+    }
+}
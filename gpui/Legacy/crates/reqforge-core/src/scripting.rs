@@ -0,0 +1,549 @@
+//! Pre-request and post-response scripting for reqforge-core.
+//!
+//! Scripts are written in [Rhai](https://rhai.rs) and run in a sandboxed engine
+//! with no file system or network access and bounded CPU/memory usage.
+//!
+//! Scripts see the following bindings:
+//! - `vars` — map of the current variables; assignments are written back
+//!   (e.g. `vars.token = response.json.access_token;`)
+//! - `request` — map with `name`, `method`, `url`, `headers`, `query` and `body`;
+//!   changes made in a pre-request script are applied to the outgoing request.
+//!   A header or query key that appears more than once shows its last value,
+//!   and all of its values are sent unless the script assigns or removes it
+//! - `response` — post-response only: `status`, `status_text`, `headers`,
+//!   `body`, `json` (parsed body or `()`) and `elapsed_ms`
+//!
+//! Helper functions: `sha256_hex`, `hmac_sha256_hex`, `hmac_sha256_base64`,
+//! `base64_encode`, `base64_decode`, `uuid`, `timestamp` and `timestamp_ms`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use base64::Engine as _;
+use hmac::{Hmac, Mac};
+use rhai::{Dynamic, Engine, EvalAltResult, Map, Scope};
+use sha2::{Digest, Sha256};
+
+use crate::models::request::{BodyType, KeyValuePair, RequestDefinition};
+use crate::models::response::HttpResponse;
+
+const DEFAULT_MAX_OPERATIONS: u64 = 500_000;
+const MAX_STRING_SIZE: usize = 16 * 1024 * 1024;
+const MAX_COLLECTION_SIZE: usize = 100_000;
+
+/// The point in the request lifecycle at which a script runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptPhase {
+    PreRequest,
+    PostResponse,
+}
+
+impl std::fmt::Display for ScriptPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScriptPhase::PreRequest => write!(f, "Pre-request"),
+            ScriptPhase::PostResponse => write!(f, "Post-response"),
+        }
+    }
+}
+
+/// Errors raised while evaluating a script.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ScriptError {
+    #[error("{phase} script error: {message}")]
+    Execution { phase: ScriptPhase, message: String },
+
+    #[error("{phase} script replaced `{binding}` with a non-map value")]
+    InvalidBinding { phase: ScriptPhase, binding: &'static str },
+}
+
+/// Result of a successful script run.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptOutcome {
+    /// Variables the script added or changed.
+    pub updated_variables: HashMap<String, String>,
+    /// Variables the script removed from `vars`.
+    pub removed_variables: Vec<String>,
+    /// Lines written with `print` or `debug`.
+    pub logs: Vec<String>,
+}
+
+/// Sandboxed Rhai runner for request scripts.
+#[derive(Debug, Clone)]
+pub struct ScriptEngine {
+    max_operations: u64,
+}
+
+impl Default for ScriptEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScriptEngine {
+    pub fn new() -> Self {
+        Self { max_operations: DEFAULT_MAX_OPERATIONS }
+    }
+
+    /// Limit the number of operations a single script may perform.
+    pub fn with_max_operations(mut self, max_operations: u64) -> Self {
+        self.max_operations = max_operations;
+        self
+    }
+
+    /// Run a pre-request script.
+    ///
+    /// The script may mutate `req` (URL, headers, query params and raw body)
+    /// and `vars`. Runs before interpolation, so variables it sets are
+    /// visible to `{{placeholders}}` in the request.
+    pub fn run_pre_request(
+        &self,
+        script: &str,
+        req: &mut RequestDefinition,
+        vars: &mut HashMap<String, String>,
+    ) -> Result<ScriptOutcome, ScriptError> {
+        let phase = ScriptPhase::PreRequest;
+        let mut scope = Scope::new();
+        scope.push("vars", to_map(vars));
+        scope.push("request", request_to_map(req));
+
+        let logs = self.run(phase, script, &mut scope)?;
+
+        let request = take_map(&mut scope, phase, "request")?;
+        apply_request_map(req, request);
+        let (updated_variables, removed_variables) = merge_vars(vars, take_map(&mut scope, phase, "vars")?);
+
+        Ok(ScriptOutcome { updated_variables, removed_variables, logs })
+    }
+
+    /// Run a post-response script against the resolved request and its response.
+    pub fn run_post_response(
+        &self,
+        script: &str,
+        req: &RequestDefinition,
+        response: &HttpResponse,
+        vars: &mut HashMap<String, String>,
+    ) -> Result<ScriptOutcome, ScriptError> {
+        let phase = ScriptPhase::PostResponse;
+        let mut scope = Scope::new();
+        scope.push("vars", to_map(vars));
+        scope.push_constant("request", request_to_map(req));
+        scope.push_constant("response", response_to_map(response));
+
+        let logs = self.run(phase, script, &mut scope)?;
+        let (updated_variables, removed_variables) = merge_vars(vars, take_map(&mut scope, phase, "vars")?);
+
+        Ok(ScriptOutcome { updated_variables, removed_variables, logs })
+    }
+
+    fn run(&self, phase: ScriptPhase, script: &str, scope: &mut Scope) -> Result<Vec<String>, ScriptError> {
+        let logs = Arc::new(Mutex::new(Vec::new()));
+        let engine = self.build_engine(logs.clone());
+
+        engine
+            .run_with_scope(scope, script)
+            .map_err(|e| ScriptError::Execution { phase, message: e.to_string() })?;
+
+        let logs = logs.lock().map(|l| l.clone()).unwrap_or_default();
+        Ok(logs)
+    }
+
+    fn build_engine(&self, logs: Arc<Mutex<Vec<String>>>) -> Engine {
+        let mut engine = Engine::new();
+        engine.set_max_operations(self.max_operations);
+        engine.set_max_call_levels(32);
+        engine.set_max_expr_depths(64, 32);
+        engine.set_max_string_size(MAX_STRING_SIZE);
+        engine.set_max_array_size(MAX_COLLECTION_SIZE);
+        engine.set_max_map_size(MAX_COLLECTION_SIZE);
+        engine.disable_symbol("eval");
+
+        let print_logs = logs.clone();
+        engine.on_print(move |s| {
+            if let Ok(mut l) = print_logs.lock() {
+                l.push(s.to_string());
+            }
+        });
+        engine.on_debug(move |s, _, _| {
+            if let Ok(mut l) = logs.lock() {
+                l.push(s.to_string());
+            }
+        });
+
+        engine.register_fn("sha256_hex", |input: &str| -> String {
+            hex_encode(&Sha256::digest(input.as_bytes()))
+        });
+        engine.register_fn("hmac_sha256_hex", |key: &str, message: &str| -> String {
+            hex_encode(&hmac_sha256(key, message))
+        });
+        engine.register_fn("hmac_sha256_base64", |key: &str, message: &str| -> String {
+            base64::engine::general_purpose::STANDARD.encode(hmac_sha256(key, message))
+        });
+        engine.register_fn("base64_encode", |input: &str| -> String {
+            base64::engine::general_purpose::STANDARD.encode(input.as_bytes())
+        });
+        engine.register_fn("base64_decode", |input: &str| -> Result<String, Box<EvalAltResult>> {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(input.trim())
+                .map_err(|e| e.to_string())?;
+            String::from_utf8(bytes).map_err(|e| e.to_string().into())
+        });
+        engine.register_fn("uuid", || -> String { uuid::Uuid::new_v4().to_string() });
+        engine.register_fn("timestamp", || -> i64 { chrono::Utc::now().timestamp() });
+        engine.register_fn("timestamp_ms", || -> i64 { chrono::Utc::now().timestamp_millis() });
+
+        engine
+    }
+}
+
+fn hmac_sha256(key: &str, message: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(message.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn to_map(values: &HashMap<String, String>) -> Map {
+    values
+        .iter()
+        .map(|(k, v)| (k.as_str().into(), Dynamic::from(v.clone())))
+        .collect()
+}
+
+fn pairs_to_map(pairs: &[KeyValuePair]) -> Map {
+    pairs
+        .iter()
+        .filter(|p| p.enabled)
+        .map(|p| (p.key.as_str().into(), Dynamic::from(p.value.clone())))
+        .collect()
+}
+
+fn request_to_map(req: &RequestDefinition) -> Map {
+    let body = match &req.body {
+        BodyType::Raw { content, .. } => Dynamic::from(content.clone()),
+        _ => Dynamic::UNIT,
+    };
+
+    let mut map = Map::new();
+    map.insert("name".into(), Dynamic::from(req.name.clone()));
+    map.insert("method".into(), Dynamic::from(req.method.to_string()));
+    map.insert("url".into(), Dynamic::from(req.url.clone()));
+    map.insert("headers".into(), Dynamic::from_map(pairs_to_map(&req.headers)));
+    map.insert("query".into(), Dynamic::from_map(pairs_to_map(&req.query_params)));
+    map.insert("body".into(), body);
+    map
+}
+
+fn response_to_map(response: &HttpResponse) -> Map {
+    let headers: Map = response
+        .headers
+        .iter()
+        .map(|(k, v)| (k.as_str().into(), Dynamic::from(v.clone())))
+        .collect();
+    let json = response
+        .body_text()
+        .and_then(|text| serde_json::from_str::<serde_json::Value>(text).ok())
+        .and_then(|value| rhai::serde::to_dynamic(value).ok())
+        .unwrap_or(Dynamic::UNIT);
+
+    let mut map = Map::new();
+    map.insert("status".into(), Dynamic::from(response.status as i64));
+    map.insert("status_text".into(), Dynamic::from(response.status_text.clone()));
+    map.insert("headers".into(), Dynamic::from_map(headers));
+    map.insert("body".into(), Dynamic::from(response.body_text().unwrap_or("").to_string()));
+    map.insert("json".into(), json);
+    map.insert("elapsed_ms".into(), Dynamic::from(response.elapsed.as_millis() as i64));
+    map
+}
+
+fn take_map(scope: &mut Scope, phase: ScriptPhase, binding: &'static str) -> Result<Map, ScriptError> {
+    scope
+        .get_value::<Map>(binding)
+        .ok_or(ScriptError::InvalidBinding { phase, binding })
+}
+
+/// Merge script variables back into `vars`, returning the ones that changed
+/// and the keys the script removed.
+fn merge_vars(vars: &mut HashMap<String, String>, map: Map) -> (HashMap<String, String>, Vec<String>) {
+    let mut removed: Vec<String> = vars.keys().filter(|key| !map.contains_key(key.as_str())).cloned().collect();
+    removed.sort();
+    for key in &removed {
+        vars.remove(key);
+    }
+    let mut updated = HashMap::new();
+    for (key, value) in map {
+        let key = key.to_string();
+        let value = dynamic_to_string(value);
+        if vars.get(&key) != Some(&value) {
+            vars.insert(key.clone(), value.clone());
+            updated.insert(key, value);
+        }
+    }
+    (updated, removed)
+}
+
+fn dynamic_to_string(value: Dynamic) -> String {
+    if value.is_unit() {
+        String::new()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Apply a script-modified map back onto enabled pairs, touching only the
+/// keys the script changed.
+///
+/// The map holds one value per key, so repeated keys (`?tag=a&tag=b`) are
+/// left as they are unless the script assigns or removes that key. A changed
+/// key updates its first enabled pair (keeping its description) and drops
+/// the other enabled ones, removed keys drop all of their enabled pairs and
+/// new keys are appended. Disabled pairs are left untouched.
+fn apply_pairs(pairs: &mut Vec<KeyValuePair>, map: Map) {
+    let before = pairs_to_map(pairs);
+    let mut changed: Vec<(String, Option<String>)> = before
+        .keys()
+        .filter(|key| !map.contains_key(*key))
+        .map(|key| (key.to_string(), None))
+        .collect();
+    let mut added = Vec::new();
+    for (key, value) in map {
+        let value = dynamic_to_string(value);
+        match before.get(&key) {
+            Some(old) if dynamic_to_string(old.clone()) == value => {}
+            Some(_) => changed.push((key.to_string(), Some(value))),
+            None => added.push(KeyValuePair { key: key.to_string(), value, enabled: true, description: None }),
+        }
+    }
+
+    for (key, value) in changed {
+        let mut value = value;
+        pairs.retain_mut(|pair| {
+            if !pair.enabled || pair.key != key {
+                return true;
+            }
+            // The first pair takes the new value; any others are dropped
+            match value.take() {
+                Some(value) => {
+                    pair.value = value;
+                    true
+                }
+                None => false,
+            }
+        });
+    }
+    pairs.extend(added);
+}
+
+fn apply_request_map(req: &mut RequestDefinition, mut map: Map) {
+    if let Some(url) = map.remove("url") {
+        req.url = dynamic_to_string(url);
+    }
+    if let Some(headers) = map.remove("headers").and_then(|h| h.try_cast::<Map>()) {
+        apply_pairs(&mut req.headers, headers);
+    }
+    if let Some(query) = map.remove("query").and_then(|q| q.try_cast::<Map>()) {
+        apply_pairs(&mut req.query_params, query);
+    }
+    if let Some(body) = map.remove("body")
+        && let BodyType::Raw { content, .. } = &mut req.body
+    {
+        *content = dynamic_to_string(body);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::request::{HttpMethod, RawContentType};
+    use bytes::Bytes;
+    use std::time::Duration;
+
+    fn test_response(body: &str) -> HttpResponse {
+        let mut headers = HashMap::new();
+        headers.insert("x-request-id".to_string(), "abc".to_string());
+        HttpResponse {
+            status: 200,
+            status_text: "OK".to_string(),
+            headers,
+            body: Bytes::from(body.to_string()),
+            size_bytes: body.len(),
+            elapsed: Duration::from_millis(12),
+            timing: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_pre_request_sets_header_and_variable() {
+        let mut req = RequestDefinition::new("Test", HttpMethod::POST, "https://example.com");
+        req.body = BodyType::Raw {
+            content: "{\"a\":1}".to_string(),
+            content_type: RawContentType::Json,
+        };
+        let mut vars = HashMap::new();
+        vars.insert("secret".to_string(), "key".to_string());
+
+        let outcome = ScriptEngine::new()
+            .run_pre_request(
+                r#"
+                    request.headers["X-Signature"] = hmac_sha256_hex(vars.secret, request.body);
+                    vars.nonce = "n-1";
+                    print("signed");
+                "#,
+                &mut req,
+                &mut vars,
+            )
+            .unwrap();
+
+        let sig = req.headers.iter().find(|h| h.key == "X-Signature").unwrap();
+        assert_eq!(sig.value, hex_encode(&hmac_sha256("key", "{\"a\":1}")));
+        assert_eq!(vars.get("nonce").map(String::as_str), Some("n-1"));
+        assert_eq!(outcome.updated_variables.len(), 1);
+        assert_eq!(outcome.logs, vec!["signed".to_string()]);
+    }
+
+    #[test]
+    fn test_pre_request_preserves_disabled_headers() {
+        let mut req = RequestDefinition::new("Test", HttpMethod::GET, "https://example.com");
+        req.headers.push(KeyValuePair {
+            key: "X-Off".to_string(),
+            value: "1".to_string(),
+            enabled: false,
+            description: None,
+        });
+        req.headers.push(KeyValuePair {
+            key: "X-Drop".to_string(),
+            value: "1".to_string(),
+            enabled: true,
+            description: None,
+        });
+        let mut vars = HashMap::new();
+
+        ScriptEngine::new()
+            .run_pre_request(r#"request.headers.remove("X-Drop"); request.url += "/v2";"#, &mut req, &mut vars)
+            .unwrap();
+
+        assert_eq!(req.url, "https://example.com/v2");
+        assert_eq!(req.headers.len(), 1);
+        assert_eq!(req.headers[0].key, "X-Off");
+    }
+
+    #[test]
+    fn test_pre_request_keeps_repeated_keys_it_does_not_change() {
+        let pair = |key: &str, value: &str| KeyValuePair {
+            key: key.to_string(),
+            value: value.to_string(),
+            enabled: true,
+            description: None,
+        };
+        let mut req = RequestDefinition::new("Test", HttpMethod::GET, "https://example.com");
+        req.query_params = vec![pair("tag", "a"), pair("tag", "b"), pair("id", "1"), pair("id", "2")];
+        req.headers = vec![pair("Accept", "text/html"), pair("Accept", "application/json")];
+        let mut vars = HashMap::new();
+
+        ScriptEngine::new()
+            .run_pre_request(r#"request.query.id = "3"; request.query.page = "1";"#, &mut req, &mut vars)
+            .unwrap();
+
+        let query: Vec<(&str, &str)> = req.query_params.iter().map(|p| (p.key.as_str(), p.value.as_str())).collect();
+        assert_eq!(query, vec![("tag", "a"), ("tag", "b"), ("id", "3"), ("page", "1")]);
+        assert_eq!(req.headers.len(), 2);
+    }
+
+    #[test]
+    fn test_post_response_extracts_json_value() {
+        let req = RequestDefinition::new("Login", HttpMethod::POST, "https://example.com/login");
+        let response = test_response(r#"{"access_token":"tok-123","expires_in":3600}"#);
+        let mut vars = HashMap::new();
+
+        let outcome = ScriptEngine::new()
+            .run_post_response(
+                r#"
+                    if response.status == 200 {
+                        vars.token = response.json.access_token;
+                        vars.expires = response.json.expires_in;
+                        vars.request_id = response.headers["x-request-id"];
+                    }
+                "#,
+                &req,
+                &response,
+                &mut vars,
+            )
+            .unwrap();
+
+        assert_eq!(vars.get("token").map(String::as_str), Some("tok-123"));
+        assert_eq!(vars.get("expires").map(String::as_str), Some("3600"));
+        assert_eq!(vars.get("request_id").map(String::as_str), Some("abc"));
+        assert_eq!(outcome.updated_variables.len(), 3);
+    }
+
+    #[test]
+    fn test_script_removes_variables() {
+        let req = RequestDefinition::new("Logout", HttpMethod::POST, "https://example.com/logout");
+        let response = test_response("{}");
+        let mut vars = HashMap::new();
+        vars.insert("token".to_string(), "tok-123".to_string());
+        vars.insert("user".to_string(), "alice".to_string());
+
+        let outcome = ScriptEngine::new()
+            .run_post_response(r#"vars.remove("token");"#, &req, &response, &mut vars)
+            .unwrap();
+
+        assert!(!vars.contains_key("token"));
+        assert_eq!(vars.get("user").map(String::as_str), Some("alice"));
+        assert_eq!(outcome.removed_variables, vec!["token".to_string()]);
+        assert!(outcome.updated_variables.is_empty());
+    }
+
+    #[test]
+    fn test_script_error_reports_phase() {
+        let mut req = RequestDefinition::new("Test", HttpMethod::GET, "https://example.com");
+        let mut vars = HashMap::new();
+
+        let err = ScriptEngine::new()
+            .run_pre_request("let x = ;", &mut req, &mut vars)
+            .unwrap_err();
+
+        assert!(matches!(err, ScriptError::Execution { phase: ScriptPhase::PreRequest, .. }));
+    }
+
+    #[test]
+    fn test_operation_limit_stops_infinite_loop() {
+        let mut req = RequestDefinition::new("Test", HttpMethod::GET, "https://example.com");
+        let mut vars = HashMap::new();
+
+        let result = ScriptEngine::new()
+            .with_max_operations(1_000)
+            .run_pre_request("loop { }", &mut req, &mut vars);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_helper_functions() {
+        let mut req = RequestDefinition::new("Test", HttpMethod::GET, "https://example.com");
+        let mut vars = HashMap::new();
+
+        ScriptEngine::new()
+            .run_pre_request(
+                r#"
+                    vars.hash = sha256_hex("abc");
+                    vars.round_trip = base64_decode(base64_encode("hello"));
+                    vars.has_uuid = uuid().len() == 36;
+                "#,
+                &mut req,
+                &mut vars,
+            )
+            .unwrap();
+
+        assert_eq!(
+            vars.get("hash").map(String::as_str),
+            Some("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+        assert_eq!(vars.get("round_trip").map(String::as_str), Some("hello"));
+        assert_eq!(vars.get("has_uuid").map(String::as_str), Some("true"));
+    }
+}
@@ -0,0 +1,323 @@
+//! Encryption for secret variable values.
+//!
+//! Values are sealed with AES-256-GCM. The key is either random and kept in
+//! a local keyfile, or derived from a passphrase with PBKDF2-HMAC-SHA256.
+//! `JsonStore` and `SqliteStore` use this to keep secret environment, global
+//! and collection variables out of the workspace files; exports use the
+//! self-describing `enc:v1:` format so a file can be decrypted with just the
+//! passphrase.
+
+use std::collections::{BTreeMap, HashMap};
+use std::num::NonZeroU32;
+
+use aws_lc_rs::aead::{Aad, Nonce, RandomizedNonceKey, AES_256_GCM, NONCE_LEN};
+use aws_lc_rs::pbkdf2;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::environment::Variable;
+
+/// PBKDF2 iterations used for new passphrase-derived keys.
+pub const DEFAULT_ITERATIONS: u32 = 600_000;
+
+/// Prefix of values encrypted with [`PassphraseCipher`].
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+/// Known plaintext sealed into `SecretsFile::check` to verify a passphrase.
+const CHECK_VALUE: &str = "reqforge-secrets";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SecretError {
+    #[error("Secrets are locked; unlock them with the workspace passphrase first")]
+    Locked,
+    #[error("Failed to decrypt secret (wrong passphrase or corrupted data)")]
+    Decrypt,
+    #[error("Failed to encrypt secret")]
+    Encrypt,
+    #[error("Invalid secret data: {0}")]
+    Invalid(String),
+}
+
+/// A 256-bit key for sealing secret values.
+#[derive(Clone)]
+pub struct SecretKey([u8; KEY_LEN]);
+
+impl std::fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretKey(..)")
+    }
+}
+
+impl SecretKey {
+    pub fn generate() -> Result<Self, SecretError> {
+        let mut bytes = [0u8; KEY_LEN];
+        aws_lc_rs::rand::fill(&mut bytes).map_err(|_| SecretError::Encrypt)?;
+        Ok(Self(bytes))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SecretError> {
+        let bytes: [u8; KEY_LEN] = bytes
+            .try_into()
+            .map_err(|_| SecretError::Invalid(format!("key must be {} bytes", KEY_LEN)))?;
+        Ok(Self(bytes))
+    }
+
+    pub fn from_passphrase(passphrase: &str, salt: &[u8], iterations: u32) -> Result<Self, SecretError> {
+        let iterations = NonZeroU32::new(iterations)
+            .ok_or_else(|| SecretError::Invalid("iteration count must be positive".into()))?;
+        let mut bytes = [0u8; KEY_LEN];
+        pbkdf2::derive(pbkdf2::PBKDF2_HMAC_SHA256, iterations, salt, passphrase.as_bytes(), &mut bytes);
+        Ok(Self(bytes))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Seal `plaintext`, returning base64 of nonce followed by ciphertext and tag.
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, SecretError> {
+        let key = RandomizedNonceKey::new(&AES_256_GCM, &self.0).map_err(|_| SecretError::Encrypt)?;
+        let mut data = plaintext.as_bytes().to_vec();
+        let nonce = key
+            .seal_in_place_append_tag(Aad::empty(), &mut data)
+            .map_err(|_| SecretError::Encrypt)?;
+        let mut sealed = nonce.as_ref().to_vec();
+        sealed.extend_from_slice(&data);
+        Ok(STANDARD.encode(sealed))
+    }
+
+    pub fn decrypt(&self, sealed: &str) -> Result<String, SecretError> {
+        let mut data = STANDARD.decode(sealed).map_err(|_| SecretError::Decrypt)?;
+        if data.len() < NONCE_LEN {
+            return Err(SecretError::Decrypt);
+        }
+        let nonce = Nonce::try_assume_unique_for_key(&data[..NONCE_LEN]).map_err(|_| SecretError::Decrypt)?;
+        let key = RandomizedNonceKey::new(&AES_256_GCM, &self.0).map_err(|_| SecretError::Decrypt)?;
+        let plaintext = key
+            .open_in_place(nonce, Aad::empty(), &mut data[NONCE_LEN..])
+            .map_err(|_| SecretError::Decrypt)?;
+        String::from_utf8(plaintext.to_vec()).map_err(|_| SecretError::Decrypt)
+    }
+}
+
+/// Random salt for a new passphrase-derived key.
+pub fn generate_salt() -> Result<Vec<u8>, SecretError> {
+    let mut salt = vec![0u8; SALT_LEN];
+    aws_lc_rs::rand::fill(&mut salt).map_err(|_| SecretError::Encrypt)?;
+    Ok(salt)
+}
+
+/// How the key for `secrets.json` is obtained.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum KeySource {
+    /// A random key stored next to the secrets in `secret.key`.
+    #[default]
+    Keyfile,
+    /// A key derived from a passphrase that must be supplied to unlock.
+    Passphrase { salt: String, iterations: u32 },
+}
+
+/// Contents of `secrets.json`: encrypted secret values, by owner and key.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecretsFile {
+    #[serde(default)]
+    pub key_source: KeySource,
+    /// `CHECK_VALUE` sealed with the passphrase key, to reject wrong passphrases.
+    #[serde(default)]
+    pub check: Option<String>,
+    /// Environment variables, by environment id.
+    #[serde(default)]
+    pub values: BTreeMap<Uuid, BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub globals: BTreeMap<String, String>,
+    /// Collection variables, by collection id.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub collections: BTreeMap<Uuid, BTreeMap<String, String>>,
+    /// Secret engine settings, such as the proxy password, by name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub settings: BTreeMap<String, String>,
+    /// Folder and request variables, by collection id, then folder or request id.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub collection_items: BTreeMap<Uuid, BTreeMap<Uuid, BTreeMap<String, String>>>,
+}
+
+impl SecretsFile {
+    /// Derive the passphrase key, verifying it against `check`.
+    pub fn passphrase_key(&self, passphrase: &str) -> Result<SecretKey, SecretError> {
+        let KeySource::Passphrase { salt, iterations } = &self.key_source else {
+            return Err(SecretError::Invalid("secrets are not protected by a passphrase".into()));
+        };
+        let salt = STANDARD
+            .decode(salt)
+            .map_err(|e| SecretError::Invalid(format!("bad salt: {}", e)))?;
+        let key = SecretKey::from_passphrase(passphrase, &salt, *iterations)?;
+        if let Some(check) = &self.check
+            && key.decrypt(check)? != CHECK_VALUE
+        {
+            return Err(SecretError::Decrypt);
+        }
+        Ok(key)
+    }
+
+    /// Switch to a new passphrase-derived key, returning it.
+    pub fn use_passphrase(&mut self, passphrase: &str) -> Result<SecretKey, SecretError> {
+        let salt = generate_salt()?;
+        let key = SecretKey::from_passphrase(passphrase, &salt, DEFAULT_ITERATIONS)?;
+        self.key_source = KeySource::Passphrase { salt: STANDARD.encode(salt), iterations: DEFAULT_ITERATIONS };
+        self.check = Some(key.encrypt(CHECK_VALUE)?);
+        Ok(key)
+    }
+
+    /// Whether no value is stored at all.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+            && self.globals.is_empty()
+            && self.settings.is_empty()
+            && self.collections.is_empty()
+            && self.collection_items.is_empty()
+    }
+
+    /// Drop a collection's values, returning whether it had any.
+    pub fn remove_collection(&mut self, id: Uuid) -> bool {
+        let removed = self.collections.remove(&id).is_some();
+        self.collection_items.remove(&id).is_some() || removed
+    }
+
+    /// Re-seal every value from `old` to `new`.
+    pub fn rekey(&mut self, old: &SecretKey, new: &SecretKey) -> Result<(), SecretError> {
+        let items = self.collection_items.values_mut().flat_map(|items| items.values_mut());
+        let owners = self.values.values_mut().chain(self.collections.values_mut()).chain(items);
+        for vars in owners.chain([&mut self.globals, &mut self.settings]) {
+            for value in vars.values_mut() {
+                *value = new.encrypt(&old.decrypt(value)?)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Encrypts values for export with a passphrase. Each value carries its salt
+/// and iteration count, so only the passphrase is needed to decrypt it.
+#[derive(Debug)]
+pub struct PassphraseCipher {
+    key: SecretKey,
+    salt: String,
+    iterations: u32,
+}
+
+impl PassphraseCipher {
+    pub fn new(passphrase: &str) -> Result<Self, SecretError> {
+        let salt = generate_salt()?;
+        let key = SecretKey::from_passphrase(passphrase, &salt, DEFAULT_ITERATIONS)?;
+        Ok(Self { key, salt: STANDARD.encode(salt), iterations: DEFAULT_ITERATIONS })
+    }
+
+    pub fn encrypt(&self, value: &str) -> Result<String, SecretError> {
+        Ok(format!("{}{}:{}:{}", ENCRYPTED_PREFIX, self.iterations, self.salt, self.key.encrypt(value)?))
+    }
+}
+
+/// Whether a value was produced by [`PassphraseCipher`].
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
+
+/// Blank the value of every secret variable.
+pub fn strip_secrets(variables: &mut [Variable]) {
+    for var in variables.iter_mut().filter(|v| v.secret) {
+        var.value.clear();
+    }
+}
+
+/// Encrypt the value of every secret variable.
+pub fn encrypt_secrets(variables: &mut [Variable], cipher: &PassphraseCipher) -> Result<(), SecretError> {
+    for var in variables.iter_mut().filter(|v| v.secret && !v.value.is_empty()) {
+        var.value = cipher.encrypt(&var.value)?;
+    }
+    Ok(())
+}
+
+/// Decrypt every `enc:v1:` value in imported variables, returning how many
+/// were decrypted.
+pub fn decrypt_secrets(variables: &mut [Variable], passphrase: &str) -> Result<usize, SecretError> {
+    let mut keys: HashMap<(u32, String), SecretKey> = HashMap::new();
+    let mut decrypted = 0;
+    for var in variables.iter_mut().filter(|v| is_encrypted(&v.value)) {
+        let mut parts = var.value[ENCRYPTED_PREFIX.len()..].splitn(3, ':');
+        let (Some(iterations), Some(salt), Some(sealed)) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(SecretError::Invalid(format!("malformed encrypted value for '{}'", var.key)));
+        };
+        let iterations: u32 = iterations
+            .parse()
+            .map_err(|_| SecretError::Invalid(format!("bad iteration count for '{}'", var.key)))?;
+        let key = match keys.entry((iterations, salt.to_string())) {
+            std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                let salt = STANDARD
+                    .decode(salt)
+                    .map_err(|_| SecretError::Invalid(format!("bad salt for '{}'", var.key)))?;
+                entry.insert(SecretKey::from_passphrase(passphrase, &salt, iterations)?)
+            }
+        };
+        var.value = key.decrypt(sealed)?;
+        decrypted += 1;
+    }
+    Ok(decrypted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::environment::Environment;
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let key = SecretKey::generate().unwrap();
+        let sealed = key.encrypt("s3cret-token").unwrap();
+        assert!(!sealed.contains("s3cret"));
+        assert_ne!(sealed, key.encrypt("s3cret-token").unwrap(), "nonces must differ");
+        assert_eq!(key.decrypt(&sealed).unwrap(), "s3cret-token");
+
+        let other = SecretKey::generate().unwrap();
+        assert!(matches!(other.decrypt(&sealed), Err(SecretError::Decrypt)));
+    }
+
+    #[test]
+    fn test_passphrase_check_rejects_wrong_passphrase() {
+        let mut file = SecretsFile::default();
+        let key = file.use_passphrase("correct horse").unwrap();
+        file.values.entry(Uuid::new_v4()).or_default().insert("token".into(), key.encrypt("abc").unwrap());
+
+        assert!(matches!(file.passphrase_key("wrong"), Err(SecretError::Decrypt)));
+        let unlocked = file.passphrase_key("correct horse").unwrap();
+        let sealed = file.values.values().next().unwrap()["token"].clone();
+        assert_eq!(unlocked.decrypt(&sealed).unwrap(), "abc");
+    }
+
+    #[test]
+    fn test_export_encryption_round_trip() {
+        let mut env = Environment::new("Prod");
+        env.variables.push(Variable { key: "host".into(), value: "api.test".into(), secret: false, enabled: true });
+        env.variables.push(Variable { key: "token".into(), value: "abc123".into(), secret: true, enabled: true });
+
+        let cipher = PassphraseCipher::new("hunter2").unwrap();
+        let mut exported = env.clone();
+        encrypt_secrets(&mut exported.variables, &cipher).unwrap();
+        assert_eq!(exported.variables[0].value, "api.test");
+        assert!(is_encrypted(&exported.variables[1].value));
+
+        let mut wrong = exported.clone();
+        assert!(decrypt_secrets(&mut wrong.variables, "nope").is_err());
+        assert_eq!(decrypt_secrets(&mut exported.variables, "hunter2").unwrap(), 1);
+        assert_eq!(exported.variables[1].value, "abc123");
+
+        strip_secrets(&mut env.variables);
+        assert_eq!(env.variables[0].value, "api.test");
+        assert_eq!(env.variables[1].value, "");
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value, json};
+use uuid::Uuid;
+
+use crate::models::collection::Collection;
+use crate::models::folder::CollectionItem;
+use crate::models::request::RequestDefinition;
+use crate::store::json_store::StoreError;
+use crate::store::yaml::YamlError;
+use crate::store::schema::{self, Document};
+use crate::store::{write_atomic, yaml};
+
+const COLLECTION_FILE: &str = "collection.yaml";
+const FOLDER_FILE: &str = "folder.yaml";
+
+/// File-per-request collection storage, for reviewing API changes in git.
+///
+/// Layout on disk:
+/// collections/
+///   {collection-name}/
+///     collection.yaml        -> Collection settings, plus `order` of entries
+///     {request-name}.yaml    -> RequestDefinition
+///     {folder-name}/
+///       folder.yaml          -> Folder settings, plus `order` of entries
+///       {request-name}.yaml
+///
+/// `collection.yaml` carries the `schema_version` for the whole directory.
+/// Names are slugs of the collection, folder and request names, so they only
+/// change on rename. Keys are sorted and files are only rewritten when their
+/// contents change. Entries missing from `order` (e.g. added by hand) load
+/// after the listed ones, sorted by name.
+pub struct FileStore {
+    root: PathBuf,
+}
+
+/// Files and directories a collection should consist of, relative to its directory.
+#[derive(Default)]
+struct Plan {
+    files: BTreeMap<PathBuf, String>,
+    dirs: BTreeSet<PathBuf>,
+}
+
+impl FileStore {
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self, StoreError> {
+        let root = root.into();
+        std::fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    pub fn list_collections(&self) -> Result<Vec<Collection>, StoreError> {
+        let mut collections = Vec::new();
+        for dir in self.collection_dirs()? {
+            collections.push(load_collection(&dir)?);
+        }
+        Ok(collections)
+    }
+
+    pub fn save_collection(&self, col: &Collection) -> Result<(), StoreError> {
+        let dir = self.directory_for(col)?;
+        std::fs::create_dir_all(&dir)?;
+
+        let mut plan = Plan::default();
+        let mut written = HashSet::new();
+        let order = plan_items(col, &col.tree, Path::new(""), &mut plan, &mut written)?;
+        let mut orphans: Vec<&RequestDefinition> =
+            col.requests.values().filter(|r| !written.contains(&r.id)).collect();
+        orphans.sort_by(|a, b| (&a.name, a.id).cmp(&(&b.name, b.id)));
+        let mut order = order;
+        let mut taken = reserved_names();
+        taken.extend(order.iter().map(|e| entry_stem(e).to_string()));
+        for req in orphans {
+            let name = format!("{}.yaml", unique_slug(&req.name, "request", &mut taken));
+            plan.files.insert(PathBuf::from(&name), to_yaml(req)?);
+            order.push(name);
+        }
+
+        let mut meta = serde_json::to_value(col)?;
+        if let Value::Object(map) = &mut meta {
+            map.remove("tree");
+            map.remove("requests");
+            map.insert("order".into(), order.into());
+        }
+        plan.files.insert(PathBuf::from(COLLECTION_FILE), yaml::to_string(&schema::stamp(Document::Collection, meta)));
+
+        for sub in &plan.dirs {
+            std::fs::create_dir_all(dir.join(sub))?;
+        }
+        for (path, contents) in &plan.files {
+            let path = dir.join(path);
+            if std::fs::read_to_string(&path).ok().as_deref() != Some(contents.as_str()) {
+                write_atomic(&path, contents)?;
+            }
+        }
+        remove_stale(&dir, Path::new(""), &plan)?;
+        Ok(())
+    }
+
+    pub fn delete_collection(&self, col: &Collection) -> Result<(), StoreError> {
+        if let Some(dir) = self.find_directory(col.id)? {
+            std::fs::remove_dir_all(dir)?;
+        }
+        Ok(())
+    }
+
+    fn collection_dirs(&self) -> Result<Vec<PathBuf>, StoreError> {
+        let mut dirs = Vec::new();
+        for entry in std::fs::read_dir(&self.root)? {
+            let path = entry?.path();
+            if path.join(COLLECTION_FILE).is_file() {
+                dirs.push(path);
+            }
+        }
+        dirs.sort();
+        Ok(dirs)
+    }
+
+    fn find_directory(&self, id: Uuid) -> Result<Option<PathBuf>, StoreError> {
+        for dir in self.collection_dirs()? {
+            if read_id(&dir.join(COLLECTION_FILE))? == Some(id) {
+                return Ok(Some(dir));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The directory for a collection, renaming its existing one if the name changed.
+    fn directory_for(&self, col: &Collection) -> Result<PathBuf, StoreError> {
+        let existing = self.find_directory(col.id)?;
+        let mut taken = HashSet::new();
+        for dir in self.collection_dirs()? {
+            if Some(&dir) != existing.as_ref()
+                && let Some(name) = dir.file_name()
+            {
+                taken.insert(name.to_string_lossy().into_owned());
+            }
+        }
+        let wanted = slug(&col.name, "collection");
+        if let Some(dir) = &existing
+            && dir.file_name().is_some_and(|n| is_slug_of(&n.to_string_lossy(), &wanted))
+        {
+            return Ok(dir.clone());
+        }
+        let target = self.root.join(unique_slug(&col.name, "collection", &mut taken));
+        if let Some(dir) = existing {
+            std::fs::rename(dir, &target)?;
+        }
+        Ok(target)
+    }
+}
+
+/// Plan the files for `items` inside `dir`, returning their entry names in order.
+fn plan_items(
+    col: &Collection,
+    items: &[CollectionItem],
+    dir: &Path,
+    plan: &mut Plan,
+    written: &mut HashSet<Uuid>,
+) -> Result<Vec<String>, StoreError> {
+    let mut taken = reserved_names();
+    let mut order = Vec::new();
+    for item in items {
+        match item {
+            CollectionItem::Request(id) => {
+                let Some(req) = col.requests.get(id) else { continue };
+                if !written.insert(req.id) { continue; }
+                let name = format!("{}.yaml", unique_slug(&req.name, "request", &mut taken));
+                plan.files.insert(dir.join(&name), to_yaml(req)?);
+                order.push(name);
+            }
+            CollectionItem::Folder(folder) => {
+                let name = unique_slug(&folder.name, "folder", &mut taken);
+                let sub = dir.join(&name);
+                let children = plan_items(col, &folder.children, &sub, plan, written)?;
+                let mut meta = serde_json::to_value(folder.as_ref())?;
+                if let Value::Object(map) = &mut meta {
+                    map.remove("children");
+                    map.insert("order".into(), children.into());
+                }
+                plan.files.insert(sub.join(FOLDER_FILE), yaml::to_string(&meta));
+                plan.dirs.insert(sub);
+                order.push(format!("{}/", name));
+            }
+        }
+    }
+    Ok(order)
+}
+
+/// Remove YAML files and folder directories under `dir` that are not in the plan.
+/// Other files are left alone.
+fn remove_stale(root: &Path, dir: &Path, plan: &Plan) -> Result<(), StoreError> {
+    for entry in std::fs::read_dir(root.join(dir))? {
+        let path = entry?.path();
+        let relative = dir.join(path.file_name().expect("directory entries have names"));
+        if path.is_dir() {
+            if plan.dirs.contains(&relative) {
+                remove_stale(root, &relative, plan)?;
+            } else if path.join(FOLDER_FILE).is_file() {
+                std::fs::remove_dir_all(&path)?;
+            }
+        } else if is_yaml(&path) && !plan.files.contains_key(&relative) {
+            std::fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Reassemble the collection as a single document, so it is upgraded like a
+/// `{id}.json` collection would be.
+fn load_collection(dir: &Path) -> Result<Collection, StoreError> {
+    let (mut meta, order) = read_meta(&dir.join(COLLECTION_FILE))?;
+    let version = schema::version_of(Document::Collection, &meta)?;
+    schema::check_version(Document::Collection, version)?;
+    let mut requests = Map::new();
+    let tree = load_items(dir, &order, &mut requests)?;
+    if let Value::Object(map) = &mut meta {
+        map.insert("tree".into(), Value::Array(tree));
+        map.insert("requests".into(), Value::Object(requests));
+    }
+    Ok(schema::from_value(Document::Collection, meta)?)
+}
+
+/// Load the entries of `dir` as serialized `CollectionItem`s, adding the
+/// requests found to `requests`.
+fn load_items(dir: &Path, order: &[String], requests: &mut Map<String, Value>) -> Result<Vec<Value>, StoreError> {
+    let mut entries = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().expect("directory entries have names").to_string_lossy().into_owned();
+        if path.is_dir() && path.join(FOLDER_FILE).is_file() {
+            entries.push(format!("{}/", name));
+        } else if is_yaml(&path) && name != COLLECTION_FILE && name != FOLDER_FILE {
+            entries.push(name);
+        }
+    }
+    entries.sort();
+    let position = |entry: &String| order.iter().position(|o| o == entry).unwrap_or(usize::MAX);
+    entries.sort_by_key(|entry| position(entry));
+
+    let mut items = Vec::new();
+    for entry in entries {
+        if let Some(name) = entry.strip_suffix('/') {
+            let sub = dir.join(name);
+            let (mut meta, order) = read_meta(&sub.join(FOLDER_FILE))?;
+            let children = load_items(&sub, &order, requests)?;
+            if let Value::Object(map) = &mut meta {
+                map.insert("children".into(), Value::Array(children));
+            }
+            items.push(json!({ "Folder": meta }));
+        } else {
+            let path = dir.join(&entry);
+            let req = read_yaml(&path)?;
+            let Some(id) = req.get("id").and_then(Value::as_str).map(String::from) else {
+                return Err(StoreError::Yaml {
+                    path,
+                    source: YamlError { line: 1, message: "missing request `id`".to_string() },
+                });
+            };
+            items.push(json!({ "Request": id }));
+            requests.insert(id, req);
+        }
+    }
+    Ok(items)
+}
+
+/// Read a `collection.yaml` or `folder.yaml`, splitting off its `order` list.
+fn read_meta(path: &Path) -> Result<(Value, Vec<String>), StoreError> {
+    let mut meta = read_yaml(path)?;
+    let order = match &mut meta {
+        Value::Object(map) => match map.remove("order") {
+            Some(Value::Array(entries)) => entries.into_iter().filter_map(|e| e.as_str().map(String::from)).collect(),
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    };
+    Ok((meta, order))
+}
+
+fn read_id(path: &Path) -> Result<Option<Uuid>, StoreError> {
+    Ok(read_yaml(path)?
+        .get("id")
+        .and_then(Value::as_str)
+        .and_then(|id| Uuid::parse_str(id).ok()))
+}
+
+fn read_yaml(path: &Path) -> Result<Value, StoreError> {
+    let data = std::fs::read_to_string(path)?;
+    yaml::from_str(&data).map_err(|source| StoreError::Yaml { path: path.to_path_buf(), source })
+}
+
+fn to_yaml(req: &RequestDefinition) -> Result<String, StoreError> {
+    Ok(yaml::to_string(&serde_json::to_value(req)?))
+}
+
+fn is_yaml(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == "yaml")
+}
+
+fn entry_stem(entry: &str) -> &str {
+    entry.strip_suffix('/').or_else(|| entry.strip_suffix(".yaml")).unwrap_or(entry)
+}
+
+/// A file-name-safe version of `name`: lowercase ASCII letters, digits and dashes.
+fn slug(name: &str, fallback: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.len() >= 60 { break; }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() { fallback.to_string() } else { slug.to_string() }
+}
+
+/// Whether `name` is `slug` or `slug` with a `-N` suffix from `unique_slug`.
+fn is_slug_of(name: &str, slug: &str) -> bool {
+    name == slug
+        || name
+            .strip_prefix(slug)
+            .and_then(|rest| rest.strip_prefix('-'))
+            .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Entry names that would clash with `collection.yaml` and `folder.yaml`.
+fn reserved_names() -> HashSet<String> {
+    ["collection", "folder"].into_iter().map(String::from).collect()
+}
+
+/// `slug(name)`, suffixed with `-2`, `-3`, ... until it is not in `taken`.
+fn unique_slug(name: &str, fallback: &str, taken: &mut HashSet<String>) -> String {
+    let base = slug(name, fallback);
+    let mut candidate = base.clone();
+    let mut n = 1;
+    while taken.contains(&candidate) {
+        n += 1;
+        candidate = format!("{}-{}", base, n);
+    }
+    taken.insert(candidate.clone());
+    candidate
+}
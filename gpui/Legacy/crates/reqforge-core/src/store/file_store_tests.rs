@@ -0,0 +1,172 @@
+use tempfile::TempDir;
+use uuid::Uuid;
+
+use crate::models::collection::Collection;
+use crate::models::folder::{CollectionItem, Folder};
+use crate::models::request::{BodyType, HttpMethod, KeyValuePair, RawContentType, RequestDefinition};
+use crate::models::workspace::CollectionLayout;
+use crate::store::file_store::FileStore;
+use crate::store::json_store::JsonStore;
+use crate::store::Store;
+
+fn folder(name: &str) -> Folder {
+    Folder {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        children: Vec::new(),
+        auth: Default::default(),
+        variables: Vec::new(),
+    }
+}
+
+/// A collection with a top-level request, a folder and a nested folder.
+fn sample_collection() -> Collection {
+    let mut col = Collection::new("Pet Store API");
+
+    let mut list = RequestDefinition::new("List pets", HttpMethod::GET, "{{base_url}}/pets?limit=10");
+    list.headers.push(KeyValuePair {
+        key: "Accept".to_string(),
+        value: "application/json".to_string(),
+        enabled: true,
+        description: None,
+    });
+    col.add_request(list, None);
+
+    let admin = folder("Admin");
+    let admin_id = admin.id;
+    col.tree.push(CollectionItem::Folder(Box::new(admin)));
+    let mut create = RequestDefinition::new("Create pet", HttpMethod::POST, "{{base_url}}/pets");
+    create.body = BodyType::Raw {
+        content: "{\n  \"name\": \"Rex\",\n  \"tag\": \"dog\"\n}".to_string(),
+        content_type: RawContentType::Json,
+    };
+    col.add_request(create, Some(admin_id));
+
+    let audit = folder("Audit: logs");
+    let audit_id = audit.id;
+    col.add_request(RequestDefinition::new("Zebra last", HttpMethod::GET, "{{base_url}}/z"), Some(admin_id));
+    if let Some(CollectionItem::Folder(admin)) = col.tree.last_mut() {
+        admin.children.push(CollectionItem::Folder(Box::new(audit)));
+    }
+    col.add_request(RequestDefinition::new("List logs", HttpMethod::GET, "{{base_url}}/logs"), Some(audit_id));
+    col
+}
+
+fn names(col: &Collection) -> Vec<(String, Vec<String>)> {
+    col.ordered_requests()
+        .into_iter()
+        .map(|(req, path)| (req.name.clone(), path))
+        .collect()
+}
+
+#[test]
+fn test_round_trip_preserves_tree_and_requests() {
+    let temp = TempDir::new().unwrap();
+    let store = FileStore::open(temp.path()).unwrap();
+    let col = sample_collection();
+    store.save_collection(&col).unwrap();
+
+    let dir = temp.path().join("pet-store-api");
+    assert!(dir.join("collection.yaml").is_file());
+    assert!(dir.join("list-pets.yaml").is_file());
+    assert!(dir.join("admin/folder.yaml").is_file());
+    assert!(dir.join("admin/create-pet.yaml").is_file());
+    assert!(dir.join("admin/audit-logs/list-logs.yaml").is_file());
+    let create = std::fs::read_to_string(dir.join("admin/create-pet.yaml")).unwrap();
+    assert!(create.contains("    content: |-\n      {\n        \"name\": \"Rex\","), "{}", create);
+
+    let loaded = store.list_collections().unwrap();
+    assert_eq!(loaded.len(), 1);
+    let loaded = &loaded[0];
+    assert_eq!(loaded.id, col.id);
+    assert_eq!(loaded.name, col.name);
+    assert_eq!(names(loaded), names(&col));
+    for (id, req) in &col.requests {
+        let other = &loaded.requests[id];
+        assert_eq!(serde_json::to_value(other).unwrap(), serde_json::to_value(req).unwrap());
+    }
+}
+
+#[test]
+fn test_saves_are_stable_and_remove_stale_files() {
+    let temp = TempDir::new().unwrap();
+    let store = FileStore::open(temp.path()).unwrap();
+    let mut col = sample_collection();
+    store.save_collection(&col).unwrap();
+
+    let dir = temp.path().join("pet-store-api");
+    let before = std::fs::read_to_string(dir.join("list-pets.yaml")).unwrap();
+    store.save_collection(&col).unwrap();
+    assert_eq!(std::fs::read_to_string(dir.join("list-pets.yaml")).unwrap(), before);
+
+    // Renaming a request renames its file; unrelated files are left alone
+    std::fs::write(dir.join("README.md"), "notes").unwrap();
+    let id = col.tree.iter().find_map(|item| match item {
+        CollectionItem::Request(id) => Some(*id),
+        _ => None,
+    }).unwrap();
+    col.requests.get_mut(&id).unwrap().name = "Search pets".to_string();
+    col.tree.retain(|item| !matches!(item, CollectionItem::Folder(_)));
+    store.save_collection(&col).unwrap();
+
+    assert!(!dir.join("list-pets.yaml").exists());
+    assert!(dir.join("search-pets.yaml").is_file());
+    assert!(!dir.join("admin").exists());
+    assert!(dir.join("README.md").exists());
+
+    // Renaming the collection moves its directory
+    col.name = "Pets".to_string();
+    store.save_collection(&col).unwrap();
+    assert!(!dir.exists());
+    assert!(temp.path().join("pets/search-pets.yaml").is_file());
+
+    store.delete_collection(&col).unwrap();
+    assert!(store.list_collections().unwrap().is_empty());
+}
+
+#[test]
+fn test_hand_added_requests_and_name_clashes() {
+    let temp = TempDir::new().unwrap();
+    let store = FileStore::open(temp.path()).unwrap();
+    let mut col = Collection::new("Clashes");
+    col.add_request(RequestDefinition::new("Get", HttpMethod::GET, "https://a.test"), None);
+    col.add_request(RequestDefinition::new("get", HttpMethod::GET, "https://b.test"), None);
+    col.add_request(RequestDefinition::new("Folder", HttpMethod::GET, "https://c.test"), None);
+    store.save_collection(&col).unwrap();
+
+    let dir = temp.path().join("clashes");
+    assert!(dir.join("get.yaml").is_file());
+    assert!(dir.join("get-2.yaml").is_file());
+    assert!(dir.join("folder-2.yaml").is_file());
+
+    // A request file added by hand (e.g. from a merged branch) loads after the listed ones
+    let extra = RequestDefinition::new("Added", HttpMethod::DELETE, "https://d.test");
+    let yaml = crate::store::yaml::to_string(&serde_json::to_value(&extra).unwrap());
+    std::fs::write(dir.join("added.yaml"), yaml).unwrap();
+
+    let loaded = &store.list_collections().unwrap()[0];
+    let urls: Vec<_> = loaded.ordered_requests().into_iter().map(|(r, _)| r.url.clone()).collect();
+    assert_eq!(urls, vec!["https://a.test", "https://b.test", "https://c.test", "https://d.test"]);
+}
+
+#[test]
+fn test_json_store_converts_between_layouts() {
+    let temp = TempDir::new().unwrap();
+    let mut store = JsonStore::open(temp.path()).unwrap();
+    let col = sample_collection();
+    store.save_collection(&col).unwrap();
+    assert_eq!(store.collection_layout(), CollectionLayout::Json);
+
+    assert_eq!(store.set_collection_layout(CollectionLayout::Files).unwrap(), 1);
+    assert!(!temp.path().join("collections").join(format!("{}.json", col.id)).exists());
+    assert!(temp.path().join("collections/pet-store-api/collection.yaml").is_file());
+
+    let mut reopened = JsonStore::open(temp.path()).unwrap();
+    assert_eq!(reopened.collection_layout(), CollectionLayout::Files);
+    let loaded = reopened.list_collections().unwrap();
+    assert_eq!(names(&loaded[0]), names(&col));
+
+    reopened.set_collection_layout(CollectionLayout::Json).unwrap();
+    assert!(!temp.path().join("collections/pet-store-api").exists());
+    assert_eq!(names(&reopened.list_collections().unwrap()[0]), names(&col));
+}
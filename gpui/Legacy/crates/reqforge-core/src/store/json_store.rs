@@ -1,6 +1,20 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
+use std::sync::Arc;
+use crate::cookies::CookieJar;
+use crate::history::{FileHistory, HistoryBackend};
+use crate::models::auth::OAuth2Token;
 use crate::models::collection::Collection;
-use crate::models::environment::Environment;
+use crate::models::environment::{Environment, Variable};
+use crate::models::settings::EngineSettings;
+use crate::models::workspace::{CollectionLayout, WorkspaceConfig};
+use crate::secrets::{SecretError, SecretsFile};
+use crate::store::file_store::FileStore;
+use crate::store::schema::{self, Document, SchemaError};
+use crate::store::secret_vault::SecretVault;
+use crate::store::{Store, git_ignore, write_atomic};
+use crate::store::yaml::YamlError;
 
 #[derive(Debug, thiserror::Error)]
 pub enum StoreError {
@@ -8,64 +22,307 @@ pub enum StoreError {
     Io(#[from] std::io::Error),
     #[error("Serialization error: {0}")]
     Serde(#[from] serde_json::Error),
+    #[error("Secrets error: {0}")]
+    Secrets(#[from] SecretError),
+    #[error("Invalid YAML in {}: {source}", path.display())]
+    Yaml { path: PathBuf, source: YamlError },
+    #[error("{0}")]
+    Schema(#[from] SchemaError),
+    #[error("Database error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
 }
 
-/// Layout on disk:
+/// Workspace files holding credentials, which are added to `.gitignore`.
+const PRIVATE_FILES: &[&str] = &["secrets.json", "secret.key", "oauth_tokens.json", "cookies.json"];
+
+/// Layout on disk (every file carries a `schema_version`, see `store::schema`):
 /// workspace_dir/
-///   environments.json      -> Vec<Environment>
+///   workspace.json         -> WorkspaceConfig
+///   environments.json      -> Vec<Environment> (secret values blanked)
+///   secrets.json           -> SecretsFile (encrypted secret values, git-ignored)
+///   secret.key             -> keyfile for SecretsFile, unless passphrase-protected
+///   globals.json           -> Vec<Variable> (secret values blanked)
+///   oauth_tokens.json      -> HashMap<String, OAuth2Token> (git-ignored)
+///   settings.json          -> EngineSettings
+///   cookies.json           -> CookieJar (git-ignored)
+///   history.json           -> request history, with bodies in history/ (see FileHistory)
 ///   collections/
-///     {collection_id}.json -> Collection
+///     {collection_id}.json -> Collection (CollectionLayout::Json, secret values blanked)
+///     {collection-name}/   -> Collection (CollectionLayout::Files, see FileStore)
 pub struct JsonStore {
     root: PathBuf,
+    /// Key for `secrets.json`, from `secret.key` or a passphrase.
+    vault: SecretVault,
+    layout: CollectionLayout,
 }
 
 impl JsonStore {
     pub fn open(root: impl Into<PathBuf>) -> Result<Self, StoreError> {
         let root = root.into();
         std::fs::create_dir_all(root.join("collections"))?;
-        Ok(Self { root })
+        let vault = SecretVault::new(root.join("secret.key"));
+        let mut store = Self { root, vault, layout: CollectionLayout::Json };
+        store.layout = store.load_workspace_config()?.collection_layout;
+        Ok(store)
     }
 
-    // --- Environments ---
+    pub fn collection_layout(&self) -> CollectionLayout {
+        self.layout
+    }
+
+    /// Convert every collection to `layout` and use it from now on. Returns
+    /// the number of collections converted.
+    pub fn set_collection_layout(&mut self, layout: CollectionLayout) -> Result<usize, StoreError> {
+        if layout == self.layout { return Ok(0); }
+        let collections = self.list_collections()?;
+        for col in &collections {
+            self.save_collection_as(layout, col)?;
+        }
+        let mut config = self.load_workspace_config()?;
+        config.collection_layout = layout;
+        self.save_workspace_config(&config)?;
+        for col in &collections {
+            self.delete_collection_as(self.layout, col)?;
+        }
+        self.layout = layout;
+        Ok(collections.len())
+    }
+
+    pub fn load_workspace_config(&self) -> Result<WorkspaceConfig, StoreError> {
+        Ok(self.read_document("workspace.json", Document::Workspace)?.unwrap_or_default())
+    }
+
+    pub fn save_workspace_config(&self, config: &WorkspaceConfig) -> Result<(), StoreError> {
+        self.write_document("workspace.json", Document::Workspace, config)
+    }
 
-    pub fn load_environments(&self) -> Result<Vec<Environment>, StoreError> {
-        let path = self.root.join("environments.json");
-        if !path.exists() { return Ok(Vec::new()); }
+    /// Read and upgrade a versioned document, or `None` if it does not exist.
+    fn read_document<T: serde::de::DeserializeOwned>(&self, name: &str, document: Document) -> Result<Option<T>, StoreError> {
+        let path = self.root.join(name);
+        if !path.exists() { return Ok(None); }
         let data = std::fs::read_to_string(&path)?;
-        Ok(serde_json::from_str(&data)?)
+        Ok(Some(schema::from_str(document, &data)?))
+    }
+
+    fn write_document<T: serde::Serialize + ?Sized>(&self, name: &str, document: Document, value: &T) -> Result<(), StoreError> {
+        write_atomic(&self.root.join(name), schema::to_string(document, value)?)?;
+        Ok(())
+    }
+
+    fn load_secrets_file(&self) -> Result<SecretsFile, StoreError> {
+        Ok(self.read_document("secrets.json", Document::Secrets)?.unwrap_or_default())
+    }
+
+    fn save_secrets_file(&self, secrets: &SecretsFile) -> Result<(), StoreError> {
+        self.write_document("secrets.json", Document::Secrets, secrets)?;
+        self.ignore_secret_files()
+    }
+
+    /// Make sure secrets, the keyfile, OAuth tokens and session cookies are
+    /// never committed with the workspace.
+    fn ignore_secret_files(&self) -> Result<(), StoreError> {
+        git_ignore(&self.root, PRIVATE_FILES)
+    }
+
+    /// Move the secret variable values of the collection, its folders and
+    /// requests into `secrets.json`, returning the collection as it is
+    /// written to disk.
+    fn seal_collection<'a>(&self, col: &'a Collection) -> Result<Cow<'a, Collection>, StoreError> {
+        let mut secrets = self.load_secrets_file()?;
+        let stored = secrets.collections.contains_key(&col.id) || secrets.collection_items.contains_key(&col.id);
+        if !stored && !col.has_secret_variables() {
+            return Ok(Cow::Borrowed(col));
+        }
+        let mut public = col.clone();
+        if self.vault.seal_collection(&mut secrets, &mut public)? {
+            self.save_secrets_file(&secrets)?;
+        }
+        Ok(Cow::Owned(public))
+    }
+
+    fn save_collection_as(&self, layout: CollectionLayout, col: &Collection) -> Result<(), StoreError> {
+        let col = self.seal_collection(col)?;
+        if layout == CollectionLayout::Files {
+            return self.file_store()?.save_collection(&col);
+        }
+        self.write_document(&format!("collections/{}.json", col.id), Document::Collection, &*col)
+    }
+
+    fn delete_collection_as(&self, layout: CollectionLayout, col: &Collection) -> Result<(), StoreError> {
+        if layout == CollectionLayout::Files {
+            return self.file_store()?.delete_collection(col);
+        }
+        let path = self.root.join("collections").join(format!("{}.json", col.id));
+        if path.exists() { std::fs::remove_file(path)?; }
+        Ok(())
+    }
+
+    fn file_store(&self) -> Result<FileStore, StoreError> {
+        FileStore::open(self.root.join("collections"))
+    }
+}
+
+impl Store for JsonStore {
+    // --- Environments ---
+
+    /// Load environments, filling secret values from `secrets.json` when the
+    /// secrets are unlocked. While locked, secret values are left empty.
+    fn load_environments(&self) -> Result<Vec<Environment>, StoreError> {
+        let Some(mut envs): Option<Vec<Environment>> = self.read_document("environments.json", Document::Environments)? else {
+            return Ok(Vec::new());
+        };
+
+        let secrets = self.load_secrets_file()?;
+        for env in &mut envs {
+            self.vault.open(&secrets, secrets.values.get(&env.id), &mut env.variables)?;
+        }
+        Ok(envs)
     }
 
-    pub fn save_environments(&self, envs: &[Environment]) -> Result<(), StoreError> {
-        let json = serde_json::to_string_pretty(envs)?;
-        std::fs::write(self.root.join("environments.json"), json)?;
+    /// Save environments, moving secret values into `secrets.json` (see
+    /// `SecretVault::seal`).
+    fn save_environments(&self, envs: &[Environment]) -> Result<(), StoreError> {
+        let mut secrets = self.load_secrets_file()?;
+        let had_secrets = !secrets.values.is_empty();
+        let mut values = BTreeMap::new();
+        let mut public = envs.to_vec();
+        for env in &mut public {
+            let sealed = self.vault.seal(&secrets, secrets.values.get(&env.id), &mut env.variables)?;
+            if !sealed.is_empty() {
+                values.insert(env.id, sealed);
+            }
+        }
+
+        if had_secrets || !values.is_empty() {
+            secrets.values = values;
+            self.save_secrets_file(&secrets)?;
+        }
+        self.write_document("environments.json", Document::Environments, &public)
+    }
+
+    // --- Secrets ---
+
+    fn secrets_locked(&self) -> Result<bool, StoreError> {
+        Ok(self.vault.locked(&self.load_secrets_file()?))
+    }
+
+    fn unlock_secrets(&self, passphrase: &str) -> Result<(), StoreError> {
+        self.vault.unlock(&self.load_secrets_file()?, passphrase)
+    }
+
+    /// Re-encrypt stored secrets with a passphrase-derived key, or with a
+    /// local keyfile when `passphrase` is `None`. Secrets must be unlocked.
+    fn set_secrets_passphrase(&self, passphrase: Option<&str>) -> Result<(), StoreError> {
+        let mut secrets = self.load_secrets_file()?;
+        let key = self.vault.rekey(&mut secrets, passphrase)?;
+        self.save_secrets_file(&secrets)?;
+        self.vault.activate(key);
         Ok(())
     }
 
+    // --- Global variables ---
+
+    fn load_globals(&self) -> Result<Vec<Variable>, StoreError> {
+        let mut globals: Vec<Variable> = self.read_document("globals.json", Document::Globals)?.unwrap_or_default();
+        let secrets = self.load_secrets_file()?;
+        self.vault.open(&secrets, Some(&secrets.globals), &mut globals)?;
+        Ok(globals)
+    }
+
+    /// Save globals, moving secret values into `secrets.json`.
+    fn save_globals(&self, globals: &[Variable]) -> Result<(), StoreError> {
+        let mut secrets = self.load_secrets_file()?;
+        let mut public = globals.to_vec();
+        let sealed = self.vault.seal(&secrets, Some(&secrets.globals), &mut public)?;
+        if !secrets.globals.is_empty() || !sealed.is_empty() {
+            secrets.globals = sealed;
+            self.save_secrets_file(&secrets)?;
+        }
+        self.write_document("globals.json", Document::Globals, &public)
+    }
+
     // --- Collections ---
 
-    pub fn list_collections(&self) -> Result<Vec<Collection>, StoreError> {
-        let dir = self.root.join("collections");
-        let mut collections = Vec::new();
-        for entry in std::fs::read_dir(&dir)? {
-            let entry = entry?;
-            if entry.path().extension().map(|e| e == "json").unwrap_or(false) {
-                let data = std::fs::read_to_string(entry.path())?;
-                collections.push(serde_json::from_str(&data)?);
+    fn list_collections(&self) -> Result<Vec<Collection>, StoreError> {
+        let mut collections: Vec<Collection> = if self.layout == CollectionLayout::Files {
+            self.file_store()?.list_collections()?
+        } else {
+            let mut collections = Vec::new();
+            for entry in std::fs::read_dir(self.root.join("collections"))? {
+                let entry = entry?;
+                if entry.path().extension().map(|e| e == "json").unwrap_or(false) {
+                    let data = std::fs::read_to_string(entry.path())?;
+                    collections.push(schema::from_str(Document::Collection, &data)?);
+                }
             }
+            collections
+        };
+
+        let secrets = self.load_secrets_file()?;
+        for col in &mut collections {
+            self.vault.open_collection(&secrets, col)?;
         }
         Ok(collections)
     }
 
-    pub fn save_collection(&self, col: &Collection) -> Result<(), StoreError> {
-        let path = self.root.join("collections").join(format!("{}.json", col.id));
-        let json = serde_json::to_string_pretty(col)?;
-        std::fs::write(path, json)?;
-        Ok(())
+    fn save_collection(&self, col: &Collection) -> Result<(), StoreError> {
+        self.save_collection_as(self.layout, col)
     }
 
-    pub fn delete_collection(&self, col: &Collection) -> Result<(), StoreError> {
-        let path = self.root.join("collections").join(format!("{}.json", col.id));
-        if path.exists() { std::fs::remove_file(path)?; }
+    fn delete_collection(&self, col: &Collection) -> Result<(), StoreError> {
+        self.delete_collection_as(self.layout, col)?;
+        let mut secrets = self.load_secrets_file()?;
+        if secrets.remove_collection(col.id) {
+            self.save_secrets_file(&secrets)?;
+        }
         Ok(())
     }
+
+    // --- Engine settings ---
+
+    fn load_settings(&self) -> Result<EngineSettings, StoreError> {
+        let mut settings: EngineSettings = self.read_document("settings.json", Document::Settings)?.unwrap_or_default();
+        self.vault.open_settings(&self.load_secrets_file()?, &mut settings)?;
+        Ok(settings)
+    }
+
+    /// Save settings, moving the proxy password into `secrets.json`.
+    fn save_settings(&self, settings: &EngineSettings) -> Result<(), StoreError> {
+        let mut secrets = self.load_secrets_file()?;
+        let mut public = settings.clone();
+        let sealed = self.vault.seal_settings(&secrets, &mut public)?;
+        if !secrets.settings.is_empty() || !sealed.is_empty() {
+            secrets.settings = sealed;
+            self.save_secrets_file(&secrets)?;
+        }
+        self.write_document("settings.json", Document::Settings, &public)
+    }
+
+    // --- Cookies ---
+
+    fn load_cookies(&self) -> Result<CookieJar, StoreError> {
+        Ok(self.read_document("cookies.json", Document::Cookies)?.unwrap_or_default())
+    }
+
+    fn save_cookies(&self, jar: &CookieJar) -> Result<(), StoreError> {
+        self.write_document("cookies.json", Document::Cookies, jar)?;
+        self.ignore_secret_files()
+    }
+
+    // --- OAuth tokens ---
+
+    fn load_oauth_tokens(&self) -> Result<HashMap<String, OAuth2Token>, StoreError> {
+        Ok(self.read_document("oauth_tokens.json", Document::OAuthTokens)?.unwrap_or_default())
+    }
+
+    fn save_oauth_tokens(&self, tokens: &HashMap<String, OAuth2Token>) -> Result<(), StoreError> {
+        self.write_document("oauth_tokens.json", Document::OAuthTokens, tokens)?;
+        self.ignore_secret_files()
+    }
+
+    // --- History ---
+
+    fn history(&self) -> Arc<dyn HistoryBackend> {
+        Arc::new(FileHistory::new(&self.root))
+    }
 }
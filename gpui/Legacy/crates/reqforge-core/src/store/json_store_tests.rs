@@ -4,8 +4,9 @@ use uuid::Uuid;
 use crate::models::collection::Collection;
 use crate::models::environment::{Environment, Variable};
 use crate::models::folder::{CollectionItem, Folder};
-use crate::models::request::{BodyType, HttpMethod, KeyValuePair, RawContentType, RequestDefinition};
+use crate::models::request::{BodyType, HttpMethod, KeyValuePair, MultipartPart, RawContentType, RequestDefinition};
 use crate::store::json_store::JsonStore;
+use crate::store::Store;
 
 /// Helper to create a test environment with sample data
 fn create_test_environment(name: &str) -> Environment {
@@ -92,18 +93,22 @@ fn create_test_collection(name: &str) -> Collection {
     // Build tree structure: root has folder1 and request1, folder1 has folder2 and request2, folder2 has request3
     collection.tree = vec![
         CollectionItem::Request(req1_id),
-        CollectionItem::Folder(Folder {
+        CollectionItem::Folder(Box::new(Folder {
             id: folder1_id,
             name: "User Operations".to_string(),
             children: vec![
                 CollectionItem::Request(req2_id),
-                CollectionItem::Folder(Folder {
+                CollectionItem::Folder(Box::new(Folder {
                     id: folder2_id,
                     name: "Nested Folder".to_string(),
                     children: vec![CollectionItem::Request(req3_id)],
-                }),
+                    auth: Default::default(),
+                    variables: Vec::new(),
+                })),
             ],
-        }),
+            auth: Default::default(),
+            variables: Vec::new(),
+        })),
     ];
 
     collection
@@ -159,6 +164,8 @@ fn requests_equal(req1: &RequestDefinition, req2: &RequestDefinition) -> bool {
                     .zip(f2.iter())
                     .all(|(p1, p2)| p1.key == p2.key && p1.value == p2.value && p1.enabled == p2.enabled)
         }
+        (BodyType::Multipart(p1), BodyType::Multipart(p2)) => p1 == p2,
+        (BodyType::Binary { path: p1 }, BodyType::Binary { path: p2 }) => p1 == p2,
         _ => false,
     }
 }
@@ -377,6 +384,16 @@ mod json_store_tests {
         let patch_req = create_test_request("PATCH", HttpMethod::PATCH, "https://api.test.com/resource");
         let delete_req = create_test_request("DELETE", HttpMethod::DELETE, "https://api.test.com/resource");
 
+        let mut multipart_req = create_test_request("Upload", HttpMethod::POST, "https://api.test.com/upload");
+        let mut avatar = MultipartPart::file("avatar", "/tmp/avatar.png");
+        avatar.content_type = Some("image/png".to_string());
+        multipart_req.body = BodyType::Multipart(vec![MultipartPart::text("title", "Hello"), avatar]);
+
+        let mut binary_req = create_test_request("Binary", HttpMethod::PUT, "https://api.test.com/blob");
+        binary_req.body = BodyType::Binary { path: "/tmp/blob.bin".to_string() };
+
+        collection.requests.insert(multipart_req.id, multipart_req);
+        collection.requests.insert(binary_req.id, binary_req);
         collection.requests.insert(get_req.id, get_req);
         collection.requests.insert(post_with_form.id, post_with_form);
         collection.requests.insert(patch_req.id, patch_req);
@@ -508,6 +525,43 @@ mod json_store_tests {
         assert!(loaded.is_empty());
     }
 
+    #[test]
+    fn test_engine_settings_roundtrip() {
+        use crate::models::settings::{EngineSettings, HttpVersionPref, ProxySettings, RedirectPolicy};
+
+        let temp_dir = TempDir::new().unwrap();
+        let store = JsonStore::open(temp_dir.path()).unwrap();
+
+        // Missing file gives the defaults
+        assert_eq!(store.load_settings().unwrap(), EngineSettings::default());
+
+        let mut settings = EngineSettings {
+            timeout_ms: None,
+            proxy: Some(ProxySettings {
+                url: "http://proxy.internal:3128".to_string(),
+                username: Some("me".to_string()),
+                password: Some("pr0xy-secret".to_string()),
+                no_proxy: vec![".internal".to_string(), "10.0.0.0/8".to_string()],
+            }),
+            redirects: RedirectPolicy::None,
+            http_version: HttpVersionPref::Http1Only,
+            ..Default::default()
+        };
+        settings.tls.ca_bundle_path = Some("/etc/ssl/corp-ca.pem".to_string());
+        settings.tls.accept_invalid_certs = true;
+
+        store.save_settings(&settings).unwrap();
+        assert_eq!(store.load_settings().unwrap(), settings);
+
+        // The proxy password is kept in secrets.json, encrypted
+        for name in ["settings.json", "secrets.json"] {
+            assert!(!std::fs::read_to_string(temp_dir.path().join(name)).unwrap().contains("pr0xy-secret"));
+        }
+        settings.proxy.as_mut().unwrap().password = None;
+        store.save_settings(&settings).unwrap();
+        assert_eq!(store.load_settings().unwrap(), settings);
+    }
+
     #[test]
     fn test_empty_collection() {
         let temp_dir = TempDir::new().unwrap();
@@ -578,4 +632,117 @@ mod json_store_tests {
             assert_eq!(orig_var.value, loaded_var.value);
         }
     }
+
+    #[test]
+    fn test_writes_are_atomic() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("data.json");
+
+        crate::store::write_atomic(&path, "first").unwrap();
+        crate::store::write_atomic(&path, "second").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second");
+
+        // No temp files are left next to the target
+        let entries: Vec<_> = std::fs::read_dir(temp_dir.path()).unwrap().collect();
+        assert_eq!(entries.len(), 1);
+
+        // A failed write leaves the old contents in place
+        assert!(crate::store::write_atomic(&temp_dir.path().join("missing/data.json"), "x").is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second");
+    }
+
+    #[test]
+    fn test_oauth_tokens_are_git_ignored() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = JsonStore::open(temp_dir.path()).unwrap();
+        std::fs::write(temp_dir.path().join(".gitignore"), "target/").unwrap();
+
+        let token = crate::models::auth::OAuth2Token {
+            access_token: "at".into(),
+            refresh_token: Some("rt".into()),
+            expires_at: None,
+        };
+        store
+            .save_oauth_tokens(&std::collections::HashMap::from([("Prod".to_string(), token)]))
+            .unwrap();
+
+        let ignored = std::fs::read_to_string(temp_dir.path().join(".gitignore")).unwrap();
+        assert!(ignored.starts_with("target/\n"));
+        assert!(ignored.lines().any(|line| line == "oauth_tokens.json"));
+    }
+
+    #[test]
+    fn test_cookies_are_git_ignored() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = JsonStore::open(temp_dir.path()).unwrap();
+
+        store.save_cookies(&crate::cookies::CookieJar::new()).unwrap();
+
+        let ignored = std::fs::read_to_string(temp_dir.path().join(".gitignore")).unwrap();
+        assert_eq!(ignored.lines().filter(|line| *line == "cookies.json").count(), 1);
+        store.save_cookies(&crate::cookies::CookieJar::new()).unwrap();
+        let ignored = std::fs::read_to_string(temp_dir.path().join(".gitignore")).unwrap();
+        assert_eq!(ignored.lines().filter(|line| *line == "cookies.json").count(), 1);
+    }
+
+    #[test]
+    fn test_migrate_between_stores() {
+        use crate::models::workspace::CollectionLayout;
+
+        let from_dir = TempDir::new().unwrap();
+        let to_dir = TempDir::new().unwrap();
+        let from = JsonStore::open(from_dir.path()).unwrap();
+        let mut to = JsonStore::open(to_dir.path()).unwrap();
+        to.set_collection_layout(CollectionLayout::Files).unwrap();
+
+        let env = create_test_environment("Staging");
+        let col = create_test_collection("Migrated");
+        from.save_environments(std::slice::from_ref(&env)).unwrap();
+        from.save_collection(&col).unwrap();
+
+        let report = crate::store::migrate(&from, &to).unwrap();
+        assert_eq!(report.collections, 1);
+        assert_eq!(report.environments, 1);
+
+        let loaded = to.load_environments().unwrap();
+        assert!(environments_equal(&env, &loaded[0]));
+        assert_eq!(loaded[0].variables[1].value, "secret123");
+        assert!(collections_equal(&col, &to.list_collections().unwrap()[0]));
+        assert!(to_dir.path().join("collections/migrated/collection.yaml").is_file());
+
+        // A locked source is refused rather than copying blank secrets
+        from.set_secrets_passphrase(Some("hunter2")).unwrap();
+        let locked = JsonStore::open(from_dir.path()).unwrap();
+        assert!(crate::store::migrate(&locked, &to).is_err());
+    }
+
+    #[test]
+    fn test_unversioned_files_upgrade_and_newer_files_are_refused() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let env = create_test_environment("Legacy");
+        let mut public = env.clone();
+        public.variables[1].secret = false;
+
+        // Files written before schema versions existed
+        std::fs::write(root.join("environments.json"), serde_json::to_string(&[&public]).unwrap()).unwrap();
+        std::fs::write(root.join("workspace.json"), r#"{"collection_layout": "json"}"#).unwrap();
+        let store = JsonStore::open(root).unwrap();
+        let loaded = store.load_environments().unwrap();
+        assert!(environments_equal(&public, &loaded[0]));
+
+        store.save_environments(&loaded).unwrap();
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(root.join("environments.json")).unwrap()).unwrap();
+        assert_eq!(saved["schema_version"], crate::store::schema::SCHEMA_VERSION);
+        assert_eq!(saved["data"][0]["name"], "Legacy");
+
+        // A collection from a newer ReqForge is refused with a clear error
+        let col = create_test_collection("Future");
+        let mut future = serde_json::to_value(&col).unwrap();
+        future["schema_version"] = (crate::store::schema::SCHEMA_VERSION + 1).into();
+        std::fs::write(root.join("collections/future.json"), future.to_string()).unwrap();
+        let err = store.list_collections().unwrap_err();
+        assert!(err.to_string().contains("newer version of ReqForge"), "{}", err);
+    }
 }
@@ -1,6 +1,153 @@
 pub mod json_store;
+pub mod file_store;
+pub mod sqlite_store;
+pub mod yaml;
+pub mod schema;
+mod secret_vault;
 
 pub use json_store::{JsonStore, StoreError};
+pub use file_store::FileStore;
+pub use sqlite_store::SqliteStore;
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::cookies::CookieJar;
+use crate::history::HistoryBackend;
+use crate::models::auth::OAuth2Token;
+use crate::models::collection::Collection;
+use crate::models::environment::{Environment, Variable};
+use crate::models::settings::EngineSettings;
 
 #[cfg(test)]
 mod json_store_tests;
+#[cfg(test)]
+mod file_store_tests;
+#[cfg(test)]
+mod sqlite_store_tests;
+
+/// Persistence backend for a workspace. `ReqForgeCore` only talks to this,
+/// so a backend can keep data in files, a database or anywhere else.
+///
+/// Loads return the default (empty) value when nothing has been saved yet.
+pub trait Store: Send + Sync {
+    /// Environments with secret values filled in, unless the secrets are locked.
+    fn load_environments(&self) -> Result<Vec<Environment>, StoreError>;
+    fn save_environments(&self, envs: &[Environment]) -> Result<(), StoreError>;
+
+    /// Whether secret values are protected by a passphrase that has not been
+    /// supplied yet.
+    fn secrets_locked(&self) -> Result<bool, StoreError>;
+    /// Unlock passphrase-protected secrets for this session.
+    fn unlock_secrets(&self, passphrase: &str) -> Result<(), StoreError>;
+    /// Protect secrets with a passphrase, or with the backend's default key
+    /// when `passphrase` is `None`.
+    fn set_secrets_passphrase(&self, passphrase: Option<&str>) -> Result<(), StoreError>;
+
+    /// Globals with secret values filled in, unless the secrets are locked.
+    fn load_globals(&self) -> Result<Vec<Variable>, StoreError>;
+    fn save_globals(&self, globals: &[Variable]) -> Result<(), StoreError>;
+
+    /// Collections with secret variable values filled in, unless the secrets
+    /// are locked.
+    fn list_collections(&self) -> Result<Vec<Collection>, StoreError>;
+    fn save_collection(&self, col: &Collection) -> Result<(), StoreError>;
+    fn delete_collection(&self, col: &Collection) -> Result<(), StoreError>;
+
+    fn load_settings(&self) -> Result<EngineSettings, StoreError>;
+    fn save_settings(&self, settings: &EngineSettings) -> Result<(), StoreError>;
+
+    fn load_cookies(&self) -> Result<CookieJar, StoreError>;
+    fn save_cookies(&self, jar: &CookieJar) -> Result<(), StoreError>;
+
+    fn load_oauth_tokens(&self) -> Result<HashMap<String, OAuth2Token>, StoreError>;
+    fn save_oauth_tokens(&self, tokens: &HashMap<String, OAuth2Token>) -> Result<(), StoreError>;
+
+    /// Where request history and its response bodies are kept.
+    fn history(&self) -> Arc<dyn HistoryBackend>;
+}
+
+/// What `migrate` copied.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    pub collections: usize,
+    pub environments: usize,
+    pub globals: usize,
+    pub history: usize,
+}
+
+/// Copy everything, including request history and its response bodies, from
+/// one backend to another. Both must have their secrets unlocked, otherwise
+/// secret values would be lost.
+pub fn migrate(from: &dyn Store, to: &dyn Store) -> Result<MigrationReport, StoreError> {
+    if from.secrets_locked()? || to.secrets_locked()? {
+        return Err(crate::secrets::SecretError::Locked.into());
+    }
+    let collections = from.list_collections()?;
+    let environments = from.load_environments()?;
+    let globals = from.load_globals()?;
+    let (from_history, to_history) = (from.history(), to.history());
+    let history = from_history.load_entries()?;
+
+    to.save_settings(&from.load_settings()?)?;
+    to.save_environments(&environments)?;
+    to.save_globals(&globals)?;
+    for col in &collections {
+        to.save_collection(col)?;
+    }
+    to.save_cookies(&from.load_cookies()?)?;
+    to.save_oauth_tokens(&from.load_oauth_tokens()?)?;
+    for entry in history.iter().filter(|e| e.response.body.is_some()) {
+        if let Some(body) = from_history.read_body(entry.id)? {
+            to_history.write_body(entry.id, &body)?;
+        }
+    }
+    to_history.save_entries(&history)?;
+
+    Ok(MigrationReport {
+        collections: collections.len(),
+        environments: environments.len(),
+        globals: globals.len(),
+        history: history.len(),
+    })
+}
+
+/// Add `names` to the `.gitignore` in `dir`, unless they are listed already.
+pub(crate) fn git_ignore(dir: &Path, names: &[&str]) -> Result<(), StoreError> {
+    let path = dir.join(".gitignore");
+    let existing = if path.exists() { std::fs::read_to_string(&path)? } else { String::new() };
+    let missing: Vec<&str> = names
+        .iter()
+        .copied()
+        .filter(|name| !existing.lines().any(|line| line.trim() == *name))
+        .collect();
+    if missing.is_empty() { return Ok(()); }
+    let mut contents = existing;
+    if !contents.is_empty() && !contents.ends_with('\n') { contents.push('\n'); }
+    for name in missing {
+        contents.push_str(name);
+        contents.push('\n');
+    }
+    write_atomic(&path, contents)?;
+    Ok(())
+}
+
+/// Write a file so readers see either the old or the new contents, never a
+/// partial write: the data goes to a temporary file that is synced and then
+/// renamed over `path`.
+pub(crate) fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    let file_name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let tmp = path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()));
+    let result = (|| {
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(contents.as_ref())?;
+        file.sync_all()?;
+        std::fs::rename(&tmp, path)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    result
+}
@@ -0,0 +1,205 @@
+//! Schema versions for persisted documents.
+//!
+//! Every file ReqForge writes carries a `schema_version`. Objects get it as a
+//! top-level key; lists and maps are wrapped as
+//! `{ "schema_version": N, "data": ... }`. Files written before versioning
+//! existed have no version and count as version 0.
+//!
+//! On load, `upgrade` runs the payload through each migration from the file's
+//! version up to `SCHEMA_VERSION`. Files from a newer ReqForge are refused
+//! rather than half-read and then overwritten.
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+/// The schema version this build writes.
+pub const SCHEMA_VERSION: u32 = 1;
+
+const VERSION_KEY: &str = "schema_version";
+const DATA_KEY: &str = "data";
+
+/// Upgrades a document's payload from version `i` to `i + 1`, where `i` is the
+/// index in this list. Append a function here whenever a model change would
+/// break reading older files, and bump `SCHEMA_VERSION`.
+const MIGRATIONS: &[fn(Document, &mut Value)] = &[
+    v1_add_schema_version,
+];
+
+/// Kinds of persisted document, so migrations can tell them apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Document {
+    Collection,
+    Environment,
+    Environments,
+    Globals,
+    Settings,
+    Cookies,
+    OAuthTokens,
+    Secrets,
+    Workspace,
+    History,
+    /// One history entry, as the SQLite store keeps them.
+    HistoryEntry,
+    Templates,
+    /// A workspace export archive, versioned by its manifest.
+    Export,
+}
+
+impl Document {
+    pub fn name(self) -> &'static str {
+        match self {
+            Document::Collection => "collection",
+            Document::Environment => "environment",
+            Document::Environments => "environments",
+            Document::Globals => "globals",
+            Document::Settings => "settings",
+            Document::Cookies => "cookies",
+            Document::OAuthTokens => "OAuth tokens",
+            Document::Secrets => "secrets",
+            Document::Workspace => "workspace config",
+            Document::History => "history",
+            Document::HistoryEntry => "history entry",
+            Document::Templates => "templates",
+            Document::Export => "workspace export",
+        }
+    }
+
+    /// Whether the payload is a list or map, stored under `data`.
+    fn is_wrapped(self) -> bool {
+        matches!(
+            self,
+            Document::Environments | Document::Globals | Document::OAuthTokens | Document::History | Document::Templates
+        )
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SchemaError {
+    #[error(
+        "{} was written by a newer version of ReqForge (schema version {found}, this version supports up to {supported}); update ReqForge to open it",
+        document.name()
+    )]
+    Newer { document: Document, found: u32, supported: u32 },
+    #[error("{} has an invalid schema_version", document.name())]
+    Invalid { document: Document },
+    #[error("Failed to read {}: {source}", document.name())]
+    Serde { document: Document, source: serde_json::Error },
+}
+
+/// The schema version of a stored document, 0 if it predates versioning.
+pub fn version_of(document: Document, value: &Value) -> Result<u32, SchemaError> {
+    match value.get(VERSION_KEY) {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or(SchemaError::Invalid { document }),
+    }
+}
+
+/// Fail if a document at `version` is newer than this build understands.
+pub fn check_version(document: Document, version: u32) -> Result<(), SchemaError> {
+    if version > SCHEMA_VERSION {
+        return Err(SchemaError::Newer { document, found: version, supported: SCHEMA_VERSION });
+    }
+    Ok(())
+}
+
+/// Take the payload out of a stored document and migrate it to `SCHEMA_VERSION`.
+pub fn upgrade(document: Document, mut value: Value) -> Result<Value, SchemaError> {
+    let version = version_of(document, &value)?;
+    check_version(document, version)?;
+    let mut payload = if version == 0 {
+        value
+    } else if document.is_wrapped() {
+        value.get_mut(DATA_KEY).map(Value::take).ok_or(SchemaError::Invalid { document })?
+    } else {
+        if let Value::Object(map) = &mut value {
+            map.remove(VERSION_KEY);
+        }
+        value
+    };
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(document, &mut payload);
+    }
+    Ok(payload)
+}
+
+/// Stamp a payload with `SCHEMA_VERSION` for storage.
+pub fn stamp(document: Document, payload: Value) -> Value {
+    match payload {
+        Value::Object(mut map) if !document.is_wrapped() => {
+            map.insert(VERSION_KEY.into(), SCHEMA_VERSION.into());
+            Value::Object(map)
+        }
+        payload => {
+            let mut map = Map::new();
+            map.insert(VERSION_KEY.into(), SCHEMA_VERSION.into());
+            map.insert(DATA_KEY.into(), payload);
+            Value::Object(map)
+        }
+    }
+}
+
+/// Serialize `value` as a versioned, pretty-printed JSON document.
+pub fn to_string<T: Serialize + ?Sized>(document: Document, value: &T) -> Result<String, SchemaError> {
+    let payload = serde_json::to_value(value).map_err(|source| SchemaError::Serde { document, source })?;
+    serde_json::to_string_pretty(&stamp(document, payload)).map_err(|source| SchemaError::Serde { document, source })
+}
+
+/// Parse a JSON document of any supported version.
+pub fn from_str<T: DeserializeOwned>(document: Document, data: &str) -> Result<T, SchemaError> {
+    let value = serde_json::from_str(data).map_err(|source| SchemaError::Serde { document, source })?;
+    from_value(document, value)
+}
+
+/// Deserialize an already-parsed document of any supported version.
+pub fn from_value<T: DeserializeOwned>(document: Document, value: Value) -> Result<T, SchemaError> {
+    serde_json::from_value(upgrade(document, value)?).map_err(|source| SchemaError::Serde { document, source })
+}
+
+/// Version 1 introduced `schema_version` itself; payloads are unchanged.
+fn v1_add_schema_version(_: Document, _: &mut Value) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_unversioned_documents_load_as_version_zero() {
+        let old = json!([{ "key": "a", "value": "1", "secret": false, "enabled": true }]);
+        assert_eq!(version_of(Document::Globals, &old).unwrap(), 0);
+        assert_eq!(upgrade(Document::Globals, old.clone()).unwrap(), old);
+
+        let old = json!({ "collection_layout": "files" });
+        assert_eq!(upgrade(Document::Workspace, old.clone()).unwrap(), old);
+    }
+
+    #[test]
+    fn test_stamp_round_trips() {
+        let list = json!([1, 2, 3]);
+        let stored = stamp(Document::History, list.clone());
+        assert_eq!(stored, json!({ "schema_version": SCHEMA_VERSION, "data": [1, 2, 3] }));
+        assert_eq!(upgrade(Document::History, stored).unwrap(), list);
+
+        let object = json!({ "name": "Pets" });
+        let stored = stamp(Document::Collection, object.clone());
+        assert_eq!(stored["schema_version"], json!(SCHEMA_VERSION));
+        assert_eq!(upgrade(Document::Collection, stored).unwrap(), object);
+    }
+
+    #[test]
+    fn test_newer_and_invalid_versions_are_refused() {
+        let newer = json!({ "schema_version": SCHEMA_VERSION + 1, "name": "Pets" });
+        let err = upgrade(Document::Collection, newer).unwrap_err();
+        assert!(matches!(err, SchemaError::Newer { found, .. } if found == SCHEMA_VERSION + 1));
+        assert!(err.to_string().contains("newer version of ReqForge"), "{}", err);
+
+        let invalid = json!({ "schema_version": "one", "name": "Pets" });
+        assert!(matches!(upgrade(Document::Collection, invalid), Err(SchemaError::Invalid { .. })));
+        let unwrapped = json!({ "schema_version": 1 });
+        assert!(matches!(upgrade(Document::Globals, unwrapped), Err(SchemaError::Invalid { .. })));
+    }
+}
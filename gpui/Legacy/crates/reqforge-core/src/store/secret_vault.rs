@@ -0,0 +1,211 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+use uuid::Uuid;
+
+use crate::models::collection::Collection;
+use crate::models::environment::Variable;
+use crate::models::settings::EngineSettings;
+use crate::secrets::{KeySource, SecretError, SecretKey, SecretsFile};
+use crate::store::{StoreError, write_atomic};
+
+/// Name of the proxy password in `SecretsFile::settings`.
+const PROXY_PASSWORD: &str = "proxy_password";
+
+/// The key for a store's `SecretsFile`, and moving secret variable values in
+/// and out of it. Backends differ only in where they keep the `SecretsFile`.
+pub(crate) struct SecretVault {
+    keyfile: PathBuf,
+    /// Key for the secrets, once loaded from the keyfile or unlocked.
+    key: RwLock<Option<SecretKey>>,
+}
+
+impl SecretVault {
+    /// A vault whose random key, if it has one, is kept in `keyfile`.
+    pub(crate) fn new(keyfile: PathBuf) -> Self {
+        Self { keyfile, key: RwLock::new(None) }
+    }
+
+    /// The key for `secrets`, loading the keyfile if needed. With `create`, a
+    /// keyfile is generated when there is no key and nothing encrypted yet.
+    pub(crate) fn key(&self, secrets: &SecretsFile, create: bool) -> Result<Option<SecretKey>, StoreError> {
+        if let Some(key) = self.key.read().unwrap().as_ref() {
+            return Ok(Some(key.clone()));
+        }
+        if secrets.key_source != KeySource::Keyfile { return Ok(None); }
+        let key = if self.keyfile.exists() {
+            let encoded = std::fs::read_to_string(&self.keyfile)?;
+            let bytes = STANDARD.decode(encoded.trim())
+                .map_err(|e| SecretError::Invalid(format!("bad keyfile: {}", e)))?;
+            SecretKey::from_bytes(&bytes)?
+        } else if create && secrets.is_empty() {
+            let key = SecretKey::generate()?;
+            self.write_keyfile(&key)?;
+            key
+        } else {
+            return Ok(None);
+        };
+        *self.key.write().unwrap() = Some(key.clone());
+        Ok(Some(key))
+    }
+
+    fn write_keyfile(&self, key: &SecretKey) -> Result<(), StoreError> {
+        let encoded = STANDARD.encode(key.as_bytes());
+        write_atomic(&self.keyfile, encoded)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&self.keyfile, std::fs::Permissions::from_mode(0o600))?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn locked(&self, secrets: &SecretsFile) -> bool {
+        matches!(secrets.key_source, KeySource::Passphrase { .. }) && self.key.read().unwrap().is_none()
+    }
+
+    pub(crate) fn unlock(&self, secrets: &SecretsFile, passphrase: &str) -> Result<(), StoreError> {
+        if let KeySource::Passphrase { .. } = secrets.key_source {
+            let key = secrets.passphrase_key(passphrase)?;
+            *self.key.write().unwrap() = Some(key);
+        }
+        Ok(())
+    }
+
+    /// Re-encrypt `secrets` with a passphrase-derived key, or with a new
+    /// keyfile when `passphrase` is `None`. Secrets must be unlocked. The new
+    /// key is used once `activate` is called, after `secrets` are saved.
+    pub(crate) fn rekey(&self, secrets: &mut SecretsFile, passphrase: Option<&str>) -> Result<SecretKey, StoreError> {
+        let old = self.key(secrets, true)?.ok_or(SecretError::Locked)?;
+        let new = match passphrase {
+            Some(passphrase) => secrets.use_passphrase(passphrase)?,
+            None => {
+                secrets.key_source = KeySource::Keyfile;
+                secrets.check = None;
+                SecretKey::generate()?
+            }
+        };
+        secrets.rekey(&old, &new)?;
+        match passphrase {
+            Some(_) if self.keyfile.exists() => std::fs::remove_file(&self.keyfile)?,
+            Some(_) => {}
+            None => self.write_keyfile(&new)?,
+        }
+        Ok(new)
+    }
+
+    pub(crate) fn activate(&self, key: SecretKey) {
+        *self.key.write().unwrap() = Some(key);
+    }
+
+    /// Fill empty secret `variables` from their `sealed` values when the
+    /// secrets are unlocked. While locked, secret values are left empty.
+    pub(crate) fn open(
+        &self,
+        secrets: &SecretsFile,
+        sealed: Option<&BTreeMap<String, String>>,
+        variables: &mut [Variable],
+    ) -> Result<(), StoreError> {
+        let Some(sealed) = sealed.filter(|sealed| !sealed.is_empty()) else { return Ok(()) };
+        let Some(key) = self.key(secrets, false)? else { return Ok(()) };
+        for var in variables.iter_mut().filter(|v| v.secret && v.value.is_empty()) {
+            if let Some(value) = sealed.get(&var.key) {
+                var.value = key.decrypt(value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Blank secret values in `variables`, returning them sealed.
+    ///
+    /// An empty secret value keeps the `stored` one while the secrets are
+    /// locked; setting a new value while locked fails with `SecretError::Locked`.
+    pub(crate) fn seal(
+        &self,
+        secrets: &SecretsFile,
+        stored: Option<&BTreeMap<String, String>>,
+        variables: &mut [Variable],
+    ) -> Result<BTreeMap<String, String>, StoreError> {
+        let mut sealed = BTreeMap::new();
+        for var in variables.iter_mut().filter(|v| v.secret) {
+            let value = if var.value.is_empty() {
+                if self.key(secrets, false)?.is_some() { continue; }
+                match stored.and_then(|vars| vars.get(&var.key)) {
+                    Some(value) => value.clone(),
+                    None => continue,
+                }
+            } else {
+                self.key(secrets, true)?.ok_or(SecretError::Locked)?.encrypt(&var.value)?
+            };
+            sealed.insert(var.key.clone(), value);
+            var.value.clear();
+        }
+        Ok(sealed)
+    }
+
+    /// `open` for the proxy password in `settings`.
+    pub(crate) fn open_settings(&self, secrets: &SecretsFile, settings: &mut EngineSettings) -> Result<(), StoreError> {
+        let Some(proxy) = settings.proxy.as_mut() else { return Ok(()) };
+        let mut vars = [proxy_password(proxy.password.take())];
+        self.open(secrets, Some(&secrets.settings), &mut vars)?;
+        let [password] = vars;
+        proxy.password = Some(password.value).filter(|value| !value.is_empty());
+        Ok(())
+    }
+
+    /// `seal` for the proxy password in `settings`.
+    pub(crate) fn seal_settings(
+        &self,
+        secrets: &SecretsFile,
+        settings: &mut EngineSettings,
+    ) -> Result<BTreeMap<String, String>, StoreError> {
+        let Some(proxy) = settings.proxy.as_mut() else { return Ok(BTreeMap::new()) };
+        self.seal(secrets, Some(&secrets.settings), &mut [proxy_password(proxy.password.take())])
+    }
+
+    /// `open` for a collection's variables and those of its folders and requests.
+    pub(crate) fn open_collection(&self, secrets: &SecretsFile, col: &mut Collection) -> Result<(), StoreError> {
+        self.open(secrets, secrets.collections.get(&col.id), &mut col.variables)?;
+        let items = secrets.collection_items.get(&col.id);
+        for (id, variables) in col.item_variables_mut() {
+            self.open(secrets, items.and_then(|items| items.get(&id)), variables)?;
+        }
+        Ok(())
+    }
+
+    /// `seal` for a collection's variables and those of its folders and
+    /// requests, keeping the sealed values in `secrets`. Returns whether
+    /// `secrets` changed.
+    pub(crate) fn seal_collection(&self, secrets: &mut SecretsFile, col: &mut Collection) -> Result<bool, StoreError> {
+        let sealed = self.seal(secrets, secrets.collections.get(&col.id), &mut col.variables)?;
+        let stored = secrets.collection_items.get(&col.id);
+        let mut items = BTreeMap::new();
+        for (id, variables) in col.item_variables_mut() {
+            let sealed = self.seal(secrets, stored.and_then(|items| items.get(&id)), variables)?;
+            if !sealed.is_empty() {
+                items.insert(id, sealed);
+            }
+        }
+        let changed = keep_sealed(&mut secrets.collections, col.id, sealed);
+        Ok(keep_sealed(&mut secrets.collection_items, col.id, items) || changed)
+    }
+}
+
+fn proxy_password(password: Option<String>) -> Variable {
+    Variable { key: PROXY_PASSWORD.to_string(), value: password.unwrap_or_default(), secret: true, enabled: true }
+}
+
+/// Store `sealed` under `id`, or drop the entry when it is empty. Returns
+/// whether `map` changed.
+fn keep_sealed<K: Ord, V>(map: &mut BTreeMap<Uuid, BTreeMap<K, V>>, id: Uuid, sealed: BTreeMap<K, V>) -> bool {
+    if sealed.is_empty() {
+        map.remove(&id).is_some()
+    } else {
+        map.insert(id, sealed);
+        true
+    }
+}
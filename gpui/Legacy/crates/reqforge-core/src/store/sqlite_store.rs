@@ -0,0 +1,375 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::cookies::CookieJar;
+use crate::history::HistoryBackend;
+use crate::models::auth::OAuth2Token;
+use crate::models::collection::Collection;
+use crate::models::environment::{Environment, Variable};
+use crate::models::history::RequestHistoryEntry;
+use crate::models::settings::EngineSettings;
+use crate::secrets::SecretsFile;
+use crate::store::schema::{self, Document};
+use crate::store::secret_vault::SecretVault;
+use crate::store::{Store, StoreError, git_ignore};
+
+/// File name `ReqForgeCore::open` looks for to use a `SqliteStore` for a
+/// workspace directory.
+pub const DATABASE_FILE: &str = "reqforge.db";
+
+const TABLES: &str = "
+    CREATE TABLE IF NOT EXISTS documents (name TEXT PRIMARY KEY, data TEXT NOT NULL);
+    CREATE TABLE IF NOT EXISTS collections (id TEXT PRIMARY KEY, data TEXT NOT NULL);
+    CREATE TABLE IF NOT EXISTS history (id TEXT PRIMARY KEY, position INTEGER NOT NULL, data TEXT NOT NULL);
+    CREATE TABLE IF NOT EXISTS history_bodies (id TEXT PRIMARY KEY, body BLOB NOT NULL, written_at INTEGER NOT NULL);
+";
+
+/// A workspace in a single SQLite database, for workspaces with many
+/// collections and a long history. Saving a collection or a response body
+/// only writes its own row, saving the history only writes the entries that
+/// were added or changed and deletes the pruned ones, and secret values are
+/// written in the same transaction as the variables they belong to.
+///
+/// Tables (every `data` column holds a versioned document, see `store::schema`):
+///   documents(name, data)                -> environments, globals, secrets,
+///                                           settings, cookies, oauth_tokens
+///   collections(id, data)                -> Collection (secret values blanked)
+///   history(id, position, data)          -> RequestHistoryEntry, most recent has the lowest position
+///   history_bodies(id, body, written_at) -> gzip-compressed response bodies
+///
+/// Secret values are sealed as in `JsonStore`; the keyfile is `{database}.key`
+/// next to the database. The database and keyfile are added to the
+/// `.gitignore` in their directory.
+pub struct SqliteStore {
+    conn: Arc<Mutex<Connection>>,
+    vault: SecretVault,
+}
+
+impl SqliteStore {
+    /// Open or create the database at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        let path = path.as_ref();
+        let file_name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+            git_ignore(dir, &[&format!("{}*", file_name)])?;
+        }
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.execute_batch(TABLES)?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            vault: SecretVault::new(path.with_file_name(format!("{}.key", file_name))),
+        })
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        lock(&self.conn)
+    }
+}
+
+fn lock(conn: &Mutex<Connection>) -> MutexGuard<'_, Connection> {
+    conn.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Read and upgrade a versioned document, or `None` if it was never saved.
+fn read_document<T: DeserializeOwned>(conn: &Connection, name: &str, document: Document) -> Result<Option<T>, StoreError> {
+    let data: Option<String> = conn
+        .query_row("SELECT data FROM documents WHERE name = ?1", [name], |row| row.get(0))
+        .optional()?;
+    Ok(data.map(|data| schema::from_str(document, &data)).transpose()?)
+}
+
+fn write_document<T: Serialize + ?Sized>(conn: &Connection, name: &str, document: Document, value: &T) -> Result<(), StoreError> {
+    conn.execute(
+        "INSERT INTO documents (name, data) VALUES (?1, ?2) ON CONFLICT(name) DO UPDATE SET data = excluded.data",
+        params![name, schema::to_string(document, value)?],
+    )?;
+    Ok(())
+}
+
+fn load_secrets(conn: &Connection) -> Result<SecretsFile, StoreError> {
+    Ok(read_document(conn, "secrets", Document::Secrets)?.unwrap_or_default())
+}
+
+impl Store for SqliteStore {
+    // --- Environments ---
+
+    fn load_environments(&self) -> Result<Vec<Environment>, StoreError> {
+        let conn = self.conn();
+        let mut envs: Vec<Environment> = read_document(&conn, "environments", Document::Environments)?.unwrap_or_default();
+        let secrets = load_secrets(&conn)?;
+        for env in &mut envs {
+            self.vault.open(&secrets, secrets.values.get(&env.id), &mut env.variables)?;
+        }
+        Ok(envs)
+    }
+
+    fn save_environments(&self, envs: &[Environment]) -> Result<(), StoreError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let mut secrets = load_secrets(&tx)?;
+        let mut values = BTreeMap::new();
+        let mut public = envs.to_vec();
+        for env in &mut public {
+            let sealed = self.vault.seal(&secrets, secrets.values.get(&env.id), &mut env.variables)?;
+            if !sealed.is_empty() {
+                values.insert(env.id, sealed);
+            }
+        }
+        if values != secrets.values {
+            secrets.values = values;
+            write_document(&tx, "secrets", Document::Secrets, &secrets)?;
+        }
+        write_document(&tx, "environments", Document::Environments, &public)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    // --- Secrets ---
+
+    fn secrets_locked(&self) -> Result<bool, StoreError> {
+        Ok(self.vault.locked(&load_secrets(&self.conn())?))
+    }
+
+    fn unlock_secrets(&self, passphrase: &str) -> Result<(), StoreError> {
+        self.vault.unlock(&load_secrets(&self.conn())?, passphrase)
+    }
+
+    fn set_secrets_passphrase(&self, passphrase: Option<&str>) -> Result<(), StoreError> {
+        let conn = self.conn();
+        let mut secrets = load_secrets(&conn)?;
+        let key = self.vault.rekey(&mut secrets, passphrase)?;
+        write_document(&conn, "secrets", Document::Secrets, &secrets)?;
+        self.vault.activate(key);
+        Ok(())
+    }
+
+    // --- Global variables ---
+
+    fn load_globals(&self) -> Result<Vec<Variable>, StoreError> {
+        let conn = self.conn();
+        let mut globals: Vec<Variable> = read_document(&conn, "globals", Document::Globals)?.unwrap_or_default();
+        let secrets = load_secrets(&conn)?;
+        self.vault.open(&secrets, Some(&secrets.globals), &mut globals)?;
+        Ok(globals)
+    }
+
+    fn save_globals(&self, globals: &[Variable]) -> Result<(), StoreError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let mut secrets = load_secrets(&tx)?;
+        let mut public = globals.to_vec();
+        let sealed = self.vault.seal(&secrets, Some(&secrets.globals), &mut public)?;
+        if sealed != secrets.globals {
+            secrets.globals = sealed;
+            write_document(&tx, "secrets", Document::Secrets, &secrets)?;
+        }
+        write_document(&tx, "globals", Document::Globals, &public)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    // --- Collections ---
+
+    fn list_collections(&self) -> Result<Vec<Collection>, StoreError> {
+        let conn = self.conn();
+        let secrets = load_secrets(&conn)?;
+        let mut stmt = conn.prepare("SELECT data FROM collections ORDER BY rowid")?;
+        let mut collections = Vec::new();
+        for data in stmt.query_map([], |row| row.get::<_, String>(0))? {
+            let mut col: Collection = schema::from_str(Document::Collection, &data?)?;
+            self.vault.open_collection(&secrets, &mut col)?;
+            collections.push(col);
+        }
+        Ok(collections)
+    }
+
+    fn save_collection(&self, col: &Collection) -> Result<(), StoreError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let mut secrets = load_secrets(&tx)?;
+        let mut public = col.clone();
+        if self.vault.seal_collection(&mut secrets, &mut public)? {
+            write_document(&tx, "secrets", Document::Secrets, &secrets)?;
+        }
+        tx.execute(
+            "INSERT INTO collections (id, data) VALUES (?1, ?2) ON CONFLICT(id) DO UPDATE SET data = excluded.data",
+            params![col.id.to_string(), schema::to_string(Document::Collection, &public)?],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn delete_collection(&self, col: &Collection) -> Result<(), StoreError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM collections WHERE id = ?1", [col.id.to_string()])?;
+        let mut secrets = load_secrets(&tx)?;
+        if secrets.remove_collection(col.id) {
+            write_document(&tx, "secrets", Document::Secrets, &secrets)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    // --- Engine settings ---
+
+    fn load_settings(&self) -> Result<EngineSettings, StoreError> {
+        let conn = self.conn();
+        let mut settings: EngineSettings = read_document(&conn, "settings", Document::Settings)?.unwrap_or_default();
+        self.vault.open_settings(&load_secrets(&conn)?, &mut settings)?;
+        Ok(settings)
+    }
+
+    fn save_settings(&self, settings: &EngineSettings) -> Result<(), StoreError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let mut secrets = load_secrets(&tx)?;
+        let mut public = settings.clone();
+        let sealed = self.vault.seal_settings(&secrets, &mut public)?;
+        if sealed != secrets.settings {
+            secrets.settings = sealed;
+            write_document(&tx, "secrets", Document::Secrets, &secrets)?;
+        }
+        write_document(&tx, "settings", Document::Settings, &public)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    // --- Cookies ---
+
+    fn load_cookies(&self) -> Result<CookieJar, StoreError> {
+        Ok(read_document(&self.conn(), "cookies", Document::Cookies)?.unwrap_or_default())
+    }
+
+    fn save_cookies(&self, jar: &CookieJar) -> Result<(), StoreError> {
+        write_document(&self.conn(), "cookies", Document::Cookies, jar)
+    }
+
+    // --- OAuth tokens ---
+
+    fn load_oauth_tokens(&self) -> Result<HashMap<String, OAuth2Token>, StoreError> {
+        Ok(read_document(&self.conn(), "oauth_tokens", Document::OAuthTokens)?.unwrap_or_default())
+    }
+
+    fn save_oauth_tokens(&self, tokens: &HashMap<String, OAuth2Token>) -> Result<(), StoreError> {
+        write_document(&self.conn(), "oauth_tokens", Document::OAuthTokens, tokens)
+    }
+
+    // --- History ---
+
+    fn history(&self) -> Arc<dyn HistoryBackend> {
+        Arc::new(SqliteHistory { conn: self.conn.clone() })
+    }
+}
+
+/// History in the `history` and `history_bodies` tables of a `SqliteStore`.
+#[derive(Debug)]
+struct SqliteHistory {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl HistoryBackend for SqliteHistory {
+    fn load_entries(&self) -> Result<Vec<RequestHistoryEntry>, StoreError> {
+        let conn = lock(&self.conn);
+        let mut stmt = conn.prepare("SELECT data FROM history ORDER BY position")?;
+        let mut entries = Vec::new();
+        for data in stmt.query_map([], |row| row.get::<_, String>(0))? {
+            entries.push(schema::from_str(Document::HistoryEntry, &data?)?);
+        }
+        Ok(entries)
+    }
+
+    fn save_entries(&self, entries: &[RequestHistoryEntry]) -> Result<(), StoreError> {
+        let mut conn = lock(&self.conn);
+        let tx = conn.transaction()?;
+        let mut stored = HashMap::new();
+        {
+            let mut select = tx.prepare("SELECT id, position, data FROM history")?;
+            for row in select.query_map([], |row| Ok((row.get::<_, String>(0)?, (row.get::<_, i64>(1)?, row.get::<_, String>(2)?))))? {
+                let (id, row) = row?;
+                stored.insert(id, row);
+            }
+        }
+        {
+            let mut upsert = tx.prepare(
+                "INSERT INTO history (id, position, data) VALUES (?1, ?2, ?3) \
+                 ON CONFLICT(id) DO UPDATE SET position = excluded.position, data = excluded.data",
+            )?;
+            // Walk from the oldest entry, keeping stored positions while they
+            // still decrease, so that adding an entry doesn't renumber the rest
+            let mut older: Option<i64> = None;
+            for entry in entries.iter().rev() {
+                let id = entry.id.to_string();
+                let data = schema::to_string(Document::HistoryEntry, entry)?;
+                let row = stored.remove(&id);
+                let position = match &row {
+                    Some((position, _)) if older.is_none_or(|older| *position < older) => *position,
+                    _ => older.map_or(0, |older| older - 1),
+                };
+                if row.is_none_or(|(stored_position, stored_data)| stored_position != position || stored_data != data) {
+                    upsert.execute(params![id, position, data])?;
+                }
+                older = Some(position);
+            }
+        }
+        {
+            let mut delete = tx.prepare("DELETE FROM history WHERE id = ?1")?;
+            for id in stored.keys() {
+                delete.execute([id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn write_body(&self, id: uuid::Uuid, compressed: &[u8]) -> Result<(), StoreError> {
+        lock(&self.conn).execute(
+            "INSERT OR REPLACE INTO history_bodies (id, body, written_at) VALUES (?1, ?2, ?3)",
+            params![id.to_string(), compressed, unix_seconds(SystemTime::now())],
+        )?;
+        Ok(())
+    }
+
+    fn read_body(&self, id: uuid::Uuid) -> Result<Option<Vec<u8>>, StoreError> {
+        Ok(lock(&self.conn)
+            .query_row("SELECT body FROM history_bodies WHERE id = ?1", [id.to_string()], |row| row.get(0))
+            .optional()?)
+    }
+
+    fn remove_body(&self, id: uuid::Uuid) -> Result<(), StoreError> {
+        lock(&self.conn).execute("DELETE FROM history_bodies WHERE id = ?1", [id.to_string()])?;
+        Ok(())
+    }
+
+    fn remove_orphaned_bodies(&self, kept: &HashSet<uuid::Uuid>, cutoff: SystemTime) -> Result<(), StoreError> {
+        let mut conn = lock(&self.conn);
+        let tx = conn.transaction()?;
+        let ids: Vec<String> = {
+            let mut stmt = tx.prepare("SELECT id FROM history_bodies WHERE written_at < ?1")?;
+            let ids = stmt.query_map([unix_seconds(cutoff)], |row| row.get(0))?;
+            ids.collect::<Result<_, _>>()?
+        };
+        for id in ids.into_iter().filter(|id| !id.parse().is_ok_and(|id| kept.contains(&id))) {
+            tx.execute("DELETE FROM history_bodies WHERE id = ?1", [id])?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn clear_bodies(&self) -> Result<(), StoreError> {
+        lock(&self.conn).execute("DELETE FROM history_bodies", [])?;
+        Ok(())
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0)
+}
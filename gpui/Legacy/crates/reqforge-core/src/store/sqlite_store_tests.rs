@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tempfile::TempDir;
+use uuid::Uuid;
+
+use crate::history::RequestHistory;
+use crate::models::auth::OAuth2Token;
+use crate::models::collection::Collection;
+use crate::models::environment::{Environment, Variable};
+use crate::models::folder::{CollectionItem, Folder};
+use crate::models::history::{RequestHistoryEntry, ResponseSnapshot};
+use crate::models::request::{HttpMethod, RequestDefinition};
+use crate::models::settings::{EngineSettings, ProxySettings};
+use crate::store::json_store::JsonStore;
+use crate::store::sqlite_store::{DATABASE_FILE, SqliteStore};
+use crate::store::{Store, migrate};
+
+fn variable(key: &str, value: &str, secret: bool) -> Variable {
+    Variable { key: key.to_string(), value: value.to_string(), secret, enabled: true }
+}
+
+fn environment(name: &str) -> Environment {
+    Environment {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        variables: vec![variable("base_url", "https://api.example.com", false), variable("api_key", "env-secret", true)],
+    }
+}
+
+fn collection(name: &str) -> Collection {
+    let mut col = Collection::new(name);
+    let request = RequestDefinition::new("List users", HttpMethod::GET, "{{base_url}}/users");
+    col.add_request(request, None);
+    col.variables = vec![variable("token", "collection-secret", true)];
+    col
+}
+
+fn history_entry(name: &str) -> RequestHistoryEntry {
+    let request = RequestDefinition::new(name, HttpMethod::GET, "https://example.com");
+    let response = ResponseSnapshot {
+        status: 200,
+        status_text: "OK".to_string(),
+        size_bytes: 5,
+        elapsed_millis: 1,
+        success: true,
+        timing: None,
+        headers: Default::default(),
+        body: None,
+    };
+    RequestHistoryEntry::new(request, response, None, None)
+}
+
+#[test]
+fn test_round_trip_keeps_secrets_out_of_the_database() {
+    let temp = TempDir::new().unwrap();
+    let path = temp.path().join(DATABASE_FILE);
+    let env = environment("Staging");
+    let col = collection("Users");
+    let settings = EngineSettings {
+        connect_timeout_ms: 1234,
+        proxy: Some(ProxySettings {
+            url: "http://proxy.internal:3128".to_string(),
+            username: Some("me".to_string()),
+            password: Some("proxy-secret".to_string()),
+            no_proxy: Vec::new(),
+        }),
+        ..Default::default()
+    };
+    let tokens = HashMap::from([(
+        "env:config".to_string(),
+        OAuth2Token { access_token: "abc".to_string(), refresh_token: None, expires_at: None },
+    )]);
+    {
+        let store = SqliteStore::open(&path).unwrap();
+        store.save_environments(std::slice::from_ref(&env)).unwrap();
+        store.save_globals(&[variable("global_key", "global-secret", true)]).unwrap();
+        store.save_collection(&col).unwrap();
+        store.save_settings(&settings).unwrap();
+        store.save_oauth_tokens(&tokens).unwrap();
+    }
+
+    let raw = String::from_utf8_lossy(&std::fs::read(&path).unwrap()).into_owned();
+    for secret in ["env-secret", "global-secret", "collection-secret", "proxy-secret"] {
+        assert!(!raw.contains(secret), "{} is stored in plain text", secret);
+    }
+    assert!(temp.path().join(format!("{}.key", DATABASE_FILE)).is_file());
+    let ignored = std::fs::read_to_string(temp.path().join(".gitignore")).unwrap();
+    assert!(ignored.lines().any(|line| line == "reqforge.db*"));
+
+    let store = SqliteStore::open(&path).unwrap();
+    assert_eq!(
+        serde_json::to_value(&store.load_environments().unwrap()[0]).unwrap(),
+        serde_json::to_value(&env).unwrap()
+    );
+    assert_eq!(store.load_globals().unwrap()[0].value, "global-secret");
+    let loaded = store.list_collections().unwrap();
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(serde_json::to_value(&loaded[0]).unwrap(), serde_json::to_value(&col).unwrap());
+    assert_eq!(store.load_settings().unwrap(), settings);
+    assert_eq!(store.load_oauth_tokens().unwrap()["env:config"].access_token, "abc");
+}
+
+#[test]
+fn test_collections_are_saved_and_deleted_one_row_at_a_time() {
+    let temp = TempDir::new().unwrap();
+    let store = SqliteStore::open(temp.path().join(DATABASE_FILE)).unwrap();
+    let mut first = collection("First");
+    let second = collection("Second");
+    store.save_collection(&first).unwrap();
+    store.save_collection(&second).unwrap();
+
+    first.name = "Renamed".to_string();
+    store.save_collection(&first).unwrap();
+    let names: Vec<String> = store.list_collections().unwrap().into_iter().map(|c| c.name).collect();
+    assert_eq!(names, ["Renamed", "Second"]);
+
+    store.delete_collection(&first).unwrap();
+    let loaded = store.list_collections().unwrap();
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(loaded[0].id, second.id);
+    assert_eq!(loaded[0].variables[0].value, "collection-secret");
+}
+
+#[test]
+fn test_folder_and_request_secrets_stay_out_of_the_database() {
+    let temp = TempDir::new().unwrap();
+    let path = temp.path().join(DATABASE_FILE);
+    let mut col = collection("Users");
+    let folder_id = Uuid::new_v4();
+    col.tree.push(CollectionItem::Folder(Box::new(Folder {
+        id: folder_id,
+        name: "Admin".to_string(),
+        children: Vec::new(),
+        auth: Default::default(),
+        variables: vec![variable("folder_token", "folder-secret", true)],
+    })));
+    let mut request = RequestDefinition::new("Delete user", HttpMethod::DELETE, "{{base_url}}/users/1");
+    request.variables = vec![variable("request_token", "request-secret", true)];
+    col.add_request(request, Some(folder_id));
+    {
+        let store = SqliteStore::open(&path).unwrap();
+        store.save_collection(&col).unwrap();
+    }
+
+    let raw = String::from_utf8_lossy(&std::fs::read(&path).unwrap()).into_owned();
+    for secret in ["folder-secret", "request-secret"] {
+        assert!(!raw.contains(secret), "{} is stored in plain text", secret);
+    }
+    let store = SqliteStore::open(&path).unwrap();
+    let loaded = store.list_collections().unwrap();
+    assert_eq!(serde_json::to_value(&loaded[0]).unwrap(), serde_json::to_value(&col).unwrap());
+}
+
+#[test]
+fn test_passphrase_locks_secrets_until_unlocked() {
+    let temp = TempDir::new().unwrap();
+    let path = temp.path().join(DATABASE_FILE);
+    let env = environment("Staging");
+    {
+        let store = SqliteStore::open(&path).unwrap();
+        store.save_environments(std::slice::from_ref(&env)).unwrap();
+        store.set_secrets_passphrase(Some("hunter2")).unwrap();
+    }
+    assert!(!temp.path().join(format!("{}.key", DATABASE_FILE)).exists());
+
+    let store = SqliteStore::open(&path).unwrap();
+    assert!(store.secrets_locked().unwrap());
+    assert_eq!(store.load_environments().unwrap()[0].variables[1].value, "");
+    assert!(store.unlock_secrets("wrong").is_err());
+    store.unlock_secrets("hunter2").unwrap();
+    assert_eq!(store.load_environments().unwrap()[0].variables[1].value, "env-secret");
+}
+
+#[test]
+fn test_history_entries_and_bodies_are_kept_in_the_database() {
+    let temp = TempDir::new().unwrap();
+    let path = temp.path().join(DATABASE_FILE);
+    let entry = history_entry("Get");
+    {
+        let store = SqliteStore::open(&path).unwrap();
+        let mut history = RequestHistory::with_backend(store.history());
+        history.add_entry(history_entry("Older"));
+        history.add_entry_with_body(entry.clone(), b"hello").unwrap();
+        history.save().unwrap();
+    }
+    assert!(!temp.path().join("history.json").exists());
+
+    let store = SqliteStore::open(&path).unwrap();
+    let mut history = RequestHistory::with_backend(store.history());
+    history.load().unwrap();
+    assert_eq!(history.get_all().iter().map(|e| e.request.name.as_str()).collect::<Vec<_>>(), ["Get", "Older"]);
+    assert_eq!(history.response_body(entry.id).unwrap().unwrap(), b"hello");
+
+    history.clear();
+    assert_eq!(store.history().read_body(entry.id).unwrap(), None);
+}
+
+#[test]
+fn test_saving_history_only_writes_added_and_pruned_entries() {
+    let temp = TempDir::new().unwrap();
+    let path = temp.path().join(DATABASE_FILE);
+    let store = SqliteStore::open(&path).unwrap();
+    let mut history = RequestHistory::with_backend(store.history()).with_max_size(2);
+    let oldest = history_entry("Oldest");
+    history.add_entry(oldest.clone());
+    history.add_entry(history_entry("Older"));
+    history.save().unwrap();
+
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE writes (id TEXT, op TEXT);
+         CREATE TRIGGER history_insert AFTER INSERT ON history BEGIN INSERT INTO writes VALUES (new.id, 'insert'); END;
+         CREATE TRIGGER history_update AFTER UPDATE ON history BEGIN INSERT INTO writes VALUES (new.id, 'update'); END;
+         CREATE TRIGGER history_delete AFTER DELETE ON history BEGIN INSERT INTO writes VALUES (old.id, 'delete'); END;",
+    )
+    .unwrap();
+
+    let newest = history_entry("Newest");
+    history.add_entry(newest.clone());
+    history.save().unwrap();
+
+    let mut stmt = conn.prepare("SELECT id, op FROM writes ORDER BY rowid").unwrap();
+    let writes: Vec<(String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(
+        writes,
+        [(newest.id.to_string(), "insert".to_string()), (oldest.id.to_string(), "delete".to_string())]
+    );
+
+    let mut reloaded = RequestHistory::with_backend(store.history());
+    reloaded.load().unwrap();
+    assert_eq!(reloaded.get_all().iter().map(|e| e.request.name.as_str()).collect::<Vec<_>>(), ["Newest", "Older"]);
+}
+
+#[test]
+fn test_migrate_copies_history_between_backends() {
+    let json_dir = TempDir::new().unwrap();
+    let json = JsonStore::open(json_dir.path()).unwrap();
+    let env = environment("Staging");
+    let col = collection("Users");
+    json.save_environments(std::slice::from_ref(&env)).unwrap();
+    json.save_collection(&col).unwrap();
+    let entry = history_entry("Get");
+    let mut history = RequestHistory::new(json_dir.path().to_path_buf());
+    history.add_entry_with_body(entry.clone(), b"{\"ok\":true}").unwrap();
+    history.save().unwrap();
+
+    let sqlite_dir = TempDir::new().unwrap();
+    let sqlite = SqliteStore::open(sqlite_dir.path().join(DATABASE_FILE)).unwrap();
+    let report = migrate(&json, &sqlite).unwrap();
+    assert_eq!((report.collections, report.environments, report.history), (1, 1, 1));
+    assert_eq!(sqlite.load_environments().unwrap()[0].variables[1].value, "env-secret");
+    assert_eq!(sqlite.list_collections().unwrap()[0].variables[0].value, "collection-secret");
+
+    let mut history = RequestHistory::with_backend(sqlite.history());
+    history.load().unwrap();
+    assert_eq!(history.get_all()[0].id, entry.id);
+    assert_eq!(history.response_body(entry.id).unwrap().unwrap(), b"{\"ok\":true}");
+
+    // And back to files
+    let back_dir = TempDir::new().unwrap();
+    let back = JsonStore::open(back_dir.path()).unwrap();
+    assert_eq!(migrate(&sqlite, &back).unwrap().history, 1);
+    let mut history = RequestHistory::with_backend(Arc::new(crate::history::FileHistory::new(back_dir.path())));
+    history.load().unwrap();
+    assert_eq!(history.response_body(entry.id).unwrap().unwrap(), b"{\"ok\":true}");
+}
+
+#[test]
+fn test_core_opens_workspace_database() {
+    let temp = TempDir::new().unwrap();
+    let col = collection("Users");
+    {
+        let store = SqliteStore::open(temp.path().join(DATABASE_FILE)).unwrap();
+        store.save_collection(&col).unwrap();
+    }
+    let core = crate::ReqForgeCore::open(temp.path()).unwrap();
+    assert_eq!(core.collections.len(), 1);
+    assert_eq!(core.collections[0].id, col.id);
+    assert!(!temp.path().join("collections").exists());
+}
@@ -0,0 +1,229 @@
+//! YAML for the file-per-request layout and OpenAPI import/export, on top of
+//! `serde_yaml`, converting to and from `serde_json::Value`.
+//!
+//! `to_string` writes keys in sorted order, so the same data always gives the
+//! same file. `from_str` accepts any YAML 1.2 document: anchors and aliases
+//! are resolved, `<<` merge keys are applied, tags are dropped and scalar
+//! keys such as `200:` are read as strings.
+
+use serde_json::{Map, Number, Value};
+use serde_yaml::value::{Mapping, Value as Yaml};
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("line {line}: {message}")]
+pub struct YamlError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl From<serde_yaml::Error> for YamlError {
+    fn from(e: serde_yaml::Error) -> Self {
+        Self {
+            line: e.location().map(|location| location.line()).unwrap_or(0),
+            message: e.to_string(),
+        }
+    }
+}
+
+/// Serialize a value as a YAML document.
+pub fn to_string(value: &Value) -> String {
+    serde_yaml::to_string(&to_yaml(value)).expect("a YAML value always serializes")
+}
+
+/// Parse a YAML document into the equivalent JSON value.
+pub fn from_str(input: &str) -> Result<Value, YamlError> {
+    let mut yaml: Yaml = serde_yaml::from_str(input)?;
+    yaml.apply_merge()?;
+    to_json(yaml)
+}
+
+fn to_yaml(value: &Value) -> Yaml {
+    match value {
+        Value::Null => Yaml::Null,
+        Value::Bool(b) => Yaml::Bool(*b),
+        Value::Number(n) => Yaml::Number(if let Some(i) = n.as_i64() {
+            i.into()
+        } else if let Some(u) = n.as_u64() {
+            u.into()
+        } else {
+            n.as_f64().unwrap_or_default().into()
+        }),
+        Value::String(s) => Yaml::String(s.clone()),
+        Value::Array(items) => Yaml::Sequence(items.iter().map(to_yaml).collect()),
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let mut mapping = Mapping::with_capacity(map.len());
+            for key in keys {
+                mapping.insert(Yaml::String(key.clone()), to_yaml(&map[key]));
+            }
+            Yaml::Mapping(mapping)
+        }
+    }
+}
+
+fn to_json(yaml: Yaml) -> Result<Value, YamlError> {
+    Ok(match yaml {
+        Yaml::Null => Value::Null,
+        Yaml::Bool(b) => Value::Bool(b),
+        Yaml::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                i.into()
+            } else if let Some(u) = n.as_u64() {
+                u.into()
+            } else {
+                let f = n.as_f64().unwrap_or(f64::NAN);
+                Value::Number(Number::from_f64(f).ok_or_else(|| error(format!("{} has no JSON equivalent", n)))?)
+            }
+        }
+        Yaml::String(s) => Value::String(s),
+        Yaml::Sequence(items) => Value::Array(items.into_iter().map(to_json).collect::<Result<_, _>>()?),
+        Yaml::Mapping(mapping) => {
+            let mut map = Map::with_capacity(mapping.len());
+            for (key, value) in mapping {
+                map.insert(key_string(key)?, to_json(value)?);
+            }
+            Value::Object(map)
+        }
+        Yaml::Tagged(tagged) => to_json(tagged.value)?,
+    })
+}
+
+/// JSON keys are strings, so scalar keys are written the way they appear.
+fn key_string(key: Yaml) -> Result<String, YamlError> {
+    match key {
+        Yaml::String(s) => Ok(s),
+        Yaml::Number(n) => Ok(n.to_string()),
+        Yaml::Bool(b) => Ok(b.to_string()),
+        Yaml::Null => Ok("null".to_string()),
+        Yaml::Tagged(tagged) => key_string(tagged.value),
+        Yaml::Sequence(_) | Yaml::Mapping(_) => Err(error("complex mapping keys are not supported".to_string())),
+    }
+}
+
+fn error(message: String) -> YamlError {
+    YamlError { line: 0, message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_round_trip_preserves_values() {
+        let value = json!({
+            "name": "List users",
+            "url": "{{base_url}}/users?page=1",
+            "id": "0f9a5c1e-2b7d-4c1a-9d3e-5a6b7c8d9e0f",
+            "count": 3,
+            "ratio": 0.5,
+            "enabled": true,
+            "missing": null,
+            "tricky": ["yes", "123", "- dash", "a: b", "  padded", "trailing:", "#hash", "", "say \"hi\"", "tab\there"],
+            "body": { "Raw": { "content": "{\n  \"a\": 1\n}", "content_type": "Json" } },
+            "script": "let x = 1;\nlet y = 2;\n",
+            "blank_lines": "first\n\nthird",
+            "headers": [
+                { "key": "Accept", "value": "application/json", "enabled": true, "description": null },
+                { "key": "X-Empty", "value": "", "enabled": false, "description": "multi\nline" }
+            ],
+            "nested": [[1, 2], [], {}],
+            "empty_map": {},
+            "empty_list": []
+        });
+        let yaml = to_string(&value);
+        assert_eq!(from_str(&yaml).unwrap(), value, "yaml was:\n{}", yaml);
+        assert_eq!(to_string(&from_str(&yaml).unwrap()), yaml);
+    }
+
+    #[test]
+    fn test_output_is_readable_and_sorted() {
+        let value = json!({ "url": "https://example.com/a", "method": "GET", "script": "a\nb\n", "tags": ["x", "z"] });
+        assert_eq!(
+            to_string(&value),
+            "method: GET\nscript: |\n  a\n  b\ntags:\n- x\n- z\nurl: https://example.com/a\n"
+        );
+    }
+
+    #[test]
+    fn test_reads_hand_written_style() {
+        let yaml = "# comment\nname: Login   # trailing\nheaders:\n- key: A\n  value: '1'\nbody: |-\n  line one\n  line two\n";
+        assert_eq!(
+            from_str(yaml).unwrap(),
+            json!({ "name": "Login", "headers": [{ "key": "A", "value": "1" }], "body": "line one\nline two" })
+        );
+        let err = from_str("a: 1\n  b: 2\n").unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+
+    #[test]
+    fn test_rejects_bad_indentation_without_panicking() {
+        let err = from_str("a:\n\tb: 1\n").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(from_str("body: |\n    line one\n  中文\n").is_err());
+        assert_eq!(from_str("body: |\n  中文\n").unwrap(), json!({ "body": "中文\n" }));
+    }
+
+    #[test]
+    fn test_resolves_anchors_and_merge_keys() {
+        assert_eq!(from_str("a: &x\n  b: 1\nc: *x\n").unwrap(), json!({ "a": { "b": 1 }, "c": { "b": 1 } }));
+        assert_eq!(
+            from_str("base: &base\n  x: 1\n  y: 2\nderived:\n  <<: *base\n  y: 3\n").unwrap(),
+            json!({ "base": { "x": 1, "y": 2 }, "derived": { "x": 1, "y": 3 } })
+        );
+    }
+
+    #[test]
+    fn test_reads_openapi_style() {
+        let yaml = r#"---
+openapi: 3.0.3
+info:
+  title: 'Pet''s API'
+  description: >
+    Manage pets
+    and owners.
+
+    Second paragraph.
+  summary: A plain description
+    continued on the next line
+tags: [pets, "owners"]
+paths:
+  /pets/{id}:
+    get:
+      parameters:
+        - { name: id, in: path, required: true }
+      responses:
+        200:
+          content:
+            application/json:
+              schema: { $ref: '#/components/schemas/Pet' }
+      security: [
+        { api_key: [] }
+      ]
+servers:
+  - url: '{{base_url}}/v1'
+    other: !custom "{{host}}/x"
+"#;
+        assert_eq!(
+            from_str(yaml).unwrap(),
+            json!({
+                "openapi": "3.0.3",
+                "info": {
+                    "title": "Pet's API",
+                    "description": "Manage pets and owners.\nSecond paragraph.\n",
+                    "summary": "A plain description continued on the next line"
+                },
+                "tags": ["pets", "owners"],
+                "paths": { "/pets/{id}": { "get": {
+                    "parameters": [{ "name": "id", "in": "path", "required": true }],
+                    "responses": { "200": { "content": { "application/json": {
+                        "schema": { "$ref": "#/components/schemas/Pet" }
+                    } } } },
+                    "security": [{ "api_key": [] }]
+                } } },
+                "servers": [{ "url": "{{base_url}}/v1", "other": "{{host}}/x" }]
+            })
+        );
+    }
+}
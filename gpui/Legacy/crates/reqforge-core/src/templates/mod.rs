@@ -4,6 +4,7 @@ use crate::models::template::{
     RequestTemplate, TemplateApplicationResult, TemplateCategory,
 };
 use crate::models::request::{KeyValuePair, BodyType};
+use crate::store::schema::{self, Document, SchemaError};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use uuid::Uuid;
@@ -30,7 +31,7 @@ impl TemplateManager {
         let templates_path = self.workspace_dir.join("templates.json");
         if templates_path.exists() {
             let data = std::fs::read_to_string(&templates_path)?;
-            self.custom_templates = serde_json::from_str(&data)?;
+            self.custom_templates = schema::from_str(Document::Templates, &data)?;
         }
         Ok(())
     }
@@ -38,8 +39,8 @@ impl TemplateManager {
     /// Save custom templates to workspace
     pub fn save_custom_templates(&self) -> Result<(), TemplateError> {
         let templates_path = self.workspace_dir.join("templates.json");
-        let json = serde_json::to_string_pretty(&self.custom_templates)?;
-        std::fs::write(templates_path, json)?;
+        let json = schema::to_string(Document::Templates, &self.custom_templates)?;
+        crate::store::write_atomic(&templates_path, json)?;
         Ok(())
     }
 
@@ -176,6 +177,11 @@ impl TemplateManager {
             headers,
             query_params,
             body,
+            auth: Default::default(),
+            settings: Default::default(),
+            variables: Default::default(),
+            scripts: Default::default(),
+            assertions: Vec::new(),
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
         };
@@ -244,6 +250,9 @@ pub enum TemplateError {
 
     #[error("Serialization error: {0}")]
     Serde(#[from] serde_json::Error),
+
+    #[error("{0}")]
+    Schema(#[from] SchemaError),
 }
 
 #[cfg(test)]
@@ -2,7 +2,7 @@
 //!
 //! Provides comprehensive validation for HTTP requests before execution.
 
-use crate::models::request::{RequestDefinition, HttpMethod, BodyType, RawContentType};
+use crate::models::request::{RequestDefinition, HttpMethod, BodyType, RawContentType, MultipartValue};
 use std::collections::HashSet;
 
 /// Try to parse a URL, returning an error if it fails
@@ -307,6 +307,60 @@ pub fn validate_body(method: &HttpMethod, body: &BodyType) -> ValidationResult<(
 
             Ok(())
         }
+
+        BodyType::Multipart(parts) => {
+            // Check if body is allowed for this method
+            if !method_supports_body(method) {
+                return Err(ValidationError::invalid_body(format!(
+                    "HTTP method {} does not support a request body",
+                    method
+                )));
+            }
+
+            // Check if there's at least one part
+            if parts.is_empty() {
+                return Err(ValidationError::invalid_body(
+                    "Multipart body must contain at least one part",
+                ));
+            }
+
+            // Validate each enabled part
+            for part in parts.iter().filter(|p| p.enabled) {
+                if part.name.trim().is_empty() {
+                    return Err(ValidationError::invalid_body(
+                        "Multipart part name cannot be empty",
+                    ));
+                }
+                if let MultipartValue::File { path, .. } = &part.value
+                    && path.trim().is_empty()
+                {
+                    return Err(ValidationError::invalid_body(format!(
+                        "Multipart file part '{}' has no file path",
+                        part.name
+                    )));
+                }
+            }
+
+            Ok(())
+        }
+
+        BodyType::Binary { path } => {
+            // Check if body is allowed for this method
+            if !method_supports_body(method) {
+                return Err(ValidationError::invalid_body(format!(
+                    "HTTP method {} does not support a request body",
+                    method
+                )));
+            }
+
+            if path.trim().is_empty() {
+                return Err(ValidationError::invalid_body(
+                    "Binary body must reference a file path",
+                ));
+            }
+
+            Ok(())
+        }
     }
 }
 
@@ -370,7 +424,7 @@ pub fn validate_request(req: &RequestDefinition) -> ValidationResult<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::request::{KeyValuePair, HttpMethod};
+    use crate::models::request::{KeyValuePair, HttpMethod, MultipartPart};
 
     // URL validation tests
     #[test]
@@ -620,6 +674,33 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("at least one"));
     }
 
+    #[test]
+    fn test_validate_body_multipart() {
+        let body = BodyType::Multipart(vec![
+            MultipartPart::text("title", "Hello"),
+            MultipartPart::file("avatar", "/tmp/avatar.png"),
+        ]);
+        assert!(validate_body(&HttpMethod::POST, &body).is_ok());
+        assert!(validate_body(&HttpMethod::GET, &body).is_err());
+
+        let no_path = BodyType::Multipart(vec![MultipartPart::file("avatar", " ")]);
+        let result = validate_body(&HttpMethod::POST, &no_path);
+        assert!(result.unwrap_err().to_string().contains("no file path"));
+
+        let result = validate_body(&HttpMethod::POST, &BodyType::Multipart(vec![]));
+        assert!(result.unwrap_err().to_string().contains("at least one part"));
+    }
+
+    #[test]
+    fn test_validate_body_binary() {
+        let body = BodyType::Binary { path: "/tmp/upload.bin".into() };
+        assert!(validate_body(&HttpMethod::PUT, &body).is_ok());
+        assert!(validate_body(&HttpMethod::DELETE, &body).is_err());
+
+        let empty = BodyType::Binary { path: String::new() };
+        assert!(validate_body(&HttpMethod::PUT, &empty).is_err());
+    }
+
     // Request validation tests
     #[test]
     fn test_validate_request_valid_get() {
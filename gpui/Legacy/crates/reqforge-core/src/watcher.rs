@@ -0,0 +1,301 @@
+//! Noticing changes made to a workspace outside this process (git pull,
+//! another editor, the CLI).
+//!
+//! `WorkspaceWatcher` reports changes to the files holding collections,
+//! environments and globals. When it reports any, `ReqForgeCore::reload` compares what is on disk with what
+//! is in memory and with what was last loaded or saved, to tell external
+//! changes apart from unsaved local edits.
+
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::time::SystemTime;
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Workspace files, besides `collections/`, that `reload` reads.
+const WATCHED_FILES: [&str; 2] = ["environments.json", "globals.json"];
+
+/// Something in the workspace that can change on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WorkspaceItem {
+    Collection(Uuid),
+    Environment(Uuid),
+    Globals,
+}
+
+/// A change found by `ReqForgeCore::reload`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkspaceEvent {
+    /// New on disk; now loaded.
+    Added(WorkspaceItem),
+    /// Changed on disk with no local edits; the new version is now loaded.
+    Changed(WorkspaceItem),
+    /// Deleted on disk with no local edits; now removed.
+    Removed(WorkspaceItem),
+    /// Changed or deleted on disk while it has unsaved local edits. The
+    /// in-memory version is kept until `ReqForgeCore::resolve_conflict`.
+    Conflict(WorkspaceItem),
+}
+
+/// How to settle a `WorkspaceEvent::Conflict`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictResolution {
+    /// Write the in-memory version over the one on disk.
+    KeepMine,
+    /// Drop local edits and load the version on disk.
+    TakeTheirs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+/// Watches the files `ReqForgeCore::reload` reads, `collections/`,
+/// `environments.json` and `globals.json`, for changes.
+///
+/// File system notifications are used where available; otherwise those
+/// paths (and nothing else in the workspace) are rescanned on each poll.
+/// Hidden files and directories (such as in-progress atomic writes) are
+/// ignored.
+#[derive(Debug)]
+pub struct WorkspaceWatcher {
+    root: PathBuf,
+    source: Source,
+}
+
+#[derive(Debug)]
+enum Source {
+    Notify {
+        _watcher: RecommendedWatcher,
+        events: Receiver<notify::Result<notify::Event>>,
+    },
+    Scan(HashMap<PathBuf, FileStamp>),
+}
+
+impl WorkspaceWatcher {
+    /// Start watching `root`; files already there are not reported.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        // Notifications can be unavailable, e.g. when the inotify watch
+        // limit is reached
+        let source = notify_source(&root).unwrap_or_else(|_| Source::Scan(scan(&root)));
+        Self { root, source }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Files added, modified or removed since the last poll, sorted.
+    pub fn poll(&mut self) -> Vec<PathBuf> {
+        let mut changed: Vec<PathBuf> = match &mut self.source {
+            Source::Notify { events, .. } => events
+                .try_iter()
+                .filter_map(Result::ok)
+                .filter(|event| !matches!(event.kind, EventKind::Access(_)))
+                .flat_map(|event| event.paths)
+                .filter(|path| is_watched(&self.root, path))
+                .collect(),
+            Source::Scan(files) => {
+                let current = scan(&self.root);
+                let changed = current
+                    .iter()
+                    .filter(|(path, stamp)| files.get(*path) != Some(stamp))
+                    .map(|(path, _)| path.clone())
+                    .chain(files.keys().filter(|path| !current.contains_key(*path)).cloned())
+                    .collect();
+                *files = current;
+                changed
+            }
+        };
+        changed.sort();
+        changed.dedup();
+        changed
+    }
+}
+
+fn notify_source(root: &Path) -> notify::Result<Source> {
+    let (tx, events) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(root, RecursiveMode::Recursive)?;
+    Ok(Source::Notify { _watcher: watcher, events })
+}
+
+/// Whether `path` is one of the files `reload` reads.
+fn is_watched(root: &Path, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(root) else { return false };
+    let mut components = relative.components().map(|c| c.as_os_str().to_string_lossy());
+    let Some(first) = components.next() else { return false };
+    match first.as_ref() {
+        name if WATCHED_FILES.contains(&name) => components.next().is_none(),
+        "collections" => components.all(|name| !name.starts_with('.')),
+        _ => false,
+    }
+}
+
+fn scan(root: &Path) -> HashMap<PathBuf, FileStamp> {
+    let mut files = HashMap::new();
+    for name in WATCHED_FILES {
+        let path = root.join(name);
+        if let Ok(metadata) = std::fs::metadata(&path) {
+            files.insert(path, FileStamp { modified: metadata.modified().ok(), len: metadata.len() });
+        }
+    }
+    scan_dir(&root.join("collections"), &mut files);
+    files
+}
+
+fn scan_dir(dir: &Path, files: &mut HashMap<PathBuf, FileStamp>) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        if entry.file_name().to_string_lossy().starts_with('.') { continue; }
+        let Ok(metadata) = entry.metadata() else { continue };
+        if metadata.is_dir() {
+            scan_dir(&entry.path(), files);
+        } else {
+            files.insert(entry.path(), FileStamp { modified: metadata.modified().ok(), len: metadata.len() });
+        }
+    }
+}
+
+/// A hash of `value`'s JSON form, for telling whether two versions differ.
+/// Maps serialize with sorted keys, so equal values always hash the same.
+pub(crate) fn fingerprint<T: Serialize>(value: &T) -> u64 {
+    let json = serde_json::to_value(value).map(|v| v.to_string()).unwrap_or_default();
+    let mut hasher = DefaultHasher::new();
+    json.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Bring `memory` up to date with `disk` for one kind of item, leaving items
+/// with unsaved local edits alone. `baselines` holds the fingerprint of each
+/// item as last loaded or saved.
+pub(crate) fn reconcile<T: Serialize>(
+    memory: &mut Vec<T>,
+    disk: Vec<T>,
+    item: impl Fn(&T) -> WorkspaceItem,
+    baselines: &mut HashMap<WorkspaceItem, u64>,
+    events: &mut Vec<WorkspaceEvent>,
+) {
+    let mut disk: HashMap<WorkspaceItem, T> = disk.into_iter().map(|value| (item(&value), value)).collect();
+
+    let mut kept = Vec::with_capacity(memory.len());
+    for mine in memory.drain(..) {
+        let key = item(&mine);
+        let mine_print = fingerprint(&mine);
+        let edited = baselines.get(&key) != Some(&mine_print);
+        match disk.remove(&key) {
+            Some(theirs) => {
+                let theirs_print = fingerprint(&theirs);
+                if theirs_print == mine_print {
+                    baselines.insert(key, theirs_print);
+                    kept.push(mine);
+                } else if !edited {
+                    baselines.insert(key, theirs_print);
+                    events.push(WorkspaceEvent::Changed(key));
+                    kept.push(theirs);
+                } else {
+                    if baselines.get(&key) != Some(&theirs_print) {
+                        events.push(WorkspaceEvent::Conflict(key));
+                    }
+                    kept.push(mine);
+                }
+            }
+            // Never saved, so not missing from disk
+            None if !baselines.contains_key(&key) => kept.push(mine),
+            None if !edited => {
+                baselines.remove(&key);
+                events.push(WorkspaceEvent::Removed(key));
+            }
+            None => {
+                events.push(WorkspaceEvent::Conflict(key));
+                kept.push(mine);
+            }
+        }
+    }
+
+    // On disk but not in memory: new, or deleted locally without saving
+    let mut added: Vec<(WorkspaceItem, T)> = disk.into_iter().collect();
+    added.sort_by_key(|(key, _)| *key);
+    for (key, theirs) in added {
+        let theirs_print = fingerprint(&theirs);
+        match baselines.get(&key) {
+            None => {
+                baselines.insert(key, theirs_print);
+                events.push(WorkspaceEvent::Added(key));
+                kept.push(theirs);
+            }
+            Some(&base) if base != theirs_print => events.push(WorkspaceEvent::Conflict(key)),
+            Some(_) => {}
+        }
+    }
+    *memory = kept;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Poll until something is reported, as notifications arrive asynchronously.
+    fn poll_changes(watcher: &mut WorkspaceWatcher) -> Vec<PathBuf> {
+        for _ in 0..50 {
+            let changed = watcher.poll();
+            if !changed.is_empty() {
+                std::thread::sleep(std::time::Duration::from_millis(100));
+                let mut changed = [changed, watcher.poll()].concat();
+                changed.sort();
+                changed.dedup();
+                return changed;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        Vec::new()
+    }
+
+    #[test]
+    fn test_poll_reports_added_modified_and_removed_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        std::fs::create_dir(root.join("collections")).unwrap();
+        std::fs::write(root.join("environments.json"), "1").unwrap();
+        let mut watcher = WorkspaceWatcher::new(&root);
+        assert!(watcher.poll().is_empty());
+
+        std::fs::write(root.join("environments.json"), "12").unwrap();
+        std::fs::create_dir(root.join("collections/api")).unwrap();
+        std::fs::write(root.join("collections/api/b.yaml"), "1").unwrap();
+        std::fs::write(root.join("collections/.b.json.1.tmp"), "1").unwrap();
+        // Files `reload` does not read are not reported
+        std::fs::create_dir(root.join("history")).unwrap();
+        std::fs::write(root.join("history/x.gz"), "1").unwrap();
+        for name in ["history.json", "cookies.json", "oauth_tokens.json", "settings.json"] {
+            std::fs::write(root.join(name), "1").unwrap();
+        }
+        let changed = poll_changes(&mut watcher);
+        assert!(changed.contains(&root.join("environments.json")), "{:?}", changed);
+        // Files created with their directory may only show up as the directory
+        assert!(changed.iter().any(|path| path.starts_with(root.join("collections/api"))), "{:?}", changed);
+        assert!(changed.iter().all(|path| is_watched(&root, path)), "{:?}", changed);
+
+        std::fs::remove_file(root.join("environments.json")).unwrap();
+        assert_eq!(poll_changes(&mut watcher), vec![root.join("environments.json")]);
+    }
+
+    #[test]
+    fn test_scan_only_reads_watched_files() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::create_dir(temp.path().join("collections")).unwrap();
+        std::fs::write(temp.path().join("collections/a.json"), "1").unwrap();
+        std::fs::write(temp.path().join("globals.json"), "1").unwrap();
+        std::fs::write(temp.path().join("history.json"), "1").unwrap();
+        let mut files: Vec<PathBuf> = scan(temp.path()).into_keys().collect();
+        files.sort();
+        assert_eq!(files, vec![temp.path().join("collections/a.json"), temp.path().join("globals.json")]);
+    }
+}